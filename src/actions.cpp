@@ -0,0 +1,83 @@
+#include "actions.h"
+
+#include <QCoreApplication>
+
+QVector<ActionInfo> ActionRegistry::catalog()
+{
+    const auto tr = [](const char *text) {
+        return QCoreApplication::translate("ActionRegistry", text);
+    };
+    return {
+        {QStringLiteral("show-window"),      tr("Show PinIt"), QString()},
+        {QStringLiteral("toggle-window"),    tr("Show / hide PinIt"), QString()},
+        {QStringLiteral("open-settings"),    tr("Open PinIt's settings"), QString()},
+        {QStringLiteral("quick-panel"),      tr("Open the quick panel"), QString()},
+        {QStringLiteral("pick-window"),      tr("Pin a window…"), QString()},
+        {QStringLiteral("adopt-topmost"),    tr("Adopt always-on-top windows…"), QString()},
+        {QStringLiteral("pin-foreground"),   tr("Pin / unpin the focused window"), QString()},
+        {QStringLiteral("pin-under-cursor"), tr("Pin / unpin the window under the mouse"),
+         QString()},
+        {QStringLiteral("pin-app"),          tr("Pin an app's window and bring it to the front"),
+         QStringLiteral("<process>")},
+        {QStringLiteral("move-to-monitor"),  tr("Move the focused window to another monitor"),
+         QStringLiteral("<number>")},
+        {QStringLiteral("pin-temporarily"),  tr("Pin the focused window for a while"),
+         QStringLiteral("[<seconds>]")},
+        {QStringLiteral("pin-last"),         tr("Pin / unpin the last pinned window"), QString()},
+        {QStringLiteral("focus-last-pinned"), tr("Bring the last pinned window to the front"),
+         QString()},
+        {QStringLiteral("unpin-all"),        tr("Unpin all windows"), QString()},
+        {QStringLiteral("opacity-up"),       tr("Increase opacity"), QString()},
+        {QStringLiteral("opacity-down"),     tr("Decrease opacity"), QString()},
+        {QStringLiteral("set-opacity"),      tr("Set the focused pin's opacity"),
+         QStringLiteral("<percent>")},
+        {QStringLiteral("pin-with-opacity"), tr("Pin the focused window and set its opacity"),
+         QStringLiteral("[<percent>]")},
+        {QStringLiteral("opacity-slider"),   tr("Adjust the focused pin's opacity with a slider"),
+         QString()},
+        {QStringLiteral("hide-all-pinned"),  tr("Hide / show all pinned windows"), QString()},
+        {QStringLiteral("snapshot-save"),    tr("Save a snapshot"), QStringLiteral("<name>")},
+        {QStringLiteral("snapshot-restore"), tr("Restore a snapshot"), QStringLiteral("<name>")},
+        {QStringLiteral("kiosk-exit"),       tr("Leave kiosk mode"), QString()},
+        {QStringLiteral("run-macro"),        tr("Run a macro"), QStringLiteral("<name>")},
+        {QStringLiteral("slot-assign"),      tr("Put the focused window in a pin slot"),
+         QStringLiteral("<1-3>")},
+        {QStringLiteral("slot-activate"),
+         tr("Bring a pin slot's window to the front, or toggle its pin"),
+         QStringLiteral("<1-3>")},
+        {QStringLiteral("set-config-directory"), tr("Move PinIt's data folder"),
+         QStringLiteral("local|roaming|<folder>")},
+        {QStringLiteral("advance-onboarding"), tr("Mark a first-run step as done"),
+         QStringLiteral("<step>|all")},
+    };
+}
+
+bool ActionRegistry::isKnown(const QString &id)
+{
+    for (const ActionInfo &a : catalog()) {
+        if (a.id == id)
+            return true;
+    }
+    return false;
+}
+
+void ActionRegistry::bind(const QString &id, Handler run)
+{
+    Q_ASSERT_X(isKnown(id), "ActionRegistry::bind", "id missing from catalog()");
+    m_handlers.insert(id, std::move(run));
+}
+
+bool ActionRegistry::invoke(const QString &id, const QStringList &args, intptr_t target) const
+{
+    const auto it = m_handlers.constFind(id);
+    if (it == m_handlers.constEnd()) {
+        qWarning("Unknown action \"%s\"", qUtf8Printable(id));
+        return false;
+    }
+    const intptr_t outer = m_target;
+    if (target != 0)
+        m_target = target;
+    const bool ok = it.value()(args);
+    m_target = outer;
+    return ok;
+}
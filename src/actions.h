@@ -0,0 +1,45 @@
+#pragma once
+//
+// ActionRegistry — the things PinIt can be told to do, under stable ids
+// ("pin-foreground", "unpin-all", "snapshot-restore", …). The global hotkeys
+// and the single-instance command channel (command line, Jump List) dispatch
+// through it, so an action behaves the same wherever it's triggered from.
+//
+// The catalog (ids, labels, argument hints) is static so it can be listed
+// before anything is running; main() binds a handler to each id.
+//
+#include <QHash>
+#include <QString>
+#include <QStringList>
+#include <QVector>
+
+#include <cstdint>
+#include <functional>
+
+struct ActionInfo {
+    QString id;
+    QString label;   // translated
+    QString args;    // e.g. "<percent>"; empty if it takes none
+};
+
+class ActionRegistry
+{
+public:
+    using Handler = std::function<bool(const QStringList &args)>;   // false if it failed
+
+    static QVector<ActionInfo> catalog();
+    static bool isKnown(const QString &id);
+
+    void bind(const QString &id, Handler run);
+    // False for an unknown or unbound id, or when the action itself fails.
+    // A target (a trigger's window) is what actions on "the focused window"
+    // act on instead, for as long as the handler runs.
+    bool invoke(const QString &id, const QStringList &args = {}, intptr_t target = 0) const;
+    // The window invoke() was given, 0 outside such a call. A macro started
+    // with one hands it on to each of its steps.
+    intptr_t target() const { return m_target; }
+
+private:
+    QHash<QString, Handler> m_handlers;
+    mutable intptr_t        m_target = 0;
+};
@@ -0,0 +1,213 @@
+#include "audit.h"
+#include "persistence.h"
+
+#include <QDateTime>
+#include <QDir>
+#include <QFile>
+#include <QFileInfo>
+#include <QJsonDocument>
+#include <QMutex>
+#include <QStringList>
+#include <QSysInfo>
+#include <QTemporaryFile>
+#include <QThreadPool>
+
+namespace {
+
+bool    g_enabled = false;
+QString g_location;
+qint64  g_maxBytes = 1024 * 1024;
+int     g_keep = 5;
+// Written by the writer thread, read by status().
+QMutex  g_mutex;
+quint64 g_written = 0;
+QString g_lastError;
+
+// Records are written one at a time, in order, on a thread of their own: the
+// folder is often a share, where mkpath, stat and open can take seconds. The
+// thread goes away when there's nothing to write.
+QThreadPool &writer()
+{
+    static QThreadPool pool;
+    static const bool once = (pool.setMaxThreadCount(1), true);
+    Q_UNUSED(once);
+    return pool;
+}
+
+QString auditPath()
+{
+    const QString dir = g_location.isEmpty() ? persistence::dataDir()
+                                             : persistence::expandVariables(g_location);
+    return QDir(dir).filePath(QStringLiteral("audit.jsonl"));
+}
+
+QString userName()
+{
+    const QString user = qEnvironmentVariable("USERNAME");
+    const QString domain = qEnvironmentVariable("USERDOMAIN");
+    return domain.isEmpty() ? user : domain + QLatin1Char('\\') + user;
+}
+
+// audit.jsonl → .1 → .2 …; the oldest past `keep` is deleted.
+void rotate(const QString &path, qint64 maxBytes, int keep)
+{
+    if (QFileInfo(path).size() < maxBytes)
+        return;
+    const auto old = [&path](int n) { return path + QLatin1Char('.') + QString::number(n); };
+    QFile::remove(old(keep));
+    for (int n = keep - 1; n >= 1; --n)
+        QFile::rename(old(n), old(n + 1));
+    if (keep > 0)
+        QFile::rename(path, old(1));
+    else
+        QFile::remove(path);
+}
+
+// Queues the record. Where it goes and when to roll over are taken now, so a
+// setting changed meanwhile applies from the next record on.
+void write(const QJsonObject &event)
+{
+    const QString path = auditPath();
+    const qint64 maxBytes = g_maxBytes;
+    const int keep = g_keep;
+    const QByteArray line = QJsonDocument(event).toJson(QJsonDocument::Compact) + '\n';
+    writer().start([path, maxBytes, keep, line]() {
+        QDir().mkpath(QFileInfo(path).absolutePath());
+        rotate(path, maxBytes, keep);
+        QFile f(path);
+        const bool ok = f.open(QIODevice::Append) && f.write(line) == line.size();
+        const QMutexLocker lock(&g_mutex);
+        if (!ok) {
+            // Warn once per distinct failure (an unreachable share fails every time).
+            if (f.errorString() != g_lastError)
+                qWarning("Audit log %s can't be written: %s",
+                         qUtf8Printable(QDir::toNativeSeparators(path)),
+                         qUtf8Printable(f.errorString()));
+            g_lastError = f.errorString();
+            return;
+        }
+        g_lastError.clear();
+        ++g_written;
+    });
+}
+
+} // namespace
+
+namespace audit {
+
+void setEnabled(bool enabled)
+{
+    g_enabled = enabled;
+}
+
+bool isEnabled()
+{
+    return g_enabled;
+}
+
+void setLocation(const QString &folder)
+{
+    g_location = folder;
+}
+
+void setRotation(qint64 maxBytes, int keep)
+{
+    g_maxBytes = maxBytes;
+    g_keep = keep;
+}
+
+void flush()
+{
+    writer().waitForDone();
+}
+
+void record(const QString &action, const QString &process, const QString &result,
+            const QJsonObject &detail)
+{
+    if (!g_enabled)
+        return;
+    QJsonObject event;
+    event["ts"] = QDateTime::currentDateTimeUtc().toString(Qt::ISODateWithMs);
+    event["user"] = userName();
+    event["machine"] = QSysInfo::machineHostName();
+    if (!process.isEmpty())
+        event["process"] = process;
+    event["action"] = action;
+    event["result"] = result;
+    if (!detail.isEmpty())
+        event["detail"] = detail;
+    write(event);
+}
+
+void recordSettingChanges(const QJsonObject &from, const QJsonObject &to)
+{
+    if (!g_enabled)
+        return;
+    const auto changed = [](const QString &key, const QJsonValue &a, const QJsonValue &b) {
+        if (a != b)
+            record(QStringLiteral("setting"), QString(), QStringLiteral("ok"),
+                   {{"key", key}, {"from", a}, {"to", b}});
+    };
+    QStringList keys = from.keys() + to.keys();
+    keys.removeDuplicates();
+    for (const QString &key : keys) {
+        const QJsonValue a = from.value(key), b = to.value(key);
+        if (key != QLatin1String("shortcuts")) {
+            changed(key, a, b);
+            continue;
+        }
+        const QJsonObject sa = a.toObject(), sb = b.toObject();
+        QStringList actions = sa.keys() + sb.keys();
+        actions.removeDuplicates();
+        for (const QString &action : actions)
+            changed(key + QLatin1Char('.') + action, sa.value(action), sb.value(action));
+    }
+}
+
+Status status()
+{
+    Status s;
+    s.enabled = g_enabled;
+    s.path = QDir::toNativeSeparators(auditPath());
+    const QFileInfo info(auditPath());
+    s.bytes = info.size();
+    for (int n = 1; n <= g_keep; ++n) {
+        if (QFile::exists(info.filePath() + QLatin1Char('.') + QString::number(n)))
+            ++s.rotated;
+    }
+    // Writable means the file can be opened for append or, before the first
+    // record, a file can be made in the folder — or in the nearest folder that
+    // exists, since the first write creates the rest. Nothing is left behind.
+    QString error;
+    if (info.exists()) {
+        QFile f(info.filePath());
+        s.writable = f.open(QIODevice::Append);
+        error = f.errorString();
+    } else {
+        QDir dir(info.absolutePath());
+        while (!dir.exists() && dir.cdUp()) {}
+        QTemporaryFile probe(dir.filePath(QStringLiteral("audit-XXXXXX.tmp")));
+        s.writable = dir.exists() && probe.open();
+        error = probe.errorString();
+    }
+    const QMutexLocker lock(&g_mutex);
+    s.written = g_written;
+    s.lastError = s.writable ? g_lastError : error;
+    return s;
+}
+
+QJsonObject toJson(const Status &status)
+{
+    QJsonObject o;
+    o["enabled"] = status.enabled;
+    o["path"] = status.path;
+    o["writable"] = status.writable;
+    o["bytes"] = status.bytes;
+    o["rotated_files"] = status.rotated;
+    o["records_written"] = double(status.written);
+    if (!status.lastError.isEmpty())
+        o["last_error"] = status.lastError;
+    return o;
+}
+
+} // namespace audit
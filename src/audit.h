@@ -0,0 +1,55 @@
+#pragma once
+//
+// audit — an optional record of what was done with PinIt, for enterprise
+// monitoring: every pin, unpin, opacity change and setting change as one JSON
+// object per line (JSONL) in audit.jsonl:
+//
+//   {"ts":"2026-10-16T08:14:03.120Z","user":"CONTOSO\\alice","machine":"PC-042",
+//    "process":"vlc.exe","action":"pin","result":"ok"}
+//
+// "result" is "ok", "blocked" (the never-pin list) or "failed"; "detail" holds
+// the rest, e.g. {"percent": 70} or {"key": "enable_sound", "from": true,
+// "to": false}. The file lives in the data folder unless audit_log_path names
+// another (environment variables allowed, so a share like
+// \\server\audit\%COMPUTERNAME% works), and rolls over to audit.jsonl.1 … .5
+// once it passes 1 MB.
+//
+// Off by default; an administrator can turn it on through Group Policy
+// (audit_log = 1). While disabled every record*() call is a no-op. Records
+// are written in the background, in order; flush() waits for them.
+//
+#include <QJsonObject>
+#include <QString>
+
+namespace audit {
+
+void setEnabled(bool enabled);
+bool isEnabled();
+// Folder for audit.jsonl; empty = persistence::dataDir().
+void setLocation(const QString &folder);
+// When to roll the file over, and how many old ones to keep. Tests shrink it.
+void setRotation(qint64 maxBytes, int keep);
+
+void record(const QString &action, const QString &process, const QString &result,
+            const QJsonObject &detail = QJsonObject());
+void flush();   // until every record so far is on disk (or has failed)
+// One "setting" record per key that differs, shortcuts key by key
+// ("shortcuts.toggle_pin"). Both are persistence::settingsToJson() objects.
+void recordSettingChanges(const QJsonObject &from, const QJsonObject &to);
+
+struct Status {
+    bool    enabled = false;
+    QString path;          // the current audit.jsonl
+    bool    writable = false;
+    qint64  bytes = 0;     // size of the current file
+    int     rotated = 0;   // old files kept next to it
+    quint64 written = 0;   // records written by this process
+    QString lastError;     // why the last write failed; empty if it didn't
+};
+
+// Looks at the file without creating it. written and lastError are as of
+// the records written so far.
+Status      status();
+QJsonObject toJson(const Status &status);   // for `PinIt.exe --audit-status`
+
+} // namespace audit
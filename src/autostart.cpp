@@ -0,0 +1,345 @@
+#include <windows.h>
+#include <shellapi.h>
+#include <taskschd.h>
+
+#include "autostart.h"
+#include "pinerror.h"
+
+#include <QCoreApplication>
+#include <QDir>
+#include <QFile>
+#include <QSettings>
+#include <QStringList>
+#include <QTemporaryFile>
+
+#include <string>
+
+namespace {
+
+// Per-user autostart entries live here (value name "PinIt").
+const char *const kRunKey =
+    "HKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+const char *const kTaskName = "PinIt";
+
+QString command(const QString &arguments)
+{
+    const QString exe = QDir::toNativeSeparators(QCoreApplication::applicationFilePath());
+    return arguments.trimmed().isEmpty()
+               ? QStringLiteral("\"%1\"").arg(exe)
+               : QStringLiteral("\"%1\" %2").arg(exe, arguments.trimmed());
+}
+
+// A BSTR for the length of a call.
+class Bstr
+{
+public:
+    explicit Bstr(const QString &s)
+        : m_s(SysAllocStringLen(reinterpret_cast<const OLECHAR *>(s.utf16()), UINT(s.size())))
+    {}
+    ~Bstr() { SysFreeString(m_s); }
+    Bstr(const Bstr &) = delete;
+    Bstr &operator=(const Bstr &) = delete;
+    operator BSTR() const { return m_s; }
+
+private:
+    BSTR m_s;
+};
+
+// COM for the calling thread, if it hasn't got it already: the GUI thread
+// has (Qt's OleInitialize), a worker thread may not.
+class ComScope
+{
+public:
+    ComScope() : m_hr(CoInitializeEx(nullptr, COINIT_MULTITHREADED)) {}
+    ~ComScope()
+    {
+        if (SUCCEEDED(m_hr))
+            CoUninitialize();
+    }
+    ComScope(const ComScope &) = delete;
+    ComScope &operator=(const ComScope &) = delete;
+
+private:
+    HRESULT m_hr;
+};
+
+// The Task Scheduler's root folder, where our task lives; nullptr if the
+// service can't be reached.
+ITaskFolder *taskFolder()
+{
+    ITaskService *service = nullptr;
+    if (FAILED(CoCreateInstance(CLSID_TaskScheduler, nullptr, CLSCTX_INPROC_SERVER,
+                                IID_ITaskService, reinterpret_cast<void **>(&service))))
+        return nullptr;
+    ITaskFolder *folder = nullptr;
+    VARIANT none;   // this PC, this user
+    VariantInit(&none);
+    if (SUCCEEDED(service->Connect(none, none, none, none)))
+        service->GetFolder(Bstr(QStringLiteral("\\")), &folder);
+    service->Release();
+    return folder;
+}
+
+// Our logon task's definition as Task Scheduler XML; empty if there's none.
+QString taskXml()
+{
+    ITaskFolder *folder = taskFolder();
+    if (!folder)
+        return QString();
+    QString xml;
+    IRegisteredTask *task = nullptr;
+    if (SUCCEEDED(folder->GetTask(Bstr(QString::fromLatin1(kTaskName)), &task)) && task) {
+        BSTR s = nullptr;
+        if (SUCCEEDED(task->get_Xml(&s)) && s)
+            xml = QString::fromWCharArray(s, int(SysStringLen(s)));
+        SysFreeString(s);
+        task->Release();
+    }
+    folder->Release();
+    return xml;
+}
+
+HRESULT registerTask(const QString &xml)
+{
+    ITaskFolder *folder = taskFolder();
+    if (!folder)
+        return E_FAIL;
+    VARIANT none;
+    VariantInit(&none);
+    IRegisteredTask *task = nullptr;
+    const HRESULT hr = folder->RegisterTask(Bstr(QString::fromLatin1(kTaskName)), Bstr(xml),
+                                            TASK_CREATE_OR_UPDATE, none, none,
+                                            TASK_LOGON_INTERACTIVE_TOKEN, none, &task);
+    if (task)
+        task->Release();
+    folder->Release();
+    return hr;
+}
+
+// Registering a task that runs as administrator needs administrator rights
+// itself: a copy of PinIt started through a UAC prompt (ShellExecute
+// "runas") registers it with --register-task.
+bool registerTaskElevated(const QString &xml)
+{
+    QTemporaryFile file(QDir::temp().filePath(QStringLiteral("pinit-task-XXXXXX.xml")));
+    if (!file.open())
+        return false;
+    file.write(xml.toUtf8());
+    file.close();
+
+    const std::wstring exe =
+        QDir::toNativeSeparators(QCoreApplication::applicationFilePath()).toStdWString();
+    const std::wstring params =
+        QStringLiteral("--register-task \"%1\"")
+            .arg(QDir::toNativeSeparators(file.fileName()))
+            .toStdWString();
+    SHELLEXECUTEINFOW info = {};
+    info.cbSize       = sizeof info;
+    info.fMask        = SEE_MASK_NOCLOSEPROCESS;
+    info.lpVerb       = L"runas";
+    info.lpFile       = exe.c_str();
+    info.lpParameters = params.c_str();
+    info.nShow        = SW_HIDE;
+    if (!ShellExecuteExW(&info) || !info.hProcess)
+        return false;   // includes the user declining the UAC prompt
+    WaitForSingleObject(info.hProcess, 10000);
+    DWORD code = DWORD(-1);
+    GetExitCodeProcess(info.hProcess, &code);
+    CloseHandle(info.hProcess);
+    return code == 0;
+}
+
+bool setRunKey(const QString &value)
+{
+    QSettings run(QString::fromLatin1(kRunKey), QSettings::NativeFormat);
+    if (value.isEmpty())
+        run.remove(QStringLiteral("PinIt"));
+    else
+        run.setValue(QStringLiteral("PinIt"), value);
+    run.sync();
+    return run.status() == QSettings::NoError;
+}
+
+// The exe in a Run-key command line: quoted, or up to the first space.
+QString exeOf(const QString &command)
+{
+    const QString c = command.trimmed();
+    if (c.startsWith(QLatin1Char('"')))
+        return c.mid(1, c.indexOf(QLatin1Char('"'), 1) - 1);
+    return c.section(QLatin1Char(' '), 0, 0);
+}
+
+bool sameFile(const QString &a, const QString &b)
+{
+    return QDir::cleanPath(QDir::fromNativeSeparators(a))
+               .compare(QDir::cleanPath(QDir::fromNativeSeparators(b)), Qt::CaseInsensitive)
+           == 0;
+}
+
+// The <Command> of our logon task.
+QString taskExe()
+{
+    const QString xml = taskXml();
+    const int from = xml.indexOf(QLatin1String("<Command>"));
+    const int to = xml.indexOf(QLatin1String("</Command>"));
+    if (from < 0 || to < from)
+        return QString();
+    const int start = from + int(qstrlen("<Command>"));
+    // Just the exe (arguments have an element of their own), unquoted.
+    QString exe = xml.mid(start, to - start).trimmed().replace(QLatin1String("&amp;"),
+                                                               QLatin1String("&"));
+    if (exe.startsWith(QLatin1Char('"')))
+        exe = exeOf(exe);
+    return exe;
+}
+
+bool taskExists()
+{
+    return !taskXml().isEmpty();
+}
+
+// Never elevated: a task PinIt registered to run as administrator is removed
+// by that PinIt, which runs as administrator itself. Any other PinIt gets
+// Access denied and says so, rather than popping up a UAC prompt just to
+// remove it.
+bool removeTask()
+{
+    ITaskFolder *folder = taskFolder();
+    if (!folder)
+        return false;
+    const HRESULT hr = folder->DeleteTask(Bstr(QString::fromLatin1(kTaskName)), 0);
+    folder->Release();
+    if (SUCCEEDED(hr) || hr == HRESULT_FROM_WIN32(ERROR_FILE_NOT_FOUND))
+        return true;
+    qWarning("Couldn't remove the logon task (0x%08lx)%s", static_cast<unsigned long>(hr),
+             hr == E_ACCESSDENIED ? "; it runs as administrator, so only an administrator "
+                                    "PinIt can remove it"
+                                  : "");
+    return false;
+}
+
+// A logon task for the current user only, described in Task Scheduler XML.
+const char *const kTaskXml = R"xml(<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <Triggers>
+    <LogonTrigger>
+      <UserId>%1</UserId>
+      <Delay>PT%2S</Delay>
+    </LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      <UserId>%1</UserId>
+      <LogonType>InteractiveToken</LogonType>
+      <RunLevel>%5</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      <Command>%3</Command>
+      <Arguments>%4</Arguments>
+    </Exec>
+  </Actions>
+</Task>
+)xml";
+
+bool createTask(const autostart::Mode &mode)
+{
+    const QString user = QStringLiteral("%1\\%2").arg(qEnvironmentVariable("USERDOMAIN"),
+                                                      qEnvironmentVariable("USERNAME"));
+    const QString exe = QDir::toNativeSeparators(QCoreApplication::applicationFilePath());
+    const QString xml = QString::fromLatin1(kTaskXml)
+                            .arg(user.toHtmlEscaped())
+                            .arg(mode.delaySeconds)
+                            .arg(exe.toHtmlEscaped(), mode.arguments.trimmed().toHtmlEscaped(),
+                                 mode.elevated ? QStringLiteral("HighestAvailable")
+                                               : QStringLiteral("LeastPrivilege"));
+
+    const HRESULT hr = registerTask(xml);
+    if (hr == E_ACCESSDENIED && mode.elevated)
+        return registerTaskElevated(xml);
+    if (FAILED(hr))
+        qWarning("Couldn't register the logon task (0x%08lx)", static_cast<unsigned long>(hr));
+    return SUCCEEDED(hr);
+}
+
+} // namespace
+
+namespace autostart {
+
+bool apply(const Mode &mode)
+{
+    const ComScope com;
+    bool ok;
+    if (!mode.enabled) {
+        ok = setRunKey(QString()) && removeTask();
+    } else if (usesTask(mode)) {
+        ok = createTask(mode) && setRunKey(QString());
+    } else {
+        ok = setRunKey(command(mode.arguments)) && removeTask();
+    }
+    if (!ok)
+        qWarning("[%s] Couldn't update the login entry (delay %ds)",
+                 qUtf8Printable(errorCode(PinError::AutostartFailed)), mode.delaySeconds);
+    return ok;
+}
+
+bool usesTask(const Mode &mode)
+{
+    return mode.backend == Backend::TaskScheduler || mode.delaySeconds > 0 || mode.elevated;
+}
+
+QString registeredExe()
+{
+    const ComScope com;
+    QSettings run(QString::fromLatin1(kRunKey), QSettings::NativeFormat);
+    const QString value = run.value(QStringLiteral("PinIt")).toString();
+    return value.isEmpty() ? taskExe() : exeOf(value);
+}
+
+Drift heal(const Mode &mode)
+{
+    if (!mode.enabled)
+        return Drift::None;
+    const QString registeredPath = registeredExe();
+    const QString exe = QCoreApplication::applicationFilePath();
+    if (registeredPath.isEmpty() || sameFile(registeredPath, exe))
+        return Drift::None;
+    qInfo("Login entry starts %s, not %s", qUtf8Printable(registeredPath),
+          qUtf8Printable(QDir::toNativeSeparators(exe)));
+    if (usesTask(mode) && mode.elevated)
+        return Drift::NeedsUser;
+    return apply(mode) ? Drift::Healed : Drift::NeedsUser;
+}
+
+bool registerTaskFile(const QString &xmlFile)
+{
+    QFile file(xmlFile);
+    if (!file.open(QIODevice::ReadOnly))
+        return false;
+    const ComScope com;
+    const HRESULT hr = registerTask(QString::fromUtf8(file.readAll()));
+    if (FAILED(hr))
+        qWarning("Couldn't register the logon task (0x%08lx)", static_cast<unsigned long>(hr));
+    return SUCCEEDED(hr);
+}
+
+QString registered()
+{
+    QSettings run(QString::fromLatin1(kRunKey), QSettings::NativeFormat);
+    const QString value = run.value(QStringLiteral("PinIt")).toString();
+    if (!value.isEmpty())
+        return value;
+    const ComScope com;
+    return taskExists() ? QStringLiteral("Task Scheduler: \"%1\"").arg(QLatin1String(kTaskName))
+                        : QString();
+}
+
+} // namespace autostart
@@ -0,0 +1,62 @@
+#pragma once
+//
+// autostart — start PinIt when the user logs in.
+//
+// By default this is the per-user Run key, as before. The Run key can't wait
+// or run elevated, so a delayed or administrator start — or choosing the Task
+// Scheduler backend outright — registers a logon task instead, through the
+// Task Scheduler's COM API. Switching between the two removes the other
+// entry, which is also how an existing Run-key setup migrates, so PinIt never
+// starts twice.
+//
+// Only registering an administrator task asks for a UAC prompt; removing one
+// never does (see apply()). Every call here is safe off the GUI thread.
+//
+#include <QString>
+
+namespace autostart {
+
+enum class Backend { RunKey, TaskScheduler };
+
+struct Mode {
+    bool    enabled = false;
+    Backend backend = Backend::RunKey;
+    int     delaySeconds = 0;                          // 0 = straight away (Run key)
+    QString arguments = QStringLiteral("--minimized");  // start silently in the tray
+    // Start as administrator so PinIt can pin elevated windows (UIPI blocks a
+    // normal PinIt). Needs a UAC prompt to register.
+    bool    elevated = false;
+};
+
+// Whether mode ends up as a scheduled task rather than the Run key.
+bool usesTask(const Mode &mode);
+
+// Register (or remove) the login entry. False if Windows refused the write —
+// including removing an administrator task from a PinIt that isn't running
+// as administrator.
+bool apply(const Mode &mode);
+
+// Human-readable description of what is actually registered right now, or an
+// empty string if nothing is — used by Diagnostics to spot drift.
+QString registered();
+
+// The exe the login entry starts; empty if there's none (or it can't be read).
+QString registeredExe();
+
+enum class Drift {
+    None,        // nothing registered, or it already starts this exe
+    Healed,      // it started another path and now starts this one
+    NeedsUser,   // it starts another path and couldn't be fixed quietly
+};
+
+// Run at startup: if the login entry starts a different PinIt.exe than this
+// one — the portable folder was renamed, an update moved the install — point
+// it here again. An elevated task isn't re-created, since that would pop up a
+// UAC prompt out of nowhere; that's NeedsUser.
+Drift heal(const Mode &mode);
+
+// `PinIt.exe --register-task <file>`: what apply() runs through a UAC prompt
+// to register an administrator task, described in the XML file.
+bool registerTaskFile(const QString &xmlFile);
+
+} // namespace autostart
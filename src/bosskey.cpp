@@ -0,0 +1,77 @@
+#include "bosskey.h"
+#include "persistence.h"
+#include "pinmanager.h"
+#include "winpin.h"
+
+namespace {
+inline void *H(intptr_t h) { return reinterpret_cast<void *>(h); }
+} // namespace
+
+BossKey::BossKey(PinManager *manager, QObject *parent)
+    : QObject(parent)
+    , m_manager(manager)
+{
+    // Hidden by a PinIt that never got to show them again.
+    const QVector<persistence::HiddenWindow> left = persistence::load().hidden;
+    if (left.isEmpty())
+        return;
+    int shown = 0;
+    for (const persistence::HiddenWindow &h : left) {
+        void *hwnd = H(static_cast<intptr_t>(h.hwnd));
+        if (winpin::isValidWindow(hwnd) && winpin::processId(hwnd) == h.pid
+            && winpin::setVisible(hwnd, true))
+            ++shown;
+    }
+    qInfo("Boss key: showed %d window(s) left hidden last time", shown);
+    persistence::saveHiddenWindows({});
+}
+
+BossKey::~BossKey()
+{
+    restore();
+}
+
+void BossKey::toggle()
+{
+    if (isActive()) {
+        restore();
+        return;
+    }
+
+    for (const PinnedWindow &w : m_manager->pinnedWindows()) {
+        // Already minimized/hidden windows are left as they are, and so are
+        // not brought back later either.
+        if (winpin::isMinimized(H(w.hwnd)))
+            continue;
+        if (winpin::setVisible(H(w.hwnd), false))
+            m_hidden.push_back(w.hwnd);
+    }
+    qInfo("Boss key: hid %d pinned window(s)", int(m_hidden.size()));
+    if (!m_hidden.isEmpty()) {
+        save();
+        emit toggled(true, int(m_hidden.size()));
+    }
+}
+
+void BossKey::restore()
+{
+    if (m_hidden.isEmpty())
+        return;
+    int shown = 0;
+    for (intptr_t hwnd : m_hidden) {
+        if (winpin::isValidWindow(H(hwnd)) && winpin::setVisible(H(hwnd), true))
+            ++shown;
+    }
+    m_hidden.clear();
+    save();
+    qInfo("Boss key: showed %d window(s) again", shown);
+    emit toggled(false, shown);
+}
+
+void BossKey::save() const
+{
+    QVector<persistence::HiddenWindow> hidden;
+    for (intptr_t hwnd : m_hidden)
+        hidden.push_back({qint64(hwnd), winpin::processId(H(hwnd))});
+    persistence::saveHiddenWindows(hidden);
+}
@@ -0,0 +1,38 @@
+#pragma once
+//
+// BossKey — hide every pinned window at once, and bring back exactly those
+// windows on the next press. Hiding (SW_HIDE) rather than minimizing keeps
+// them off the taskbar too. The hidden set is remembered here, not derived
+// from the pins, so a window unpinned (or pinned) in between is still handled
+// correctly, and anything still hidden is shown again when PinIt quits. The
+// set is also saved (persistence::HiddenWindow), so windows a crashed PinIt
+// left hidden come back when it starts again.
+//
+#include <QObject>
+#include <QVector>
+#include <cstdint>
+
+class PinManager;
+
+class BossKey : public QObject
+{
+    Q_OBJECT
+public:
+    explicit BossKey(PinManager *manager, QObject *parent = nullptr);
+    ~BossKey() override;
+
+    bool isActive() const { return !m_hidden.isEmpty(); }
+
+public slots:
+    void toggle();
+    void restore();   // show whatever we hid; no-op if nothing is hidden
+
+signals:
+    void toggled(bool hidden, int count);
+
+private:
+    void save() const;
+
+    PinManager        *m_manager = nullptr;
+    QVector<intptr_t>  m_hidden;
+};
@@ -0,0 +1,63 @@
+#include "capture.h"
+
+#include <windows.h>
+
+#ifndef PW_RENDERFULLCONTENT
+#define PW_RENDERFULLCONTENT 0x00000002
+#endif
+
+namespace {
+
+inline HWND H(void *hwnd) { return reinterpret_cast<HWND>(hwnd); }
+
+} // namespace
+
+namespace capture {
+
+QImage windowImage(void *hwnd)
+{
+    RECT r;
+    if (!GetWindowRect(H(hwnd), &r) || IsIconic(H(hwnd)))
+        return QImage();
+    const int w = r.right - r.left, h = r.bottom - r.top;
+    if (w <= 0 || h <= 0)
+        return QImage();
+
+    BITMAPINFO bi = {};
+    bi.bmiHeader.biSize = sizeof(bi.bmiHeader);
+    bi.bmiHeader.biWidth = w;
+    bi.bmiHeader.biHeight = -h;   // top-down, like QImage
+    bi.bmiHeader.biPlanes = 1;
+    bi.bmiHeader.biBitCount = 32;
+    bi.bmiHeader.biCompression = BI_RGB;
+
+    HDC screen = GetDC(nullptr);
+    HDC dc = CreateCompatibleDC(screen);
+    void *bits = nullptr;
+    HBITMAP bmp = CreateDIBSection(screen, &bi, DIB_RGB_COLORS, &bits, nullptr, 0);
+    QImage image;
+    if (dc && bmp) {
+        HGDIOBJ old = SelectObject(dc, bmp);
+        if (PrintWindow(H(hwnd), dc, PW_RENDERFULLCONTENT)) {
+            GdiFlush();
+            // The alpha channel PrintWindow leaves behind is meaningless.
+            image = QImage(static_cast<const uchar *>(bits), w, h, w * 4, QImage::Format_RGB32)
+                        .copy();
+        }
+        SelectObject(dc, old);
+    }
+    if (bmp)
+        DeleteObject(bmp);
+    if (dc)
+        DeleteDC(dc);
+    ReleaseDC(nullptr, screen);
+    return image;
+}
+
+bool saveWindow(void *hwnd, const QString &path)
+{
+    const QImage image = windowImage(hwnd);
+    return !image.isNull() && image.save(path);
+}
+
+} // namespace capture
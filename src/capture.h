@@ -0,0 +1,17 @@
+#pragma once
+//
+// capture — grab an image of a single window, even one that's partly covered
+// or off-screen (PrintWindow with PW_RENDERFULLCONTENT, which also works for
+// DirectX/browser content on Windows 8.1 and later).
+//
+// QtGui, like windowicon, so it stays out of the Core-only winpin.
+//
+#include <QImage>
+#include <QString>
+
+namespace capture {
+
+QImage windowImage(void *hwnd);                       // null on failure
+bool   saveWindow(void *hwnd, const QString &path);   // PNG (or by the suffix)
+
+} // namespace capture
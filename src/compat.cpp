@@ -0,0 +1,39 @@
+#include "compat.h"
+
+namespace compat {
+
+const QHash<QString, persistence::AppCompat> &builtIn()
+{
+    static const QHash<QString, persistence::AppCompat> table = [] {
+        persistence::AppCompat opaque;
+        opaque.noLayering = true;
+        persistence::AppCompat settles;   // re-creates its frame just after showing
+        settles.reassertAfterMs = 500;
+        return QHash<QString, persistence::AppCompat>{
+            {QStringLiteral("mstsc.exe"), opaque},       // Remote Desktop Connection
+            {QStringLiteral("msrdc.exe"), opaque},       // Remote Desktop client
+            {QStringLiteral("vmconnect.exe"), opaque},   // Hyper-V
+            {QStringLiteral("vmware.exe"), opaque},
+            {QStringLiteral("virtualboxvm.exe"), opaque},
+            {QStringLiteral("discord.exe"), settles},
+            {QStringLiteral("slack.exe"), settles},
+            {QStringLiteral("ms-teams.exe"), settles},
+        };
+    }();
+    return table;
+}
+
+QHash<QString, persistence::AppCompat> table(const QHash<QString, persistence::AppCompat> &user,
+                                             const QStringList &noLayeringApps)
+{
+    QHash<QString, persistence::AppCompat> out = builtIn();
+    for (auto it = user.begin(); it != user.end(); ++it)
+        out.insert(it.key().toLower(), it.value());
+    for (const QString &app : noLayeringApps) {
+        if (!app.trimmed().isEmpty())
+            out[app.trimmed().toLower()].noLayering = true;
+    }
+    return out;
+}
+
+} // namespace compat
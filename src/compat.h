@@ -0,0 +1,30 @@
+#pragma once
+//
+// compat — workarounds for apps that don't get along with the way PinIt pins
+// windows (see persistence::AppCompat):
+//
+//   no_layering        never made layered, so its pins stay opaque — remote
+//                      desktop and VM viewers go black or crawl otherwise
+//   enforcement        the re-enforcement policy its pins start with
+//   reassert_after_ms  pin again this long after pinning, for apps that drop
+//                      topmost while their window settles
+//
+// A small built-in table, keyed by lower-cased exe name; the app_compat
+// setting adds apps, and an entry there replaces the built-in one outright.
+// Its keys may be wildcards ("mstsc*.exe"). The older no_layering_apps list
+// still works: each name or wildcard on it gets no_layering on top.
+//
+#include <QHash>
+#include <QString>
+
+#include "persistence.h"
+
+namespace compat {
+
+const QHash<QString, persistence::AppCompat> &builtIn();
+// builtIn() with user's entries, then noLayeringApps, laid over it; what
+// PinManager consults.
+QHash<QString, persistence::AppCompat> table(const QHash<QString, persistence::AppCompat> &user,
+                                             const QStringList &noLayeringApps = {});
+
+} // namespace compat
@@ -0,0 +1,656 @@
+#include "configschema.h"
+#include "actions.h"
+#include "macros.h"
+#include "persistence.h"
+#include "shortcuts.h"
+#include "triggers.h"
+
+#include <QCoreApplication>
+#include <QJsonArray>
+#include <QJsonDocument>
+#include <QJsonParseError>
+#include <QStringList>
+#include <QTime>
+
+#include <algorithm>
+
+namespace {
+
+using configschema::Issue;
+
+QString tr(const char *s)
+{
+    return QCoreApplication::translate("configschema", s);
+}
+
+QString child(const QString &path, const QString &key)
+{
+    return path.isEmpty() ? key : path + QLatin1Char('.') + key;
+}
+
+QString entry(const QString &path, const QString &key)
+{
+    return path + QStringLiteral("[\"") + key + QStringLiteral("\"]");
+}
+
+QString item(const QString &path, int i)
+{
+    return path + QStringLiteral("[%1]").arg(i);
+}
+
+QString quoted(const QString &s)
+{
+    return QStringLiteral("“%1”").arg(s);
+}
+
+QString describe(const QJsonValue &v)
+{
+    switch (v.type()) {
+    case QJsonValue::Null:      return QStringLiteral("null");
+    case QJsonValue::Bool:      return QLatin1String(v.toBool() ? "true" : "false");
+    case QJsonValue::Double:    return QString::number(v.toDouble());
+    case QJsonValue::String:    return QLatin1Char('"') + v.toString() + QLatin1Char('"');
+    case QJsonValue::Array:     return tr("an array");
+    case QJsonValue::Object:    return tr("an object");
+    case QJsonValue::Undefined: break;
+    }
+    return QString();
+}
+
+int editDistance(const QString &a, const QString &b)
+{
+    QVector<int> row(b.size() + 1);
+    for (int j = 0; j <= b.size(); ++j)
+        row[j] = j;
+    for (int i = 1; i <= a.size(); ++i) {
+        int diagonal = row[0];
+        row[0] = i;
+        for (int j = 1; j <= b.size(); ++j) {
+            const int above = row[j];
+            const int cost = a[i - 1].toLower() == b[j - 1].toLower() ? 0 : 1;
+            row[j] = std::min({row[j] + 1, row[j - 1] + 1, diagonal + cost});
+            diagonal = above;
+        }
+    }
+    return row[b.size()];
+}
+
+// The candidate a typo most likely meant, or empty if none is close.
+QString closest(const QString &s, const QStringList &candidates)
+{
+    QString best;
+    int bestDistance = qMax(1, qMin(3, int(s.size()) / 3)) + 1;
+    for (const QString &c : candidates) {
+        const int d = editDistance(s, c);
+        if (d < bestDistance) {
+            best = c;
+            bestDistance = d;
+        }
+    }
+    return best;
+}
+
+QStringList actionIds()
+{
+    QStringList ids;
+    for (const ActionInfo &a : ActionRegistry::catalog())
+        ids << a.id;
+    return ids;
+}
+
+// A scalar setting: its key and what it may hold.
+struct Field {
+    enum Kind { Bool, Int, String, Choice, Shortcut } kind;
+    const char *key;
+    int         min = 0;
+    int         max = 0;
+    QStringList choices = {};
+};
+
+class Checker
+{
+public:
+    QVector<Issue> issues;
+
+    void add(const QString &path, const QString &expected, const QJsonValue &found,
+             const QString &suggestion)
+    {
+        issues.push_back({path, expected, describe(found), suggestion});
+    }
+
+    bool object(const QString &path, const QJsonValue &v)
+    {
+        if (v.isObject())
+            return true;
+        add(path, tr("an object { … }"), v, tr("the whole section is ignored until it is one"));
+        return false;
+    }
+
+    bool array(const QString &path, const QJsonValue &v)
+    {
+        if (v.isArray())
+            return true;
+        add(path, tr("an array [ … ]"), v, tr("the whole list is ignored until it is one"));
+        return false;
+    }
+
+    void boolean(const QString &path, const QJsonValue &v)
+    {
+        if (v.isBool())
+            return;
+        const QString s = v.toString().toLower();
+        add(path, tr("true or false"), v,
+            s == QLatin1String("true") || s == QLatin1String("false")
+                ? tr("write %1 without quotes").arg(s)
+                : tr("the default is used instead"));
+    }
+
+    void integer(const QString &path, const QJsonValue &v, int min, int max)
+    {
+        const QString expected = tr("a whole number from %1 to %2").arg(min).arg(max);
+        bool ok = false;
+        const int asText = v.toString().toInt(&ok);
+        if (v.isString() && ok) {
+            add(path, expected, v, tr("write %1 without quotes").arg(asText));
+        } else if (!v.isDouble() || v.toDouble() != double(v.toInt())) {
+            add(path, expected, v, tr("the default is used instead"));
+        } else if (v.toInt() < min || v.toInt() > max) {
+            add(path, expected, v,
+                tr("use %1").arg(std::clamp(v.toInt(), min, max)));
+        }
+    }
+
+    bool string(const QString &path, const QJsonValue &v)
+    {
+        if (v.isString())
+            return true;
+        add(path, tr("a string in \"quotes\""), v, tr("the default is used instead"));
+        return false;
+    }
+
+    void required(const QString &path, const QJsonObject &o, const char *key)
+    {
+        if (!o.value(QLatin1String(key)).toString().isEmpty())
+            return;
+        add(child(path, QLatin1String(key)), tr("a non-empty string"), o.value(QLatin1String(key)),
+            tr("the entry is skipped without it"));
+    }
+
+    void choice(const QString &path, const QJsonValue &v, const QStringList &choices)
+    {
+        if (!string(path, v) || choices.contains(v.toString()))
+            return;
+        const QString guess = closest(v.toString(), choices);
+        add(path, tr("one of %1").arg(choices.join(QStringLiteral(", "))), v,
+            guess.isEmpty() ? tr("the default is used instead")
+                            : tr("did you mean %1?").arg(quoted(guess)));
+    }
+
+    void shortcut(const QString &path, const QJsonValue &v)
+    {
+        if (!string(path, v))
+            return;
+        const QString s = v.toString();
+        if (shortcuts::check({s}) == shortcuts::Problem::None)
+            return;
+        add(path, tr("a shortcut like \"super+ctrl+KeyT\" or a chord like \"ctrl+alt+KeyP,KeyO\""),
+            v, tr("join modifiers (super, ctrl, alt, shift) and one key (KeyA–KeyZ, "
+                  "Digit0–Digit9, Equal, Minus) with +"));
+    }
+
+    void time(const QString &path, const QJsonValue &v)
+    {
+        if (!string(path, v))
+            return;
+        if (!QTime::fromString(v.toString(), QStringLiteral("HH:mm")).isValid())
+            add(path, tr("a 24-hour time like \"09:30\""), v, tr("use two digits each, HH:mm"));
+    }
+
+    void rect(const QString &path, const QJsonValue &v)
+    {
+        const QJsonArray a = v.toArray();
+        const bool ok = v.isArray() && a.size() == 4
+                        && std::all_of(a.begin(), a.end(),
+                                       [](const QJsonValue &n) { return n.isDouble(); });
+        if (!ok)
+            add(path, tr("[x, y, width, height]"), v, tr("it is ignored"));
+    }
+
+    void stringList(const QString &path, const QJsonValue &v)
+    {
+        if (!array(path, v))
+            return;
+        const QJsonArray a = v.toArray();
+        for (int i = 0; i < a.size(); ++i)
+            string(item(path, i), a[i]);
+    }
+
+    // ["action-id", "arg", …] — an action from the registry, or a macro's wait.
+    void action(const QString &path, const QJsonValue &v, bool allowWait)
+    {
+        const QJsonArray words = v.toArray();
+        if (!v.isArray() || words.isEmpty() || !words[0].isString()) {
+            add(path, tr("[\"action-id\", arguments…]"), v,
+                tr("PinIt.exe --list-actions prints the ids"));
+            return;
+        }
+        for (int i = 1; i < words.size(); ++i)
+            string(item(path, i), words[i]);
+        const QString id = words[0].toString();
+        if (allowWait && id == QLatin1String("wait")) {
+            // Whatever the macro editor accepts.
+            const persistence::MacroStep step{id, {words.at(1).toString()}};
+            QString error;
+            const bool ok = words.size() == 2
+                            && !macros::parseSteps(macros::formatSteps({step}), &error).isEmpty();
+            if (!ok)
+                add(item(path, 1), tr("milliseconds from 0 to 60000"), words.at(1),
+                    tr("write it as a string, e.g. [\"wait\", \"500\"]"));
+            return;
+        }
+        if (ActionRegistry::isKnown(id))
+            return;
+        const QString guess = closest(id, actionIds());
+        add(item(path, 0), tr("an action id"), words[0],
+            guess.isEmpty() ? tr("PinIt.exe --list-actions prints the ids")
+                            : tr("did you mean %1?").arg(quoted(guess)));
+    }
+
+    void fields(const QString &path, const QJsonObject &o, const QVector<Field> &spec)
+    {
+        for (const Field &f : spec) {
+            const QJsonValue v = o.value(QLatin1String(f.key));
+            if (v.isUndefined())
+                continue;
+            const QString at = child(path, QLatin1String(f.key));
+            switch (f.kind) {
+            case Field::Bool:     boolean(at, v); break;
+            case Field::Int:      integer(at, v, f.min, f.max); break;
+            case Field::String:   string(at, v); break;
+            case Field::Choice:   choice(at, v, f.choices); break;
+            case Field::Shortcut: shortcut(at, v); break;
+            }
+        }
+    }
+
+    // Keys PinIt doesn't read at all — usually a typo of one it does.
+    void unknownKeys(const QString &path, const QJsonObject &o, const QStringList &known)
+    {
+        for (auto it = o.begin(); it != o.end(); ++it) {
+            if (known.contains(it.key()))
+                continue;
+            const QString guess = closest(it.key(), known);
+            issues.push_back({child(path, it.key()), tr("a known setting"), QString(),
+                              guess.isEmpty() ? tr("PinIt ignores it; remove it")
+                                              : tr("did you mean %1?").arg(quoted(guess))});
+        }
+    }
+};
+
+QStringList keysOf(const QVector<Field> &spec, QStringList extra = {})
+{
+    for (const Field &f : spec)
+        extra << QLatin1String(f.key);
+    return extra;
+}
+
+void checkShortcuts(Checker &c, const QString &path, const QJsonObject &o)
+{
+    static const QVector<Field> spec = {
+        {Field::Shortcut, "toggle_pin"},      {Field::Shortcut, "opacity_up"},
+        {Field::Shortcut, "opacity_down"},    {Field::Shortcut, "toggle_window"},
+        {Field::Shortcut, "pin_under_cursor"}, {Field::Shortcut, "quick_panel"},
+        {Field::Shortcut, "hide_all_pinned"}, {Field::Shortcut, "pin_temporarily"},
+        {Field::Shortcut, "kiosk_exit"},
+        {Field::Shortcut, "toggle_last_pinned"}, {Field::Shortcut, "focus_last_pinned"},
+        {Field::Shortcut, "pin_with_opacity"},  {Field::Shortcut, "opacity_slider"},
+        {Field::Shortcut, "slot_1"},          {Field::Shortcut, "slot_2"},
+        {Field::Shortcut, "slot_3"},          {Field::Shortcut, "assign_slot_1"},
+        {Field::Shortcut, "assign_slot_2"},   {Field::Shortcut, "assign_slot_3"},
+        {Field::Bool, "toggle_pin_hold"},
+        {Field::Choice, "backend", 0, 0, {"register_hotkey", "keyboard_hook"}},
+    };
+    c.fields(path, o, spec);
+    c.unknownKeys(path, o, keysOf(spec));
+
+    // Two actions on one combo: only the first would ever get it. Bindings
+    // that are bad on their own were reported above and are left out here.
+    QStringList keys, bindings;
+    for (const Field &f : spec) {
+        const QJsonValue v = o.value(QLatin1String(f.key));
+        if (f.kind == Field::Shortcut && v.isString()
+            && shortcuts::check({v.toString()}) == shortcuts::Problem::None) {
+            keys << QLatin1String(f.key);
+            bindings << v.toString();
+        }
+    }
+    int at = -1;
+    const shortcuts::Problem p = shortcuts::check(bindings, &at);
+    if (p == shortcuts::Problem::Duplicate || p == shortcuts::Problem::ChordPrefix)
+        c.issues.push_back({child(path, keys[at]),
+                            p == shortcuts::Problem::Duplicate
+                                ? tr("a shortcut no other action uses")
+                                : tr("a shortcut that doesn't start another action's chord"),
+                            describe(bindings[at]), tr("pick a different key")});
+}
+
+void checkSettings(Checker &c, const QString &path, const QJsonObject &o)
+{
+    static const QVector<Field> spec = {
+        {Field::Bool, "enable_sound"},
+        {Field::Bool, "has_seen_tray_notice"},   // before onboarding; still read
+        {Field::Bool, "start_with_windows"},
+        {Field::Int, "autostart_delay", 0, 600},
+        {Field::String, "autostart_args"},
+        {Field::Choice, "autostart_backend", 0, 0, {"run_key", "task_scheduler"}},
+        {Field::Bool, "autostart_elevated"},
+        {Field::Bool, "usage_stats"},
+        {Field::Bool, "audit_log"},
+        {Field::String, "audit_log_path"},
+        {Field::Bool, "event_log"},
+        {Field::Bool, "keep_on_top"},
+        {Field::Bool, "animate_opacity"},
+        {Field::Int, "opacity_fade_ms", 0, 5000},
+        {Field::String, "language"},
+        {Field::Choice, "tray_icon_theme", 0, 0, {"auto", "light", "dark"}},
+        {Field::Choice, "pin_feedback", 0, 0, {"none", "flash", "border-pulse"}},
+        {Field::Choice, "backdrop", 0, 0, {"none", "blur", "acrylic"}},
+        {Field::String, "title_marker"},
+        {Field::Bool, "osd"},
+        {Field::Choice, "osd_position", 0, 0, {"window", "screen"}},
+        {Field::Int, "osd_duration_ms", 0, 60000},
+        {Field::Int, "temporary_pin_seconds", 1, 7 * 24 * 3600},
+        {Field::Int, "pin_opacity_percent", 20, 100},
+        {Field::Int, "idle_unpin_minutes", 0, 7 * 24 * 60},
+        {Field::Bool, "kiosk"},
+        {Field::String, "kiosk_path"},
+        {Field::Choice, "coexistence", 0, 0, {"defer", "override", "ask"}},
+        {Field::Choice, "topmost_lost", 0, 0, {"restore", "mark"}},
+        {Field::Bool, "corner_badge"},
+        {Field::Bool, "badge_click_unpin"},
+        {Field::Bool, "pin_owned_windows"},
+        {Field::Bool, "uia_matching"},
+        {Field::Bool, "remote_no_opacity"},
+    };
+    c.fields(path, o, spec);
+    c.unknownKeys(path, o,
+                  keysOf(spec, {"shortcuts", "protected_process_choices", "compact_rules",
+                                "macros", "schedules", "triggers", "pin_slots",
+                                "no_layering_apps", "app_compat", "onboarding"}));
+
+    const QString sc = child(path, QStringLiteral("shortcuts"));
+    if (o.contains("shortcuts") && c.object(sc, o.value("shortcuts")))
+        checkShortcuts(c, sc, o.value("shortcuts").toObject());
+
+    const QString pc = child(path, QStringLiteral("protected_process_choices"));
+    if (o.contains("protected_process_choices")
+        && c.object(pc, o.value("protected_process_choices"))) {
+        const QJsonObject choices = o.value("protected_process_choices").toObject();
+        for (auto it = choices.begin(); it != choices.end(); ++it)
+            c.boolean(entry(pc, it.key()), it.value());
+    }
+
+    const QString cr = child(path, QStringLiteral("compact_rules"));
+    if (o.contains("compact_rules") && c.object(cr, o.value("compact_rules"))) {
+        static const QVector<Field> rule = {
+            {Field::Int, "width", 1, 7680},
+            {Field::Int, "height", 1, 4320},
+            {Field::Choice, "corner", 0, 0,
+             {"top-left", "top-right", "bottom-left", "bottom-right"}},
+        };
+        const QJsonObject rules = o.value("compact_rules").toObject();
+        for (auto it = rules.begin(); it != rules.end(); ++it) {
+            const QString at = entry(cr, it.key());
+            if (!c.object(at, it.value()))
+                continue;
+            const QJsonObject r = it.value().toObject();
+            for (const char *key : {"width", "height"}) {
+                if (!r.contains(QLatin1String(key)))
+                    c.add(child(at, QLatin1String(key)), tr("a whole number from 1 up"),
+                          QJsonValue::Undefined, tr("the rule is skipped without it"));
+            }
+            c.fields(at, r, rule);
+            c.unknownKeys(at, r, keysOf(rule));
+        }
+    }
+
+    const QString ms = child(path, QStringLiteral("macros"));
+    if (o.contains("macros") && c.array(ms, o.value("macros"))) {
+        const QJsonArray list = o.value("macros").toArray();
+        for (int i = 0; i < list.size(); ++i) {
+            const QString at = item(ms, i);
+            if (!c.object(at, list[i]))
+                continue;
+            const QJsonObject m = list[i].toObject();
+            c.required(at, m, "name");
+            if (m.contains("shortcut"))
+                c.shortcut(child(at, QStringLiteral("shortcut")), m.value("shortcut"));
+            const QString steps = child(at, QStringLiteral("steps"));
+            if (c.array(steps, m.value("steps"))) {
+                const QJsonArray a = m.value("steps").toArray();
+                for (int s = 0; s < a.size(); ++s)
+                    c.action(item(steps, s), a[s], /*allowWait=*/true);
+            }
+            c.unknownKeys(at, m, {"name", "shortcut", "steps"});
+        }
+    }
+
+    const QString ss = child(path, QStringLiteral("schedules"));
+    if (o.contains("schedules") && c.array(ss, o.value("schedules"))) {
+        static const QVector<Field> scheduleSpec = {
+            {Field::String, "name"},
+            {Field::Int, "every_minutes", 0, 24 * 60},
+            {Field::Bool, "enabled"},
+        };
+        const QJsonArray list = o.value("schedules").toArray();
+        for (int i = 0; i < list.size(); ++i) {
+            const QString at = item(ss, i);
+            if (!c.object(at, list[i]))
+                continue;
+            const QJsonObject j = list[i].toObject();
+            c.fields(at, j, scheduleSpec);
+            c.action(child(at, QStringLiteral("action")), j.value("action"), false);
+            if (j.contains("at"))
+                c.time(child(at, QStringLiteral("at")), j.value("at"));
+            const QString days = child(at, QStringLiteral("days"));
+            if (j.contains("days") && c.array(days, j.value("days"))) {
+                const QJsonArray a = j.value("days").toArray();
+                for (int d = 0; d < a.size(); ++d)
+                    c.integer(item(days, d), a[d], 1, 7);
+            }
+            c.unknownKeys(at, j, keysOf(scheduleSpec, {"action", "at", "days"}));
+        }
+    }
+
+    const QString ts = child(path, QStringLiteral("triggers"));
+    if (o.contains("triggers") && c.array(ts, o.value("triggers"))) {
+        QStringList events;
+        for (const auto &e : triggers::events())
+            events << e.first;
+        static const QVector<Field> triggerSpec = {
+            {Field::String, "name"},
+            {Field::String, "process"},
+            {Field::Bool, "enabled"},
+        };
+        const QJsonArray list = o.value("triggers").toArray();
+        for (int i = 0; i < list.size(); ++i) {
+            const QString at = item(ts, i);
+            if (!c.object(at, list[i]))
+                continue;
+            const QJsonObject j = list[i].toObject();
+            c.fields(at, j, triggerSpec);
+            c.required(at, j, "when");
+            if (j.contains("when"))
+                c.choice(child(at, QStringLiteral("when")), j.value("when"), events);
+            c.action(child(at, QStringLiteral("action")), j.value("action"), false);
+            c.unknownKeys(at, j, keysOf(triggerSpec, {"when", "action"}));
+        }
+    }
+
+    const QString nl = child(path, QStringLiteral("no_layering_apps"));
+    if (o.contains("no_layering_apps") && c.array(nl, o.value("no_layering_apps"))) {
+        const QJsonArray apps = o.value("no_layering_apps").toArray();
+        for (int i = 0; i < apps.size(); ++i)
+            c.string(item(nl, i), apps[i]);
+    }
+
+    const QString ac = child(path, QStringLiteral("app_compat"));
+    if (o.contains("app_compat") && c.object(ac, o.value("app_compat"))) {
+        static const QVector<Field> compatSpec = {
+            {Field::Bool, "no_layering"},
+            {Field::Choice, "enforcement", 0, 0, {"aggressive", "normal", "once"}},
+            {Field::Int, "reassert_after_ms", 0, 10000},
+        };
+        const QJsonObject apps = o.value("app_compat").toObject();
+        for (auto it = apps.begin(); it != apps.end(); ++it) {
+            const QString at = entry(ac, it.key());
+            if (!c.object(at, it.value()))
+                continue;
+            const QJsonObject j = it.value().toObject();
+            c.fields(at, j, compatSpec);
+            c.unknownKeys(at, j, keysOf(compatSpec));
+        }
+    }
+
+    // Step names aren't checked: a newer PinIt's steps are kept as they are.
+    const QString ob = child(path, QStringLiteral("onboarding"));
+    if (o.contains("onboarding") && c.object(ob, o.value("onboarding"))) {
+        const QJsonObject progress = o.value("onboarding").toObject();
+        for (auto it = progress.begin(); it != progress.end(); ++it)
+            c.choice(entry(ob, it.key()), it.value(), {"done", "skipped"});
+    }
+
+    const QString ps = child(path, QStringLiteral("pin_slots"));
+    if (o.contains("pin_slots") && c.object(ps, o.value("pin_slots"))) {
+        static const QVector<Field> slotSpec = {
+            {Field::String, "process_name"},
+            {Field::String, "title"},
+        };
+        QStringList numbers;
+        for (int i = 1; i <= persistence::kPinSlots; ++i)
+            numbers << QString::number(i);
+        const QJsonObject pinSlots = o.value("pin_slots").toObject();
+        for (auto it = pinSlots.begin(); it != pinSlots.end(); ++it) {
+            const QString at = entry(ps, it.key());
+            if (!numbers.contains(it.key())) {
+                c.add(at, tr("a slot number from 1 to %1").arg(persistence::kPinSlots),
+                      it.key(), tr("it is ignored"));
+                continue;
+            }
+            if (!c.object(at, it.value()))
+                continue;
+            const QJsonObject j = it.value().toObject();
+            c.required(at, j, "process_name");
+            c.fields(at, j, slotSpec);
+            c.unknownKeys(at, j, keysOf(slotSpec));
+        }
+    }
+}
+
+void checkPins(Checker &c, const QString &path, const QJsonObject &pins)
+{
+    static const QVector<Field> spec = {
+        {Field::String, "process_name"},
+        {Field::String, "title"},
+        {Field::Int, "opacity", 0, 255},
+        {Field::String, "group"},
+        {Field::Bool, "keep_when_idle"},
+        {Field::Bool, "follow_monitor"},
+        {Field::Choice, "dock_edge", 0, 0, {"left", "right", "top", "bottom"}},
+        {Field::Bool, "auto_restart"},
+        {Field::Choice, "enforcement", 0, 0, {"aggressive", "normal", "once"}},
+        {Field::String, "launch_path"},
+        {Field::String, "process_path"},
+        {Field::String, "command_line"},
+        {Field::String, "title_pattern"},
+        {Field::String, "automation_id"},
+    };
+    for (auto it = pins.begin(); it != pins.end(); ++it) {
+        const QString at = entry(path, it.key());
+        if (!c.object(at, it.value()))
+            continue;
+        const QJsonObject p = it.value().toObject();
+        c.required(at, p, "process_name");
+        c.fields(at, p, spec);
+        if (p.contains("restore_rect"))
+            c.rect(child(at, QStringLiteral("restore_rect")), p.value("restore_rect"));
+        if (p.contains("launch_args"))
+            c.stringList(child(at, QStringLiteral("launch_args")), p.value("launch_args"));
+        c.unknownKeys(at, p, keysOf(spec, {"restore_rect", "launch_args"}));
+    }
+}
+
+void checkSnapshots(Checker &c, const QString &path, const QJsonObject &snapshots)
+{
+    static const QVector<Field> spec = {
+        {Field::String, "process_name"},
+        {Field::String, "path"},
+        {Field::String, "command_line"},
+        {Field::String, "title"},
+        {Field::Int, "opacity", 0, 255},
+        {Field::String, "group"},
+    };
+    for (auto it = snapshots.begin(); it != snapshots.end(); ++it) {
+        const QString at = entry(path, it.key());
+        if (!c.array(at, it.value()))
+            continue;
+        const QJsonArray entries = it.value().toArray();
+        for (int i = 0; i < entries.size(); ++i) {
+            const QString e = item(at, i);
+            if (!c.object(e, entries[i]))
+                continue;
+            const QJsonObject o = entries[i].toObject();
+            c.required(e, o, "process_name");
+            c.fields(e, o, spec);
+            if (o.contains("rect"))
+                c.rect(child(e, QStringLiteral("rect")), o.value("rect"));
+            c.unknownKeys(e, o, keysOf(spec, {"rect"}));
+        }
+    }
+}
+
+} // namespace
+
+namespace configschema {
+
+QVector<Issue> validate(const QByteArray &json)
+{
+    QJsonParseError err;
+    const QJsonDocument doc = QJsonDocument::fromJson(json, &err);
+    if (err.error != QJsonParseError::NoError)
+        return {{QString(), tr("valid JSON"), QString(),
+                 tr("%1 at character %2; fix it, or use tray → Restore backup")
+                     .arg(err.errorString())
+                     .arg(err.offset)}};
+    if (!doc.isObject())
+        return {{QString(), tr("an object { … }"), doc.isArray() ? tr("an array") : QString(),
+                 tr("the file holds { \"pins\": …, \"settings\": … }")}};
+    return validate(doc.object());
+}
+
+QVector<Issue> validate(const QJsonObject &root)
+{
+    Checker c;
+    c.unknownKeys(QString(), root, {"pins", "settings", "snapshots", "hidden"});
+    if (root.contains("pins") && c.object(QStringLiteral("pins"), root.value("pins")))
+        checkPins(c, QStringLiteral("pins"), root.value("pins").toObject());
+    if (root.contains("settings") && c.object(QStringLiteral("settings"), root.value("settings")))
+        checkSettings(c, QStringLiteral("settings"), root.value("settings").toObject());
+    if (root.contains("snapshots")
+        && c.object(QStringLiteral("snapshots"), root.value("snapshots")))
+        checkSnapshots(c, QStringLiteral("snapshots"), root.value("snapshots").toObject());
+    if (root.contains("hidden"))   // written by the boss key, never by hand
+        c.array(QStringLiteral("hidden"), root.value("hidden"));
+    return c.issues;
+}
+
+QString format(const Issue &issue)
+{
+    const QString where = issue.path.isEmpty() ? tr("(document)") : issue.path;
+    const QString what = issue.found.isEmpty()
+                             ? tr("expected %1").arg(issue.expected)
+                             : tr("expected %1, found %2").arg(issue.expected, issue.found);
+    return QStringLiteral("%1: %2 — %3").arg(where, what, issue.suggestion);
+}
+
+} // namespace configschema
@@ -0,0 +1,35 @@
+#pragma once
+//
+// configschema — checks a saved-state document (pinned.json, or a backup of
+// it) against the format persistence reads, and says precisely what's wrong.
+// Loading stays lenient: a value of the wrong type or out of range still
+// falls back to its default, and an unknown key is still ignored. This is what
+// tells the user that happened, instead of a setting quietly not applying.
+//
+// Used on startup, when pinned.json is edited by hand, before restoring a
+// backup, and by `PinIt.exe --validate-config [file]`.
+//
+#include <QByteArray>
+#include <QJsonObject>
+#include <QString>
+#include <QVector>
+
+namespace configschema {
+
+struct Issue {
+    QString path;         // where: settings.shortcuts.toggle_pin, pins["vlc.exe:0"].opacity
+    QString expected;     // what belongs there: "a whole number from 0 to 255"
+    QString found;        // what's there instead: "\"80%\"", "an array"; empty if missing
+    QString suggestion;   // what to do: "did you mean “osd_position”?"
+};
+
+// Every problem found, in document order; empty if the document is fine. A
+// document that isn't JSON at all is one issue at path "".
+QVector<Issue> validate(const QByteArray &json);
+QVector<Issue> validate(const QJsonObject &root);
+
+// One line for logs and the command line:
+// "settings.osd_duration_ms: expected a whole number from 0 to 60000, found "fast" — …"
+QString format(const Issue &issue);
+
+} // namespace configschema
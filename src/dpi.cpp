@@ -0,0 +1,43 @@
+#include <windows.h>
+#include "dpi.h"
+
+#include <QWidget>
+
+namespace dpi {
+
+int ofWindow(void *hwnd)
+{
+    const UINT value = hwnd ? GetDpiForWindow(static_cast<HWND>(hwnd)) : 0;
+    return value ? static_cast<int>(value) : kDefault;
+}
+
+qreal scale(void *hwnd)
+{
+    return ofWindow(hwnd) / qreal(kDefault);
+}
+
+int scaled(int logicalPx, int dpi)
+{
+    return MulDiv(logicalPx, dpi, kDefault);
+}
+
+void place(QWidget *overlay, const QRect &native)
+{
+    SetWindowPos(reinterpret_cast<HWND>(overlay->winId()), nullptr, native.x(), native.y(),
+                 native.width(), native.height(),
+                 SWP_NOZORDER | SWP_NOACTIVATE | SWP_NOOWNERZORDER);
+}
+
+bool isDpiChanged(const QByteArray &eventType, void *message, int *newDpi)
+{
+    if (eventType != "windows_generic_MSG")
+        return false;
+    const MSG *msg = static_cast<MSG *>(message);
+    if (msg->message != WM_DPICHANGED)
+        return false;
+    if (newDpi)
+        *newDpi = HIWORD(msg->wParam);   // X and Y are always equal
+    return true;
+}
+
+} // namespace dpi
@@ -0,0 +1,33 @@
+#pragma once
+//
+// dpi — per-monitor DPI for the helper windows PinIt draws over other apps
+// (pin badges, the OSD, edge-dock slivers). Sizes are given in logical
+// pixels at 96 DPI and scaled to the DPI of the window they decorate, so an
+// overlay looks the same size on a 100 % and a 175 % monitor side by side.
+//
+// Qt places widgets in logical coordinates, which are ambiguous where
+// monitors of different scale meet; overlays that track another window are
+// placed in physical pixels instead (place()).
+//
+#include <QByteArray>
+#include <QRect>
+
+class QWidget;
+
+namespace dpi {
+
+constexpr int kDefault = 96;
+
+int   ofWindow(void *hwnd);              // GetDpiForWindow; kDefault if unknown
+qreal scale(void *hwnd);                 // 1.0 at 96 DPI, 1.5 at 144, …
+int   scaled(int logicalPx, int dpi);    // logical pixels → physical at dpi
+
+// Move and size one of our top-level helper widgets to a rect in physical
+// screen pixels, without activating it or changing its Z-order.
+void place(QWidget *overlay, const QRect &native);
+
+// For nativeEvent overrides: the message is WM_DPICHANGED (the widget moved
+// to a monitor of another scale); *newDpi gets the new value.
+bool isDpiChanged(const QByteArray &eventType, void *message, int *newDpi = nullptr);
+
+} // namespace dpi
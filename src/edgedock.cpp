@@ -0,0 +1,168 @@
+#include "edgedock.h"
+#include "pinmanager.h"
+#include "winpin.h"
+#include "dpi.h"
+#include "perf.h"
+
+#include <QTimer>
+
+#include <algorithm>
+
+namespace {
+
+constexpr int kSliver = 4;          // logical pixels left on screen while hidden
+constexpr int kSlideMs = 150;
+constexpr int kHideDelayMs = 600;   // grace period before sliding away again
+constexpr int kReach = 8;           // slack around the window before it counts as "away"
+                                    // (both scaled to the window's DPI)
+
+inline void *H(intptr_t h) { return reinterpret_cast<void *>(h); }
+
+QRect hiddenRect(const QRect &shown, const QRect &work, const QString &edge, int sliver)
+{
+    QRect r = shown;
+    if (edge == QLatin1String("left"))
+        r.moveRight(work.left() + sliver - 1);
+    else if (edge == QLatin1String("right"))
+        r.moveLeft(work.right() - sliver + 1);
+    else if (edge == QLatin1String("top"))
+        r.moveBottom(work.top() + sliver - 1);
+    else
+        r.moveTop(work.bottom() - sliver + 1);
+    return r;
+}
+
+} // namespace
+
+EdgeDock::EdgeDock(PinManager *manager, QObject *parent)
+    : QObject(parent)
+    , m_manager(manager)
+{
+    m_timer = new QTimer(this);
+    m_timer->setTimerType(Qt::PreciseTimer);
+    connect(m_timer, &QTimer::timeout, this, &EdgeDock::tick);
+    connect(m_manager, &PinManager::pinStateChanged, this, &EdgeDock::sync);
+    sync(m_manager->pinnedWindows());
+}
+
+EdgeDock::~EdgeDock()
+{
+    for (auto it = m_docks.cbegin(); it != m_docks.cend(); ++it)
+        release(it.key(), it.value());
+}
+
+void EdgeDock::sync(const QVector<PinnedWindow> &pins)
+{
+    QHash<intptr_t, QString> wanted;
+    for (const PinnedWindow &w : pins) {
+        if (!w.dockEdge.isEmpty())
+            wanted.insert(w.hwnd, w.dockEdge);
+    }
+
+    for (auto it = m_docks.begin(); it != m_docks.end();) {
+        if (wanted.contains(it.key())) {
+            ++it;
+            continue;
+        }
+        release(it.key(), it.value());
+        it = m_docks.erase(it);
+    }
+
+    for (auto it = wanted.cbegin(); it != wanted.cend(); ++it) {
+        auto existing = m_docks.find(it.key());
+        if (existing != m_docks.end()) {
+            if (existing->edge != it.value()) {
+                existing->edge = it.value();
+                if (existing->hidden)   // go straight to the new edge
+                    startSlide(it.key(), *existing, true);
+            }
+            continue;
+        }
+        Dock d;
+        d.edge = it.value();
+        d.shown = winpin::windowRect(H(it.key()));
+        d.work = winpin::monitorWorkArea(H(it.key()));
+        if (d.shown.isEmpty() || d.work.isEmpty())
+            continue;
+        startSlide(it.key(), m_docks.insert(it.key(), d).value(), true);
+    }
+    updateTimer();
+}
+
+void EdgeDock::tick()
+{
+    perf::tick("edgedock");
+    const QPoint cursor = winpin::cursorPos();
+    const intptr_t fg = reinterpret_cast<intptr_t>(winpin::foregroundWindow());
+
+    for (auto it = m_docks.begin(); it != m_docks.end(); ++it) {
+        const intptr_t hwnd = it.key();
+        Dock &d = it.value();
+        if (!winpin::isValidWindow(H(hwnd)) || winpin::isHung(H(hwnd)))
+            continue;   // closed ones are dropped by the next sync
+
+        if (d.animating) {
+            const qreal t = qMin<qreal>(1.0, d.slide.elapsed() / qreal(kSlideMs));
+            const qreal eased = 1.0 - (1.0 - t) * (1.0 - t);
+            const QPoint delta = d.to.topLeft() - d.from.topLeft();
+            winpin::moveWindow(H(hwnd), QRect(d.from.topLeft() + delta * eased, d.to.size()));
+            if (t >= 1.0)
+                d.animating = false;
+            continue;
+        }
+
+        const QRect now = winpin::windowRect(H(hwnd));
+        if (d.hidden) {
+            if (now.adjusted(-1, -1, 1, 1).contains(cursor) || hwnd == fg)
+                startSlide(hwnd, d, false);
+            continue;
+        }
+
+        // Slid in: stay while the mouse is over it or the user is working in it.
+        const int reach = dpi::scaled(kReach, dpi::ofWindow(H(hwnd)));
+        if (now.adjusted(-reach, -reach, reach, reach).contains(cursor) || hwnd == fg) {
+            d.away.invalidate();
+            continue;
+        }
+        if (!d.away.isValid()) {
+            d.away.start();
+        } else if (d.away.elapsed() >= kHideDelayMs) {
+            d.shown = now;   // the user may have moved or resized it meanwhile
+            d.work = winpin::monitorWorkArea(H(hwnd));
+            startSlide(hwnd, d, true);
+        }
+    }
+    updateTimer();
+}
+
+void EdgeDock::startSlide(intptr_t hwnd, Dock &d, bool hide)
+{
+    d.from = winpin::windowRect(H(hwnd));
+    d.to = hide ? hiddenRect(d.shown, d.work, d.edge,
+                             dpi::scaled(kSliver, dpi::ofWindow(H(hwnd))))
+                : d.shown;
+    d.hidden = hide;
+    d.animating = true;
+    d.slide.start();
+    d.away.invalidate();
+}
+
+void EdgeDock::release(intptr_t hwnd, const Dock &d)
+{
+    if ((d.hidden || d.animating) && winpin::isValidWindow(H(hwnd)))
+        winpin::moveWindow(H(hwnd), d.shown);
+}
+
+void EdgeDock::updateTimer()
+{
+    if (m_docks.isEmpty()) {
+        m_timer->stop();
+        return;
+    }
+    // Smooth while sliding, relaxed while just watching the mouse.
+    const bool sliding = std::any_of(m_docks.cbegin(), m_docks.cend(),
+                                     [](const Dock &d) { return d.animating; });
+    m_timer->setInterval(sliding ? 16 : 50);
+    if (!m_timer->isActive())
+        m_timer->start();
+}
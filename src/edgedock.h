@@ -0,0 +1,53 @@
+#pragma once
+//
+// EdgeDock — appbar-style auto-hide for pinned windows. A window docked to a
+// screen edge (PinnedWindow::dockEdge) slides off that edge, leaving a few
+// pixels showing, and slides back in when the mouse touches them or the
+// window is brought to the front. Once the mouse has been away for a moment
+// it slides out again. Works best on an outer edge of the desktop; on an
+// inner edge the hidden part lands on the neighbouring monitor.
+//
+// The mouse is polled (like the rest of PinIt) and only while something is
+// docked. Undocking, unpinning or quitting puts the window back in place.
+//
+#include <QElapsedTimer>
+#include <QHash>
+#include <QObject>
+#include <QRect>
+#include <QString>
+#include <QVector>
+#include <cstdint>
+
+class PinManager;
+class QTimer;
+struct PinnedWindow;
+
+class EdgeDock : public QObject
+{
+    Q_OBJECT
+public:
+    explicit EdgeDock(PinManager *manager, QObject *parent = nullptr);
+    ~EdgeDock() override;
+
+private:
+    struct Dock {
+        QString       edge;        // "left", "right", "top" or "bottom"
+        QRect         shown;       // where the window sits when slid in
+        QRect         work;        // work area of its monitor
+        bool          hidden = false;
+        bool          animating = false;
+        QRect         from, to;    // the slide in progress
+        QElapsedTimer slide;
+        QElapsedTimer away;        // since the mouse left the slid-in window
+    };
+
+    void sync(const QVector<PinnedWindow> &pins);
+    void tick();
+    void startSlide(intptr_t hwnd, Dock &d, bool hide);
+    void release(intptr_t hwnd, const Dock &d);   // back in place, for good
+    void updateTimer();
+
+    PinManager            *m_manager = nullptr;
+    QTimer                *m_timer = nullptr;
+    QHash<intptr_t, Dock>  m_docks;
+};
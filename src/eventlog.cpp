@@ -0,0 +1,91 @@
+#include "eventlog.h"
+
+#include <windows.h>
+
+#include <cwchar>
+
+namespace {
+
+HANDLE                       g_source = nullptr;
+bool                         g_filterInstalled = false;
+LPTOP_LEVEL_EXCEPTION_FILTER g_previousFilter = nullptr;
+
+WORD typeOf(eventlog::Event event)
+{
+    switch (event) {
+    case eventlog::Event::Started:       return EVENTLOG_INFORMATION_TYPE;
+    case eventlog::Event::HookFailed:
+    case eventlog::Event::HotkeysFailed: return EVENTLOG_WARNING_TYPE;
+    case eventlog::Event::Crashed:       return EVENTLOG_ERROR_TYPE;
+    }
+    return EVENTLOG_INFORMATION_TYPE;
+}
+
+void reportText(eventlog::Event event, const wchar_t *text)
+{
+    const wchar_t *strings[] = {text};
+    ReportEventW(g_source, typeOf(event), 0, DWORD(event), nullptr, 1, 0, strings, nullptr);
+}
+
+// Runs on the crashing thread with the process in an unknown state, so it
+// sticks to the stack and plain Win32: no Qt, no allocation.
+LONG WINAPI crashFilter(EXCEPTION_POINTERS *info)
+{
+    if (g_source) {
+        const EXCEPTION_RECORD *rec = info->ExceptionRecord;
+        HMODULE module = nullptr;
+        wchar_t path[MAX_PATH] = L"unknown module";
+        GetModuleHandleExW(GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS
+                               | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+                           static_cast<LPCWSTR>(rec->ExceptionAddress), &module);
+        if (module)
+            GetModuleFileNameW(module, path, MAX_PATH);
+        const wchar_t *name = wcsrchr(path, L'\\') ? wcsrchr(path, L'\\') + 1 : path;
+        const auto offset = reinterpret_cast<const char *>(rec->ExceptionAddress)
+                            - reinterpret_cast<const char *>(module);
+        wchar_t text[512];
+        swprintf(text, 512, L"PinIt crashed: exception 0x%08lX in %ls+0x%llX.",
+                 rec->ExceptionCode, name, static_cast<unsigned long long>(offset));
+        reportText(eventlog::Event::Crashed, text);
+    }
+    return g_previousFilter ? g_previousFilter(info) : EXCEPTION_CONTINUE_SEARCH;
+}
+
+} // namespace
+
+namespace eventlog {
+
+void setEnabled(bool enabled)
+{
+    if (enabled == (g_source != nullptr))
+        return;
+    if (!enabled) {
+        DeregisterEventSource(g_source);
+        g_source = nullptr;
+        return;
+    }
+    g_source = RegisterEventSourceW(nullptr, L"PinIt");
+    if (!g_source) {
+        qWarning("Windows Event Log unavailable (error %lu)", GetLastError());
+        return;
+    }
+    // Installed once and left in place; it does nothing while disabled.
+    if (!g_filterInstalled) {
+        g_previousFilter = SetUnhandledExceptionFilter(crashFilter);
+        g_filterInstalled = true;
+    }
+}
+
+bool isEnabled()
+{
+    return g_source != nullptr;
+}
+
+void report(Event event, const QString &message)
+{
+    if (!g_source)
+        return;
+    reportText(event, reinterpret_cast<const wchar_t *>(message.utf16()));
+}
+
+} // namespace eventlog
@@ -0,0 +1,35 @@
+#pragma once
+//
+// eventlog — reports the events a fleet administrator cares about to the
+// Windows Application log (source "PinIt"), so they can be collected with the
+// usual tooling (Event Viewer, Windows Event Forwarding, a SIEM agent):
+//
+//   1000  Information  PinIt started
+//   1001  Warning      the low-level keyboard hook couldn't be installed
+//   1002  Warning      hotkeys couldn't be registered
+//   1003  Error        PinIt crashed (exception code and faulting module)
+//
+// Off by default (setting event_log; Group Policy can force it). Events go
+// out even if the source was never registered; to get clean descriptions in
+// Event Viewer instead of "the description for Event ID … cannot be found",
+// register it once as administrator:
+//   New-EventLog -LogName Application -Source PinIt
+//
+#include <QString>
+
+namespace eventlog {
+
+enum class Event {
+    Started       = 1000,
+    HookFailed    = 1001,
+    HotkeysFailed = 1002,
+    Crashed       = 1003,
+};
+
+// Opens the event source and installs the crash reporter; false closes it.
+void setEnabled(bool enabled);
+bool isEnabled();
+
+void report(Event event, const QString &message);
+
+} // namespace eventlog
@@ -1,23 +1,113 @@
 #include "globalhotkey.h"
 #include "shortcuts.h"
 
+#include <QTimer>
+
 #include <windows.h>
 
 namespace {
 
+// The keyboard-hook backend needs a static entry point; only one manager
+// exists per process, so it's tracked here while the hook is installed.
+GlobalHotkeyManager *g_hookOwner = nullptr;
+
+LRESULT CALLBACK keyboardHookProc(int code, WPARAM wParam, LPARAM lParam)
+{
+    if (code == HC_ACTION && g_hookOwner) {
+        const auto *kb = reinterpret_cast<const KBDLLHOOKSTRUCT *>(lParam);
+        const bool down = (wParam == WM_KEYDOWN || wParam == WM_SYSKEYDOWN);
+        // Injected keys (including our own Start-menu suppressor) pass through.
+        if ((kb->flags & LLKHF_INJECTED) == 0
+            && g_hookOwner->handleHookKey(kb->vkCode, down))
+            return 1;   // swallow: the combo was ours
+    }
+    return CallNextHookEx(nullptr, code, wParam, lParam);
+}
+
+// Modifiers currently held, as MOD_* flags (what RegisterHotKey would match).
+unsigned heldModifiers()
+{
+    auto down = [](int vk) { return (GetAsyncKeyState(vk) & 0x8000) != 0; };
+    unsigned mods = 0;
+    if (down(VK_LWIN) || down(VK_RWIN)) mods |= MOD_WIN;
+    if (down(VK_CONTROL))               mods |= MOD_CONTROL;
+    if (down(VK_MENU))                  mods |= MOD_ALT;
+    if (down(VK_SHIFT))                 mods |= MOD_SHIFT;
+    return mods;
+}
+
+bool isModifierKey(unsigned vk)
+{
+    switch (vk) {
+    case VK_LWIN: case VK_RWIN:
+    case VK_CONTROL: case VK_LCONTROL: case VK_RCONTROL:
+    case VK_MENU: case VK_LMENU: case VK_RMENU:
+    case VK_SHIFT: case VK_LSHIFT: case VK_RSHIFT:
+        return true;
+    default:
+        return false;
+    }
+}
+
 // Hotkey ids passed to RegisterHotKey; also matched in the event filter.
 enum HotkeyId {
     IdTogglePin    = 1,
     IdOpacityUp    = 2,
     IdOpacityDown  = 3,
     IdToggleWindow = 4,
+    IdPinUnderCursor = 5,
+    IdQuickPanel   = 6,
+    IdHideAllPinned = 7,
+    IdPinTemporarily = 8,
+    IdKioskExit    = 9,
+    IdSlot         = 10,   // + slot index
+    IdAssignSlot   = IdSlot + persistence::kPinSlots,
+    IdToggleLastPinned = IdAssignSlot + persistence::kPinSlots,
+    IdFocusLastPinned,
+    IdPinWithOpacity,
+    IdOpacitySlider,
 };
 
+// Macros, chord leaders and the temporarily-armed second steps get ids from
+// ranges of their own, so they never collide with the action ids above or
+// each other. Every binding has at most one leader, so the leaders stay below
+// kMaxBindings; each chord arms at most two second steps (with and without
+// the leader's modifiers held).
+constexpr int kFirstMacroId    = 500;
+constexpr int kMaxMacros       = 400;   // stays below kProbeId
+constexpr int kMaxBindings     = IdOpacitySlider + 1 + kMaxMacros;
+constexpr int kFirstLeaderId   = 1000;
+constexpr int kFirstFollowerId = kFirstLeaderId + kMaxBindings;
+
+// How long after a chord's first step the second step is accepted.
+constexpr int kChordTimeoutMs = 1500;
+
+// Id for isAvailable()'s throwaway registration (app ids must be < 0xC000).
+constexpr int kProbeId = 999;
+
+static_assert(IdOpacitySlider < kFirstMacroId, "action ids run into the macro ids");
+static_assert(kFirstMacroId + kMaxMacros <= kProbeId, "macro ids run into the probe id");
+static_assert(kFirstFollowerId + 2 * kMaxBindings < 0xC000, "hotkey ids out of range");
+
+// WM_HOTKEY has no key-up counterpart, so hold mode polls the key state.
+constexpr int kReleasePollMs = 30;
+// Held at least this long, the pin key previews; released sooner, it's a tap
+// and toggles the pin as usual.
+constexpr int kHoldMs = 250;
+
 } // namespace
 
 GlobalHotkeyManager::GlobalHotkeyManager(QObject *parent)
     : QObject(parent)
 {
+    m_chordTimer = new QTimer(this);
+    m_chordTimer->setSingleShot(true);
+    m_chordTimer->setInterval(kChordTimeoutMs);
+    connect(m_chordTimer, &QTimer::timeout, this, &GlobalHotkeyManager::disarmChord);
+
+    m_releaseTimer = new QTimer(this);
+    m_releaseTimer->setInterval(kReleasePollMs);
+    connect(m_releaseTimer, &QTimer::timeout, this, &GlobalHotkeyManager::pollPreviewKey);
 }
 
 GlobalHotkeyManager::~GlobalHotkeyManager()
@@ -31,15 +121,98 @@ bool GlobalHotkeyManager::registerOne(int id, const QString &shortcut)
     if (!shortcuts::parse(shortcut, mods, vk))
         return false;
 
+    if (!grab(id, mods, vk))
+        return false;
+    m_registered << id;
+    return true;
+}
+
+bool GlobalHotkeyManager::grab(int id, unsigned mods, unsigned vk)
+{
+    if (m_hook) {
+        m_hookCombos.insert(id, (quint64(mods) << 32) | vk);
+        return true;
+    }
     // MOD_NOREPEAT: holding the keys fires once, not a stream.
     return RegisterHotKey(nullptr, id, mods | MOD_NOREPEAT, vk) != FALSE;
 }
 
+void GlobalHotkeyManager::release(int id)
+{
+    if (m_hook)
+        m_hookCombos.remove(id);
+    else
+        UnregisterHotKey(nullptr, id);
+}
+
+bool GlobalHotkeyManager::isAvailable(const QString &shortcut)
+{
+    unsigned mods = 0, vk = 0;
+    if (!shortcuts::parse(shortcuts::steps(shortcut).value(0), mods, vk))
+        return false;
+    if (RegisterHotKey(nullptr, kProbeId, mods | MOD_NOREPEAT, vk) == FALSE)
+        return false;
+    UnregisterHotKey(nullptr, kProbeId);
+    return true;
+}
+
+bool GlobalHotkeyManager::registerChord(int action, const QString &shortcut)
+{
+    const QStringList parts = shortcuts::steps(shortcut);
+    Follower f;
+    f.action = action;
+    unsigned mods = 0, vk = 0;
+    if (parts.size() != 2 || !shortcuts::parse(parts[0], mods, vk)
+        || !shortcuts::parse(parts[1], f.mods, f.vk))
+        return false;
+
+    // Several chords may share a leader (Ctrl+Alt+P then O / then T).
+    const quint64 key = (quint64(mods) << 32) | vk;
+    int leader = m_leaderIds.value(key, 0);
+    if (leader == 0) {
+        leader = kFirstLeaderId + m_leaderIds.size();
+        if (!grab(leader, mods, vk))
+            return false;
+        m_registered << leader;
+        m_leaderIds.insert(key, leader);
+    }
+
+    // Let the user keep holding the leader's modifiers for the second key.
+    m_chords[leader].push_back(f);
+    if ((f.mods | mods) != f.mods) {
+        Follower held = f;
+        held.mods |= mods;
+        m_chords[leader].push_back(held);
+    }
+    return true;
+}
+
 bool GlobalHotkeyManager::registerAll(const persistence::ShortcutConfig &c)
 {
     unregisterAll();
     m_failed.clear();
+    m_succeeded.clear();
     m_anyRegistered = false;
+    m_active = c;
+
+    if (c.keyboardHook) {
+        // Fallback backend: see every key press and match combos ourselves.
+        // Catches Win-key combos Windows refuses to hand out via RegisterHotKey.
+        m_hook = SetWindowsHookExW(WH_KEYBOARD_LL, keyboardHookProc,
+                                   GetModuleHandleW(nullptr), 0);
+        if (m_hook)
+            g_hookOwner = this;
+        else
+            qWarning("Keyboard hook unavailable (error %lu); using RegisterHotKey",
+                     GetLastError());
+    }
+
+    // Hold mode watches the pin shortcut's last key (the second step of a chord).
+    m_holdToPreview = c.holdToPreview;
+    const QStringList pinSteps = shortcuts::steps(c.togglePin);
+    unsigned pinMods = 0;
+    if (pinSteps.isEmpty() || !shortcuts::parse(pinSteps.last(), pinMods, m_pinKey))
+        m_pinKey = 0;
 
     struct Entry { int id; const char *label; QString shortcut; };
     const Entry entries[] = {
@@ -47,21 +220,233 @@ bool GlobalHotkeyManager::registerAll(const persistence::ShortcutConfig &c)
         { IdOpacityUp,    "Opacity +", c.opacityUp },
         { IdOpacityDown,  "Opacity -", c.opacityDown },
         { IdToggleWindow, "Show/Hide", c.toggleWindow },
+        { IdPinUnderCursor, "Pin under cursor", c.pinUnderCursor },
+        { IdQuickPanel,   "Quick panel", c.quickPanel },
+        { IdHideAllPinned, "Hide all pinned", c.hideAllPinned },
+        { IdPinTemporarily, "Pin for a while", c.pinTemporarily },
+        { IdKioskExit,    "Exit kiosk mode", c.kioskExit },
+        { IdToggleLastPinned, "Pin/Unpin last pinned", c.toggleLastPinned },
+        { IdFocusLastPinned, "Go to last pinned", c.focusLastPinned },
+        { IdPinWithOpacity, "Pin with opacity", c.pinWithOpacity },
+        { IdOpacitySlider, "Opacity slider", c.opacitySlider },
     };
 
     for (const Entry &e : entries) {
-        if (registerOne(e.id, e.shortcut))
+        const bool ok = shortcuts::isChord(e.shortcut) ? registerChord(e.id, e.shortcut)
+                                                       : registerOne(e.id, e.shortcut);
+        if (ok) {
             m_anyRegistered = true;
-        else
+            m_succeeded << QString::fromLatin1(e.label);
+        } else {
             m_failed << QString::fromLatin1(e.label);
+        }
+    }
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        const Entry slotEntries[] = {
+            { IdSlot + i,       "Slot %1",           c.slot[i] },
+            { IdAssignSlot + i, "Assign to slot %1", c.assignSlot[i] },
+        };
+        for (const Entry &e : slotEntries) {
+            const bool ok = shortcuts::isChord(e.shortcut) ? registerChord(e.id, e.shortcut)
+                                                           : registerOne(e.id, e.shortcut);
+            const QString label = QString::fromLatin1(e.label).arg(i + 1);
+            if (ok) {
+                m_anyRegistered = true;
+                m_succeeded << label;
+            } else {
+                m_failed << label;
+            }
+        }
+    }
+    // A macro can't take a combo already bound above or to an earlier macro
+    // (the Macros dialog refuses that, a hand-edited file may not).
+    QStringList taken;
+    for (const QString &s : persistence::bindings(c)) {
+        if (shortcuts::check({s}) == shortcuts::Problem::None)
+            taken << s;
+    }
+    for (int i = 0; i < m_macros.size(); ++i) {
+        const QString &shortcut = m_macros[i].second;
+        const int id = kFirstMacroId + i;
+        const QString label = QStringLiteral("Macro: %1").arg(m_macros[i].first);
+        if (shortcuts::check(taken + QStringList{shortcut}) != shortcuts::Problem::None) {
+            qWarning("Hotkey %s of macro %s clashes with another binding; not registered",
+                     qUtf8Printable(shortcut), qUtf8Printable(m_macros[i].first));
+            m_failed << label;
+            continue;
+        }
+        taken << shortcut;
+        const bool ok = shortcuts::isChord(shortcut) ? registerChord(id, shortcut)
+                                                     : registerOne(id, shortcut);
+        if (ok) {
+            m_anyRegistered = true;
+            m_succeeded << label;
+        } else {
+            m_failed << label;
+        }
     }
     return m_anyRegistered;
 }
 
+bool GlobalHotkeyManager::update(const persistence::ShortcutConfig &config)
+{
+    const persistence::ShortcutConfig previous = m_active;
+    const bool hadAny = m_anyRegistered;
+    if (registerAll(config))
+        return true;
+    if (hadAny) {
+        const QStringList failed = m_failed;
+        qWarning("None of the new hotkeys could be registered; keeping the previous ones");
+        if (!registerAll(previous))
+            qWarning("The previous hotkeys could not be registered again either");
+        m_failed = failed;   // what the caller asked for is what failed
+    }
+    return false;
+}
+
+void GlobalHotkeyManager::setMacroShortcuts(const QVector<persistence::Macro> &macros)
+{
+    m_macros.clear();
+    for (const persistence::Macro &m : macros) {
+        if (!m.shortcut.isEmpty() && m_macros.size() < kMaxMacros)
+            m_macros.push_back({m.name, m.shortcut});
+    }
+}
+
 void GlobalHotkeyManager::unregisterAll()
 {
-    for (int id : { IdTogglePin, IdOpacityUp, IdOpacityDown, IdToggleWindow })
-        UnregisterHotKey(nullptr, id);
+    disarmChord();
+    for (int id : m_registered)
+        release(id);
+    m_registered.clear();
+    m_leaderIds.clear();
+    m_chords.clear();
+
+    if (m_hook) {
+        UnhookWindowsHookEx(static_cast<HHOOK>(m_hook));
+        m_hook = nullptr;
+        g_hookOwner = nullptr;
+    }
+    m_hookCombos.clear();
+    m_hookHeld.clear();
+}
+
+void GlobalHotkeyManager::armChord(int leaderId)
+{
+    disarmChord();
+    int id = kFirstFollowerId;
+    for (const Follower &f : m_chords.value(leaderId)) {
+        if (grab(id, f.mods, f.vk))
+            m_armed.insert(id, f.action);
+        ++id;
+    }
+    m_chordTimer->start();
+}
+
+void GlobalHotkeyManager::disarmChord()
+{
+    m_chordTimer->stop();
+    for (auto it = m_armed.cbegin(); it != m_armed.cend(); ++it)
+        release(it.key());
+    m_armed.clear();
+}
+
+void GlobalHotkeyManager::pollPreviewKey()
+{
+    if (GetAsyncKeyState(static_cast<int>(m_pinKey)) & 0x8000) {
+        if (!m_previewing && m_pinHeld.hasExpired(kHoldMs)) {
+            m_previewing = true;
+            emit previewPressed();
+        }
+        return;   // still held
+    }
+    m_releaseTimer->stop();
+    if (m_previewing) {
+        m_previewing = false;
+        emit previewReleased();
+    } else {
+        emit togglePin();   // a tap
+    }
+}
+
+bool GlobalHotkeyManager::dispatch(int action)
+{
+    if (action == IdTogglePin && m_holdToPreview && m_pinKey != 0) {
+        if (!m_releaseTimer->isActive()) {   // not a key repeat
+            m_previewing = false;
+            m_pinHeld.start();
+            m_releaseTimer->start();
+        }
+        return true;
+    }
+
+    switch (action) {
+    case IdTogglePin:    emit togglePin();    return true;
+    case IdOpacityUp:    emit opacityUp();    return true;
+    case IdOpacityDown:  emit opacityDown();  return true;
+    case IdToggleWindow: emit toggleWindow(); return true;
+    case IdPinUnderCursor: emit pinUnderCursor(); return true;
+    case IdQuickPanel:   emit quickPanel();   return true;
+    case IdHideAllPinned: emit hideAllPinned(); return true;
+    case IdPinTemporarily: emit pinTemporarily(); return true;
+    case IdKioskExit:    emit kioskExit();    return true;
+    case IdToggleLastPinned: emit toggleLastPinned(); return true;
+    case IdFocusLastPinned: emit focusLastPinned(); return true;
+    case IdPinWithOpacity: emit pinWithOpacity(); return true;
+    case IdOpacitySlider: emit opacitySlider(); return true;
+    default:
+        if (action >= IdSlot && action < IdSlot + persistence::kPinSlots) {
+            emit slotPressed(action - IdSlot + 1);
+            return true;
+        }
+        if (action >= IdAssignSlot && action < IdAssignSlot + persistence::kPinSlots) {
+            emit assignSlotPressed(action - IdAssignSlot + 1);
+            return true;
+        }
+        if (action >= kFirstMacroId && action < kFirstMacroId + m_macros.size()) {
+            emit macroTriggered(m_macros[action - kFirstMacroId].first);
+            return true;
+        }
+        return false;
+    }
+}
+
+bool GlobalHotkeyManager::handleHookKey(unsigned vk, bool down)
+{
+    if (!down) {
+        m_hookHeld.remove(vk);
+        return false;
+    }
+    if (isModifierKey(vk))
+        return false;
+
+    const quint64 combo = (quint64(heldModifiers()) << 32) | vk;
+    for (auto it = m_hookCombos.cbegin(); it != m_hookCombos.cend(); ++it) {
+        if (it.value() != combo)
+            continue;
+
+        // Auto-repeat while held fires once, like MOD_NOREPEAT.
+        if (m_hookHeld.contains(vk))
+            return true;
+        m_hookHeld.insert(vk);
+
+        // We swallowed the key, so Windows saw a lone Win press and would open
+        // Start on release. A dummy (unassigned) key event in between stops it.
+        if ((combo >> 32) & MOD_WIN) {
+            INPUT in[2] = {};
+            in[0].type = in[1].type = INPUT_KEYBOARD;
+            in[0].ki.wVk = in[1].ki.wVk = 0xE8;
+            in[1].ki.dwFlags = KEYEVENTF_KEYUP;
+            SendInput(2, in, sizeof(INPUT));
+        }
+
+        // Dispatch after the hook returns — the hook must be quick, and the
+        // handler may re-register (arm/disarm a chord) which edits m_hookCombos.
+        const int id = it.key();
+        QTimer::singleShot(0, this, [this, id]() { onHotkey(id); });
+        return true;
+    }
+    return false;
 }
 
 bool GlobalHotkeyManager::nativeEventFilter(const QByteArray &eventType,
@@ -73,12 +458,19 @@ bool GlobalHotkeyManager::nativeEventFilter(const QByteArray &eventType,
     MSG *msg = static_cast<MSG *>(message);
     if (msg->message != WM_HOTKEY)
         return false;
+    return onHotkey(static_cast<int>(msg->wParam));
+}
 
-    switch (msg->wParam) {
-    case IdTogglePin:    emit togglePin();    return true;
-    case IdOpacityUp:    emit opacityUp();    return true;
-    case IdOpacityDown:  emit opacityDown();  return true;
-    case IdToggleWindow: emit toggleWindow(); return true;
-    default:             return false;
+bool GlobalHotkeyManager::onHotkey(int id)
+{
+    if (m_armed.contains(id)) {
+        const int action = m_armed.value(id);
+        disarmChord();
+        return dispatch(action);
+    }
+    if (m_chords.contains(id)) {
+        armChord(id);
+        return true;
     }
+    return dispatch(id);
 }
@@ -6,11 +6,27 @@
 // Shortcut strings use the Tauri syntax stored in pinned.json
 // (e.g. "super+ctrl+KeyT") so configuration stays file-compatible.
 //
+// Two-step chords ("ctrl+alt+KeyP,KeyO") register only their first step. When
+// it fires, the second steps sharing that leader are registered for a short
+// window (kChordTimeoutMs) and released again on a hit or on timeout, so the
+// plain follow-up key is only ever grabbed for a moment.
+//
+// Backends: RegisterHotKey (default), or — when ShortcutConfig::keyboardHook
+// is set — a WH_KEYBOARD_LL hook that matches combos itself, for Win-key
+// shortcuts Windows won't hand out. Both feed the same id-based dispatch.
+//
 #include <QObject>
 #include <QAbstractNativeEventFilter>
+#include <QElapsedTimer>
+#include <QHash>
+#include <QPair>
+#include <QSet>
+#include <QVector>
 
 #include "persistence.h"
 
+class QTimer;
+
 class GlobalHotkeyManager : public QObject, public QAbstractNativeEventFilter
 {
     Q_OBJECT
@@ -18,25 +34,96 @@ public:
     explicit GlobalHotkeyManager(QObject *parent = nullptr);
     ~GlobalHotkeyManager() override;
 
-    // Register the four PinIt shortcuts. Returns false only if none could be
+    // Register every PinIt shortcut. Returns false only if none could be
     // registered; partial failures are reported via failedActions().
     bool registerAll(const persistence::ShortcutConfig &config);
+    // Switch to an edited config. If not one of its shortcuts registers, the
+    // config held before is registered again and false is returned, so a bad
+    // edit can't leave PinIt with no hotkeys; activeConfig() then still
+    // reports the old one. (When nothing was held before there's nothing to
+    // go back to.)
+    bool update(const persistence::ShortcutConfig &config);
+    persistence::ShortcutConfig activeConfig() const { return m_active; }
     void unregisterAll();
+    // Macro hotkeys, registered alongside the config's by the next
+    // registerAll(); a hit emits macroTriggered with the macro's name.
+    void setMacroShortcuts(const QVector<persistence::Macro> &macros);
 
     QStringList failedActions() const { return m_failed; }
+    QStringList registeredActions() const { return m_succeeded; }
+    bool        usingKeyboardHook() const { return m_hook != nullptr; }
+
+    // Try a temporary registration of the shortcut's first step and release it
+    // straight away: false means another app (or Windows) already holds it.
+    // Combos PinIt itself currently holds also report false.
+    static bool isAvailable(const QString &shortcut);
 
     bool nativeEventFilter(const QByteArray &eventType, void *message,
                            qintptr *result) override;
 
+    // Keyboard-hook backend only: called from the hook for every key event.
+    // Returns true when the key completed one of our combos (and is swallowed).
+    bool handleHookKey(unsigned vk, bool down);
+
 signals:
     void togglePin();
     void opacityUp();
     void opacityDown();
     void toggleWindow();
+    void pinUnderCursor();
+    void quickPanel();
+    void hideAllPinned();
+    void pinTemporarily();
+    void kioskExit();
+    void toggleLastPinned();
+    void focusLastPinned();
+    void pinWithOpacity();
+    void opacitySlider();
+    void slotPressed(int slot);         // 1-based
+    void assignSlotPressed(int slot);
+    void macroTriggered(const QString &name);
+    // Hold-to-preview mode (ShortcutConfig::holdToPreview): holding the pin
+    // key emits these instead of togglePin(); a quick tap still toggles.
+    void previewPressed();
+    void previewReleased();
 
 private:
+    // Second step of a chord, waiting on its leader.
+    struct Follower {
+        unsigned mods = 0;
+        unsigned vk = 0;
+        int      action = 0;
+    };
+
     bool registerOne(int id, const QString &shortcut);
+    bool grab(int id, unsigned mods, unsigned vk);   // via the active backend
+    void release(int id);
+    bool onHotkey(int id);         // a registered id fired (either backend)
+    bool registerChord(int action, const QString &shortcut);
+    void armChord(int leaderId);   // leader fired: grab its second steps
+    void disarmChord();            // hit or timeout: release them again
+    bool dispatch(int action);     // emit the signal for an action id
+    void pollPreviewKey();         // hold mode: watch for the pin key's release
 
     QStringList m_failed;
+    QStringList m_succeeded;
     bool        m_anyRegistered = false;
+    persistence::ShortcutConfig m_active;   // what the last registerAll() applied
+
+    QVector<int>                   m_registered;   // every id we hold, for cleanup
+    QHash<quint64, int>            m_leaderIds;    // (mods << 32 | vk) -> leader id
+    QHash<int, QVector<Follower>>  m_chords;       // leader id -> its second steps
+    QHash<int, int>                m_armed;        // temp id -> action, while armed
+    QTimer *m_chordTimer = nullptr;
+    QVector<QPair<QString, QString>> m_macros;   // (name, shortcut), by id offset
+
+    bool     m_holdToPreview = false;
+    unsigned m_pinKey = 0;              // vk of the pin shortcut's final key
+    QTimer  *m_releaseTimer = nullptr;  // polls that key while it's held
+    QElapsedTimer m_pinHeld;            // since the pin key went down
+    bool     m_previewing = false;      // held long enough: previewPressed() sent
+
+    void                 *m_hook = nullptr;   // HHOOK when the hook backend is active
+    QHash<int, quint64>   m_hookCombos;       // id -> (mods << 32 | vk)
+    QSet<unsigned>        m_hookHeld;         // matched keys still down (no repeat)
 };
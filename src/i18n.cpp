@@ -0,0 +1,44 @@
+#include "i18n.h"
+
+#include <QCoreApplication>
+#include <QDir>
+#include <QLibraryInfo>
+#include <QLocale>
+#include <QTranslator>
+
+namespace i18n {
+
+QStringList available()
+{
+    QStringList codes;
+    const QStringList files =
+        QDir(QStringLiteral(":/i18n")).entryList({QStringLiteral("pinit_*.qm")}, QDir::Files);
+    for (QString f : files)
+        codes << f.remove(QStringLiteral("pinit_")).remove(QStringLiteral(".qm"));
+    return codes;
+}
+
+void install(const QString &lang)
+{
+    const QLocale locale = lang.isEmpty() ? QLocale::system() : QLocale(lang);
+    QLocale::setDefault(locale);   // numbers and dates follow the language too
+
+    auto *app = new QTranslator(qApp);
+    if (app->load(locale, QStringLiteral("pinit"), QStringLiteral("_"), QStringLiteral(":/i18n"))) {
+        QCoreApplication::installTranslator(app);
+        qInfo("Loaded translation %s", qUtf8Printable(locale.name()));
+    } else {
+        delete app;
+        return;   // no PinIt catalog — stay fully English rather than half-translated
+    }
+
+    // Qt's own strings (standard dialog buttons), when they're deployed.
+    auto *qt = new QTranslator(qApp);
+    if (qt->load(locale, QStringLiteral("qtbase"), QStringLiteral("_"),
+                 QLibraryInfo::path(QLibraryInfo::TranslationsPath)))
+        QCoreApplication::installTranslator(qt);
+    else
+        delete qt;
+}
+
+} // namespace i18n
@@ -0,0 +1,20 @@
+#pragma once
+//
+// i18n — load the UI translation. Every user-facing string already goes
+// through tr(); this picks the catalog (compiled from translations/*.ts and
+// embedded under :/i18n) for the saved language or, by default, the Windows
+// display language. Falls back to English when there's no catalog.
+//
+#include <QString>
+#include <QStringList>
+
+namespace i18n {
+
+// Language codes with a bundled catalog ("de", "fr", …).
+QStringList available();
+
+// Install the catalog for lang ("" = follow Windows). Call before any widget
+// is created — strings are translated when they're built.
+void install(const QString &lang);
+
+} // namespace i18n
@@ -0,0 +1,92 @@
+#include "kiosk.h"
+#include "pinmanager.h"
+#include "winpin.h"
+
+#include <QFileInfo>
+#include <QTimer>
+
+namespace {
+
+constexpr int kLaunchTimeoutMs = 30000;
+
+inline void *H(intptr_t h) { return reinterpret_cast<void *>(h); }
+
+} // namespace
+
+Kiosk::Kiosk(PinManager *manager, QObject *parent)
+    : QObject(parent)
+    , m_manager(manager)
+{
+    m_timer = new QTimer(this);
+    m_timer->setInterval(1000);
+    connect(m_timer, &QTimer::timeout, this, &Kiosk::enforce);
+}
+
+void Kiosk::start(const QString &exePath)
+{
+    if (exePath.isEmpty())
+        return;
+    m_path = exePath;
+    m_active = true;
+    m_launching = false;
+    qInfo("Kiosk mode on: %s", qUtf8Printable(exePath));
+    enforce();
+    m_timer->start();
+    emit activeChanged(true);
+}
+
+void Kiosk::stop()
+{
+    if (!m_active)
+        return;
+    m_timer->stop();
+    m_active = false;
+    // Leave the app running, just no longer forced on top.
+    if (m_hwnd && m_manager->isPinned(m_hwnd))
+        m_manager->unpin(m_hwnd, /*announce=*/false);
+    m_hwnd = 0;
+    qInfo("Kiosk mode off");
+    emit activeChanged(false);
+}
+
+intptr_t Kiosk::findWindow() const
+{
+    if (m_hwnd && winpin::isValidWindow(H(m_hwnd)))
+        return m_hwnd;
+    const QString exe = QFileInfo(m_path).fileName();
+    for (const winpin::PinnableWindow &w : winpin::enumerateWindows()) {
+        if (!w.title.isEmpty() && w.processName.compare(exe, Qt::CaseInsensitive) == 0)
+            return w.hwnd;
+    }
+    return 0;
+}
+
+void Kiosk::enforce()
+{
+    if (!m_active)
+        return;
+
+    const intptr_t hwnd = findWindow();
+    if (!hwnd) {
+        m_hwnd = 0;
+        if (m_launching)
+            return;
+        // Not running (or it closed): start it. launchAndPin() pins the window
+        // once it shows up; the next tick maximizes and focuses it.
+        m_launching = m_manager->launchAndPin(m_path, {}, kLaunchTimeoutMs);
+        if (m_launching)
+            QTimer::singleShot(kLaunchTimeoutMs, this, [this]() { m_launching = false; });
+        return;
+    }
+
+    m_hwnd = hwnd;
+    m_launching = false;
+    if (winpin::isHung(H(hwnd)))
+        return;
+    if (!m_manager->isPinned(hwnd))
+        m_manager->pin(hwnd, /*announce=*/false);
+    if (!winpin::isMaximized(H(hwnd)))
+        winpin::maximize(H(hwnd));
+    if (winpin::foregroundWindow() != H(hwnd))
+        winpin::activate(H(hwnd));
+}
@@ -0,0 +1,41 @@
+#pragma once
+//
+// Kiosk — signage / ops-screen mode. One app (by exe) is kept pinned,
+// maximized and in the foreground; if it closes it's started again. Checked
+// once a second, so anything that pops up over it is pushed back within a
+// moment. The escape hotkey (Ctrl+Alt+K by default) stops it.
+//
+#include <QObject>
+#include <QString>
+#include <cstdint>
+
+class PinManager;
+class QTimer;
+
+class Kiosk : public QObject
+{
+    Q_OBJECT
+public:
+    explicit Kiosk(PinManager *manager, QObject *parent = nullptr);
+
+    bool    isActive() const { return m_active; }
+    QString appPath() const { return m_path; }
+
+public slots:
+    void start(const QString &exePath);
+    void stop();
+
+signals:
+    void activeChanged(bool active);
+
+private:
+    void enforce();
+    intptr_t findWindow() const;   // the app's main window, or 0
+
+    PinManager *m_manager = nullptr;
+    QTimer     *m_timer = nullptr;
+    QString     m_path;
+    bool        m_active = false;
+    bool        m_launching = false;   // waiting on launchAndPin()
+    intptr_t    m_hwnd = 0;            // the window we pinned
+};
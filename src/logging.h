@@ -1,7 +1,7 @@
 #pragma once
 //
 // logging — route Qt's qDebug/qInfo/qWarning/qCritical to a log file at
-// %LOCALAPPDATA%\PinIt\pinit.log so user-reported issues can be diagnosed.
+// pinit.log in persistence::dataDir() so user-reported issues can be diagnosed.
 // Lightweight: a single appended text file with simple size-based rotation.
 //
 namespace logging {
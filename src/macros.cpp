@@ -0,0 +1,125 @@
+#include "macros.h"
+#include "actions.h"
+
+#include <QCoreApplication>
+#include <QProcess>
+#include <QTimer>
+
+namespace {
+
+// Gives a window an earlier step activated time to come to the front.
+constexpr int kStepDelayMs = 150;
+constexpr int kMaxWaitMs   = 60000;
+
+const QLatin1String kWait("wait");
+
+QString quoted(const QString &arg)
+{
+    if (!arg.isEmpty() && !arg.contains(QLatin1Char(' ')) && !arg.contains(QLatin1Char('"')))
+        return arg;
+    QString out = arg;
+    out.replace(QLatin1String("\""), QLatin1String("\"\"\""));   // splitCommand's escape
+    return QLatin1Char('"') + out + QLatin1Char('"');
+}
+
+} // namespace
+
+namespace macros {
+
+QVector<persistence::MacroStep> parseSteps(const QString &text, QString *error)
+{
+    const auto tr = [](const char *s) { return QCoreApplication::translate("macros", s); };
+    QVector<persistence::MacroStep> steps;
+    const QStringList lines = text.split(QLatin1Char('\n'));
+    for (int i = 0; i < lines.size(); ++i) {
+        const QString line = lines[i].trimmed();
+        if (line.isEmpty() || line.startsWith(QLatin1Char('#')))
+            continue;
+        QStringList words = QProcess::splitCommand(line);
+        if (words.isEmpty())
+            continue;
+        persistence::MacroStep step;
+        step.action = words.takeFirst();
+        step.args = words;
+        QString problem;
+        if (step.action == kWait) {
+            bool ok = false;
+            const int ms = step.args.value(0).toInt(&ok);
+            if (step.args.size() != 1 || !ok || ms < 0 || ms > kMaxWaitMs)
+                problem = tr("wait takes a time in milliseconds, up to 60000");
+        } else if (!ActionRegistry::isKnown(step.action)) {
+            problem = tr("there's no action called “%1”").arg(step.action);
+        }
+        if (!problem.isEmpty()) {
+            if (error)
+                *error = tr("Line %1: %2").arg(i + 1).arg(problem);
+            return {};
+        }
+        steps.push_back(step);
+    }
+    if (error)
+        error->clear();
+    return steps;
+}
+
+QString formatSteps(const QVector<persistence::MacroStep> &steps)
+{
+    QStringList lines;
+    for (const persistence::MacroStep &s : steps) {
+        QStringList words = {s.action};
+        for (const QString &a : s.args)
+            words << quoted(a);
+        lines << words.join(QLatin1Char(' '));
+    }
+    return lines.join(QLatin1Char('\n'));
+}
+
+} // namespace macros
+
+MacroRunner::MacroRunner(const ActionRegistry *actions, QObject *parent)
+    : QObject(parent)
+    , m_actions(actions)
+{
+    m_timer = new QTimer(this);
+    m_timer->setSingleShot(true);
+    connect(m_timer, &QTimer::timeout, this, &MacroRunner::next);
+}
+
+bool MacroRunner::run(const persistence::Macro &macro, intptr_t target)
+{
+    if (isRunning()) {
+        qWarning("Macro %s not started: %s is still running", qUtf8Printable(macro.name),
+                 qUtf8Printable(m_macro.name));
+        return false;
+    }
+    qInfo("Running macro %s (%d step(s))", qUtf8Printable(macro.name),
+          static_cast<int>(macro.steps.size()));
+    m_macro = macro;
+    m_target = target;
+    m_step = 0;
+    next();
+    return true;
+}
+
+void MacroRunner::next()
+{
+    if (m_step >= m_macro.steps.size()) {
+        m_step = -1;
+        emit finished(m_macro.name);
+        return;
+    }
+    const persistence::MacroStep &step = m_macro.steps[m_step++];
+    if (step.action == kWait) {
+        m_timer->start(step.args.value(0).toInt());
+        return;
+    }
+    if (!m_actions->invoke(step.action, step.args, m_target)) {
+        qWarning("Macro %s stopped at step %d (%s)", qUtf8Printable(m_macro.name), m_step,
+                 qUtf8Printable(step.action));
+        const int failed = m_step;
+        m_step = -1;
+        emit stepFailed(m_macro.name, failed, step.action);
+        return;
+    }
+    m_timer->start(kStepDelayMs);
+}
@@ -0,0 +1,58 @@
+#pragma once
+//
+// Macros — named sequences of actions (see actions.h), e.g. "pin OBS, set
+// opacity 80, move it to monitor 2". Stored with the settings, run from the
+// tray or a hotkey of their own.
+//
+// MacroRunner steps through one macro at a time with a short pause between
+// steps, so a window an earlier step activated is in front before the next
+// step acts on "the focused window". A failed step stops the macro and is
+// reported with its position. A macro a trigger starts keeps acting on the
+// trigger's window (ActionRegistry::target()) rather than whatever is in front.
+//
+#include <QObject>
+#include <QString>
+#include <QVector>
+
+#include "persistence.h"
+
+#include <cstdint>
+
+class ActionRegistry;
+class QTimer;
+
+namespace macros {
+
+// One step per line: `<action-id> [args…]`, with "double quotes" around an
+// argument that has spaces, or `wait <ms>`. Blank lines and # comments are
+// skipped. On a bad line returns nothing and sets *error.
+QVector<persistence::MacroStep> parseSteps(const QString &text, QString *error);
+QString formatSteps(const QVector<persistence::MacroStep> &steps);
+
+} // namespace macros
+
+class MacroRunner : public QObject
+{
+    Q_OBJECT
+public:
+    explicit MacroRunner(const ActionRegistry *actions, QObject *parent = nullptr);
+
+    // False if one is already running. target, if set, is what each step's
+    // "focused window" means.
+    bool run(const persistence::Macro &macro, intptr_t target = 0);
+    bool isRunning() const { return m_step >= 0; }
+
+signals:
+    void finished(const QString &name);
+    // step is 1-based; the macro stopped there.
+    void stepFailed(const QString &name, int step, const QString &action);
+
+private:
+    void next();
+
+    const ActionRegistry *m_actions = nullptr;
+    QTimer               *m_timer = nullptr;
+    persistence::Macro    m_macro;
+    int                   m_step = -1;   // next step to run; -1 when idle
+    intptr_t              m_target = 0;
+};
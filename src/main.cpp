@@ -7,18 +7,43 @@
 //   MainWindow           -> UI + system tray
 //
 #include <QApplication>
+#include <QDir>
+#include <QFile>
+#include <QHash>
+#include <QJsonDocument>
 #include <QLocalServer>
 #include <QLocalSocket>
 #include <QMessageBox>
 #include <QIcon>
 #include <QSystemTrayIcon>
 #include <QSessionManager>
+#include <QTimer>
+#include <QTextStream>
 
 #include "pinmanager.h"
 #include "globalhotkey.h"
 #include "mainwindow.h"
 #include "persistence.h"
 #include "logging.h"
+#include "i18n.h"
+#include "taskbar.h"
+#include "usage.h"
+#include "audit.h"
+#include "autostart.h"
+#include "eventlog.h"
+#include "bosskey.h"
+#include "actions.h"
+#include "macros.h"
+#include "schedule.h"
+#include "sessionevents.h"
+#include "triggers.h"
+#include "perf.h"
+#include "selftest.h"
+#include "onboarding.h"
+#include "winpin.h"
+#include "platform.h"
+#include "configschema.h"
+#include "policy.h"
 #include "version.h"
 
 // Warm "paper" theme — ported from the original PinIt CSS variables.
@@ -64,6 +89,13 @@ QPushButton#unpin {
 }
 QPushButton#unpin:hover { background: #f6e3da; color: #b6855a; border-color: #c49464; }
 
+QPushButton#selfPin {
+    background: transparent; border: 1px solid transparent; border-radius: 8px;
+    padding: 0; font-size: 14px;
+}
+QPushButton#selfPin:hover { background: #f0ede6; }
+QPushButton#selfPin:checked { background: #f6e3da; border-color: #c49464; }
+
 QCheckBox { color: #5a564e; font-size: 12px; spacing: 7px; }
 
 QSlider::groove:horizontal { height: 4px; background: #e6e2da; border-radius: 2px; }
@@ -73,6 +105,15 @@ QSlider::handle:horizontal {
     margin: -6px 0; border-radius: 7px;
 }
 QScrollArea { background: transparent; border: none; }
+
+QFrame#quickPanel, QFrame#opacitySlider {
+    background: #f8f6f2; border: 1px solid rgba(0,0,0,0.15); border-radius: 10px;
+}
+QPushButton#panelTitle {
+    background: transparent; border: none; text-align: left; padding: 4px 2px;
+    font-weight: 600;
+}
+QPushButton#panelTitle:hover { color: #b6855a; }
 )qss";
 
 int main(int argc, char *argv[])
@@ -84,29 +125,160 @@ int main(int argc, char *argv[])
     QApplication::setWindowIcon(QIcon(QStringLiteral(":/icon.png")));
     app.setStyleSheet(QString::fromUtf8(kStyleSheet));
 
+    const QStringList args = QCoreApplication::arguments();
+    // `PinIt.exe --validate-config [file]` — check a hand-edited pinned.json
+    // (the live one by default) and list what PinIt can't use. Exit code 1 if
+    // anything turned up. Only reads: the file is opened directly, so nothing
+    // in the data folder is created or changed.
+    if (const int at = args.indexOf(QStringLiteral("--validate-config")); at >= 0) {
+        QTextStream out(stdout);
+        const bool named = at + 1 < args.size() && !args.at(at + 1).startsWith(QLatin1String("--"));
+        const QString path = named ? args.at(at + 1) : persistence::filePath();
+        QFile f(path);
+        if (!f.open(QIODevice::ReadOnly)) {
+            if (!named) {
+                out << "No settings saved yet (" << QDir::toNativeSeparators(path) << ").\n";
+                return 0;
+            }
+            out << "Can't read " << QDir::toNativeSeparators(path) << '\n';
+            return 2;
+        }
+        const QByteArray data = f.readAll();
+        const QVector<configschema::Issue> issues = configschema::validate(data);
+        for (const configschema::Issue &issue : issues)
+            out << configschema::format(issue) << '\n';
+        if (issues.isEmpty())
+            out << "No problems found.\n";
+        return issues.isEmpty() ? 0 : 1;
+    }
+
     logging::init();
     qInfo("PinIt %s starting", PINIT_VERSION_STR);
+    const platform::Capabilities &caps = platform::capabilities();
+    qInfo("%s build %d%s", qUtf8Printable(caps.osName), caps.build,
+          caps.elevated ? ", running as administrator" : "");
+
+    // Before any window or menu exists, so everything is built translated.
+    i18n::install(persistence::loadSettings().language);
+
+    // Started elevated by autostart::apply() to register an administrator
+    // logon task.
+    if (const int at = args.indexOf(QStringLiteral("--register-task"));
+        at >= 0 && at + 1 < args.size())
+        return autostart::registerTaskFile(args.at(at + 1)) ? 0 : 1;
+
+    // `PinIt.exe --list-actions > actions.txt` — the ids --action accepts.
+    if (args.contains(QStringLiteral("--list-actions"))) {
+        QTextStream out(stdout);
+        for (const ActionInfo &a : ActionRegistry::catalog()) {
+            out << a.id;
+            if (!a.args.isEmpty())
+                out << ' ' << a.args;
+            out << '\t' << a.label << '\n';
+        }
+        return 0;
+    }
+
+    // `PinIt.exe --effective-settings` — every setting in use and where it
+    // came from (default, the user's own, or Group Policy).
+    if (args.contains(QStringLiteral("--effective-settings"))) {
+        QTextStream out(stdout);
+        for (const policy::Setting &s : policy::effectiveSettings())
+            out << policy::format(s) << '\n';
+        return 0;
+    }
+
+    // `PinIt.exe --audit-status` — whether the audit log is on, where it is,
+    // and whether it can be written there, as JSON.
+    if (args.contains(QStringLiteral("--audit-status"))) {
+        const persistence::UserSettings settings = persistence::loadSettings();
+        audit::setLocation(settings.auditLogPath);
+        audit::setEnabled(settings.auditLog);
+        QTextStream(stdout) << QJsonDocument(audit::toJson(audit::status())).toJson();
+        return 0;
+    }
 
-    // Single instance: if PinIt is already running, ask it to show its window
+    // `PinIt.exe --onboarding-state` — which first-run steps are done, as JSON.
+    if (args.contains(QStringLiteral("--onboarding-state"))) {
+        const QJsonObject state = onboarding::toJson(persistence::loadSettings().onboarding);
+        QTextStream(stdout) << QJsonDocument(state).toJson();
+        return 0;
+    }
+
+    // `PinIt.exe --self-test` — pin, unpin, opacity and the hooks tried on a
+    // hidden window of our own, as JSON; exits 1 if anything failed.
+    if (args.contains(QStringLiteral("--self-test"))) {
+        const QVector<selftest::Check> checks = selftest::run();
+        QTextStream(stdout) << QJsonDocument(selftest::toJson(checks)).toJson();
+        return selftest::allPassed(checks) ? 0 : 1;
+    }
+
+    // Jump List tasks relaunch PinIt with one of these flags, and
+    // `--action <id> [args…]` names any action (see actions.h). The command
+    // is run here or handed to the already-running instance.
+    QStringList command = {QStringLiteral("show-window")};
+    if (const int at = args.indexOf(QStringLiteral("--action")); at >= 0 && at + 1 < args.size()) {
+        command = {args.at(at + 1)};
+        for (int i = at + 2; i < args.size() && !args.at(i).startsWith(QLatin1String("--")); ++i)
+            command << args.at(i);
+    } else if (args.contains(QStringLiteral("--unpin-all"))) {
+        command = {QStringLiteral("unpin-all")};
+    } else if (args.contains(QStringLiteral("--pick-window"))) {
+        command = {QStringLiteral("pick-window")};
+    } else if (args.contains(QStringLiteral("--adopt-topmost"))) {
+        command = {QStringLiteral("adopt-topmost")};
+    } else if (args.contains(QStringLiteral("--open-settings"))) {
+        command = {QStringLiteral("open-settings")};
+    }
+
+    // Safe mode: for when a setting, hook or saved pin makes PinIt misbehave at
+    // startup. No session hooks, no hotkeys, no triggers or schedules, no kiosk
+    // mode, no saved pins re-pinned (nor overwritten) — just the window, to fix
+    // things from.
+    const bool safeMode = args.contains(QStringLiteral("--safe-mode"))
+                          || (QGuiApplication::queryKeyboardModifiers() & Qt::ShiftModifier);
+
+    // Single instance: if PinIt is already running, send it the command
     // (via a local socket) and exit — instead of dying silently.
     const QString kInstanceServer = QStringLiteral("PinIt_SingleInstance_v2");
     {
         QLocalSocket probe;
         probe.connectToServer(kInstanceServer);
         if (probe.waitForConnected(200)) {
-            probe.write("show");
+            if (safeMode) {
+                // The running copy may be the one misbehaving; handing it
+                // "show-window" would only bring it back.
+                qInfo("Another instance is running; not starting in safe mode");
+                QMessageBox::information(
+                    nullptr, QStringLiteral("PinIt"),
+                    QObject::tr("PinIt is already running, so it can't start in safe mode.\n\n"
+                                "Quit it from its tray icon first — or, if it doesn't "
+                                "respond, end PinIt.exe in Task Manager — then start "
+                                "safe mode again."));
+                return 1;
+            }
+            // The action id and its arguments, one per line.
+            probe.write(command.join(QLatin1Char('\n')).toUtf8());
             probe.flush();
             probe.waitForBytesWritten(200);
-            qInfo("Another instance is running; asked it to show");
+            qInfo("Another instance is running; sent it \"%s\"", qUtf8Printable(command.first()));
             return 0;
         }
     }
 
+    if (safeMode)
+        qInfo("Starting in safe mode");
+    // Only the instance that owns the data folder backs it up; a Jump List
+    // click or a command-line flag that just ran or forwarded a command
+    // mustn't write into backups/ under a running copy.
+    persistence::backup();
+
     // Keep running when the window closes to the tray.
     app.setQuitOnLastWindowClosed(false);
 
     PinManager manager;
-    MainWindow window(&manager);
+    manager.setSavingEnabled(!safeMode);
+    MainWindow window(&manager, safeMode);
 
     // On quit, un-pin/un-fade any windows we touched so nothing is left stuck
     // always-on-top or translucent.
@@ -119,61 +291,531 @@ int main(int argc, char *argv[])
     QObject::connect(&app, &QGuiApplication::commitDataRequest, &manager,
                      [&manager](QSessionManager &) { manager.markSessionEnding(); });
 
-    // Listen for later launches; each connection means "show the window".
+    // Handlers are bound further down, once everything they drive exists.
+    ActionRegistry actions;
+    auto runCommand = [&](const QStringList &cmd) {
+        const QString id = cmd.value(0);
+        if (id != QLatin1String("show-window"))
+            usage::recordFeature(QStringLiteral("command_") + id);
+        if (!ActionRegistry::isKnown(id))
+            window.showFromTray();   // anything unrecognised
+        else if (!actions.invoke(id, cmd.mid(1)))
+            window.notify(QObject::tr("Couldn't run “%1”.").arg(id));
+    };
+
+    // Listen for later launches; each sends one command (see above).
     QLocalServer::removeServer(kInstanceServer);   // clear a stale socket from a crash
     QLocalServer instanceServer;
     instanceServer.listen(kInstanceServer);
     QObject::connect(&instanceServer, &QLocalServer::newConnection, &window, [&]() {
-        while (QLocalSocket *c = instanceServer.nextPendingConnection())
-            c->deleteLater();
-        window.showFromTray();
+        while (QLocalSocket *c = instanceServer.nextPendingConnection()) {
+            QObject::connect(c, &QLocalSocket::readyRead, &window, [c, &runCommand]() {
+                const QStringList cmd =
+                    QString::fromUtf8(c->readAll().trimmed()).split(QLatin1Char('\n'));
+                c->disconnectFromServer();
+                c->deleteLater();
+                runCommand(cmd);
+            });
+            QObject::connect(c, &QLocalSocket::disconnected, c, &QObject::deleteLater);
+        }
     });
 
+    QVector<taskbar::JumpTask> jumpTasks = {
+        { QObject::tr("Pin a window…"),     { QStringLiteral("--pick-window") } },
+        { QObject::tr("Unpin all windows"), { QStringLiteral("--unpin-all") } },
+        { QObject::tr("Open settings"),     { QStringLiteral("--open-settings") } },
+    };
+    // Pass --portable on, so the relaunched copy logs to the same data folder.
+    if (args.contains(QStringLiteral("--portable"))) {
+        for (taskbar::JumpTask &t : jumpTasks)
+            t.arguments << QStringLiteral("--portable");
+    }
+    taskbar::setJumpListTasks(jumpTasks);
+
     GlobalHotkeyManager hotkeys;
     app.installNativeEventFilter(&hotkeys);
 
-    QObject::connect(&hotkeys, &GlobalHotkeyManager::togglePin,
-                     &manager, &PinManager::toggleForeground);
-    QObject::connect(&hotkeys, &GlobalHotkeyManager::opacityUp,
-                     &manager, [&manager]() { manager.adjustForegroundOpacity(5); });
-    QObject::connect(&hotkeys, &GlobalHotkeyManager::opacityDown,
-                     &manager, [&manager]() { manager.adjustForegroundOpacity(-5); });
-    QObject::connect(&hotkeys, &GlobalHotkeyManager::toggleWindow,
-                     &window, &MainWindow::toggleVisibility);
+    BossKey bossKey(&manager);
+    // Never leave windows hidden behind us when PinIt exits.
+    QObject::connect(&app, &QApplication::aboutToQuit, &bossKey, &BossKey::restore);
+    QObject::connect(&app, &QApplication::aboutToQuit, &app, &audit::flush);
+
+    // Everything PinIt can be told to do, by id (see actions.h).
+    // "The focused window", or the window a trigger fired for.
+    const auto fg = [&actions]() {
+        return actions.target() ? actions.target()
+                                : reinterpret_cast<intptr_t>(winpin::foregroundWindow());
+    };
+    const auto simple = [&actions](const char *id, std::function<void()> run) {
+        actions.bind(QString::fromLatin1(id), [run](const QStringList &) {
+            run();
+            return true;
+        });
+    };
+    simple("show-window", [&window]() { window.showFromTray(); });
+    simple("toggle-window", [&window]() { window.toggleVisibility(); });
+    simple("open-settings", [&window]() { window.showSettings(); });
+    simple("quick-panel", [&window]() { window.showQuickPanel(); });
+    simple("pick-window", [&window]() {
+        window.showFromTray();
+        window.addWindowDialog();
+    });
+    simple("adopt-topmost", [&window]() { window.adoptTopmostDialog(); });
+    simple("pin-foreground", [&manager, &actions]() {
+        if (actions.target())
+            manager.toggle(actions.target());
+        else
+            manager.toggleForeground();
+    });
+    simple("pin-under-cursor", [&manager]() { manager.toggleUnderCursor(); });
+    simple("pin-last", [&manager]() { manager.toggleLastPinned(); });
+    actions.bind(QStringLiteral("focus-last-pinned"),
+                 [&manager](const QStringList &) { return manager.focusLastPinned(); });
+    actions.bind(QStringLiteral("pin-temporarily"), [&](const QStringList &a) {
+        if (a.isEmpty() && actions.target()) {
+            const intptr_t hwnd = actions.target();
+            return manager.isPinned(hwnd) ? manager.unpin(hwnd)
+                                          : manager.pinFor(hwnd, manager.temporaryPinDuration());
+        }
+        if (a.isEmpty()) {
+            manager.toggleForegroundTemporary();
+            return true;
+        }
+        bool ok = false;
+        const int seconds = a.first().toInt(&ok);
+        return ok && manager.pinFor(fg(), seconds);
+    });
+    simple("unpin-all", [&manager, &window]() {
+        window.notify(QObject::tr("Unpinned %n window(s).", "", manager.unpinAll()));
+    });
+    simple("opacity-up", [&manager, fg]() { manager.adjustOpacity(fg(), 5); });
+    simple("opacity-down", [&manager, fg]() { manager.adjustOpacity(fg(), -5); });
+    actions.bind(QStringLiteral("set-opacity"), [&manager, fg](const QStringList &a) {
+        bool ok = false;
+        const int percent = a.value(0).toInt(&ok);
+        return ok && manager.setOpacity(fg(), percent);
+    });
+    actions.bind(QStringLiteral("pin-with-opacity"), [&](const QStringList &a) {
+        if (a.isEmpty() && !actions.target()) {
+            manager.pinForegroundWithOpacity();
+            return true;
+        }
+        bool ok = a.isEmpty();
+        const int percent = a.isEmpty() ? manager.pinOpacity() : a.first().toInt(&ok);
+        return ok && manager.pinWithOpacity(fg(), percent);
+    });
+    actions.bind(QStringLiteral("opacity-slider"), [&window, fg](const QStringList &) {
+        return window.showOpacitySlider(fg());
+    });
+    simple("hide-all-pinned", [&bossKey]() { bossKey.toggle(); });
+    actions.bind(QStringLiteral("snapshot-save"), [&manager](const QStringList &a) {
+        return !a.isEmpty() && manager.saveSnapshot(a.join(QLatin1Char(' ')));
+    });
+    actions.bind(QStringLiteral("snapshot-restore"), [&manager](const QStringList &a) {
+        const QString name = a.join(QLatin1Char(' '));
+        if (!manager.snapshotNames().contains(name))
+            return false;
+        manager.restoreSnapshot(name);
+        return true;
+    });
+    simple("kiosk-exit", [&window]() { window.stopKiosk(); });
+    actions.bind(QStringLiteral("pin-app"), [&manager](const QStringList &a) {
+        const QString process = a.value(0);
+        for (const winpin::PinnableWindow &w : winpin::enumerateWindows()) {
+            if (w.processName.compare(process, Qt::CaseInsensitive) == 0
+                || w.processName.compare(process + QLatin1String(".exe"),
+                                         Qt::CaseInsensitive) == 0) {
+                winpin::activate(reinterpret_cast<void *>(w.hwnd));
+                return manager.pin(w.hwnd);
+            }
+        }
+        return false;
+    });
+    actions.bind(QStringLiteral("slot-assign"), [&window](const QStringList &a) {
+        return window.assignSlot(a.value(0).toInt());
+    });
+    actions.bind(QStringLiteral("slot-activate"), [&window](const QStringList &a) {
+        return window.activateSlot(a.value(0).toInt());
+    });
+    actions.bind(QStringLiteral("move-to-monitor"), [fg](const QStringList &a) {
+        const QVector<QRect> monitors = winpin::monitorWorkAreas();
+        const int n = a.value(0).toInt();   // 1-based, like Windows' display numbers
+        void *hwnd = reinterpret_cast<void *>(fg());
+        if (!hwnd || n < 1 || n > monitors.size())
+            return false;
+        return winpin::moveWindow(hwnd, winpin::placeOnMonitor(winpin::windowRect(hwnd),
+                                                               winpin::monitorWorkArea(hwnd),
+                                                               monitors[n - 1]));
+    });
+    // "roaming" and "local" name the two usual places; anything else is a folder.
+    actions.bind(QStringLiteral("set-config-directory"), [&window](const QStringList &a) {
+        const QString where = a.join(QLatin1Char(' '));
+        if (where.isEmpty())
+            return false;
+        if (where == QLatin1String("local"))
+            return window.confirmMoveDataFolder(QString());
+        if (where == QLatin1String("roaming"))
+            return window.confirmMoveDataFolder(persistence::roamingDataDir());
+        return window.confirmMoveDataFolder(where);
+    });
+    // A step name, or "all" to skip the first-run steps (a managed PC).
+    actions.bind(QStringLiteral("advance-onboarding"), [&window](const QStringList &a) {
+        return window.advanceOnboarding(a.value(0));
+    });
+    MacroRunner macroRunner(&actions);
+    actions.bind(QStringLiteral("run-macro"), [&](const QStringList &a) {
+        const QString name = a.join(QLatin1Char(' '));
+        for (const persistence::Macro &m : window.macros()) {
+            if (m.name == name)
+                return macroRunner.run(m, actions.target());
+        }
+        return false;
+    });
+    QObject::connect(&macroRunner, &MacroRunner::stepFailed, &window,
+                     [&window](const QString &name, int step, const QString &action) {
+                         window.notify(QObject::tr("Macro “%1” stopped at step %2 (%3).")
+                                           .arg(name)
+                                           .arg(step)
+                                           .arg(action));
+                     });
+    const auto runMacro = [&actions](const QString &name) {
+        usage::recordFeature(QStringLiteral("macro"));
+        actions.invoke(QStringLiteral("run-macro"), {name});
+    };
+    QObject::connect(&window, &MainWindow::runMacroRequested, &window, runMacro);
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::macroTriggered, &window, runMacro);
+
+    Scheduler scheduler(&actions);
+    if (!safeMode) {
+        scheduler.setSchedules(window.schedules());
+        QObject::connect(&window, &MainWindow::schedulesChanged, &scheduler,
+                         &Scheduler::setSchedules);
+    }
+    QObject::connect(&scheduler, &Scheduler::ran, &window,
+                     [&window](const QString &name, bool ok) {
+                         usage::recordFeature(QStringLiteral("schedule"));
+                         if (!ok)
+                             window.notify(QObject::tr("Scheduled action “%1” didn't run.")
+                                               .arg(name));
+                     });
+
+    SessionEvents session;
+    TriggerWatcher triggerWatcher(&actions);
+    if (!safeMode) {
+        app.installNativeEventFilter(&session);
+        session.start(reinterpret_cast<void *>(window.winId()));
+        triggerWatcher.setTriggers(window.triggers());
+        QObject::connect(&window, &MainWindow::triggersChanged, &triggerWatcher,
+                         &TriggerWatcher::setTriggers);
+    }
+    QObject::connect(&session, &SessionEvents::locked, &triggerWatcher,
+                     &TriggerWatcher::onSessionLocked);
+    QObject::connect(&session, &SessionEvents::unlocked, &triggerWatcher,
+                     &TriggerWatcher::onSessionUnlocked);
+    // Hotkey trouble for the Windows Event Log (a no-op unless event_log is
+    // on), after every registration: at startup, on resume and after edits.
+    const auto reportHotkeys = [&hotkeys]() {
+        if (hotkeys.activeConfig().keyboardHook && !hotkeys.usingKeyboardHook())
+            eventlog::report(eventlog::Event::HookFailed,
+                             QStringLiteral("The low-level keyboard hook could not be installed; "
+                                            "hotkeys fell back to RegisterHotKey."));
+        if (!hotkeys.failedActions().isEmpty())
+            eventlog::report(eventlog::Event::HotkeysFailed,
+                             QStringLiteral("These hotkeys could not be registered: %1")
+                                 .arg(hotkeys.failedActions().join(QStringLiteral(", "))));
+    };
+    // Unlocking and waking from sleep can silently strip topmost, opacity and
+    // hotkeys (and a low-level keyboard hook Windows timed out). Give the
+    // desktop a moment to settle, then put everything back.
+    const auto recover = [&](const char *reason) {
+        QTimer::singleShot(2000, &manager, [&, reason]() {
+            const QStringList failedBefore = hotkeys.failedActions();
+            const int pins = manager.reapplyAll();
+            hotkeys.registerAll(window.shortcutConfig());
+            reportHotkeys();
+            qInfo("After %s: re-applied %d pin(s), re-registered hotkeys", reason, pins);
+            QStringList newlyFailed;
+            for (const QString &label : hotkeys.failedActions()) {
+                if (!failedBefore.contains(label))
+                    newlyFailed << label;
+            }
+            if (!newlyFailed.isEmpty())
+                window.notify(QObject::tr("Some hotkeys are unavailable: %1")
+                                  .arg(newlyFailed.join(QStringLiteral(", "))));
+        });
+    };
+    QObject::connect(&session, &SessionEvents::unlocked, &manager, [recover]() {
+        recover("unlock");
+    });
+    QObject::connect(&session, &SessionEvents::resumed, &manager, [recover]() {
+        recover("resume");
+    });
+    // Connecting over Remote Desktop (or back at the console) resets layered
+    // and topmost state much the same way.
+    window.setRemoteSession(SessionEvents::isRemoteSession());
+    QObject::connect(&session, &SessionEvents::sessionConnected, &window,
+                     [&window, recover](bool remote) {
+                         window.setRemoteSession(remote);
+                         recover(remote ? "remote connect" : "console connect");
+                     });
+    PerfMonitor perfMonitor;
+    QObject::connect(&perfMonitor, &PerfMonitor::warning, &window, &MainWindow::notify);
+
+    QTimer displaySettle;
+    displaySettle.setSingleShot(true);
+    displaySettle.setInterval(1500);
+    QObject::connect(&session, &SessionEvents::displayChanged, &displaySettle,
+                     qOverload<>(&QTimer::start));
+    QObject::connect(&displaySettle, &QTimer::timeout, &manager,
+                     &PinManager::handleDisplayChange);
+    QObject::connect(&triggerWatcher, &TriggerWatcher::fired, &window,
+                     [&window](const QString &name, bool ok) {
+                         usage::recordFeature(QStringLiteral("trigger"));
+                         if (!ok)
+                             window.notify(QObject::tr("Trigger “%1” couldn't run its action.")
+                                               .arg(name));
+                     });
+
+    const auto dispatch = [&hotkeys, &actions](auto signal, const char *id) {
+        QObject::connect(&hotkeys, signal, &hotkeys,
+                         [&actions, id]() { actions.invoke(QString::fromLatin1(id)); });
+    };
+    dispatch(&GlobalHotkeyManager::togglePin, "pin-foreground");
+    dispatch(&GlobalHotkeyManager::pinUnderCursor, "pin-under-cursor");
+    dispatch(&GlobalHotkeyManager::pinTemporarily, "pin-temporarily");
+    dispatch(&GlobalHotkeyManager::toggleLastPinned, "pin-last");
+    dispatch(&GlobalHotkeyManager::focusLastPinned, "focus-last-pinned");
+    dispatch(&GlobalHotkeyManager::pinWithOpacity, "pin-with-opacity");
+    dispatch(&GlobalHotkeyManager::opacitySlider, "opacity-slider");
+    dispatch(&GlobalHotkeyManager::opacityUp, "opacity-up");
+    dispatch(&GlobalHotkeyManager::opacityDown, "opacity-down");
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::slotPressed, &hotkeys, [&actions](int n) {
+        actions.invoke(QStringLiteral("slot-activate"), {QString::number(n)});
+    });
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::assignSlotPressed, &hotkeys,
+                     [&actions](int n) {
+                         actions.invoke(QStringLiteral("slot-assign"), {QString::number(n)});
+                     });
+    // Hold-to-preview is a press/release gesture rather than an action.
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::previewPressed,
+                     &manager, &PinManager::beginPreview);
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::previewReleased,
+                     &manager, &PinManager::endPreview);
+    // The OSD reads the new state, so it's connected after the actions above.
+    const auto osd = [&](void *(*target)(), bool opacity) {
+        return [&window, target, opacity]() {
+            window.showOsd(reinterpret_cast<intptr_t>(target()), opacity);
+        };
+    };
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::togglePin, &window,
+                     osd(&winpin::foregroundWindow, false));
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::pinUnderCursor, &window,
+                     osd(&winpin::windowUnderCursor, false));
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::pinTemporarily, &window,
+                     osd(&winpin::foregroundWindow, false));
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::toggleLastPinned, &window,
+                     [&window, &manager]() { window.showOsd(manager.lastPinned(), false); });
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::pinWithOpacity, &window,
+                     osd(&winpin::foregroundWindow, true));
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::opacityUp, &window,
+                     osd(&winpin::foregroundWindow, true));
+    QObject::connect(&hotkeys, &GlobalHotkeyManager::opacityDown, &window,
+                     osd(&winpin::foregroundWindow, true));
+    dispatch(&GlobalHotkeyManager::toggleWindow, "toggle-window");
+    dispatch(&GlobalHotkeyManager::quickPanel, "quick-panel");
+    dispatch(&GlobalHotkeyManager::kioskExit, "kiosk-exit");
+    dispatch(&GlobalHotkeyManager::hideAllPinned, "hide-all-pinned");
+
+    // Local usage stats and pin history (no-ops unless the user opted in).
+    QObject::connect(&manager, &PinManager::pinToggled, &manager,
+                     [](bool pinned, const QString &title, const QString &process) {
+                         if (pinned)
+                             usage::recordPin();
+                         if (usage::isEnabled())
+                             persistence::recordHistory(pinned ? QStringLiteral("pin")
+                                                               : QStringLiteral("unpin"),
+                                                        process, title);
+                     });
+    QObject::connect(&manager, &PinManager::pinClosed, &manager,
+                     [](intptr_t, const QString &title, const QString &process) {
+                         if (usage::isEnabled())
+                             persistence::recordHistory(QStringLiteral("closed"), process, title);
+                     });
+    QObject::connect(&manager, &PinManager::pinExpired, &manager,
+                     [](intptr_t, const QString &title, const QString &process) {
+                         if (usage::isEnabled())
+                             persistence::recordHistory(QStringLiteral("expired"), process, title);
+                     });
+    QObject::connect(&manager, &PinManager::pinLost, &manager,
+                     [](intptr_t, const QString &title, const QString &process) {
+                         if (usage::isEnabled())
+                             persistence::recordHistory(QStringLiteral("lost"), process, title);
+                     });
+    // Blocked pins are an audit trail for administrators, so they're kept
+    // whether or not the user opted in to history.
+    QObject::connect(&manager, &PinManager::pinBlocked, &manager,
+                     [](intptr_t, const QString &title, const QString &process) {
+                         persistence::recordHistory(QStringLiteral("blocked"), process, title);
+                     });
+    QObject::connect(&manager, &PinManager::errorOccurred, &manager,
+                     [](PinError code) { usage::recordError(errorCode(code)); });
+
+    // The structured audit trail (no-ops unless audit_log is on). A slider
+    // drag changes opacity dozens of times; only where it settles is recorded.
+    QObject::connect(&manager, &PinManager::pinToggled, &manager,
+                     [](bool pinned, const QString &, const QString &process) {
+                         audit::record(pinned ? QStringLiteral("pin") : QStringLiteral("unpin"),
+                                       process, QStringLiteral("ok"));
+                     });
+    QObject::connect(&manager, &PinManager::pinBlocked, &manager,
+                     [](intptr_t, const QString &, const QString &process) {
+                         audit::record(QStringLiteral("pin"), process, QStringLiteral("blocked"));
+                     });
+    QObject::connect(&manager, &PinManager::errorOccurred, &manager, [](PinError code) {
+        if (code != PinError::BlockedByPolicy)   // recorded as a blocked pin above
+            audit::record(QStringLiteral("error"), QString(), QStringLiteral("failed"),
+                          {{"code", errorCode(code)}});
+    });
+    auto *opacitySettled = new QTimer(&manager);
+    opacitySettled->setSingleShot(true);
+    opacitySettled->setInterval(1000);
+    QHash<intptr_t, int> settledOpacity;   // hwnd -> percent, until the timer fires
+    QObject::connect(&manager, &PinManager::opacityChanged, &manager,
+                     [opacitySettled, &settledOpacity](intptr_t hwnd, int percent) {
+                         if (!audit::isEnabled())
+                             return;
+                         settledOpacity.insert(hwnd, percent);
+                         opacitySettled->start();
+                     });
+    QObject::connect(opacitySettled, &QTimer::timeout, &manager, [&manager, &settledOpacity]() {
+        for (const PinnedWindow &w : manager.pinnedWindows()) {
+            if (settledOpacity.contains(w.hwnd))
+                audit::record(QStringLiteral("opacity"), w.processName, QStringLiteral("ok"),
+                              {{"percent", settledOpacity.value(w.hwnd)}});
+        }
+        settledOpacity.clear();
+    });
+    const auto feature = [&hotkeys](auto signal, const char *name) {
+        QObject::connect(&hotkeys, signal, &hotkeys, [name]() {
+            usage::recordFeature(QString::fromLatin1(name));
+        });
+    };
+    feature(&GlobalHotkeyManager::togglePin, "pin_hotkey");
+    feature(&GlobalHotkeyManager::previewPressed, "hold_to_preview");
+    feature(&GlobalHotkeyManager::pinUnderCursor, "pin_under_cursor");
+    feature(&GlobalHotkeyManager::opacityUp, "opacity_hotkey");
+    feature(&GlobalHotkeyManager::opacityDown, "opacity_hotkey");
+    feature(&GlobalHotkeyManager::quickPanel, "quick_panel");
+    feature(&GlobalHotkeyManager::hideAllPinned, "boss_key");
+    feature(&GlobalHotkeyManager::pinTemporarily, "temporary_pin");
+    feature(&GlobalHotkeyManager::toggleLastPinned, "pin_last");
+    feature(&GlobalHotkeyManager::focusLastPinned, "focus_last_pinned");
+    feature(&GlobalHotkeyManager::pinWithOpacity, "pin_with_opacity");
+    feature(&GlobalHotkeyManager::opacitySlider, "opacity_slider");
+    feature(&GlobalHotkeyManager::slotPressed, "pin_slot");
+    feature(&GlobalHotkeyManager::assignSlotPressed, "pin_slot_assign");
+    QObject::connect(&window, &MainWindow::settingsReloaded, &window,
+                     []() { usage::recordFeature(QStringLiteral("config_reload")); });
 
     // Re-register hotkeys when the user edits them in the Shortcuts dialog.
     QObject::connect(&window, &MainWindow::shortcutsChanged, &window,
                      [&](const persistence::ShortcutConfig &c) {
-                         if (!hotkeys.registerAll(c))
+                         if (safeMode)
+                             return;   // saved, and registered on the next normal start
+                         const bool updated = hotkeys.update(c);
+                         reportHotkeys();
+                         if (updated) {
+                             if (!hotkeys.failedActions().isEmpty())
+                                 window.notify(QObject::tr("Some hotkeys are unavailable: %1")
+                                                   .arg(hotkeys.failedActions().join(", ")));
+                             else
+                                 window.notify(QObject::tr("Shortcuts updated."));
+                         } else if (hotkeys.activeConfig() != c) {
+                             // Rolled back: show (and save) what is actually registered.
+                             window.setShortcutConfig(hotkeys.activeConfig());
                              window.notify(QObject::tr(
-                                 "Could not register the new hotkeys — another app may be using them."));
-                         else if (!hotkeys.failedActions().isEmpty())
-                             window.notify(QObject::tr("Some hotkeys are unavailable: %1")
-                                               .arg(hotkeys.failedActions().join(QStringLiteral(", "))));
-                         else
-                             window.notify(QObject::tr("Shortcuts updated."));
+                                 "Could not register the new hotkeys — another app may be using "
+                                 "them. Kept the previous ones."));
+                         } else {
+                             window.notify(QObject::tr(
+                                 "Could not register the new hotkeys — another app may be using "
+                                 "them."));
+                         }
+                     });
+
+    // Macro hotkeys ride along with every registerAll().
+    hotkeys.setMacroShortcuts(window.macros());
+    QObject::connect(&window, &MainWindow::macrosChanged, &window,
+                     [&](const QVector<persistence::Macro> &macros) {
+                         hotkeys.setMacroShortcuts(macros);
+                         if (!safeMode)
+                             hotkeys.registerAll(window.shortcutConfig());
                      });
 
-    if (!hotkeys.registerAll(window.shortcutConfig())) {
-        qWarning("No global hotkeys could be registered");
+    if (!safeMode && !hotkeys.registerAll(window.shortcutConfig())) {
+        qWarning("[%s] No global hotkeys could be registered",
+                 qUtf8Printable(errorCode(PinError::ShortcutConflict)));
         window.notify(QObject::tr(
             "Could not register global hotkeys — another app may be using them."));
     } else if (!hotkeys.failedActions().isEmpty()) {
-        qWarning("Some hotkeys unavailable: %s",
+        qWarning("[%s] Some hotkeys unavailable: %s",
+                 qUtf8Printable(errorCode(PinError::ShortcutConflict)),
                  qUtf8Printable(hotkeys.failedActions().join(QStringLiteral(", "))));
         window.notify(QObject::tr("Some hotkeys are unavailable: %1")
                           .arg(hotkeys.failedActions().join(QStringLiteral(", "))));
     }
 
+    if (!safeMode)
+        reportHotkeys();
+
     // Re-pin whatever was pinned last session.
-    manager.restoreSaved();
+    const RestoreStats restored = safeMode ? RestoreStats() : manager.restoreSaved();
+    if (safeMode)
+        eventlog::report(eventlog::Event::Started,
+                         QStringLiteral("PinIt %1 started in safe mode: no hotkeys, hooks, "
+                                        "schedules, kiosk mode or restored pins.")
+                             .arg(QStringLiteral(PINIT_VERSION_STR)));
+    else
+        eventlog::report(eventlog::Event::Started,
+                         QStringLiteral("PinIt %1 started: %2 of %3 saved pin(s) restored, "
+                                        "hotkeys via %4.")
+                             .arg(QStringLiteral(PINIT_VERSION_STR))
+                             .arg(restored.restored)
+                             .arg(restored.saved)
+                             .arg(hotkeys.usingKeyboardHook()
+                                      ? QStringLiteral("keyboard hook")
+                                      : QStringLiteral("RegisterHotKey")));
+
+    StartupStatus status;
+    status.keyboardHook      = hotkeys.usingKeyboardHook();
+    status.hotkeysRegistered = hotkeys.registeredActions();
+    status.hotkeysFailed     = hotkeys.failedActions();
+    status.pinsSaved         = restored.saved;
+    status.pinsRestored      = restored.restored;
+    status.safeMode          = safeMode;
+    window.setStartupStatus(status);
+    if (safeMode)
+        window.notify(QObject::tr("PinIt started in safe mode — hotkeys are off and saved pins "
+                                  "weren't restored."));
+    else if (restored.restored < restored.saved)
+        window.notify(QObject::tr("Couldn't re-pin %n window(s) — they weren't open yet.", "",
+                                  restored.saved - restored.restored));
+    else if (const int n = manager.adoptableWindows().size())
+        window.notify(QObject::tr("%n window(s) already always on top — tray → Adopt "
+                                  "always-on-top windows… to manage them here.", "", n));
+    window.checkConfig();   // last, so its balloon is the one left showing
 
     // When launched at login with --minimized, start silently in the tray
     // instead of popping the window. Fall back to showing it if there's no tray.
+    // Safe mode always shows it: that's where recovering happens.
     const bool startMinimized =
         QCoreApplication::arguments().contains(QStringLiteral("--minimized"));
-    if (!startMinimized || !QSystemTrayIcon::isSystemTrayAvailable())
+    if (safeMode || !startMinimized || !QSystemTrayIcon::isSystemTrayAvailable())
         window.show();
+    if (command.first() != QLatin1String("show-window"))
+        QTimer::singleShot(0, &window, [&runCommand, command]() { runCommand(command); });
+    // First-run steps, once the startup balloons above have had their turn.
+    QTimer::singleShot(5000, &window, &MainWindow::continueOnboarding);
 
     return app.exec();
 }
@@ -1,8 +1,34 @@
 #include "mainwindow.h"
 #include "pinmanager.h"
 #include "winpin.h"
+#include "windowicon.h"
+#include "capture.h"
+#include "uia.h"
+#include "macros.h"
+#include "schedule.h"
+#include "triggers.h"
+#include "perf.h"
+#include "taskbar.h"
 #include "shortcuts.h"
 #include "shortcutsdialog.h"
+#include "usage.h"
+#include "audit.h"
+#include "eventlog.h"
+#include "i18n.h"
+#include "quickpanel.h"
+#include "opacityslider.h"
+#include "pinbadges.h"
+#include "edgedock.h"
+#include "kiosk.h"
+#include "osd.h"
+#include "autostart.h"
+#include "platform.h"
+#include "compat.h"
+#include "configschema.h"
+#include "logging.h"
+#include "policy.h"
+#include "selftest.h"
+#include "onboarding.h"
 
 #include <QApplication>
 #include <QVBoxLayout>
@@ -27,11 +53,82 @@
 #include <QMessageBox>
 #include <QColor>
 #include <QCursor>
+#include <QInputDialog>
+#include <QLineEdit>
+#include <QRadioButton>
+#include <QFileDialog>
+#include <QShowEvent>
+#include <QTimer>
+#include <QSignalBlocker>
+#include <QDateTime>
+#include <QTime>
+#include <QActionGroup>
+#include <QLocale>
+#include <QPainter>
+#include <QFormLayout>
+#include <QSpinBox>
+#include <QComboBox>
+#include <QFileSystemWatcher>
+#include <QFileInfo>
+#include <QStandardPaths>
+#include <QClipboard>
+#include <QPlainTextEdit>
+#include <QTimeEdit>
+#include <QThread>
+
+#include <windows.h>
+
+#include <algorithm>
+#include <functional>
+#include <memory>
 
 #include "version.h"
 
 namespace {
 
+// SystemUsesLightTheme here is the taskbar's theme (AppsUseLightTheme is apps').
+const char *const kPersonalizeKey =
+    "HKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize";
+
+constexpr int kOnboardingGapMs = 10000;   // between first-run steps
+
+// Greys out a control (a widget, or a menu's QAction) whose setting an
+// administrator has fixed (see policy.h).
+template <typename Control>
+void lockIfPolicy(Control *control, const char *key)
+{
+    if (!policy::isLocked(QLatin1String(key)))
+        return;
+    control->setEnabled(false);
+    control->setToolTip(MainWindow::tr("Set by your administrator"));
+}
+
+bool taskbarIsLight()
+{
+    QSettings personalize(QString::fromLatin1(kPersonalizeKey), QSettings::NativeFormat);
+    return personalize.value(QStringLiteral("SystemUsesLightTheme"), 0).toInt() != 0;
+}
+
+// Monochrome tray glyph — a window with a pin in its corner — in whichever ink
+// reads on the taskbar. The full-colour app icon gets lost on both themes at 16px.
+QIcon trayGlyph(bool lightTaskbar)
+{
+    const QColor ink = lightTaskbar ? QColor(0x1f, 0x1f, 0x1f) : QColor(Qt::white);
+    QPixmap pm(64, 64);
+    pm.fill(Qt::transparent);
+    QPainter p(&pm);
+    p.setRenderHint(QPainter::Antialiasing);
+    p.setPen(QPen(ink, 5));
+    p.setBrush(Qt::NoBrush);
+    p.drawRoundedRect(QRectF(6, 18, 42, 38), 6, 6);
+    p.setPen(QPen(ink, 5, Qt::SolidLine, Qt::RoundCap));
+    p.drawLine(QPointF(34, 32), QPointF(46, 20));   // needle
+    p.setPen(Qt::NoPen);
+    p.setBrush(ink);
+    p.drawEllipse(QPointF(50, 14), 10, 10);         // head
+    return QIcon(pm);
+}
+
 QIcon appIcon()
 {
     QIcon ic(QStringLiteral(":/icon.png"));
@@ -54,6 +151,44 @@ QLabel *plusLabel(const QString &text = QStringLiteral("+"))
     return l;
 }
 
+// The chips for a whole shortcut: [Ctrl] + [Alt] + [P], with "then [O]"
+// appended for the second step of a two-step chord.
+void addKeyChips(QHBoxLayout *row, const QString &shortcut)
+{
+    const QStringList steps = shortcuts::steps(shortcut);
+    for (int s = 0; s < steps.size(); ++s) {
+        if (s > 0)
+            row->addWidget(plusLabel(QObject::tr("then")));
+        const QStringList keys = shortcuts::displayTokens(steps[s]);
+        for (int i = 0; i < keys.size(); ++i) {
+            if (i > 0)
+                row->addWidget(plusLabel());
+            row->addWidget(keyChip(keys[i]));
+        }
+    }
+}
+
+// The same as text: "Ctrl + Alt + P then O".
+QString shortcutText(const QString &shortcut)
+{
+    QStringList steps;
+    for (const QString &step : shortcuts::steps(shortcut))
+        steps << shortcuts::displayTokens(step).join(QStringLiteral(" + "));
+    return steps.join(QLatin1Char(' ') + QObject::tr("then") + QLatin1Char(' '));
+}
+
+autostart::Mode autostartMode(const persistence::UserSettings &s, bool enabled)
+{
+    autostart::Mode mode;
+    mode.enabled      = enabled;
+    mode.delaySeconds = s.autostartDelay;
+    mode.arguments    = s.autostartArgs;
+    mode.backend      = s.autostartTask ? autostart::Backend::TaskScheduler
+                                        : autostart::Backend::RunKey;
+    mode.elevated     = s.autostartElevated;
+    return mode;
+}
+
 QFrame *makeCard()
 {
     auto *card = new QFrame;
@@ -63,6 +198,16 @@ QFrame *makeCard()
 
 // Console apps (PowerShell, cmd) set their window title to a full path.
 // Show just the final component so the list stays readable.
+// Time left on a temporary pin: "4:59", or "1:02:00" past an hour.
+QString countdownText(int seconds)
+{
+    const QTime t = QTime(0, 0).addSecs(seconds);
+    return t.toString(seconds >= 3600 ? QStringLiteral("h:mm:ss") : QStringLiteral("m:ss"));
+}
+
+// Choices offered for a temporary pin's length, in seconds.
+const int kTempPinChoices[] = {60, 5 * 60, 15 * 60, 30 * 60, 60 * 60};
+
 QString displayTitle(const QString &title)
 {
     const int slash = title.lastIndexOf(QLatin1Char('\\'));
@@ -71,6 +216,41 @@ QString displayTitle(const QString &title)
     return title;
 }
 
+// Window icons are filled in once the list is on screen, a few rows per pass
+// (each row's hwnd in Qt::UserRole), so a window that's slow to answer
+// WM_GETICON doesn't hold up the dialog opening. Rows get a blank icon until
+// then, so the text doesn't shift when theirs arrives.
+void loadIconsLater(QListWidget *list, int from = 0)
+{
+    if (from == 0) {
+        QPixmap blank(16, 16);
+        blank.fill(Qt::transparent);
+        for (int i = 0; i < list->count(); ++i)
+            list->item(i)->setIcon(QIcon(blank));
+    }
+    QTimer::singleShot(0, list, [list, from]() {
+        const int to = qMin(from + 8, list->count());
+        for (int i = from; i < to; ++i) {
+            QListWidgetItem *item = list->item(i);
+            void *hwnd = reinterpret_cast<void *>(item->data(Qt::UserRole).toLongLong());
+            item->setIcon(QIcon(windowicon::forWindow(hwnd, 16)));
+        }
+        if (to < list->count())
+            loadIconsLater(list, to);
+    });
+}
+
+// Why the opacity controls are greyed out, or empty if they aren't: an
+// administrator turned transparency off, or this Windows can't do it.
+QString opacityUnavailable()
+{
+    if (policy::opacityDisabled())
+        return MainWindow::tr("Transparency is turned off by your administrator");
+    if (!platform::capabilities().opacity)
+        return MainWindow::tr("This version of Windows can't make windows see-through");
+    return QString();
+}
+
 // Deterministic avatar colour for a process name (ported from the original
 // PinIt frontend) so each pinned app gets a stable little badge.
 QColor avatarColor(const QString &name)
@@ -96,11 +276,138 @@ QString avatarInitial(const QString &name)
     return n.isEmpty() ? QStringLiteral("?") : QString(n.at(0).toUpper());
 }
 
+// The list-and-form dialog behind Macros, Scheduled actions and Triggers: the
+// entries on the left, the selected one's fields in form() on the right, all
+// checked together on OK. The caller keeps the drafts; the hooks move one
+// draft in and out of its widgets.
+class DraftDialog : public QDialog
+{
+public:
+    std::function<void(int row)> fill;    // fill the form; -1 = nothing selected
+    std::function<void(int row)> store;   // read the form back into draft `row`
+    std::function<QString()>     add;     // append a new draft; returns its name
+    std::function<void(int row)> remove;
+    // On OK: the row of the first draft with a problem, which goes in *problem;
+    // -1 if there is none.
+    std::function<int(QString *problem)> check;
+
+    DraftDialog(QWidget *parent, const QString &title, const QSize &size,
+                const QStringList &names)
+        : QDialog(parent)
+    {
+        setWindowTitle(title);
+        setWindowIcon(appIcon());
+        resize(size);
+        auto *outer = new QVBoxLayout(this);
+        auto *body = new QHBoxLayout;
+        outer->addLayout(body, 1);
+
+        auto *left = new QVBoxLayout;
+        m_list = new QListWidget(this);
+        m_list->addItems(names);
+        left->addWidget(m_list, 1);
+        auto *addBtn = new QPushButton(MainWindow::tr("Add"), this);
+        m_removeBtn = new QPushButton(MainWindow::tr("Remove"), this);
+        auto *listButtons = new QHBoxLayout;
+        listButtons->addWidget(addBtn);
+        listButtons->addWidget(m_removeBtn);
+        left->addLayout(listButtons);
+        body->addLayout(left);
+
+        m_panel = new QWidget(this);
+        m_form = new QFormLayout(m_panel);
+        m_form->setContentsMargins(0, 0, 0, 0);
+        body->addWidget(m_panel, 2);
+
+        auto *buttons = new QDialogButtonBox(QDialogButtonBox::Ok | QDialogButtonBox::Cancel,
+                                             this);
+        outer->addWidget(buttons);
+        connect(buttons, &QDialogButtonBox::rejected, this, &QDialog::reject);
+        connect(buttons, &QDialogButtonBox::accepted, this, [this, title]() {
+            storeCurrent();
+            QString problem;
+            const int row = check(&problem);
+            if (row < 0) {
+                accept();
+                return;
+            }
+            const QString name = m_list->item(row) ? m_list->item(row)->text() : QString();
+            m_list->setCurrentRow(row);
+            QMessageBox::warning(this, title, MainWindow::tr("“%1”: %2").arg(name, problem));
+        });
+        connect(m_list, &QListWidget::currentRowChanged, this, [this](int row) {
+            storeCurrent();
+            select(row);
+        });
+        connect(addBtn, &QPushButton::clicked, this, [this]() {
+            m_list->addItem(add());
+            m_list->setCurrentRow(m_list->count() - 1);
+            if (m_name) {
+                m_name->selectAll();
+                m_name->setFocus();
+            }
+        });
+        connect(m_removeBtn, &QPushButton::clicked, this, [this]() {
+            const int row = m_current;
+            if (row < 0)
+                return;
+            m_current = -1;   // nothing to store for the removed one
+            remove(row);
+            delete m_list->takeItem(row);
+        });
+    }
+
+    QFormLayout *form() const { return m_form; }
+    int current() const { return m_current; }
+
+    // The field holding the entry's name, mirrored into the list as it's typed.
+    void setNameField(QLineEdit *name)
+    {
+        m_name = name;
+        connect(name, &QLineEdit::textEdited, this, [this](const QString &text) {
+            if (QListWidgetItem *item = m_list->item(m_current))
+                item->setText(text);
+        });
+    }
+
+    bool run()
+    {
+        select(-1);
+        if (m_list->count() > 0)
+            m_list->setCurrentRow(0);
+        return exec() == QDialog::Accepted;
+    }
+
+private:
+    void storeCurrent()
+    {
+        if (m_current >= 0)
+            store(m_current);
+    }
+
+    void select(int row)
+    {
+        m_current = -1;   // don't store while the widgets are being filled
+        fill(row);
+        m_panel->setEnabled(row >= 0);
+        m_removeBtn->setEnabled(row >= 0);
+        m_current = row;
+    }
+
+    QListWidget *m_list = nullptr;
+    QPushButton *m_removeBtn = nullptr;
+    QWidget     *m_panel = nullptr;
+    QFormLayout *m_form = nullptr;
+    QLineEdit   *m_name = nullptr;
+    int          m_current = -1;
+};
+
 } // namespace
 
-MainWindow::MainWindow(PinManager *manager, QWidget *parent)
+MainWindow::MainWindow(PinManager *manager, bool safeMode, QWidget *parent)
     : QMainWindow(parent)
     , m_manager(manager)
+    , m_safeMode(safeMode)
 {
     setWindowTitle(QStringLiteral("PinIt"));
     setWindowIcon(appIcon());
@@ -109,16 +416,113 @@ MainWindow::MainWindow(PinManager *manager, QWidget *parent)
     setWindowFlags(Qt::Window | Qt::MSWindowsFixedSizeDialogHint
                    | Qt::WindowTitleHint | Qt::WindowSystemMenuHint
                    | Qt::WindowMinimizeButtonHint | Qt::WindowCloseButtonHint);
-    setFixedSize(360, 470);
+    setFixedSize(360, 520);
 
     m_settings = persistence::loadSettings();
-
+    usage::setEnabled(m_settings.usageStats);
+    audit::setLocation(m_settings.auditLogPath);
+    audit::setEnabled(m_settings.auditLog);
+    eventlog::setEnabled(m_settings.eventLog);
+    m_manager->setProtectedChoices(m_settings.protectedChoices);
+    m_manager->setPinFeedback(m_settings.pinFeedback);
+    m_manager->setBackdrop(m_settings.backdrop);
+    m_manager->setAppCompat(compat::table(m_settings.appCompat, m_settings.noLayeringApps));
+    m_manager->setTitleMarker(m_settings.titleMarker);
+    m_manager->setTemporaryPinDuration(m_settings.tempPinSeconds);
+    m_manager->setPinOpacity(m_settings.pinOpacityPercent);
+    m_manager->setIdleUnpinMinutes(m_settings.idleUnpinMinutes);
+    m_manager->setCompactRules(m_settings.compactRules);
+    m_manager->setCoexistencePolicy(m_settings.coexistence);
+    m_manager->setLostPolicy(m_settings.topmostLost);
+    m_manager->setPinOwnedWindows(m_settings.pinOwnedWindows);
+    m_manager->setUiaMatching(m_settings.uiaMatching);
+    m_manager->setOpacityDisabled(policy::opacityDisabled());
+    m_manager->setBlockedProcesses(policy::neverPin());
+    applyRemoteOpacity();
+    applyFade();
+
+    m_kiosk = new Kiosk(m_manager, this);
     buildUi();
     buildTray();
+    healAutostart();   // after the tray, so it can say if it couldn't
+    m_quickPanel = new QuickPanel(m_manager, this);
+    m_opacitySlider = new OpacitySlider(m_manager, this);
+    m_badges = new PinBadges(m_manager, this);
+    m_edgeDock = new EdgeDock(m_manager, this);
+    if (m_settings.kiosk && !m_safeMode)   // kiosk mode is a setting safe mode escapes
+        m_kiosk->start(m_settings.kioskPath);
+    m_osd = new Osd;
+    applyOsd();
+    m_badges->setClickToUnpin(m_settings.badgeClickUnpin);
+    m_badges->setEnabled(m_settings.cornerBadge);
+    // Create the native window even when starting hidden in the tray, so the
+    // theme-change broadcast (see nativeEvent) still reaches us.
+    winId();
+    applyKeepOnTop();
     rebuildList();
+    rebuildPending();
+    updatePinIndicators(m_manager->pinnedWindows());
+
+    // Watch pinned.json so hand edits (or a Dropbox-synced copy) apply live.
+    m_reloadTimer = new QTimer(this);
+    m_reloadTimer->setSingleShot(true);
+    m_reloadTimer->setInterval(300);
+    connect(m_reloadTimer, &QTimer::timeout, this, &MainWindow::reloadSettings);
+    m_configWatcher = new QFileSystemWatcher(this);
+    watchConfig();
+    connect(m_configWatcher, &QFileSystemWatcher::fileChanged,
+            m_reloadTimer, qOverload<>(&QTimer::start));
+    // Only for a pinned.json that wasn't there yet (first run, a sync catching up).
+    connect(m_configWatcher, &QFileSystemWatcher::directoryChanged, this, [this]() {
+        const QString path = persistence::filePath();
+        if (!m_configWatcher->files().contains(path) && QFileInfo::exists(path))
+            m_reloadTimer->start();
+    });
 
     connect(m_manager, &PinManager::pinsChanged, this, &MainWindow::rebuildList);
-    connect(m_manager, &PinManager::errorOccurred, this, &MainWindow::notify);
+    // Queued, so the dialog doesn't run (and pin) inside the pin() that asked.
+    connect(m_manager, &PinManager::pinConfirmationRequired,
+            this, &MainWindow::confirmProtectedPin, Qt::QueuedConnection);
+    connect(m_manager, &PinManager::coexistenceChoiceRequired,
+            this, &MainWindow::askCoexistence, Qt::QueuedConnection);
+    connect(m_manager, &PinManager::pinStateChanged, this, &MainWindow::updatePinIndicators);
+    connect(m_manager, &PinManager::titleChanged, this, &MainWindow::updateTitle);
+    connect(m_manager, &PinManager::pinCountdown, this, &MainWindow::updateCountdown);
+    connect(m_manager, &PinManager::pendingRestoresChanged, this, &MainWindow::rebuildPending);
+    // Tell the user the first time an app fights a pin; after that it's noise.
+    connect(m_manager, &PinManager::topmostReenforced, this,
+            [this](intptr_t hwnd, const QString &title, const QString &) {
+                if (m_reenforceNoticed.contains(hwnd))
+                    return;
+                m_reenforceNoticed.insert(hwnd);
+                notify(tr("PinIt restored Always-on-Top for %1").arg(displayTitle(title)));
+            });
+    connect(m_manager, &PinManager::pinLost, this,
+            [this](intptr_t, const QString &title, const QString &, const QString &culprit) {
+                notify(culprit.isEmpty()
+                           ? tr("%1 is no longer on top").arg(displayTitle(title))
+                           : tr("%1 is no longer on top — %2 may have changed it")
+                                 .arg(displayTitle(title), culprit));
+            });
+    connect(m_manager, &PinManager::displayTopologyChanged, this,
+            [this](const QVector<QRect> &monitors, int moved) {
+                if (moved > 0)
+                    notify(tr("Display layout changed (%n monitor(s)) — moved pinned "
+                              "windows back on screen.", "", monitors.size()));
+            });
+    // HWNDs get reused, so forget closed windows.
+    connect(m_manager, &PinManager::pinClosed, this,
+            [this](intptr_t hwnd) { m_reenforceNoticed.remove(hwnd); });
+    connect(m_manager, &PinManager::appRestarted, this,
+            [this](intptr_t, const QString &process) {
+                notify(tr("%1 closed — PinIt restarted it.").arg(process));
+            });
+    connect(m_manager, &PinManager::pinExpired, this,
+            [this](intptr_t, const QString &title, const QString &) {
+                notify(tr("Pin expired: %1").arg(displayTitle(title)));
+            });
+    connect(m_manager, &PinManager::errorOccurred, this,
+            [this](PinError, const QString &message) { notify(message); });
     connect(m_manager, &PinManager::pinToggled, this,
             [this](bool pinned, const QString &title, const QString &) {
                 if (pinned && m_settings.enableSound)
@@ -128,6 +532,11 @@ MainWindow::MainWindow(PinManager *manager, QWidget *parent)
             });
 }
 
+MainWindow::~MainWindow()
+{
+    delete m_osd;
+}
+
 void MainWindow::buildUi()
 {
     auto *central = new QWidget(this);
@@ -153,8 +562,52 @@ void MainWindow::buildUi()
     titleBox->addWidget(tagline);
     header->addLayout(titleBox);
     header->addStretch();
+
+    // Float PinIt itself above other apps while arranging things.
+    m_selfPin = new QPushButton(QString::fromUtf8("\xF0\x9F\x93\x8C"));   // 📌
+    m_selfPin->setObjectName(QStringLiteral("selfPin"));
+    m_selfPin->setCheckable(true);
+    m_selfPin->setChecked(m_settings.keepOnTop);
+    m_selfPin->setFixedSize(30, 30);
+    m_selfPin->setToolTip(tr("Keep PinIt on top"));
+    m_selfPin->setCursor(Qt::PointingHandCursor);
+    connect(m_selfPin, &QPushButton::toggled, this, [this](bool on) {
+        m_settings.keepOnTop = on;
+        persistence::saveSettings(m_settings);
+        applyKeepOnTop();
+    });
+    lockIfPolicy(m_selfPin, "keep_on_top");
+    header->addWidget(m_selfPin);
     root->addLayout(header);
 
+    // --- Safe mode (hidden unless started with --safe-mode / Shift) ----------
+    m_safeModeCard = makeCard();
+    auto *safeLayout = new QVBoxLayout(m_safeModeCard);
+    safeLayout->setContentsMargins(12, 8, 8, 8);
+    safeLayout->setSpacing(6);
+    auto *safeText = new QLabel(tr("<b>Safe mode</b> — hotkeys are off and last session's pins "
+                                   "weren't restored (they're kept for next time). Fix the "
+                                   "setting that's causing trouble or go back to a backup, "
+                                   "then quit and start PinIt again."));
+    safeText->setWordWrap(true);
+    safeLayout->addWidget(safeText);
+    auto *safeRow = new QHBoxLayout;
+    safeRow->setSpacing(6);
+    auto *restoreBtn = new QPushButton(tr("Restore backup"));
+    auto *restoreMenu = new QMenu(restoreBtn);
+    connect(restoreMenu, &QMenu::aboutToShow, this, [this, restoreMenu]() {
+        fillBackupMenu(restoreMenu);
+    });
+    restoreBtn->setMenu(restoreMenu);
+    safeRow->addWidget(restoreBtn);
+    auto *quitBtn = new QPushButton(tr("Quit PinIt"));
+    connect(quitBtn, &QPushButton::clicked, qApp, &QApplication::quit);
+    safeRow->addWidget(quitBtn);
+    safeRow->addStretch();
+    safeLayout->addLayout(safeRow);
+    m_safeModeCard->setVisible(m_safeMode);
+    root->addWidget(m_safeModeCard);
+
     // --- Pin button ----------------------------------------------------------
     auto *addBtn = new QPushButton(tr("+   Pin a window…"));
     addBtn->setObjectName(QStringLiteral("primary"));
@@ -176,8 +629,17 @@ void MainWindow::buildUi()
 
     auto *editShortcuts = new QPushButton(tr("Edit shortcuts…"));
     connect(editShortcuts, &QPushButton::clicked, this, &MainWindow::openShortcutsDialog);
+    lockIfPolicy(editShortcuts, "shortcuts");
     root->addWidget(editShortcuts, 0, Qt::AlignLeft);
 
+    // --- Pending restores (hidden unless a saved pin found no window) ---------
+    m_pendingCard = makeCard();
+    m_pendingLayout = new QVBoxLayout(m_pendingCard);
+    m_pendingLayout->setContentsMargins(12, 8, 8, 8);
+    m_pendingLayout->setSpacing(4);
+    m_pendingCard->setVisible(false);
+    root->addWidget(m_pendingCard);
+
     // --- PINNED (n) ----------------------------------------------------------
     m_pinnedHeader = new QLabel(tr("PINNED (0)"));
     m_pinnedHeader->setProperty("role", "section");
@@ -209,12 +671,7 @@ void MainWindow::buildUi()
     auto *use = new QLabel(tr("Use"));
     use->setProperty("role", "muted");
     hintRow->addWidget(use);
-    const QStringList toggleKeys = shortcuts::displayTokens(m_settings.shortcuts.togglePin);
-    for (int i = 0; i < toggleKeys.size(); ++i) {
-        if (i > 0)
-            hintRow->addWidget(plusLabel());
-        hintRow->addWidget(keyChip(toggleKeys[i]));
-    }
+    addKeyChips(hintRow, m_settings.shortcuts.togglePin);
     hintRow->addStretch();
     ec->addLayout(hintRow);
     m_listLayout->insertWidget(0, m_emptyCard);   // lives in the list region
@@ -226,16 +683,45 @@ void MainWindow::buildUi()
         m_settings.enableSound = on;
         persistence::saveSettings(m_settings);
     });
+    lockIfPolicy(m_soundBox, "enable_sound");
     root->addWidget(m_soundBox);
 
+    m_fadeBox = new QCheckBox(tr("Fade opacity changes"));
+    m_fadeBox->setChecked(m_settings.animateOpacity);
+    connect(m_fadeBox, &QCheckBox::toggled, this, [this](bool on) {
+        m_settings.animateOpacity = on;
+        persistence::saveSettings(m_settings);
+        applyFade();
+    });
+    lockIfPolicy(m_fadeBox, "animate_opacity");
+    root->addWidget(m_fadeBox);
+
+    auto *autostartRow = new QHBoxLayout;
     m_autostartBox = new QCheckBox(tr("Start PinIt with Windows"));
     m_autostartBox->setChecked(m_settings.startWithWindows);
     connect(m_autostartBox, &QCheckBox::toggled, this, [this](bool on) {
+        if (!applyAutostart(on)) {
+            notify(tr("Couldn't update start-with-Windows — Windows refused the change."));
+            const QSignalBlocker block(m_autostartBox);
+            m_autostartBox->setChecked(!on);
+            return;
+        }
         m_settings.startWithWindows = on;
-        applyAutostart(on);
         persistence::saveSettings(m_settings);
     });
-    root->addWidget(m_autostartBox);
+    lockIfPolicy(m_autostartBox, "start_with_windows");
+    autostartRow->addWidget(m_autostartBox);
+    autostartRow->addStretch();
+    auto *startupOptions = new QPushButton(tr("Options…"));
+    connect(startupOptions, &QPushButton::clicked, this, &MainWindow::showStartupOptions);
+    autostartRow->addWidget(startupOptions);
+    root->addLayout(autostartRow);
+    if (persistence::isPortable()) {
+        // A login entry would point at a drive that may not be there next time.
+        m_autostartBox->setEnabled(false);
+        startupOptions->setEnabled(false);
+        m_autostartBox->setToolTip(tr("Not available in portable mode."));
+    }
 
     setCentralWidget(central);
 }
@@ -243,6 +729,7 @@ void MainWindow::buildUi()
 void MainWindow::setShortcutConfig(const persistence::ShortcutConfig &cfg)
 {
     m_settings.shortcuts = cfg;
+    persistence::saveSettings(m_settings);
     if (m_shortcutsLayout)
         fillShortcutRows(m_shortcutsLayout);
 }
@@ -265,14 +752,10 @@ void MainWindow::fillShortcutRows(QVBoxLayout *scv)
 
     const persistence::ShortcutConfig &sc = m_settings.shortcuts;
 
-    auto addRow = [&](const QStringList &keys, const QString &desc) {
+    auto addRow = [&](const QString &shortcut, const QString &desc) {
         auto *row = new QHBoxLayout;
         row->setSpacing(6);
-        for (int i = 0; i < keys.size(); ++i) {
-            if (i > 0)
-                row->addWidget(plusLabel());
-            row->addWidget(keyChip(keys[i]));
-        }
+        addKeyChips(row, shortcut);
         row->addStretch();
         auto *d = new QLabel(desc);
         d->setProperty("role", "desc");
@@ -280,9 +763,13 @@ void MainWindow::fillShortcutRows(QVBoxLayout *scv)
         scv->addLayout(row);
     };
 
-    addRow(shortcuts::displayTokens(sc.togglePin), tr("Pin / unpin window"));
+    addRow(sc.togglePin, tr("Pin / unpin window"));
 
-    {   // Opacity row shows both +/- keys sharing the same modifiers.
+    if (shortcuts::isChord(sc.opacityUp) || shortcuts::isChord(sc.opacityDown)) {
+        // Chords don't share a tail key — give each its own row.
+        addRow(sc.opacityUp, tr("Opacity up"));
+        addRow(sc.opacityDown, tr("Opacity down"));
+    } else {   // Opacity row shows both +/- keys sharing the same modifiers.
         const QStringList up = shortcuts::displayTokens(sc.opacityUp);
         const QStringList down = shortcuts::displayTokens(sc.opacityDown);
         auto *row = new QHBoxLayout;
@@ -306,12 +793,29 @@ void MainWindow::fillShortcutRows(QVBoxLayout *scv)
         scv->addLayout(row);
     }
 
-    addRow(shortcuts::displayTokens(sc.toggleWindow), tr("Show / hide PinIt"));
+    addRow(sc.pinUnderCursor, tr("Pin window under mouse"));
+    addRow(sc.quickPanel, tr("Quick panel"));
+    addRow(sc.hideAllPinned, tr("Hide / show all pinned"));
+    addRow(sc.pinTemporarily, tr("Pin for a while"));
+    addRow(sc.slot[0], tr("Go to pin slot 1 (2, 3 alike)"));
+    addRow(sc.toggleLastPinned, tr("Pin / unpin the last pinned window"));
+    addRow(sc.pinWithOpacity, tr("Pin the focused window see-through"));
+    addRow(sc.opacitySlider, tr("Opacity slider for the focused pin"));
+    addRow(sc.toggleWindow, tr("Show / hide PinIt"));
 }
 
 void MainWindow::openShortcutsDialog()
 {
-    ShortcutsDialog dlg(m_settings.shortcuts, this);
+    if (policy::isLocked(QStringLiteral("shortcuts"))) {
+        notify(tr("Your administrator sets PinIt's shortcuts."));
+        return;
+    }
+    QStringList macroShortcuts;
+    for (const persistence::Macro &m : m_settings.macros) {
+        if (!m.shortcut.isEmpty())
+            macroShortcuts << m.shortcut;
+    }
+    ShortcutsDialog dlg(m_settings.shortcuts, macroShortcuts, this);
     if (dlg.exec() != QDialog::Accepted)
         return;
 
@@ -322,6 +826,130 @@ void MainWindow::openShortcutsDialog()
     emit shortcutsChanged(m_settings.shortcuts);
 }
 
+void MainWindow::watchConfig()
+{
+    // Saving replaces the file (write + rename), which drops it from the watch.
+    // The folder is watched as well, for a pinned.json that appears later.
+    const QString dir = persistence::dataDir();
+    if (!m_configWatcher->directories().contains(dir) && QFileInfo::exists(dir))
+        m_configWatcher->addPath(dir);
+    const QString path = persistence::filePath();
+    if (!m_configWatcher->files().contains(path) && QFileInfo::exists(path))
+        m_configWatcher->addPath(path);
+}
+
+void MainWindow::reloadSettings()
+{
+    watchConfig();
+    if (!persistence::changedExternally())
+        return;   // our own save
+
+    const persistence::UserSettings old = m_settings;
+    m_settings = persistence::loadSettings();
+    qInfo("pinned.json changed on disk; reloaded settings");
+    checkConfig();
+
+    {
+        const QSignalBlocker b1(m_soundBox), b2(m_autostartBox), b3(m_selfPin), b4(m_fadeBox);
+        m_soundBox->setChecked(m_settings.enableSound);
+        m_fadeBox->setChecked(m_settings.animateOpacity);
+        m_autostartBox->setChecked(m_settings.startWithWindows);
+        m_selfPin->setChecked(m_settings.keepOnTop);
+    }
+    usage::setEnabled(m_settings.usageStats);
+    audit::setLocation(m_settings.auditLogPath);
+    audit::setEnabled(m_settings.auditLog);
+    eventlog::setEnabled(m_settings.eventLog);
+    m_manager->setProtectedChoices(m_settings.protectedChoices);
+    m_manager->setPinFeedback(m_settings.pinFeedback);
+    m_manager->setBackdrop(m_settings.backdrop);
+    m_manager->setAppCompat(compat::table(m_settings.appCompat, m_settings.noLayeringApps));
+    m_manager->setTitleMarker(m_settings.titleMarker);
+    m_manager->setTemporaryPinDuration(m_settings.tempPinSeconds);
+    m_manager->setPinOpacity(m_settings.pinOpacityPercent);
+    m_manager->setIdleUnpinMinutes(m_settings.idleUnpinMinutes);
+    m_manager->setCompactRules(m_settings.compactRules);
+    m_manager->setCoexistencePolicy(m_settings.coexistence);
+    m_manager->setLostPolicy(m_settings.topmostLost);
+    m_manager->setPinOwnedWindows(m_settings.pinOwnedWindows);
+    m_manager->setUiaMatching(m_settings.uiaMatching);
+    applyRemoteOpacity();
+    if (m_settings.kiosk && !m_safeMode && !m_kiosk->isActive())
+        m_kiosk->start(m_settings.kioskPath);
+    else if (!m_settings.kiosk && m_kiosk->isActive())
+        m_kiosk->stop();
+    m_badges->setClickToUnpin(m_settings.badgeClickUnpin);
+    m_badges->setEnabled(m_settings.cornerBadge);
+    applyOsd();
+    applyFade();
+    applyKeepOnTop();
+    updateTrayIcon();
+    if (!persistence::isPortable()
+        && (m_settings.startWithWindows != old.startWithWindows
+            || m_settings.autostartDelay != old.autostartDelay
+            || m_settings.autostartArgs != old.autostartArgs
+            || m_settings.autostartTask != old.autostartTask
+            || m_settings.autostartElevated != old.autostartElevated))
+        applyAutostart(m_settings.startWithWindows);
+
+    if (m_settings.shortcuts != old.shortcuts) {
+        if (m_shortcutsLayout)
+            fillShortcutRows(m_shortcutsLayout);
+        emit shortcutsChanged(m_settings.shortcuts);   // main() re-registers the hotkeys
+    }
+    if (m_settings.macros != old.macros)
+        emit macrosChanged(m_settings.macros);
+    if (m_settings.schedules != old.schedules)
+        emit schedulesChanged(m_settings.schedules);
+    if (m_settings.triggers != old.triggers)
+        emit triggersChanged(m_settings.triggers);
+    emit settingsReloaded();
+}
+
+void MainWindow::confirmProtectedPin(intptr_t hwnd, const QString &title,
+                                     const QString &process)
+{
+    QMessageBox box(this);
+    box.setWindowTitle(tr("Pin a protected window?"));
+    box.setIcon(QMessageBox::Warning);
+    box.setText(tr("<b>%1</b> (%2) is part of Windows security or an antivirus app.")
+                    .arg(displayTitle(title).toHtmlEscaped(), process.toHtmlEscaped()));
+    box.setInformativeText(tr("Keeping it on top or see-through could hide or imitate a "
+                              "real security prompt. Pin it anyway?"));
+    box.setStandardButtons(QMessageBox::Yes | QMessageBox::No);
+    box.setDefaultButton(QMessageBox::No);
+    auto *remember = new QCheckBox(tr("Remember my choice for %1").arg(process), &box);
+    box.setCheckBox(remember);
+    const bool allow = box.exec() == QMessageBox::Yes;
+
+    if (remember->isChecked()) {
+        m_settings.protectedChoices.insert(process.toLower(), allow);
+        persistence::saveSettings(m_settings);
+        m_manager->setProtectedChoices(m_settings.protectedChoices);
+    }
+    m_manager->confirmPin(hwnd, allow);
+}
+
+void MainWindow::askCoexistence(intptr_t hwnd, const QString &title, const QString &process,
+                                const QString &tool)
+{
+    QMessageBox box(this);
+    box.setWindowTitle(tr("Already kept on top"));
+    box.setIcon(QMessageBox::Question);
+    box.setText(tr("%1 is already keeping <b>%2</b> (%3) on top.")
+                    .arg(tool, displayTitle(title).toHtmlEscaped(), process.toHtmlEscaped()));
+    box.setInformativeText(tr("PinIt can list it and handle its opacity while %1 keeps it "
+                              "on top, or take over keeping it on top itself.").arg(tool));
+    QPushButton *defer = box.addButton(tr("Leave it to %1").arg(tool), QMessageBox::AcceptRole);
+    box.addButton(tr("Take over"), QMessageBox::AcceptRole);
+    QPushButton *cancel = box.addButton(QMessageBox::Cancel);
+    box.setDefaultButton(defer);
+    box.exec();
+    if (box.clickedButton() == cancel)
+        return;
+    m_manager->resolveCoexistence(hwnd, box.clickedButton() != defer);
+}
+
 void MainWindow::rebuildList()
 {
     // Remove previously-built pin cards, keeping the empty card and the stretch.
@@ -333,6 +961,9 @@ void MainWindow::rebuildList()
         w->deleteLater();
     }
 
+    m_titleLabels.clear();
+    m_countdownLabels.clear();
+
     const QVector<PinnedWindow> pinned = m_manager->pinnedWindows();
     if (m_emptyCard)
         m_emptyCard->setVisible(pinned.isEmpty());
@@ -348,13 +979,19 @@ void MainWindow::rebuildList()
         row->setContentsMargins(10, 6, 8, 6);
         row->setSpacing(8);
 
-        // Coloured badge with the process initial.
-        auto *avatar = new QLabel(avatarInitial(w.processName));
+        // The app's own icon, or a coloured badge with the process initial.
+        auto *avatar = new QLabel;
         avatar->setFixedSize(28, 28);
         avatar->setAlignment(Qt::AlignCenter);
-        avatar->setStyleSheet(QStringLiteral(
-            "background:%1; border-radius:6px; color:white;"
-            "font-weight:700; font-size:12px;").arg(avatarColor(w.processName).name()));
+        const QPixmap icon = windowicon::forWindow(reinterpret_cast<void *>(hwnd), 24);
+        if (!icon.isNull()) {
+            avatar->setPixmap(icon);
+        } else {
+            avatar->setText(avatarInitial(w.processName));
+            avatar->setStyleSheet(QStringLiteral(
+                "background:%1; border-radius:6px; color:white;"
+                "font-weight:700; font-size:12px;").arg(avatarColor(w.processName).name()));
+        }
         row->addWidget(avatar);
 
         // Title + process name stacked tightly; takes the leftover width.
@@ -366,12 +1003,34 @@ void MainWindow::rebuildList()
         name->setText(name->fontMetrics().elidedText(
             displayTitle(w.title), Qt::ElideRight, 150));
         name->setToolTip(w.title);   // full title on hover
-        auto *proc = new QLabel(w.processName);
+        m_titleLabels.insert(hwnd, name);
+        // Grouped pins show their group after the process name.
+        QString procText = w.group.isEmpty()
+                               ? w.processName
+                               : QStringLiteral("%1 · %2").arg(w.processName, w.group);
+        if (!w.sharedWith.isEmpty())
+            procText += tr(" · with %1").arg(w.sharedWith);
+        if (w.lost)
+            procText += tr(" · no longer on top");
+        if (w.hung)
+            procText += tr(" · not responding");
+        auto *proc = new QLabel(procText);
         proc->setProperty("role", "muted");
         info->addWidget(name);
         info->addWidget(proc);
         row->addLayout(info, 1);
 
+        // Temporary pins show how long they have left.
+        if (w.expiresAt.isValid()) {
+            auto *left = new QLabel(countdownText(
+                int(qMax<qint64>(0, QDateTime::currentDateTime().secsTo(w.expiresAt)))));
+            left->setProperty("role", "muted");
+            left->setToolTip(tr("Unpins at %1").arg(QLocale().toString(
+                w.expiresAt.time(), QLocale::ShortFormat)));
+            m_countdownLabels.insert(hwnd, left);
+            row->addWidget(left);
+        }
+
         // Opacity slider + percentage.
         auto *slider = new QSlider(Qt::Horizontal);
         slider->setRange(winpin::kMinOpacity, winpin::kMaxOpacity);
@@ -380,6 +1039,11 @@ void MainWindow::rebuildList()
         // The round handle is pulled out over the thin groove (margin:-6px in
         // the QSS); without enough vertical room it gets clipped at the top.
         slider->setMinimumHeight(20);
+        slider->setEnabled(!w.hung);   // opacity can't change until it recovers
+        if (const QString why = opacityUnavailable(); !why.isEmpty()) {
+            slider->setEnabled(false);
+            slider->setToolTip(why);
+        }
         row->addWidget(slider);
 
         auto *pct = new QLabel(QStringLiteral("%1%").arg(w.opacity));
@@ -403,14 +1067,404 @@ void MainWindow::rebuildList()
                 [this, hwnd]() { m_manager->unpin(hwnd); });
         row->addWidget(unpinBtn);
 
+        // Right-click a row for group actions.
+        card->setContextMenuPolicy(Qt::CustomContextMenu);
+        connect(card, &QWidget::customContextMenuRequested, this,
+                [this, hwnd, card](const QPoint &pos) {
+                    showPinMenu(hwnd, card->mapToGlobal(pos));
+                });
+
+        if (w.hung) {
+            name->setEnabled(false);
+            avatar->setEnabled(false);
+            card->setToolTip(tr("This window isn't responding."));
+        }
+
         m_listLayout->insertWidget(m_listLayout->count() - 1, card);
     }
+}
+
+void MainWindow::rebuildPending()
+{
+    while (QLayoutItem *item = m_pendingLayout->takeAt(0)) {
+        if (QLayout *child = item->layout()) {
+            while (QLayoutItem *ci = child->takeAt(0)) {
+                if (ci->widget())
+                    ci->widget()->deleteLater();
+                delete ci;
+            }
+        }
+        delete item;
+    }
+
+    const QVector<persistence::SavedPin> pending = m_manager->pendingRestores();
+    m_pendingCard->setVisible(!pending.isEmpty());
+
+    for (const persistence::SavedPin &p : pending) {
+        auto *row = new QHBoxLayout;
+        row->setSpacing(6);
+        auto *text = new QLabel(
+            tr("Couldn't re-pin %1 — waiting for it to open.").arg(p.processName));
+        text->setProperty("role", "muted");
+        text->setToolTip(p.title);
+        row->addWidget(text, 1);
+
+        auto *retry = new QPushButton(tr("Retry"));
+        retry->setToolTip(tr("Look for the window again now"));
+        connect(retry, &QPushButton::clicked, this, [this]() {
+            if (m_manager->retryRestore() == 0)
+                notify(tr("Still couldn't find the window — is the app open?"));
+        });
+        row->addWidget(retry);
+
+        auto *dismiss = new QPushButton(QString::fromUtf8("\xE2\x9C\x95"));   // ✕
+        dismiss->setObjectName(QStringLiteral("unpin"));
+        dismiss->setFixedSize(20, 20);
+        dismiss->setToolTip(tr("Forget this pin"));
+        dismiss->setCursor(Qt::PointingHandCursor);
+        const QString key = PinManager::restoreKey(p);
+        connect(dismiss, &QPushButton::clicked, this,
+                [this, key]() { m_manager->dismissPendingRestore(key); });
+        row->addWidget(dismiss);
+        m_pendingLayout->addLayout(row);
+    }
+}
+
+void MainWindow::updateTitle(intptr_t hwnd, const QString &title)
+{
+    // Update the label in place — rebuilding the list would yank a slider the
+    // user might be dragging.
+    QLabel *name = m_titleLabels.value(hwnd);
+    if (!name)
+        return;
+    name->setText(name->fontMetrics().elidedText(displayTitle(title), Qt::ElideRight, 150));
+    name->setToolTip(title);
+}
+
+void MainWindow::updateCountdown(intptr_t hwnd, int secondsLeft)
+{
+    if (QLabel *left = m_countdownLabels.value(hwnd))
+        left->setText(countdownText(secondsLeft));
+}
+
+void MainWindow::updatePinIndicators(const QVector<PinnedWindow> &pins)
+{
+    const int n = pins.size();
+    QString summary = n == 0 ? tr("PinIt — no windows pinned")
+                             : tr("PinIt — %n window(s) pinned", "", n);
+    if (m_safeMode)
+        summary += tr(" (safe mode)");
+    if (m_tray)
+        m_tray->setToolTip(summary);
+
+    // Count badge on our own taskbar button (only there while we're visible).
+    if (isVisible())
+        taskbar::setOverlay(reinterpret_cast<void *>(winId()),
+                            n == 0 ? QIcon() : taskbar::countBadge(n), summary);
+}
+
+void MainWindow::showPinMenu(intptr_t hwnd, const QPoint &globalPos)
+{
+    QString group, process;
+    QString dockEdge, enforcement;
+    bool temporary = false, keepWhenIdle = false, follow = false, restart = false;
+    bool lost = false;
+    QString title, titlePattern;
+    for (const PinnedWindow &w : m_manager->pinnedWindows()) {
+        if (w.hwnd == hwnd) {
+            title = w.title;
+            titlePattern = w.titlePattern;
+            group = w.group;
+            process = w.processName;
+            temporary = w.expiresAt.isValid();
+            keepWhenIdle = w.keepWhenIdle;
+            follow = w.followMonitor;
+            dockEdge = w.dockEdge;
+            restart = w.autoRestart;
+            enforcement = w.enforcement;
+            lost = w.lost;
+        }
+    }
+
+    QMenu menu(this);
+    if (lost) {
+        QAction *backAct = menu.addAction(tr("Put back on top"));
+        connect(backAct, &QAction::triggered, this, [this, hwnd]() { m_manager->reassert(hwnd); });
+        menu.addSeparator();
+    }
+    QMenu *timerMenu = menu.addMenu(tr("Unpin after"));
+    for (const int seconds : kTempPinChoices) {
+        QAction *act = timerMenu->addAction(tr("%n minute(s)", "", seconds / 60));
+        connect(act, &QAction::triggered, this,
+                [this, hwnd, seconds]() { m_manager->pinFor(hwnd, seconds); });
+    }
+    if (temporary) {
+        timerMenu->addSeparator();
+        QAction *keep = timerMenu->addAction(tr("Keep pinned"));
+        connect(keep, &QAction::triggered, this, [this, hwnd]() { m_manager->pinFor(hwnd, 0); });
+    }
+    if (m_settings.idleUnpinMinutes > 0 && !temporary) {
+        QAction *idleAct = menu.addAction(tr("Keep pinned when unused"));
+        idleAct->setCheckable(true);
+        idleAct->setChecked(keepWhenIdle);
+        connect(idleAct, &QAction::toggled, this,
+                [this, hwnd](bool on) { m_manager->setKeepWhenIdle(hwnd, on); });
+    }
+    QMenu *dockMenu = menu.addMenu(tr("Dock to screen edge"));
+    auto *dockGroup = new QActionGroup(dockMenu);
+    const QList<QPair<QString, QString>> edges = {
+        {QString(), tr("Off")},
+        {QStringLiteral("left"), tr("Left")},
+        {QStringLiteral("right"), tr("Right")},
+        {QStringLiteral("top"), tr("Top")},
+        {QStringLiteral("bottom"), tr("Bottom")},
+    };
+    for (const auto &e : edges) {
+        QAction *act = dockMenu->addAction(e.second);
+        act->setCheckable(true);
+        act->setChecked(dockEdge == e.first);
+        dockGroup->addAction(act);
+        connect(act, &QAction::triggered, this,
+                [this, hwnd, edge = e.first]() { m_manager->setDockEdge(hwnd, edge); });
+    }
+    if (QApplication::screens().size() > 1) {
+        QAction *followAct = menu.addAction(tr("Follow me across monitors"));
+        followAct->setCheckable(true);
+        followAct->setChecked(follow);
+        connect(followAct, &QAction::toggled, this,
+                [this, hwnd](bool on) { m_manager->setFollowMonitor(hwnd, on); });
+    }
+    QMenu *policyMenu = menu.addMenu(tr("Keep on top"));
+    auto *policyGroup = new QActionGroup(policyMenu);
+    const QList<QPair<QString, QString>> policies = {
+        {QStringLiteral("aggressive"), tr("Aggressively (stay above other on-top windows)")},
+        {QStringLiteral("normal"), tr("Normally (restore it if something removes it)")},
+        {QStringLiteral("once"), tr("Once (set it and leave the app alone)")},
+    };
+    for (const auto &p : policies) {
+        QAction *act = policyMenu->addAction(p.second);
+        act->setCheckable(true);
+        act->setChecked(enforcement == p.first);
+        policyGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, hwnd, policy = p.first]() {
+            m_manager->setEnforcementPolicy(hwnd, policy);
+        });
+    }
+    QAction *restartAct = menu.addAction(tr("Restart the app if it closes"));
+    restartAct->setCheckable(true);
+    restartAct->setChecked(restart);
+    connect(restartAct, &QAction::toggled, this, [this, hwnd, process](bool on) {
+        if (!m_manager->setAutoRestart(hwnd, on))
+            notify(tr("Can't restart %1 — PinIt can't see where it was started from.")
+                       .arg(process));
+    });
+    // One browser process owns every browser window, so restoring by process
+    // alone picks an arbitrary one; a tab title pins down the right window.
+    if (winpin::isBrowser(process)) {
+        QAction *tabAct = menu.addAction(
+            titlePattern.isEmpty() ? tr("Restore by tab title…")
+                                   : tr("Restore by tab title: “%1”…").arg(titlePattern));
+        connect(tabAct, &QAction::triggered, this, [this, hwnd, title, titlePattern]() {
+            bool ok = false;
+            const QString pattern = QInputDialog::getText(
+                this, tr("Restore by tab title"),
+                tr("After a restart, re-pin the window whose title contains "
+                   "(leave empty to match any window of this browser):"),
+                QLineEdit::Normal,
+                titlePattern.isEmpty() ? winpin::browserTabTitle(title) : titlePattern, &ok);
+            if (ok)
+                m_manager->setTitlePattern(hwnd, pattern);
+        });
+    }
+    QAction *infoAct = menu.addAction(tr("Window details…"));
+    connect(infoAct, &QAction::triggered, this, [this, hwnd]() { showWindowInfo(hwnd); });
+    QAction *shot = menu.addAction(tr("Save a screenshot…"));
+    connect(shot, &QAction::triggered, this, [this, hwnd, process]() {
+        const QString dir = QStandardPaths::writableLocation(QStandardPaths::PicturesLocation);
+        const QString name = QStringLiteral("%1-%2.png").arg(
+            QFileInfo(process).completeBaseName(),
+            QDateTime::currentDateTime().toString(QStringLiteral("yyyyMMdd-HHmmss")));
+        const QString path = QFileDialog::getSaveFileName(
+            this, tr("Save screenshot"), QDir(dir).filePath(name), tr("PNG images (*.png)"));
+        if (path.isEmpty())
+            return;
+        if (capture::saveWindow(reinterpret_cast<void *>(hwnd), path))
+            notify(tr("Saved %1").arg(QDir::toNativeSeparators(path)));
+        else
+            notify(tr("Couldn't capture that window — is it minimized?"));
+    });
+    QAction *compact = menu.addAction(tr("Compact size for %1…").arg(process));
+    connect(compact, &QAction::triggered, this,
+            [this, process]() { editCompactRule(process); });
+    menu.addSeparator();
+    QAction *assign = menu.addAction(tr("Assign to group…"));
+    QAction *leave = nullptr, *groupOpacity = nullptr, *groupUnpin = nullptr;
+    if (!group.isEmpty()) {
+        leave = menu.addAction(tr("Remove from “%1”").arg(group));
+        menu.addSeparator();
+        groupOpacity = menu.addAction(tr("Set opacity for “%1”…").arg(group));
+        groupUnpin = menu.addAction(tr("Unpin all in “%1”").arg(group));
+    }
+
+    QAction *chosen = menu.exec(globalPos);
+    if (!chosen)
+        return;
+
+    if (chosen == assign) {
+        // Editable combo: pick an existing group or type a new name.
+        bool ok = false;
+        const QString name = QInputDialog::getItem(
+            this, tr("Assign to group"), tr("Group name:"), m_manager->groups(),
+            qMax(0, m_manager->groups().indexOf(group)), /*editable=*/true, &ok);
+        if (ok && !name.trimmed().isEmpty())
+            m_manager->setGroup(hwnd, name);
+    } else if (chosen == leave) {
+        m_manager->setGroup(hwnd, QString());
+    } else if (chosen == groupOpacity) {
+        bool ok = false;
+        const int percent = QInputDialog::getInt(
+            this, tr("Group opacity"), tr("Opacity for “%1” (%):").arg(group), 100,
+            winpin::kMinOpacity, winpin::kMaxOpacity, 5, &ok);
+        if (ok) {
+            m_manager->setGroupOpacity(group, percent);
+            rebuildList();   // sliders don't observe opacityChanged
+        }
+    } else if (chosen == groupUnpin) {
+        m_manager->unpinGroup(group);
+    }
+}
+
+void MainWindow::editCompactRule(const QString &process)
+{
+    const QString key = process.toLower();
+    const persistence::CompactRule current =
+        m_settings.compactRules.value(key, {QSize(480, 270), QStringLiteral("bottom-right")});
+
+    QDialog dlg(this);
+    dlg.setWindowTitle(tr("Compact size"));
+    auto *form = new QFormLayout(&dlg);
+    auto *hint = new QLabel(tr("Pinning a %1 window shrinks it to this size; unpinning "
+                               "puts it back.").arg(process));
+    hint->setWordWrap(true);
+    form->addRow(hint);
+    auto *enabled = new QCheckBox(tr("Resize when pinned"));
+    enabled->setChecked(m_settings.compactRules.contains(key));
+    form->addRow(enabled);
+    auto *width = new QSpinBox;
+    width->setRange(120, 3840);
+    width->setValue(current.size.width());
+    auto *height = new QSpinBox;
+    height->setRange(80, 2160);
+    height->setValue(current.size.height());
+    auto *size = new QHBoxLayout;
+    size->addWidget(width);
+    size->addWidget(new QLabel(QStringLiteral("×")));
+    size->addWidget(height);
+    form->addRow(tr("Size:"), size);
+    auto *corner = new QComboBox;
+    corner->addItem(tr("Bottom right"), QStringLiteral("bottom-right"));
+    corner->addItem(tr("Bottom left"), QStringLiteral("bottom-left"));
+    corner->addItem(tr("Top right"), QStringLiteral("top-right"));
+    corner->addItem(tr("Top left"), QStringLiteral("top-left"));
+    corner->setCurrentIndex(qMax(0, corner->findData(current.corner)));
+    form->addRow(tr("Corner:"), corner);
+    for (QWidget *w : {static_cast<QWidget *>(width), static_cast<QWidget *>(height),
+                       static_cast<QWidget *>(corner)}) {
+        w->setEnabled(enabled->isChecked());
+        connect(enabled, &QCheckBox::toggled, w, &QWidget::setEnabled);
+    }
+    auto *buttons = new QDialogButtonBox(QDialogButtonBox::Ok | QDialogButtonBox::Cancel);
+    connect(buttons, &QDialogButtonBox::accepted, &dlg, &QDialog::accept);
+    connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
+    form->addRow(buttons);
+    if (dlg.exec() != QDialog::Accepted)
+        return;
+
+    if (enabled->isChecked())
+        m_settings.compactRules.insert(key, {QSize(width->value(), height->value()),
+                                             corner->currentData().toString()});
+    else
+        m_settings.compactRules.remove(key);
+    persistence::saveSettings(m_settings);
+    m_manager->setCompactRules(m_settings.compactRules);
+}
+
+void MainWindow::startKiosk()
+{
+    const QString exe = QFileDialog::getOpenFileName(
+        this, tr("Choose the kiosk app"), m_settings.kioskPath, tr("Programs (*.exe)"));
+    if (exe.isEmpty())
+        return;
+    const QString escape = shortcuts::displayTokens(m_settings.shortcuts.kioskExit)
+                               .join(QStringLiteral(" + "));
+    const auto answer = QMessageBox::question(
+        this, tr("Kiosk mode"),
+        tr("PinIt will keep %1 maximized, on top and focused, and start it again if it "
+           "closes — also after a restart.\n\nPress %2 to leave kiosk mode.")
+            .arg(QFileInfo(exe).fileName(), escape));
+    if (answer != QMessageBox::Yes)
+        return;
+    m_settings.kiosk = true;
+    m_settings.kioskPath = exe;
+    persistence::saveSettings(m_settings);
+    m_kiosk->start(exe);
+}
+
+void MainWindow::stopKiosk()
+{
+    if (!m_kiosk->isActive())
+        return;
+    m_kiosk->stop();
+    m_settings.kiosk = false;
+    persistence::saveSettings(m_settings);
+    notify(tr("Kiosk mode is off."));
+}
+
+void MainWindow::adoptTopmostDialog()
+{
+    const QVector<winpin::PinnableWindow> candidates = m_manager->adoptableWindows();
+    if (candidates.isEmpty()) {
+        notify(tr("No other windows are always on top."));
+        return;
+    }
+
+    QDialog dlg(this);
+    dlg.setWindowTitle(tr("Adopt always-on-top windows"));
+    dlg.setWindowIcon(appIcon());
+    dlg.resize(400, 360);
+    auto *l = new QVBoxLayout(&dlg);
+    auto *prompt = new QLabel(tr("These windows are already always on top. Tick the ones "
+                                 "PinIt should manage:"), &dlg);
+    prompt->setWordWrap(true);
+    l->addWidget(prompt);
+
+    auto *list = new QListWidget(&dlg);
+    for (const winpin::PinnableWindow &w : candidates) {
+        auto *item = new QListWidgetItem(
+            QStringLiteral("%1   —   %2").arg(displayTitle(w.title), w.processName), list);
+        item->setToolTip(w.title);
+        item->setData(Qt::UserRole, QVariant::fromValue<qlonglong>(w.hwnd));
+        item->setFlags(item->flags() | Qt::ItemIsUserCheckable);
+        item->setCheckState(Qt::Checked);
+    }
+    loadIconsLater(list);
+    l->addWidget(list, 1);
+
+    auto *buttons = new QDialogButtonBox(
+        QDialogButtonBox::Ok | QDialogButtonBox::Cancel, &dlg);
+    l->addWidget(buttons);
+    connect(buttons, &QDialogButtonBox::accepted, &dlg, &QDialog::accept);
+    connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
+    if (dlg.exec() != QDialog::Accepted)
+        return;
 
-    if (m_tray) {
-        const int n = pinned.size();
-        m_tray->setToolTip(n == 0 ? tr("PinIt — no windows pinned")
-                                  : tr("PinIt — %n window(s) pinned", "", n));
+    QVector<intptr_t> chosen;
+    for (int i = 0; i < list->count(); ++i) {
+        if (list->item(i)->checkState() == Qt::Checked)
+            chosen.push_back(static_cast<intptr_t>(list->item(i)->data(Qt::UserRole).toLongLong()));
     }
+    if (!chosen.isEmpty())
+        notify(tr("Adopted %n window(s).", "", m_manager->adopt(chosen)));
 }
 
 void MainWindow::addWindowDialog()
@@ -424,11 +1478,11 @@ void MainWindow::addWindowDialog()
     l->addWidget(prompt);
 
     auto *list = new QListWidget(&dlg);
-    const QString self = windowTitle();
     for (const winpin::PinnableWindow &w : winpin::enumerateWindows()) {
         if (w.title.isEmpty() || w.title == QStringLiteral("Unknown"))
             continue;
-        if (w.title == self)
+        // By process, not title — another app's window may well be called "PinIt".
+        if (winpin::isOwnWindow(reinterpret_cast<void *>(w.hwnd)))
             continue;
         if (m_manager->isPinned(w.hwnd))
             continue;
@@ -437,10 +1491,22 @@ void MainWindow::addWindowDialog()
         item->setToolTip(w.title);
         item->setData(Qt::UserRole, QVariant::fromValue<qlonglong>(w.hwnd));
     }
+    loadIconsLater(list);
     l->addWidget(list, 1);
 
     auto *buttons = new QDialogButtonBox(
         QDialogButtonBox::Ok | QDialogButtonBox::Cancel, &dlg);
+    // Not open yet? Start the app and pin its window when it appears.
+    QPushButton *launch = buttons->addButton(tr("Launch app…"), QDialogButtonBox::ActionRole);
+    connect(launch, &QPushButton::clicked, &dlg, [this, &dlg]() {
+        const QString exe = QFileDialog::getOpenFileName(
+            &dlg, tr("Launch and pin"), QString(), tr("Programs (*.exe)"));
+        if (exe.isEmpty())
+            return;
+        dlg.reject();
+        usage::recordFeature(QStringLiteral("launch_and_pin"));
+        m_manager->launchAndPin(exe);
+    });
     l->addWidget(buttons);
     connect(buttons, &QDialogButtonBox::accepted, &dlg, &QDialog::accept);
     connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
@@ -455,39 +1521,1366 @@ void MainWindow::addWindowDialog()
     }
 }
 
-void MainWindow::showAbout()
-{
-    QMessageBox box(this);
-    box.setWindowTitle(tr("About PinIt"));
-    box.setIconPixmap(appIcon().pixmap(64, 64));
-    box.setTextFormat(Qt::RichText);
-    box.setTextInteractionFlags(Qt::TextBrowserInteraction);   // clickable links
-    box.setText(QStringLiteral(
-        "<h3>%1 %2</h3>"
-        "<p>%3</p>"
-        "<p>Built with C++ &amp; Qt %4.</p>"
-        "<p>By %5<br><a href=\"%6\">%6</a></p>"
-        "<p style='color:gray'>%7</p>")
-        .arg(QStringLiteral(PINIT_PRODUCT),
-             QStringLiteral(PINIT_VERSION_STR),
-             tr("Keep any window always on top — with a global hotkey."),
-             QStringLiteral(QT_VERSION_STR),
-             QStringLiteral(PINIT_COMPANY),
-             QStringLiteral(PINIT_URL),
-             QStringLiteral(PINIT_COPYRIGHT)));
-    box.exec();
-}
-
-void MainWindow::buildTray()
+void MainWindow::showConflicts()
 {
-    if (!QSystemTrayIcon::isSystemTrayAvailable())
+    const QVector<TopmostConflict> conflicts = m_manager->topmostConflicts();
+    if (conflicts.isEmpty()) {
+        QMessageBox::information(this, tr("Topmost conflicts"),
+                                 tr("No other always-on-top windows are covering your pins."));
         return;
+    }
 
-    m_tray = new QSystemTrayIcon(appIcon(), this);
+    QDialog dlg(this);
+    dlg.setWindowTitle(tr("Topmost conflicts"));
+    dlg.setWindowIcon(appIcon());
+    dlg.resize(420, 300);
+    auto *l = new QVBoxLayout(&dlg);
+    l->addWidget(new QLabel(tr("These apps are also always on top and cover a pinned window.\n"
+                               "Demote them so your pins stay in front:"), &dlg));
 
-    auto *menu = new QMenu(this);
-    QAction *showAct = menu->addAction(tr("Show PinIt"));
+    auto *list = new QListWidget(&dlg);
+    list->setSelectionMode(QAbstractItemView::ExtendedSelection);
+    for (const TopmostConflict &c : conflicts) {
+        auto *item = new QListWidgetItem(
+            tr("%1 (%2)   —   covers %3")
+                .arg(displayTitle(c.title), c.processName, displayTitle(c.coveredTitle)),
+            list);
+        item->setData(Qt::UserRole, QVariant::fromValue<qlonglong>(c.hwnd));
+        item->setSelected(true);
+    }
+    l->addWidget(list, 1);
+
+    auto *buttons = new QDialogButtonBox(QDialogButtonBox::Cancel, &dlg);
+    buttons->addButton(tr("Demote selected"), QDialogButtonBox::AcceptRole);
+    l->addWidget(buttons);
+    connect(buttons, &QDialogButtonBox::accepted, &dlg, &QDialog::accept);
+    connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
+
+    if (dlg.exec() != QDialog::Accepted)
+        return;
+
+    for (QListWidgetItem *item : list->selectedItems())
+        m_manager->demote(static_cast<intptr_t>(item->data(Qt::UserRole).toLongLong()));
+}
+
+void MainWindow::showWindowInfo(intptr_t hwnd)
+{
+    const winpin::WindowInfo info = winpin::windowInfo(reinterpret_cast<void *>(hwnd));
+    if (!info.hwnd) {
+        notify(tr("That window has closed."));
+        return;
+    }
+    const auto rect = [](const QRect &r) {
+        return QStringLiteral("%1,%2 %3×%4").arg(r.x()).arg(r.y()).arg(r.width()).arg(r.height());
+    };
+    const auto yesNo = [this](bool b) { return b ? tr("yes") : tr("no"); };
+    const QString automationId = uia::identify(reinterpret_cast<void *>(hwnd)).automationId;
+
+    // Plain text so Copy gives something that pastes straight into a rule or
+    // a bug report.
+    const QString text =
+        tr("Title: %1\nClass: %2\nProcess: %3 (PID %4)\nPath: %5\n")
+            .arg(info.title, info.className, info.processName)
+            .arg(info.pid)
+            .arg(QDir::toNativeSeparators(info.processPath))
+        + tr("Position: %1\nMonitor: %2 (%3)\nDPI: %4 (%5%)\n")
+              .arg(rect(info.rect), info.monitor, rect(info.monitorRect))
+              .arg(info.dpi)
+              .arg(info.dpi * 100 / 96)
+        + tr("Always on top: %1\nLayered: %2\nTool window: %3\nAdministrator: %4\n"
+             "Opacity: %5%")
+              .arg(yesNo(info.topmost), yesNo(info.layered), yesNo(info.toolWindow),
+                   yesNo(info.elevated))
+              .arg(info.opacity)
+        + (automationId.isEmpty() ? QString() : tr("\nAutomation id: %1").arg(automationId));
+
+    QMessageBox box(this);
+    box.setWindowTitle(tr("Window details"));
+    box.setText(text);
+    box.setTextInteractionFlags(Qt::TextSelectableByMouse);
+    QPushButton *copy = box.addButton(tr("Copy"), QMessageBox::ActionRole);
+    box.addButton(QMessageBox::Close);
+    box.exec();
+    if (box.clickedButton() == copy)
+        QApplication::clipboard()->setText(text);
+}
+
+void MainWindow::showDiagnostics()
+{
+    const auto list = [](const QStringList &l) {
+        return l.isEmpty() ? QStringLiteral("—") : l.join(QStringLiteral(", ")).toHtmlEscaped();
+    };
+
+    const ReenforceStats stats = m_manager->reenforceStats();
+
+    // One line per pin; the re-enforce count is what flags a topmost fighter.
+    QString perPin;
+    const QDateTime now = QDateTime::currentDateTime();
+    for (const PinnedWindow &w : m_manager->pinnedWindows()) {
+        const qint64 mins = w.pinnedAt.secsTo(now) / 60;
+        perPin += tr("%1 — pinned %2 min, topmost restored %3×, opacity changed %4×<br>")
+                      .arg(displayTitle(w.title).toHtmlEscaped())
+                      .arg(mins)
+                      .arg(w.reenforceCount)
+                      .arg(w.opacityChanges);
+    }
+    if (perPin.isEmpty())
+        perPin = QStringLiteral("—");
+
+    const perf::Stats perfStats = perf::stats();
+    QString perfLine = tr("not sampled yet");
+    if (perfStats.sampled) {
+        QStringList loops;
+        for (const auto &rate : perfStats.loopRates)
+            loops << tr("%1 %2/s").arg(rate.first).arg(rate.second, 0, 'f', 1);
+        perfLine = tr("%1% CPU, %2 MB in use (%3 MB private); %4")
+                       .arg(perfStats.cpuPercent, 0, 'f', 1)
+                       .arg(perfStats.workingSetKb / 1024)
+                       .arg(perfStats.privateKb / 1024)
+                       .arg(loops.isEmpty() ? tr("no checks running")
+                                            : loops.join(QStringLiteral(", ")));
+    }
+
+    const platform::Capabilities &caps = platform::capabilities();
+    QStringList supported;
+    if (caps.opacity)         supported << tr("opacity");
+    if (caps.clickThrough)    supported << tr("click-through");
+    if (caps.borderColor)     supported << tr("coloured borders");
+    if (caps.acrylic)         supported << tr("acrylic");
+    if (caps.blurBehind)      supported << tr("blur behind");
+    if (caps.virtualDesktops) supported << tr("virtual desktops");
+    const QString platformLine =
+        tr("%1 (build %2), %3. %4")
+            .arg(caps.osName)
+            .arg(caps.build)
+            .arg(supported.join(QStringLiteral(", ")),
+                 caps.elevated ? tr("Running as administrator: can pin any window.")
+                               : tr("Not elevated: windows of admin apps can't be pinned."));
+
+    const QString run = autostart::registered();
+    QString startup;
+    if (persistence::isPortable())
+        startup = tr("off (portable mode, data in %1)")
+                      .arg(QDir::toNativeSeparators(persistence::dataDir()).toHtmlEscaped());
+    else if (!m_settings.startWithWindows)
+        startup = run.isEmpty() ? tr("off") : tr("off, but a login entry still exists");
+    else if (run.isEmpty())
+        startup = tr("<b>on, but not registered</b>");
+    else
+        startup = tr("on (%1)").arg(run.toHtmlEscaped());
+
+    const QString safeMode =
+        m_startup.safeMode ? tr("<p><b>Safe mode:</b> session hooks, hotkeys, schedules, "
+                                "kiosk mode and pin restore were skipped.</p>")
+                           : QString();
+
+    QMessageBox box(this);
+    box.setWindowTitle(tr("PinIt diagnostics"));
+    box.setTextFormat(Qt::RichText);
+    box.setText(tr("<h3>Startup status</h3>") + safeMode
+                + tr("<p><b>Hotkey backend:</b> %1<br>"
+                     "<b>Shortcuts registered:</b> %2<br>"
+                     "<b>Shortcuts failed:</b> %3</p>"
+                     "<p><b>Saved pins restored:</b> %4 of %5 (%6 unmatched)<br>"
+                     "<b>Currently pinned:</b> %7</p>"
+                     "<p><b>Start with Windows:</b> %8<br>"
+                     "<b>Settings file:</b> %13</p>"
+                     "<p><b>Topmost re-checks:</b> %9 (re-applied %10, re-raised %16 "
+                     "aggressive, skipped %11 hung)</p>"
+                     "<p><b>Platform:</b> %14<br>"
+                     "<b>PinIt itself:</b> %15</p>"
+                     "<h3>Pinned windows</h3><p>%12</p>")
+                      .arg(m_startup.keyboardHook ? tr("keyboard hook") : tr("RegisterHotKey"),
+                           list(m_startup.hotkeysRegistered), list(m_startup.hotkeysFailed))
+                      .arg(m_startup.pinsRestored)
+                      .arg(m_startup.pinsSaved)
+                      .arg(m_startup.pinsSaved - m_startup.pinsRestored)
+                      .arg(m_manager->pinnedCount())
+                      .arg(startup)
+                      .arg(stats.passes)
+                      .arg(stats.reapplied)
+                      .arg(stats.skippedHung)
+                      .arg(perPin)
+                      .arg(QDir::toNativeSeparators(persistence::filePath()).toHtmlEscaped()
+                           + (persistence::hasHistory()
+                                  ? QString()
+                                  : tr(" (no pin history: Qt's SQLite driver is missing)")))
+                      .arg(platformLine.toHtmlEscaped())
+                      .arg(perfLine.toHtmlEscaped())
+                      .arg(stats.reraised));
+    box.exec();
+}
+
+void MainWindow::runSelfTest()
+{
+    QApplication::setOverrideCursor(Qt::WaitCursor);   // a second or so
+    const QVector<selftest::Check> checks = selftest::run();
+    QApplication::restoreOverrideCursor();
+    QString rows;
+    for (const selftest::Check &c : checks)
+        rows += tr("<b>%1:</b> %2 %3<br>")
+                    .arg(c.subsystem.toHtmlEscaped(), c.passed ? tr("pass") : tr("<b>FAIL</b>"),
+                         c.detail.toHtmlEscaped());
+    QMessageBox box(this);
+    box.setWindowTitle(tr("PinIt self-test"));
+    box.setIcon(selftest::allPassed(checks) ? QMessageBox::Information : QMessageBox::Warning);
+    box.setTextFormat(Qt::RichText);
+    box.setText((selftest::allPassed(checks)
+                     ? tr("<p>Everything PinIt needs works on this PC.</p>")
+                     : tr("<p>Some of what PinIt needs doesn't work on this PC — details are "
+                          "also in the log.</p>"))
+                + QStringLiteral("<p>") + rows + QStringLiteral("</p>"));
+    box.exec();
+}
+
+void MainWindow::showUsage()
+{
+    QDialog dlg(this);
+    dlg.setWindowTitle(tr("Usage stats"));
+    dlg.setWindowIcon(appIcon());
+    dlg.resize(360, 380);
+    auto *l = new QVBoxLayout(&dlg);
+
+    auto *consent = new QCheckBox(tr("Keep usage stats on this PC (never sent anywhere)"), &dlg);
+    consent->setChecked(m_settings.usageStats);
+    lockIfPolicy(consent, "usage_stats");
+    l->addWidget(consent);
+
+    auto *report = new QLabel(&dlg);
+    report->setTextFormat(Qt::RichText);
+    report->setWordWrap(true);
+    report->setAlignment(Qt::AlignTop | Qt::AlignLeft);
+    l->addWidget(report, 1);
+
+    const auto rows = [](const usage::Counts &counts) {
+        QString out;
+        for (const auto &c : counts)
+            out += QStringLiteral("%1: %2<br>").arg(c.first.toHtmlEscaped()).arg(c.second);
+        return out.isEmpty() ? QStringLiteral("—") : out;
+    };
+    const auto refresh = [&]() {
+        const usage::Summary s = usage::summary();
+        QString recent;
+        for (const persistence::HistoryEntry &h : persistence::history(8)) {
+            const QString what = h.event == QLatin1String("pin")     ? tr("pinned")
+                               : h.event == QLatin1String("unpin")   ? tr("unpinned")
+                               : h.event == QLatin1String("expired") ? tr("expired")
+                               : h.event == QLatin1String("lost")    ? tr("lost top spot")
+                               : h.event == QLatin1String("blocked") ? tr("blocked by policy")
+                                                                     : tr("closed");
+            recent += QStringLiteral("%1 %2 %3<br>")
+                          .arg(QLocale().toString(h.at, QLocale::ShortFormat), what,
+                               h.processName.toHtmlEscaped());
+        }
+        if (recent.isEmpty())
+            recent = QStringLiteral("—");
+        report->setText(tr("<p><b>Windows pinned:</b> %1</p>"
+                           "<p><b>Recent days</b><br>%2</p>"
+                           "<p><b>Features used</b><br>%3</p>"
+                           "<p><b>Errors</b><br>%4</p>"
+                           "<p><b>Recent activity</b><br>%5</p>")
+                            .arg(s.totalPins)
+                            .arg(rows(s.pinsPerDay), rows(s.features), rows(s.errors), recent));
+    };
+    refresh();
+
+    connect(consent, &QCheckBox::toggled, &dlg, [this](bool on) {
+        m_settings.usageStats = on;
+        usage::setEnabled(on);
+        persistence::saveSettings(m_settings);
+    });
+
+    auto *buttons = new QDialogButtonBox(QDialogButtonBox::Close, &dlg);
+    QPushButton *exportBtn = buttons->addButton(tr("Export…"), QDialogButtonBox::ActionRole);
+    QPushButton *clearBtn = buttons->addButton(tr("Clear"), QDialogButtonBox::ResetRole);
+    connect(exportBtn, &QPushButton::clicked, &dlg, [this, &dlg]() {
+        const QString path = QFileDialog::getSaveFileName(
+            &dlg, tr("Export usage stats"), QStringLiteral("pinit-usage.json"),
+            tr("JSON (*.json)"));
+        if (!path.isEmpty() && !usage::exportTo(path))
+            notify(tr("Couldn't write %1.").arg(QDir::toNativeSeparators(path)));
+    });
+    connect(clearBtn, &QPushButton::clicked, &dlg, [&]() {
+        usage::clear();
+        persistence::clearHistory();
+        refresh();
+    });
+    connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
+    l->addWidget(buttons);
+
+    dlg.exec();
+}
+
+void MainWindow::showAbout()
+{
+    QMessageBox box(this);
+    box.setWindowTitle(tr("About PinIt"));
+    box.setIconPixmap(appIcon().pixmap(64, 64));
+    box.setTextFormat(Qt::RichText);
+    box.setTextInteractionFlags(Qt::TextBrowserInteraction);   // clickable links
+    box.setText(QStringLiteral(
+        "<h3>%1 %2</h3>"
+        "<p>%3</p>"
+        "<p>Built with C++ &amp; Qt %4.</p>"
+        "<p>By %5<br><a href=\"%6\">%6</a></p>"
+        "<p style='color:gray'>%7</p>")
+        .arg(QStringLiteral(PINIT_PRODUCT),
+             QStringLiteral(PINIT_VERSION_STR),
+             tr("Keep any window always on top — with a global hotkey."),
+             QStringLiteral(QT_VERSION_STR),
+             QStringLiteral(PINIT_COMPANY),
+             QStringLiteral(PINIT_URL),
+             QStringLiteral(PINIT_COPYRIGHT)));
+    box.exec();
+}
+
+void MainWindow::applyKeepOnTop()
+{
+    // Straight on the HWND like any pin: toggling Qt::WindowStaysOnTopHint
+    // would recreate (and hide) the window.
+    void *self = reinterpret_cast<void *>(winId());
+    if (m_settings.keepOnTop)
+        winpin::applyTopmost(self);
+    else
+        winpin::removeTopmost(self);
+}
+
+void MainWindow::continueOnboarding()
+{
+    if (m_safeMode)
+        return;
+    const bool trayOnly = !m_tray || !m_tray->isVisible();
+    onboarding::Step step = onboarding::Step::TrayNotice;
+    bool found = false;
+    for (onboarding::Step s : onboarding::steps()) {
+        // With the window open, closeEvent() shows the tray notice, the first
+        // time it's closed; the other steps go ahead meanwhile.
+        if (!onboarding::isPending(m_settings.onboarding, s)
+            || (s == onboarding::Step::TrayNotice && !trayOnly && isVisible()))
+            continue;
+        step = s;
+        found = true;
+        break;
+    }
+    if (!found)
+        return;
+    switch (step) {
+    case onboarding::Step::TrayNotice:
+        if (trayOnly) {
+            finishOnboardingStep(step, /*skipped=*/true);   // no tray to explain
+        } else {
+            // Started minimized: it's in the tray already, so say so now.
+            showTrayNotice();
+            finishOnboardingStep(step);
+        }
+        return;
+    case onboarding::Step::HotkeyTutorial: {
+        const persistence::ShortcutConfig &sc = m_settings.shortcuts;
+        notify(tr("Press %1 to keep the window you're in on top, and %2 / %3 to fade it "
+                  "in and out.")
+                   .arg(shortcutText(sc.togglePin), shortcutText(sc.opacityDown),
+                        shortcutText(sc.opacityUp)));
+        finishOnboardingStep(step);
+        return;
+    }
+    case onboarding::Step::AutostartPrompt:
+        if (m_settings.startWithWindows || persistence::isPortable()
+            || policy::isLocked(QStringLiteral("start_with_windows"))) {
+            finishOnboardingStep(step, /*skipped=*/true);
+            return;
+        }
+        if (QMessageBox::question(this, tr("Start with Windows"),
+                                  tr("Start PinIt when you sign in, so your hotkeys work and "
+                                     "your pins come back without opening it first?"))
+            == QMessageBox::Yes)
+            m_autostartBox->setChecked(true);   // applies and saves it
+        finishOnboardingStep(step);
+        return;
+    case onboarding::Step::PermissionsCheck:
+        // Only what's known already; the full self-test stays on the tray menu.
+        if (platform::capabilities().elevated) {
+            finishOnboardingStep(step, /*skipped=*/true);
+            return;
+        }
+        notify(tr("All set. To pin apps that run as administrator, run PinIt as "
+                  "administrator too. If pinning doesn't work, tray → Run self-test… "
+                  "shows why."));
+        finishOnboardingStep(step);
+        return;
+    }
+}
+
+bool MainWindow::advanceOnboarding(const QString &step)
+{
+    if (step == QLatin1String("all")) {
+        for (onboarding::Step s : onboarding::steps()) {
+            if (onboarding::isPending(m_settings.onboarding, s))
+                onboarding::advance(m_settings.onboarding, s);
+        }
+        persistence::saveSettings(m_settings);
+        return true;
+    }
+    onboarding::Step s = onboarding::Step::TrayNotice;
+    if (!onboarding::fromName(step, &s))
+        return false;
+    finishOnboardingStep(s);
+    return true;
+}
+
+void MainWindow::finishOnboardingStep(onboarding::Step step, bool skipped)
+{
+    onboarding::advance(m_settings.onboarding, step, skipped);
+    persistence::saveSettings(m_settings);
+    // One step at a time, so each balloon gets read before the next.
+    if (!onboarding::finished(m_settings.onboarding))
+        QTimer::singleShot(skipped ? 0 : kOnboardingGapMs, this,
+                           &MainWindow::continueOnboarding);
+}
+
+void MainWindow::showTrayNotice()
+{
+    m_tray->showMessage(QStringLiteral("PinIt"),
+                        tr("PinIt is still running in the tray. Right-click the icon to quit."),
+                        QSystemTrayIcon::Information, 3000);
+}
+
+void MainWindow::setRemoteSession(bool remote)
+{
+    m_remoteSession = remote;
+    applyRemoteOpacity();
+}
+
+void MainWindow::applyRemoteOpacity()
+{
+    m_manager->setOpacitySuspended((m_remoteSession && m_settings.remoteNoOpacity)
+                                   || policy::opacityDisabled());
+}
+
+void MainWindow::applyFade()
+{
+    m_manager->setFadeDuration(m_settings.animateOpacity ? m_settings.fadeMs : 0);
+}
+
+void MainWindow::applyOsd()
+{
+    m_osd->setPosition(m_settings.osdPosition == QLatin1String("screen") ? Osd::Position::Screen
+                                                                         : Osd::Position::Window);
+    m_osd->setDuration(m_settings.osdDurationMs);
+}
+
+void MainWindow::showOsd(intptr_t hwnd, bool opacity)
+{
+    if (!m_settings.osd || !hwnd)
+        return;
+    const QVector<PinnedWindow> pins = m_manager->pinnedWindows();
+    const auto it = std::find_if(pins.begin(), pins.end(),
+                                 [hwnd](const PinnedWindow &w) { return w.hwnd == hwnd; });
+    QString text;
+    if (opacity) {
+        if (it == pins.end())
+            return;   // the opacity keys only act on pinned windows
+        text = tr("Opacity %1%").arg(it->opacity);
+    } else if (it != pins.end() && it->expiresAt.isValid()) {
+        text = tr("Pinned for %1: %2")
+                   .arg(countdownText(int(QDateTime::currentDateTime().secsTo(it->expiresAt))),
+                        displayTitle(it->title));
+    } else if (it != pins.end()) {
+        text = tr("Pinned: %1").arg(displayTitle(it->title));
+    } else {
+        text = tr("Unpinned: %1").arg(displayTitle(winpin::windowTitle(
+            reinterpret_cast<void *>(hwnd))));
+    }
+    m_osd->showMessage(text, platform::toLogical(winpin::windowRect(
+                                 reinterpret_cast<void *>(hwnd))));
+}
+
+void MainWindow::showQuickPanel()
+{
+    if (m_quickPanel->isVisible()) {
+        m_quickPanel->hide();
+        return;
+    }
+    m_quickPanel->popup(m_tray ? m_tray->geometry() : QRect());
+}
+
+bool MainWindow::showOpacitySlider(intptr_t hwnd)
+{
+    if (const QString why = opacityUnavailable(); !why.isEmpty()) {
+        notify(why + QLatin1Char('.'));
+        return false;
+    }
+    if (m_opacitySlider->popup(hwnd, platform::toLogical(winpin::windowRect(
+                                         reinterpret_cast<void *>(hwnd)))))
+        return true;
+    // The shortcut did nothing visible otherwise.
+    notify(tr("Pin the window first — the opacity slider only works on pinned windows."));
+    return false;
+}
+
+void MainWindow::updateTrayIcon()
+{
+    if (!m_tray)
+        return;
+    const QString theme = m_settings.trayIconTheme;
+    const bool light = theme == QLatin1String("light")
+                       || (theme != QLatin1String("dark") && taskbarIsLight());
+    m_tray->setIcon(trayGlyph(light));
+}
+
+bool MainWindow::nativeEvent(const QByteArray &eventType, void *message, qintptr *result)
+{
+    // Windows broadcasts "ImmersiveColorSet" when the light/dark theme flips.
+    const MSG *msg = static_cast<const MSG *>(message);
+    if (eventType == "windows_generic_MSG" && msg->message == WM_SETTINGCHANGE
+        && msg->lParam
+        && QString::fromWCharArray(reinterpret_cast<const wchar_t *>(msg->lParam))
+               == QLatin1String("ImmersiveColorSet"))
+        updateTrayIcon();
+    return QMainWindow::nativeEvent(eventType, message, result);
+}
+
+void MainWindow::checkConfig()
+{
+    const QString unreachable = persistence::unreachableDataDir();
+    if (!unreachable.isEmpty())
+        notify(tr("PinIt's data folder %1 isn't available on this PC, so the local one is "
+                  "used. Tray → Data folder… picks another.")
+                   .arg(QDir::toNativeSeparators(unreachable)));
+
+    persistence::Recovery recovery;
+    if (persistence::takeRecovery(&recovery)) {
+        qWarning("[state-recovered] %s; kept %d pin(s), lost: %s; original in %s",
+                 qUtf8Printable(recovery.error), recovery.pinsKept,
+                 qUtf8Printable(recovery.lost.join(QStringLiteral(", "))),
+                 qUtf8Printable(recovery.copyPath));
+        const QString lost = recovery.lost.isEmpty()
+                                 ? tr("Nothing was lost.")
+                                 : tr("Couldn't read: %1.")
+                                       .arg(recovery.lost.join(QStringLiteral(", ")));
+        notify(tr("pinned.json was damaged. Kept %n pin(s) and every setting that could still "
+                  "be read. %1 The original is saved as %2.", "", recovery.pinsKept)
+                   .arg(lost, QFileInfo(recovery.copyPath).fileName()));
+        return;   // the schema check would only repeat the parse error
+    }
+
+    const QVector<configschema::Issue> issues = configschema::validate(persistence::readRaw());
+    for (const configschema::Issue &issue : issues)
+        qWarning("pinned.json: %s", qUtf8Printable(configschema::format(issue)));
+    if (!issues.isEmpty())
+        notify(tr("%n setting(s) in pinned.json couldn't be used and were left at their "
+                  "defaults. %1", "", issues.size())
+                   .arg(configschema::format(issues.first())));
+}
+
+void MainWindow::showEffectiveSettings()
+{
+    QStringList locked, all;
+    for (const policy::Setting &s : policy::effectiveSettings()) {
+        all << policy::format(s);
+        if (s.source == policy::Setting::Policy)
+            locked << policy::format(s);
+    }
+
+    QMessageBox box(this);
+    box.setWindowTitle(tr("Effective settings"));
+    box.setText(locked.isEmpty()
+                    ? tr("None of PinIt's settings are set by policy.")
+                    : tr("%n setting(s) are set by your administrator and can't be changed "
+                         "here:\n\n%1", "", locked.size())
+                          .arg(locked.join(QLatin1Char('\n'))));
+    box.setInformativeText(tr("Show Details lists every setting PinIt is using and where "
+                              "it came from."));
+    box.setDetailedText(all.join(QLatin1Char('\n')));
+    box.setTextInteractionFlags(Qt::TextSelectableByMouse);
+    box.addButton(QMessageBox::Close);
+    box.exec();
+}
+
+void MainWindow::fillBackupMenu(QMenu *menu)
+{
+    menu->clear();
+    const QVector<persistence::Backup> backups = persistence::listBackups();
+    if (backups.isEmpty()) {
+        menu->addAction(tr("No backups yet"))->setEnabled(false);
+        return;
+    }
+    for (const persistence::Backup &b : backups) {
+        const QString when = QLocale().toString(b.at, QLocale::ShortFormat);
+        QAction *act = menu->addAction(when);
+        connect(act, &QAction::triggered, this, [this, id = b.id, when]() {
+            QString question = tr("Replace PinIt's settings, shortcuts and snapshots with the "
+                                  "backup from %1?\n\nThe current settings are backed up "
+                                  "first.").arg(when);
+            const QVector<configschema::Issue> issues =
+                configschema::validate(persistence::readBackup(id));
+            if (!issues.isEmpty()) {
+                QStringList lines;
+                for (const configschema::Issue &issue : issues.mid(0, 5))
+                    lines << configschema::format(issue);
+                question += QStringLiteral("\n\n")
+                            + tr("%n problem(s) in this backup — those settings would use "
+                                 "their defaults:", "", issues.size())
+                            + QLatin1Char('\n') + lines.join(QLatin1Char('\n'));
+            }
+            if (QMessageBox::question(this, tr("Restore backup"), question)
+                != QMessageBox::Yes)
+                return;
+            if (!persistence::restoreBackup(id)) {
+                notify(tr("Couldn't restore the backup from %1.").arg(when));
+                return;
+            }
+            reloadSettings();
+            notify(tr("Restored the backup from %1.").arg(when));
+        });
+    }
+}
+
+void MainWindow::fillMacroMenu(QMenu *menu)
+{
+    menu->clear();
+    for (const persistence::Macro &m : m_settings.macros) {
+        QAction *runAct = menu->addAction(m.name);
+        connect(runAct, &QAction::triggered, this,
+                [this, name = m.name]() { emit runMacroRequested(name); });
+    }
+    if (!m_settings.macros.isEmpty())
+        menu->addSeparator();
+    QAction *editAct = menu->addAction(tr("Edit macros…"));
+    connect(editAct, &QAction::triggered, this, &MainWindow::editMacros);
+    lockIfPolicy(editAct, "macros");
+}
+
+void MainWindow::editMacros()
+{
+    // Edited as text and only parsed on OK, so a half-typed step doesn't
+    // get in the way while switching between macros.
+    struct Draft {
+        QString name;
+        QString shortcut;
+        QString steps;
+    };
+    QVector<Draft> drafts;
+    QStringList names;
+    for (const persistence::Macro &m : m_settings.macros) {
+        drafts.push_back({m.name, m.shortcut, macros::formatSteps(m.steps)});
+        names << m.name;
+    }
+
+    DraftDialog dlg(this, tr("Macros"), QSize(600, 400), names);
+    auto *name = new QLineEdit(&dlg);
+    auto *shortcut = new QLineEdit(&dlg);
+    shortcut->setPlaceholderText(tr("optional, e.g. ctrl+alt+KeyM"));
+    auto *steps = new QPlainTextEdit(&dlg);
+    steps->setPlaceholderText(QStringLiteral("pin-app obs64.exe\nset-opacity 80\n"
+                                             "move-to-monitor 2"));
+    auto *hint = new QLabel(tr("One action per line, with its arguments; “wait 500” pauses "
+                               "half a second. Run PinIt.exe --list-actions for the list."),
+                            &dlg);
+    hint->setWordWrap(true);
+    hint->setProperty("role", "muted");
+    dlg.form()->addRow(tr("Name:"), name);
+    dlg.form()->addRow(tr("Hotkey:"), shortcut);
+    dlg.form()->addRow(tr("Steps:"), steps);
+    dlg.form()->addRow(hint);
+    dlg.setNameField(name);
+
+    dlg.fill = [&](int row) {
+        const Draft d = row >= 0 ? drafts.value(row) : Draft();
+        name->setText(d.name);
+        shortcut->setText(d.shortcut);
+        steps->setPlainText(d.steps);
+    };
+    dlg.store = [&](int row) {
+        drafts[row] = {name->text().trimmed(), shortcut->text().trimmed(),
+                       steps->toPlainText()};
+    };
+    dlg.add = [&]() {
+        drafts.push_back({tr("New macro"), QString(), QString()});
+        return drafts.last().name;
+    };
+    dlg.remove = [&](int row) { drafts.remove(row); };
+
+    QVector<persistence::Macro> result;
+    dlg.check = [&](QString *problem) {
+        result.clear();
+        QSet<QString> seen;
+        for (int i = 0; i < drafts.size(); ++i) {
+            const Draft &d = drafts[i];
+            persistence::Macro m;
+            m.name = d.name;
+            m.shortcut = d.shortcut;
+            m.steps = macros::parseSteps(d.steps, problem);
+            if (m.name.isEmpty()) {
+                *problem = tr("Give the macro a name.");
+            } else if (seen.contains(m.name)) {
+                *problem = tr("Another macro already has this name.");
+            } else if (problem->isEmpty() && m.steps.isEmpty()) {
+                *problem = tr("Add at least one step.");
+            } else if (problem->isEmpty() && !m.shortcut.isEmpty()) {
+                unsigned mods = 0, vk = 0;
+                for (const QString &step : shortcuts::steps(m.shortcut)) {
+                    if (!shortcuts::parse(step, mods, vk))
+                        *problem = tr("“%1” isn't a hotkey PinIt understands.").arg(m.shortcut);
+                }
+            }
+            if (!problem->isEmpty())
+                return i;
+            seen.insert(m.name);
+            result.push_back(m);
+        }
+
+        // Checked like the shortcuts dialog checks PinIt's own: a hotkey one
+        // of those, or an earlier macro, already uses (or starts a chord
+        // with) would never reach the macro.
+        QStringList all;
+        for (const QString &s : persistence::bindings(m_settings.shortcuts)) {
+            if (shortcuts::check({s}) == shortcuts::Problem::None)
+                all << s;
+        }
+        const int builtIn = all.size();
+        QVector<int> rows;   // all[builtIn + n] is drafts[rows[n]]'s hotkey
+        for (int i = 0; i < result.size(); ++i) {
+            if (!result[i].shortcut.isEmpty()) {
+                all << result[i].shortcut;
+                rows << i;
+            }
+        }
+        int at = -1;
+        switch (shortcuts::check(all, &at)) {
+        case shortcuts::Problem::None:
+            return -1;
+        case shortcuts::Problem::Invalid:
+            *problem = tr("A hotkey is one or two steps, like ctrl+alt+KeyM or "
+                          "ctrl+alt+KeyM,KeyO.");
+            break;
+        case shortcuts::Problem::NoModifier:
+            *problem = tr("A hotkey needs at least one modifier (Win/Ctrl/Alt/Shift).");
+            break;
+        case shortcuts::Problem::Duplicate:
+        case shortcuts::Problem::ChordPrefix:
+            *problem = tr("Another shortcut or macro already uses this hotkey, or starts "
+                          "a chord with it.");
+            break;
+        }
+        // PinIt's own shortcuts were checked when they were set.
+        return at >= builtIn ? rows[at - builtIn] : -1;
+    };
+    if (!dlg.run())
+        return;
+
+    m_settings.macros = result;
+    persistence::saveSettings(m_settings);
+    emit macrosChanged(m_settings.macros);
+}
+
+void MainWindow::editSchedules()
+{
+    // The action is kept as the text the user typed, like a macro step, and
+    // only checked on OK.
+    struct Draft {
+        persistence::Schedule schedule;
+        QString action;
+        bool    noDays = false;   // every day unticked: refused on OK
+    };
+    QVector<Draft> drafts;
+    QStringList names;
+    for (const persistence::Schedule &s : m_settings.schedules) {
+        drafts.push_back({s, macros::formatSteps({{s.action, s.args}}).trimmed()});
+        names << s.name;
+    }
+
+    DraftDialog dlg(this, tr("Scheduled actions"), QSize(600, 360), names);
+    auto *name = new QLineEdit(&dlg);
+    auto *action = new QLineEdit(&dlg);
+    action->setPlaceholderText(QStringLiteral("snapshot-restore Focus"));
+    auto *mode = new QComboBox(&dlg);
+    mode->addItem(tr("At a time of day"));
+    mode->addItem(tr("Every few minutes"));
+    auto *at = new QTimeEdit(&dlg);
+    at->setDisplayFormat(QStringLiteral("HH:mm"));
+    auto *dayRow = new QHBoxLayout;
+    QVector<QCheckBox *> days;
+    for (int d = 1; d <= 7; ++d) {
+        auto *box = new QCheckBox(QLocale().dayName(d, QLocale::ShortFormat), &dlg);
+        dayRow->addWidget(box);
+        days.push_back(box);
+    }
+    auto *every = new QSpinBox(&dlg);
+    every->setRange(1, 24 * 60);
+    every->setSuffix(tr(" min"));
+    auto *enabled = new QCheckBox(tr("Enabled"), &dlg);
+    auto *next = new QLabel(&dlg);
+    next->setProperty("role", "muted");
+    auto *hint = new QLabel(tr("An action with its arguments, as in a macro step; "
+                               "“run-macro <name>” runs a macro."),
+                            &dlg);
+    hint->setWordWrap(true);
+    hint->setProperty("role", "muted");
+    dlg.form()->addRow(tr("Name:"), name);
+    dlg.form()->addRow(tr("Action:"), action);
+    dlg.form()->addRow(tr("When:"), mode);
+    dlg.form()->addRow(tr("Time:"), at);
+    dlg.form()->addRow(tr("Days:"), dayRow);
+    dlg.form()->addRow(tr("Interval:"), every);
+    dlg.form()->addRow(enabled);
+    dlg.form()->addRow(next);
+    dlg.form()->addRow(hint);
+    dlg.setNameField(name);
+
+    const auto read = [&]() {
+        Draft d;
+        d.schedule.name = name->text().trimmed();
+        d.schedule.at = at->time();
+        for (int i = 0; i < days.size(); ++i) {
+            if (days[i]->isChecked())
+                d.schedule.days << i + 1;
+        }
+        // All seven ticked is stored as "every day" (an empty list); none
+        // ticked would read the same, so it's refused on OK instead.
+        d.noDays = d.schedule.days.isEmpty();
+        if (d.schedule.days.size() == 7)
+            d.schedule.days.clear();
+        d.schedule.everyMinutes = mode->currentIndex() == 1 ? every->value() : 0;
+        d.schedule.enabled = enabled->isChecked();
+        d.action = action->text().trimmed();
+        return d;
+    };
+    const auto updateNext = [&]() {
+        const bool interval = mode->currentIndex() == 1;
+        at->setEnabled(!interval);
+        for (QCheckBox *box : days)
+            box->setEnabled(!interval);
+        every->setEnabled(interval);
+        const Draft d = read();
+        const QDateTime when = d.noDays && !interval
+                                   ? QDateTime()
+                                   : scheduling::nextRun(d.schedule,
+                                                         QDateTime::currentDateTime());
+        next->setText(when.isValid()
+                          ? tr("Next run: %1").arg(QLocale().toString(when, QLocale::ShortFormat))
+                          : tr("Next run: never"));
+    };
+
+    dlg.fill = [&](int row) {
+        const Draft d = row >= 0 ? drafts.value(row) : Draft();
+        name->setText(d.schedule.name);
+        action->setText(d.action);
+        mode->setCurrentIndex(d.schedule.everyMinutes > 0 ? 1 : 0);
+        at->setTime(d.schedule.at);
+        for (int i = 0; i < days.size(); ++i) {
+            days[i]->setChecked(!d.noDays && (d.schedule.days.isEmpty()
+                                              || d.schedule.days.contains(i + 1)));
+        }
+        every->setValue(d.schedule.everyMinutes > 0 ? d.schedule.everyMinutes : 30);
+        enabled->setChecked(d.schedule.enabled);
+        updateNext();
+        if (row < 0)
+            next->clear();
+    };
+    dlg.store = [&](int row) { drafts[row] = read(); };
+    dlg.add = [&]() {
+        Draft d;
+        d.schedule.name = tr("New schedule");
+        drafts.push_back(d);
+        return d.schedule.name;
+    };
+    dlg.remove = [&](int row) { drafts.remove(row); };
+    connect(mode, qOverload<int>(&QComboBox::currentIndexChanged), &dlg, updateNext);
+    connect(at, &QTimeEdit::timeChanged, &dlg, updateNext);
+    connect(every, qOverload<int>(&QSpinBox::valueChanged), &dlg, updateNext);
+    connect(enabled, &QCheckBox::toggled, &dlg, updateNext);
+    for (QCheckBox *box : days)
+        connect(box, &QCheckBox::toggled, &dlg, updateNext);
+
+    QVector<persistence::Schedule> result;
+    dlg.check = [&](QString *problem) {
+        result.clear();
+        for (int i = 0; i < drafts.size(); ++i) {
+            persistence::Schedule s = drafts[i].schedule;
+            const QVector<persistence::MacroStep> steps =
+                macros::parseSteps(drafts[i].action, problem);
+            if (s.name.isEmpty()) {
+                *problem = tr("Give the schedule a name.");
+            } else if (drafts[i].noDays && s.everyMinutes == 0) {
+                *problem = tr("Tick at least one day.");
+            } else if (problem->isEmpty() && steps.size() != 1) {
+                *problem = tr("Enter exactly one action.");
+            } else if (problem->isEmpty() && steps.first().action == QLatin1String("wait")) {
+                *problem = tr("“wait” only makes sense inside a macro.");
+            }
+            if (!problem->isEmpty())
+                return i;
+            s.action = steps.first().action;
+            s.args = steps.first().args;
+            result.push_back(s);
+        }
+        return -1;
+    };
+    if (!dlg.run())
+        return;
+
+    m_settings.schedules = result;
+    persistence::saveSettings(m_settings);
+    emit schedulesChanged(m_settings.schedules);
+}
+
+void MainWindow::editTriggers()
+{
+    struct Draft {
+        persistence::Trigger trigger;
+        QString action;   // as typed, like a macro step; checked on OK
+    };
+    QVector<Draft> drafts;
+    QStringList names;
+    for (const persistence::Trigger &t : m_settings.triggers) {
+        drafts.push_back({t, macros::formatSteps({{t.action, t.args}}).trimmed()});
+        names << t.name;
+    }
+
+    DraftDialog dlg(this, tr("Triggers"), QSize(600, 320), names);
+    auto *name = new QLineEdit(&dlg);
+    auto *when = new QComboBox(&dlg);
+    for (const auto &e : triggers::events())
+        when->addItem(QCoreApplication::translate("TriggerWatcher", e.second), e.first);
+    auto *process = new QLineEdit(&dlg);
+    process->setPlaceholderText(tr("any app, or e.g. obs64.exe"));
+    auto *action = new QLineEdit(&dlg);
+    action->setPlaceholderText(QStringLiteral("pin-app obs64.exe"));
+    auto *enabled = new QCheckBox(tr("Enabled"), &dlg);
+    auto *hint = new QLabel(tr("An action with its arguments, as in a macro step. Actions on "
+                               "“the focused window” act on the window that set the trigger "
+                               "off, or on whatever is in front for monitor, lock and "
+                               "closed-window triggers."),
+                            &dlg);
+    hint->setWordWrap(true);
+    hint->setProperty("role", "muted");
+    dlg.form()->addRow(tr("Name:"), name);
+    dlg.form()->addRow(tr("When:"), when);
+    dlg.form()->addRow(tr("App:"), process);
+    dlg.form()->addRow(tr("Action:"), action);
+    dlg.form()->addRow(enabled);
+    dlg.form()->addRow(hint);
+    dlg.setNameField(name);
+
+    const auto syncProcess = [&]() {
+        process->setEnabled(triggers::takesProcess(when->currentData().toString()));
+    };
+    dlg.fill = [&](int row) {
+        const Draft d = row >= 0 ? drafts.value(row) : Draft();
+        name->setText(d.trigger.name);
+        when->setCurrentIndex(qMax(0, when->findData(d.trigger.event)));
+        process->setText(d.trigger.process);
+        action->setText(d.action);
+        enabled->setChecked(d.trigger.enabled);
+        syncProcess();
+    };
+    dlg.store = [&](int row) {
+        Draft &d = drafts[row];
+        d.trigger.name = name->text().trimmed();
+        d.trigger.event = when->currentData().toString();
+        d.trigger.process = triggers::takesProcess(d.trigger.event)
+                                ? process->text().trimmed() : QString();
+        d.trigger.enabled = enabled->isChecked();
+        d.action = action->text().trimmed();
+    };
+    dlg.add = [&]() {
+        Draft d;
+        d.trigger.name = tr("New trigger");
+        drafts.push_back(d);
+        return d.trigger.name;
+    };
+    dlg.remove = [&](int row) { drafts.remove(row); };
+    connect(when, qOverload<int>(&QComboBox::currentIndexChanged), &dlg, syncProcess);
+
+    QVector<persistence::Trigger> result;
+    dlg.check = [&](QString *problem) {
+        result.clear();
+        for (int i = 0; i < drafts.size(); ++i) {
+            persistence::Trigger t = drafts[i].trigger;
+            const QVector<persistence::MacroStep> steps =
+                macros::parseSteps(drafts[i].action, problem);
+            if (t.name.isEmpty()) {
+                *problem = tr("Give the trigger a name.");
+            } else if (problem->isEmpty() && steps.size() != 1) {
+                *problem = tr("Enter exactly one action.");
+            } else if (problem->isEmpty() && steps.first().action == QLatin1String("wait")) {
+                *problem = tr("“wait” only makes sense inside a macro.");
+            }
+            if (!problem->isEmpty())
+                return i;
+            t.action = steps.first().action;
+            t.args = steps.first().args;
+            result.push_back(t);
+        }
+        return -1;
+    };
+    if (!dlg.run())
+        return;
+
+    m_settings.triggers = result;
+    persistence::saveSettings(m_settings);
+    emit triggersChanged(m_settings.triggers);
+}
+
+void MainWindow::fillSnapshotMenu(QMenu *menu)
+{
+    menu->clear();
+
+    QAction *saveAct = menu->addAction(tr("Save current layout…"));
+    saveAct->setEnabled(m_manager->pinnedCount() > 0);
+    connect(saveAct, &QAction::triggered, this, [this]() {
+        bool ok = false;
+        const QString name = QInputDialog::getText(
+            this, tr("Save snapshot"),
+            tr("Name for this layout (saving over an existing name replaces it):"),
+            QLineEdit::Normal, QString(), &ok).trimmed();
+        if (ok && !name.isEmpty() && m_manager->saveSnapshot(name))
+            notify(tr("Saved snapshot “%1”.").arg(name));
+    });
+
+    const QStringList names = m_manager->snapshotNames();
+    if (names.isEmpty())
+        return;
+
+    menu->addSeparator();
+    for (const QString &name : names) {
+        QAction *restoreAct = menu->addAction(tr("Restore “%1”").arg(name));
+        connect(restoreAct, &QAction::triggered, this, [this, name]() {
+            usage::recordFeature(QStringLiteral("snapshot_restore"));
+            m_manager->restoreSnapshot(name);
+        });
+    }
+    QMenu *del = menu->addMenu(tr("Delete"));
+    for (const QString &name : names) {
+        QAction *delAct = del->addAction(name);
+        connect(delAct, &QAction::triggered, this, [this, name]() {
+            m_manager->deleteSnapshot(name);
+        });
+    }
+}
+
+void MainWindow::buildTray()
+{
+    if (!QSystemTrayIcon::isSystemTrayAvailable())
+        return;
+
+    m_tray = new QSystemTrayIcon(this);
+    updateTrayIcon();
+
+    auto *menu = new QMenu(this);
+    QAction *showAct = menu->addAction(tr("Show PinIt"));
     connect(showAct, &QAction::triggered, this, &MainWindow::showFromTray);
+    QAction *unpinAllAct = menu->addAction(tr("Unpin all"));
+    connect(unpinAllAct, &QAction::triggered, this, [this]() {
+        notify(tr("Unpinned %n window(s).", "", m_manager->unpinAll()));
+    });
+    QAction *adoptAct = menu->addAction(tr("Adopt always-on-top windows…"));
+    connect(adoptAct, &QAction::triggered, this, &MainWindow::adoptTopmostDialog);
+    QMenu *snapshots = menu->addMenu(tr("Snapshots"));
+    // Rebuilt each time it opens so it always lists what's on disk.
+    connect(snapshots, &QMenu::aboutToShow, this, [this, snapshots]() {
+        fillSnapshotMenu(snapshots);
+    });
+    QMenu *macroMenu = menu->addMenu(tr("Macros"));
+    connect(macroMenu, &QMenu::aboutToShow, this, [this, macroMenu]() {
+        fillMacroMenu(macroMenu);
+    });
+    QAction *schedAct = menu->addAction(tr("Scheduled actions…"));
+    connect(schedAct, &QAction::triggered, this, &MainWindow::editSchedules);
+    lockIfPolicy(schedAct, "schedules");
+    QAction *triggerAct = menu->addAction(tr("Triggers…"));
+    connect(triggerAct, &QAction::triggered, this, &MainWindow::editTriggers);
+    lockIfPolicy(triggerAct, "triggers");
+    QMenu *backups = menu->addMenu(tr("Restore backup"));
+    connect(backups, &QMenu::aboutToShow, this, [this, backups]() {
+        fillBackupMenu(backups);
+    });
+    if (!persistence::isPortable()) {
+        QAction *folderAct = menu->addAction(tr("Data folder…"));
+        connect(folderAct, &QAction::triggered, this, &MainWindow::chooseDataFolder);
+    }
+    QAction *diagAct = menu->addAction(tr("Diagnostics…"));
+    connect(diagAct, &QAction::triggered, this, &MainWindow::showDiagnostics);
+    QAction *selfTestAct = menu->addAction(tr("Run self-test…"));
+    connect(selfTestAct, &QAction::triggered, this, &MainWindow::runSelfTest);
+    QAction *effectiveAct = menu->addAction(tr("Effective settings…"));
+    connect(effectiveAct, &QAction::triggered, this, &MainWindow::showEffectiveSettings);
+    QAction *conflictsAct = menu->addAction(tr("Check topmost conflicts…"));
+    connect(conflictsAct, &QAction::triggered, this, &MainWindow::showConflicts);
+    // Only worth offering once at least one translation is bundled.
+    const QStringList languages = i18n::available();
+    if (!languages.isEmpty()) {
+        QMenu *langMenu = menu->addMenu(tr("Language"));
+        auto *group = new QActionGroup(langMenu);
+        const auto addLanguage = [&](const QString &code, const QString &label) {
+            QAction *act = langMenu->addAction(label);
+            act->setCheckable(true);
+            act->setChecked(m_settings.language == code);
+            group->addAction(act);
+            connect(act, &QAction::triggered, this, [this, code]() {
+                m_settings.language = code;
+                persistence::saveSettings(m_settings);
+                notify(tr("Restart PinIt to switch the language."));
+            });
+        };
+        addLanguage(QString(), tr("Windows default"));
+        addLanguage(QStringLiteral("en"), QStringLiteral("English"));
+        for (const QString &code : languages)
+            addLanguage(code, QLocale(code).nativeLanguageName());
+        lockIfPolicy(langMenu->menuAction(), "language");
+    }
+    QMenu *iconMenu = menu->addMenu(tr("Tray icon"));
+    auto *iconGroup = new QActionGroup(iconMenu);
+    const QList<QPair<QString, QString>> iconThemes = {
+        {QStringLiteral("auto"), tr("Match the taskbar")},
+        {QStringLiteral("light"), tr("Dark icon (light taskbar)")},
+        {QStringLiteral("dark"), tr("Light icon (dark taskbar)")},
+    };
+    for (const auto &t : iconThemes) {
+        QAction *act = iconMenu->addAction(t.second);
+        act->setCheckable(true);
+        act->setChecked(m_settings.trayIconTheme == t.first);
+        iconGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, theme = t.first]() {
+            m_settings.trayIconTheme = theme;
+            persistence::saveSettings(m_settings);
+            updateTrayIcon();
+        });
+    }
+    lockIfPolicy(iconMenu->menuAction(), "tray_icon_theme");
+    QMenu *feedbackMenu = menu->addMenu(tr("Pin feedback"));
+    auto *feedbackGroup = new QActionGroup(feedbackMenu);
+    const QList<QPair<QString, QString>> feedbackModes = {
+        {QStringLiteral("none"), tr("None")},
+        {QStringLiteral("flash"), tr("Flash the window")},
+        {QStringLiteral("border-pulse"), tr("Pulse the border (Windows 11)")},
+    };
+    for (const auto &f : feedbackModes) {
+        QAction *act = feedbackMenu->addAction(f.second);
+        act->setCheckable(true);
+        act->setChecked(m_settings.pinFeedback == f.first);
+        // Windows 10 has no border colour to pulse; it would flash instead.
+        if (f.first == QLatin1String("border-pulse"))
+            act->setEnabled(platform::capabilities().borderColor);
+        feedbackGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, mode = f.first]() {
+            m_settings.pinFeedback = mode;
+            persistence::saveSettings(m_settings);
+            m_manager->setPinFeedback(mode);
+        });
+        lockIfPolicy(act, "pin_feedback");
+    }
+    QMenu *backdropMenu = menu->addMenu(tr("Translucency effect"));
+    backdropMenu->setEnabled(opacityUnavailable().isEmpty());
+    auto *backdropGroup = new QActionGroup(backdropMenu);
+    const QList<QPair<QString, QString>> backdrops = {
+        {QStringLiteral("none"), tr("None")},
+        {QStringLiteral("blur"), tr("Blur behind")},
+        {QStringLiteral("acrylic"), tr("Acrylic (Windows 11)")},
+    };
+    for (const auto &b : backdrops) {
+        QAction *act = backdropMenu->addAction(b.second);
+        act->setCheckable(true);
+        act->setChecked(m_settings.backdrop == b.first);
+        // Greyed out where this Windows can't do it (it would fall back).
+        act->setEnabled(platform::backdropFor(b.first, platform::capabilities()) == b.first);
+        backdropGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, kind = b.first]() {
+            m_settings.backdrop = kind;
+            persistence::saveSettings(m_settings);
+            m_manager->setBackdrop(kind);
+        });
+        lockIfPolicy(act, "backdrop");
+    }
+    feedbackMenu->addSeparator();
+    QAction *markerAct = feedbackMenu->addAction(tr("Add 📌 to pinned windows' titles"));
+    markerAct->setCheckable(true);
+    markerAct->setChecked(!m_settings.titleMarker.isEmpty());
+    connect(markerAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.titleMarker = on ? QString::fromUtf8("\xF0\x9F\x93\x8C ") : QString();
+        persistence::saveSettings(m_settings);
+        m_manager->setTitleMarker(m_settings.titleMarker);
+    });
+    lockIfPolicy(markerAct, "title_marker");
+    QAction *osdAct = feedbackMenu->addAction(tr("On-screen display for hotkeys"));
+    osdAct->setCheckable(true);
+    osdAct->setChecked(m_settings.osd);
+    connect(osdAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.osd = on;
+        persistence::saveSettings(m_settings);
+    });
+    lockIfPolicy(osdAct, "osd");
+    QAction *badgeAct = feedbackMenu->addAction(tr("Show a pin badge on pinned windows"));
+    badgeAct->setCheckable(true);
+    badgeAct->setChecked(m_settings.cornerBadge);
+    QAction *badgeClickAct = feedbackMenu->addAction(tr("Click the badge to unpin"));
+    badgeClickAct->setCheckable(true);
+    badgeClickAct->setChecked(m_settings.badgeClickUnpin);
+    badgeClickAct->setEnabled(m_settings.cornerBadge);
+    connect(badgeAct, &QAction::toggled, this, [this, badgeClickAct](bool on) {
+        m_settings.cornerBadge = on;
+        persistence::saveSettings(m_settings);
+        m_badges->setEnabled(on);
+        badgeClickAct->setEnabled(on);
+    });
+    connect(badgeClickAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.badgeClickUnpin = on;
+        persistence::saveSettings(m_settings);
+        m_badges->setClickToUnpin(on);
+    });
+    lockIfPolicy(badgeAct, "corner_badge");
+    lockIfPolicy(badgeClickAct, "badge_click_unpin");
+    QMenu *tempMenu = menu->addMenu(tr("Temporary pin length"));
+    auto *tempGroup = new QActionGroup(tempMenu);
+    for (const int seconds : kTempPinChoices) {
+        QAction *act = tempMenu->addAction(tr("%n minute(s)", "", seconds / 60));
+        act->setCheckable(true);
+        act->setChecked(m_settings.tempPinSeconds == seconds);
+        tempGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, seconds]() {
+            m_settings.tempPinSeconds = seconds;
+            persistence::saveSettings(m_settings);
+            m_manager->setTemporaryPinDuration(seconds);
+        });
+    }
+    lockIfPolicy(tempMenu->menuAction(), "temporary_pin_seconds");
+    QMenu *seeThroughMenu = menu->addMenu(tr("Pin see-through at"));
+    auto *seeThroughGroup = new QActionGroup(seeThroughMenu);
+    for (const int percent : {30, 50, 70, 85}) {
+        QAction *act = seeThroughMenu->addAction(tr("%1%").arg(percent));
+        act->setCheckable(true);
+        act->setChecked(m_settings.pinOpacityPercent == percent);
+        seeThroughGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, percent]() {
+            m_settings.pinOpacityPercent = percent;
+            persistence::saveSettings(m_settings);
+            m_manager->setPinOpacity(percent);
+        });
+    }
+    lockIfPolicy(seeThroughMenu->menuAction(), "pin_opacity_percent");
+    QMenu *idleMenu = menu->addMenu(tr("Unpin when unused"));
+    auto *idleGroup = new QActionGroup(idleMenu);
+    const QList<QPair<int, QString>> idleChoices = {
+        {0, tr("Never")},
+        {30, tr("After 30 minutes")},
+        {60, tr("After 1 hour")},
+        {4 * 60, tr("After 4 hours")},
+        {24 * 60, tr("After a day")},
+    };
+    for (const auto &c : idleChoices) {
+        QAction *act = idleMenu->addAction(c.second);
+        act->setCheckable(true);
+        act->setChecked(m_settings.idleUnpinMinutes == c.first);
+        idleGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, minutes = c.first]() {
+            m_settings.idleUnpinMinutes = minutes;
+            persistence::saveSettings(m_settings);
+            m_manager->setIdleUnpinMinutes(minutes);
+        });
+    }
+    lockIfPolicy(idleMenu->menuAction(), "idle_unpin_minutes");
+    QAction *ownedAct = menu->addAction(tr("Keep pinned apps' dialogs on top"));
+    ownedAct->setCheckable(true);
+    ownedAct->setChecked(m_settings.pinOwnedWindows);
+    connect(ownedAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.pinOwnedWindows = on;
+        persistence::saveSettings(m_settings);
+        m_manager->setPinOwnedWindows(on);
+    });
+    lockIfPolicy(ownedAct, "pin_owned_windows");
+    QAction *uiaAct = menu->addAction(tr("Match windows by UI Automation (slower)"));
+    uiaAct->setCheckable(true);
+    uiaAct->setChecked(m_settings.uiaMatching);
+    uiaAct->setToolTip(tr("Helps restore the right window of apps whose titles keep "
+                          "changing. Takes effect for windows pinned from now on."));
+    connect(uiaAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.uiaMatching = on;
+        persistence::saveSettings(m_settings);
+        m_manager->setUiaMatching(on);
+    });
+    lockIfPolicy(uiaAct, "uia_matching");
+    QAction *remoteAct = menu->addAction(tr("No transparency over Remote Desktop"));
+    remoteAct->setCheckable(true);
+    remoteAct->setChecked(m_settings.remoteNoOpacity);
+    remoteAct->setToolTip(tr("Translucent windows are slow to draw over a remote "
+                             "connection. Pins keep their opacity for next time."));
+    connect(remoteAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.remoteNoOpacity = on;
+        persistence::saveSettings(m_settings);
+        applyRemoteOpacity();
+    });
+    lockIfPolicy(remoteAct, "remote_no_opacity");
+    QMenu *coexistMenu = menu->addMenu(tr("Other always-on-top tools"));
+    auto *coexistGroup = new QActionGroup(coexistMenu);
+    const QList<QPair<QString, QString>> coexistModes = {
+        {QStringLiteral("defer"), tr("Let them keep their windows on top")},
+        {QStringLiteral("override"), tr("Take over windows I pin")},
+        {QStringLiteral("ask"), tr("Ask each time")},
+    };
+    for (const auto &c : coexistModes) {
+        QAction *act = coexistMenu->addAction(c.second);
+        act->setCheckable(true);
+        act->setChecked(m_settings.coexistence == c.first);
+        coexistGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, mode = c.first]() {
+            m_settings.coexistence = mode;
+            persistence::saveSettings(m_settings);
+            m_manager->setCoexistencePolicy(mode);
+        });
+    }
+    lockIfPolicy(coexistMenu->menuAction(), "coexistence");
+    QMenu *lostMenu = menu->addMenu(tr("When a pin loses its top spot"));
+    auto *lostGroup = new QActionGroup(lostMenu);
+    const QList<QPair<QString, QString>> lostModes = {
+        {QStringLiteral("restore"), tr("Put it back on top")},
+        {QStringLiteral("mark"), tr("Leave it and tell me")},
+    };
+    for (const auto &c : lostModes) {
+        QAction *act = lostMenu->addAction(c.second);
+        act->setCheckable(true);
+        act->setChecked(m_settings.topmostLost == c.first);
+        lostGroup->addAction(act);
+        connect(act, &QAction::triggered, this, [this, mode = c.first]() {
+            m_settings.topmostLost = mode;
+            persistence::saveSettings(m_settings);
+            m_manager->setLostPolicy(mode);
+        });
+    }
+    lockIfPolicy(lostMenu->menuAction(), "topmost_lost");
+    QAction *kioskAct = menu->addAction(tr("Kiosk mode…"));
+    connect(kioskAct, &QAction::triggered, this, [this]() {
+        if (m_kiosk->isActive())
+            stopKiosk();
+        else
+            startKiosk();
+    });
+    connect(m_kiosk, &Kiosk::activeChanged, kioskAct, [kioskAct](bool on) {
+        kioskAct->setText(on ? MainWindow::tr("Exit kiosk mode") : MainWindow::tr("Kiosk mode…"));
+    });
+    lockIfPolicy(kioskAct, "kiosk");
+    QAction *usageAct = menu->addAction(tr("Usage stats…"));
+    connect(usageAct, &QAction::triggered, this, &MainWindow::showUsage);
+    QAction *auditAct = menu->addAction(tr("Audit log"));
+    auditAct->setCheckable(true);
+    auditAct->setChecked(m_settings.auditLog);
+    connect(auditAct, &QAction::toggled, this, [this](bool on) {
+        // Enabled before saving and disabled after, so the switch itself is audited.
+        m_settings.auditLog = on;
+        if (on)
+            audit::setEnabled(true);
+        persistence::saveSettings(m_settings);
+        audit::setEnabled(on);
+    });
+    lockIfPolicy(auditAct, "audit_log");
+    QAction *eventLogAct = menu->addAction(tr("Report to Windows Event Log"));
+    eventLogAct->setCheckable(true);
+    eventLogAct->setChecked(m_settings.eventLog);
+    connect(eventLogAct, &QAction::toggled, this, [this](bool on) {
+        m_settings.eventLog = on;
+        persistence::saveSettings(m_settings);
+        eventlog::setEnabled(on);
+    });
+    lockIfPolicy(eventLogAct, "event_log");
     QAction *aboutAct = menu->addAction(tr("About PinIt"));
     connect(aboutAct, &QAction::triggered, this, &MainWindow::showAbout);
     menu->addSeparator();
@@ -495,31 +2888,215 @@ void MainWindow::buildTray()
     connect(quitAct, &QAction::triggered, qApp, &QApplication::quit);
 
     m_tray->setContextMenu(menu);
-    m_tray->setToolTip(QStringLiteral("PinIt"));
     connect(m_tray, &QSystemTrayIcon::activated, this,
             [this](QSystemTrayIcon::ActivationReason reason) {
-                if (reason == QSystemTrayIcon::Trigger ||
-                    reason == QSystemTrayIcon::DoubleClick)
+                // Click: the quick panel. Double-click: the full window (the
+                // click before it already opened the panel, so close that).
+                if (reason == QSystemTrayIcon::Trigger) {
+                    showQuickPanel();
+                } else if (reason == QSystemTrayIcon::DoubleClick) {
+                    m_quickPanel->hide();
                     toggleVisibility();
+                }
             });
     m_tray->show();
 }
 
-void MainWindow::applyAutostart(bool enabled)
+bool MainWindow::applyAutostart(bool enabled)
+{
+    return autostart::apply(autostartMode(m_settings, enabled));
+}
+
+void MainWindow::healAutostart()
+{
+    if (persistence::isPortable())
+        return;   // the login entry, if any, belongs to an installed copy
+    // Reading (and re-registering) the logon task goes through the Task
+    // Scheduler service, which can be slow at login: not on the startup path.
+    const autostart::Mode mode = autostartMode(m_settings, m_settings.startWithWindows);
+    auto drift = std::make_shared<autostart::Drift>(autostart::Drift::None);
+    QThread *worker = QThread::create([mode, drift]() { *drift = autostart::heal(mode); });
+    connect(worker, &QThread::finished, worker, &QObject::deleteLater);
+    connect(worker, &QThread::finished, this, [this, drift]() {
+        switch (*drift) {
+        case autostart::Drift::None:
+            break;
+        case autostart::Drift::Healed:
+            qInfo("Start with Windows now starts this copy of PinIt");
+            break;
+        case autostart::Drift::NeedsUser:
+            notify(tr("Start with Windows still points at PinIt's old location — turn “Start "
+                      "PinIt with Windows” off and on again to fix it."));
+            break;
+        }
+    });
+    worker->start(QThread::LowPriority);
+}
+
+void MainWindow::showStartupOptions()
+{
+    QDialog dlg(this);
+    dlg.setWindowTitle(tr("Startup options"));
+    dlg.setWindowIcon(appIcon());
+    auto *form = new QFormLayout(&dlg);
+
+    auto *delay = new QSpinBox(&dlg);
+    delay->setRange(0, 600);
+    delay->setSuffix(tr(" s"));
+    delay->setSpecialValueText(tr("No delay"));
+    delay->setValue(m_settings.autostartDelay);
+    delay->setToolTip(tr("Wait this long after you sign in. A delayed start is "
+                         "registered with Task Scheduler."));
+    lockIfPolicy(delay, "autostart_delay");
+    form->addRow(tr("Delay after login:"), delay);
+
+    auto *args = new QLineEdit(m_settings.autostartArgs, &dlg);
+    args->setToolTip(tr("--minimized starts PinIt quietly in the tray."));
+    lockIfPolicy(args, "autostart_args");
+    form->addRow(tr("Launch arguments:"), args);
+
+    auto *backend = new QComboBox(&dlg);
+    backend->addItem(tr("Registry (Run key)"));
+    backend->addItem(tr("Task Scheduler"));
+    backend->setCurrentIndex(m_settings.autostartTask ? 1 : 0);
+    form->addRow(tr("Register with:"), backend);
+
+    auto *elevated = new QCheckBox(tr("Run as administrator (to pin admin windows)"), &dlg);
+    elevated->setChecked(m_settings.autostartElevated);
+    elevated->setToolTip(tr("Windows won't let a normal app pin windows running as "
+                            "administrator. Uses Task Scheduler; asks for approval once."));
+    lockIfPolicy(elevated, "autostart_elevated");
+    form->addRow(elevated);
+
+    // A delay or elevation only works as a scheduled task.
+    const bool backendLocked = policy::isLocked(QStringLiteral("autostart_backend"));
+    lockIfPolicy(backend, "autostart_backend");
+    const auto syncBackend = [=]() {
+        const bool forced = delay->value() > 0 || elevated->isChecked();
+        if (forced && !backendLocked)
+            backend->setCurrentIndex(1);
+        backend->setEnabled(!forced && !backendLocked);
+    };
+    connect(delay, QOverload<int>::of(&QSpinBox::valueChanged), &dlg, syncBackend);
+    connect(elevated, &QCheckBox::toggled, &dlg, syncBackend);
+    syncBackend();
+
+    auto *buttons = new QDialogButtonBox(QDialogButtonBox::Ok | QDialogButtonBox::Cancel, &dlg);
+    connect(buttons, &QDialogButtonBox::accepted, &dlg, &QDialog::accept);
+    connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
+    form->addRow(buttons);
+    if (dlg.exec() != QDialog::Accepted)
+        return;
+
+    m_settings.autostartDelay = delay->value();
+    m_settings.autostartArgs  = args->text().trimmed();
+    m_settings.autostartTask  = backend->currentIndex() == 1;
+    m_settings.autostartElevated = elevated->isChecked();
+    persistence::saveSettings(m_settings);
+    // Re-register so the new options take effect at the next login.
+    if (m_settings.startWithWindows && !applyAutostart(true))
+        notify(tr("Couldn't update start-with-Windows — Windows refused the change."));
+}
+
+void MainWindow::chooseDataFolder()
 {
-    QSettings run(QStringLiteral(
-        "HKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Run"),
-        QSettings::NativeFormat);
-    if (enabled) {
-        const QString exe = QDir::toNativeSeparators(
-            QCoreApplication::applicationFilePath());
-        // --minimized: when launched at login, start silently in the tray
-        // instead of popping the window every boot.
-        run.setValue(QStringLiteral("PinIt"),
-                     QStringLiteral("\"%1\" --minimized").arg(exe));
+    const auto same = [](const QString &a, const QString &b) {
+        return QDir::cleanPath(a).compare(QDir::cleanPath(b), Qt::CaseInsensitive) == 0;
+    };
+    const QString current = persistence::dataDir();
+    const QString local = persistence::localDataDir();
+    const QString roaming = persistence::roamingDataDir();
+
+    QDialog dlg(this);
+    dlg.setWindowTitle(tr("Data folder"));
+    dlg.setWindowIcon(appIcon());
+    auto *layout = new QVBoxLayout(&dlg);
+    layout->addWidget(new QLabel(tr("Where PinIt keeps its pins, settings, backups and log:"),
+                                 &dlg));
+    auto *localBtn = new QRadioButton(
+        tr("This PC only (%1)").arg(QDir::toNativeSeparators(local)), &dlg);
+    auto *roamingBtn = new QRadioButton(
+        tr("Roaming profile, follows you to other PCs (%1)").arg(QDir::toNativeSeparators(roaming)),
+        &dlg);
+    auto *customBtn = new QRadioButton(tr("Another folder:"), &dlg);
+    layout->addWidget(localBtn);
+    layout->addWidget(roamingBtn);
+    auto *customRow = new QHBoxLayout;
+    customRow->addWidget(customBtn);
+    auto *folder = new QLineEdit(&dlg);
+    customRow->addWidget(folder, 1);
+    auto *browse = new QPushButton(tr("Browse…"), &dlg);
+    customRow->addWidget(browse);
+    layout->addLayout(customRow);
+
+    if (same(current, local)) {
+        localBtn->setChecked(true);
+    } else if (same(current, roaming)) {
+        roamingBtn->setChecked(true);
     } else {
-        run.remove(QStringLiteral("PinIt"));
+        customBtn->setChecked(true);
+        folder->setText(QDir::toNativeSeparators(current));
+    }
+    const auto syncCustom = [=]() {
+        folder->setEnabled(customBtn->isChecked());
+        browse->setEnabled(customBtn->isChecked());
+    };
+    connect(customBtn, &QRadioButton::toggled, &dlg, syncCustom);
+    syncCustom();
+    connect(browse, &QPushButton::clicked, &dlg, [&dlg, folder]() {
+        const QString dir = QFileDialog::getExistingDirectory(&dlg, tr("Data folder"),
+                                                              folder->text());
+        if (!dir.isEmpty())
+            folder->setText(QDir::toNativeSeparators(dir));
+    });
+
+    auto *note = new QLabel(tr("Your current data is copied over, unless the folder already "
+                               "has PinIt data; then that is used instead."), &dlg);
+    note->setWordWrap(true);
+    layout->addWidget(note);
+    auto *buttons = new QDialogButtonBox(QDialogButtonBox::Ok | QDialogButtonBox::Cancel, &dlg);
+    connect(buttons, &QDialogButtonBox::accepted, &dlg, &QDialog::accept);
+    connect(buttons, &QDialogButtonBox::rejected, &dlg, &QDialog::reject);
+    layout->addWidget(buttons);
+    if (dlg.exec() != QDialog::Accepted)
+        return;
+
+    QString path;   // empty = back to this PC
+    if (roamingBtn->isChecked())
+        path = roaming;
+    else if (customBtn->isChecked())
+        path = folder->text().trimmed();
+    if (customBtn->isChecked() && path.isEmpty())
+        return;
+    moveDataFolder(path);
+}
+
+bool MainWindow::confirmMoveDataFolder(const QString &path)
+{
+    const QString to = path.isEmpty() ? persistence::localDataDir() : path;
+    const auto answer = QMessageBox::question(
+        this, tr("Data folder"),
+        tr("Another program asked to move PinIt's data to %1. Move it?")
+            .arg(QDir::toNativeSeparators(to)));
+    return answer == QMessageBox::Yes && moveDataFolder(path);
+}
+
+bool MainWindow::moveDataFolder(const QString &path)
+{
+    QString error;
+    if (!persistence::setConfigDirectory(path, &error)) {
+        qWarning("Couldn't move the data folder: %s", qUtf8Printable(error));
+        notify(tr("Couldn't move PinIt's data: %1.").arg(error));
+        return false;
     }
+    logging::init();   // pinit.log moves along
+    const QStringList watched = m_configWatcher->files() + m_configWatcher->directories();
+    if (!watched.isEmpty())
+        m_configWatcher->removePaths(watched);
+    reloadSettings();   // watches the new file, and applies its settings if they differ
+    notify(tr("PinIt's data is now in %1.")
+               .arg(QDir::toNativeSeparators(persistence::dataDir())));
+    return true;
 }
 
 void MainWindow::toggleVisibility()
@@ -537,6 +3114,54 @@ void MainWindow::showFromTray()
     activateWindow();
 }
 
+void MainWindow::showSettings()
+{
+    showFromTray();
+    m_soundBox->setFocus(Qt::OtherFocusReason);   // the first of them
+}
+
+bool MainWindow::assignSlot(int slot)
+{
+    void *fg = winpin::foregroundWindow();
+    if (slot < 1 || slot > persistence::kPinSlots)
+        return false;
+    if (!fg || winpin::isOwnWindow(fg)) {
+        notify(tr("No window to put in slot %1 — click a window first.").arg(slot));
+        return false;
+    }
+    persistence::PinSlot &s = m_settings.pinSlots[slot - 1];
+    s.processName = winpin::processName(fg);
+    s.title = winpin::windowTitle(fg);
+    for (const PinnedWindow &w : m_manager->pinnedWindows()) {
+        if (w.hwnd == reinterpret_cast<intptr_t>(fg))
+            s.title = w.title;   // without the title marker
+    }
+    persistence::saveSettings(m_settings);
+    notify(tr("Slot %1: %2").arg(slot).arg(s.title.isEmpty() ? s.processName : s.title));
+    return true;
+}
+
+bool MainWindow::activateSlot(int slot)
+{
+    if (slot < 1 || slot > persistence::kPinSlots)
+        return false;
+    const persistence::PinSlot &s = m_settings.pinSlots[slot - 1];
+    if (s.processName.isEmpty()) {
+        notify(tr("Slot %1 is empty — use its assign shortcut on a window first.").arg(slot));
+        return false;
+    }
+    const intptr_t hwnd = m_manager->findSlotWindow(s);
+    if (hwnd == 0) {
+        notify(tr("Slot %1: %2 isn't open.").arg(slot).arg(s.processName));
+        return false;
+    }
+    if (winpin::foregroundWindow() != reinterpret_cast<void *>(hwnd))
+        return winpin::activate(reinterpret_cast<void *>(hwnd));
+    const bool ok = m_manager->toggle(hwnd);
+    showOsd(hwnd, false);
+    return ok;
+}
+
 void MainWindow::notify(const QString &message)
 {
     if (m_tray && m_tray->isVisible())
@@ -544,18 +3169,22 @@ void MainWindow::notify(const QString &message)
                             QSystemTrayIcon::Information, 2500);
 }
 
+void MainWindow::showEvent(QShowEvent *event)
+{
+    QMainWindow::showEvent(event);
+    // The taskbar button is created as the window shows; badge it once it exists.
+    QTimer::singleShot(0, this, [this]() { updatePinIndicators(m_manager->pinnedWindows()); });
+}
+
 void MainWindow::closeEvent(QCloseEvent *event)
 {
     if (m_tray && m_tray->isVisible()) {
         hide();
         event->ignore();
-        if (!m_settings.hasSeenTrayNotice) {
-            m_settings.hasSeenTrayNotice = true;
-            persistence::saveSettings(m_settings);
-            m_tray->showMessage(
-                QStringLiteral("PinIt"),
-                tr("PinIt is still running in the tray. Right-click the icon to quit."),
-                QSystemTrayIcon::Information, 3000);
+        if (onboarding::isPending(m_settings.onboarding, onboarding::Step::TrayNotice)
+            && !m_safeMode) {
+            showTrayNotice();
+            finishOnboardingStep(onboarding::Step::TrayNotice);
         }
     } else {
         // No system tray to live in — closing the window must actually quit,
@@ -4,50 +4,166 @@
 // an "add window" picker, settings, and the system-tray integration.
 //
 #include <QMainWindow>
+#include <QHash>
+#include <QSet>
+#include <QStringList>
+#include <QVector>
+#include <cstdint>
 
+#include "onboarding.h"
 #include "persistence.h"
 
 class PinManager;
+struct PinnedWindow;
 class QVBoxLayout;
 class QWidget;
 class QSystemTrayIcon;
+class QMenu;
+class QuickPanel;
+class OpacitySlider;
+class PinBadges;
+class EdgeDock;
+class Kiosk;
+class Osd;
 class QCheckBox;
+class QPushButton;
+class QFileSystemWatcher;
+class QTimer;
 class QLabel;
 
+// What happened at startup, collected by main() for the Diagnostics panel so
+// users don't have to dig through pinit.log.
+struct StartupStatus {
+    bool        keyboardHook = false;       // hotkey backend in use
+    QStringList hotkeysRegistered;
+    QStringList hotkeysFailed;
+    int         pinsSaved = 0;
+    int         pinsRestored = 0;
+    bool        safeMode = false;           // --safe-mode, or Shift held at launch
+};
+
 class MainWindow : public QMainWindow
 {
     Q_OBJECT
 public:
-    explicit MainWindow(PinManager *manager, QWidget *parent = nullptr);
+    // safeMode: see isSafeMode().
+    explicit MainWindow(PinManager *manager, bool safeMode = false, QWidget *parent = nullptr);
+    ~MainWindow() override;
 
+    // Show and save cfg without emitting shortcutsChanged — for putting back
+    // the shortcuts still registered after an edit couldn't be.
     void setShortcutConfig(const persistence::ShortcutConfig &cfg);
+    void setStartupStatus(const StartupStatus &status) { m_startup = status; }
+    // Check the saved state against configschema: log every problem and tell
+    // the user about them, since those settings fall back to their defaults.
+    // If load() had to salvage a damaged file, report what was lost instead.
+    // Runs at startup and after each hand edit.
+    void checkConfig();
+    // Move PinIt's data folder while running (persistence::setConfigDirectory),
+    // take up the settings found there, and say how it went.
+    bool moveDataFolder(const QString &path);
+    // The same, for `--action set-config-directory`: asks first, since any
+    // local program can send the action.
+    bool confirmMoveDataFolder(const QString &path);
+    // Pin slots (1-based): remember the foreground window as slot n, or bring
+    // slot n's window to the front — toggling its pin if it's there already.
+    bool assignSlot(int slot);
+    bool activateSlot(int slot);
 
     // The settings MainWindow loaded at construction (so main() doesn't have to
     // read the file a second time just to register the initial hotkeys).
     persistence::ShortcutConfig shortcutConfig() const { return m_settings.shortcuts; }
+    QVector<persistence::Macro> macros() const { return m_settings.macros; }
+    QVector<persistence::Schedule> schedules() const { return m_settings.schedules; }
+    QVector<persistence::Trigger> triggers() const { return m_settings.triggers; }
 
 signals:
     void shortcutsChanged(const persistence::ShortcutConfig &cfg);
+    void settingsReloaded();   // pinned.json was edited outside PinIt and re-read
+    void macrosChanged(const QVector<persistence::Macro> &macros);   // main() re-registers
+    void runMacroRequested(const QString &name);                     // tray → Macros
+    void schedulesChanged(const QVector<persistence::Schedule> &schedules);
+    void triggersChanged(const QVector<persistence::Trigger> &triggers);
 
 public slots:
     void toggleVisibility();      // bound to the Show/Hide hotkey
     void showFromTray();
+    void showSettings();          // showFromTray, with the settings at the bottom focused
     void notify(const QString &message);   // transient tray balloon
+    void addWindowDialog();                // "Pin a window…" picker
+    void adoptTopmostDialog();             // take over windows already always on top
+    void showQuickPanel();                 // tray flyout; toggles if already open
+    bool showOpacitySlider(intptr_t hwnd); // false (and says so) if hwnd isn't pinned
+    // Show the next first-run step, if it can be shown now; the rest follow
+    // one at a time. The tray notice waits for the window to be closed,
+    // without holding up the steps after it.
+    void continueOnboarding();
+    void stopKiosk();                      // the escape hotkey; remembered as off
+    // On-screen display after a hotkey acted on hwnd: its pin state, or its
+    // opacity. No-op when the OSD is turned off.
+    void showOsd(intptr_t hwnd, bool opacity);
+    // Whether PinIt is being used over Remote Desktop; with the matching
+    // setting on, pins' opacity is suspended for as long as it is.
+    void setRemoteSession(bool remote);
+    // Safe mode (--safe-mode, or Shift held while PinIt starts) skips the
+    // session hooks, the hotkeys, schedules, kiosk mode and restoring saved
+    // pins, so a bad setting can be undone. Shows a banner offering a backup
+    // to go back to.
+    bool isSafeMode() const { return m_safeMode; }
+    // Move the first-run steps (see onboarding.h) on, by step name or "all".
+    // False for a step name that doesn't exist.
+    bool advanceOnboarding(const QString &step);
 
 protected:
     void closeEvent(QCloseEvent *event) override;   // hide to tray
+    void showEvent(QShowEvent *event) override;     // taskbar button reappears
+    bool nativeEvent(const QByteArray &eventType, void *message,
+                     qintptr *result) override;     // taskbar theme changes
 
 private slots:
     void rebuildList();
-    void addWindowDialog();
+    void showConflicts();
+    void showDiagnostics();
+    void runSelfTest();             // selftest::run(), results in a message box
+    void showEffectiveSettings();   // each setting's value and whether policy fixes it
+    void showUsage();             // opt-in local usage stats
+    void updatePinIndicators(const QVector<PinnedWindow> &pins);   // tray tooltip + badge
+    void updateTitle(intptr_t hwnd, const QString &title);
+    void updateCountdown(intptr_t hwnd, int secondsLeft);   // temporary pin rows
+    void rebuildPending();        // "couldn't re-pin X" rows above the list
     void showAbout();
     void openShortcutsDialog();
+    void confirmProtectedPin(intptr_t hwnd, const QString &title, const QString &process);
+    void askCoexistence(intptr_t hwnd, const QString &title, const QString &process,
+                        const QString &tool);
+    void reloadSettings();        // pick up hand edits / synced copies of pinned.json
+    void watchConfig();           // (re)add pinned.json and its folder to the watch
 
 private:
     void buildUi();
     void buildTray();
-    void applyAutostart(bool enabled);
+    bool applyAutostart(bool enabled);   // false if Windows refused the login entry
+    void healAutostart();               // login entry starts an old path: fix it (off-thread)
+    void showStartupOptions();          // delay + arguments for start-with-Windows
+    void chooseDataFolder();            // tray "Data folder…": local, roaming or custom
     void fillShortcutRows(QVBoxLayout *scv);   // (re)builds the SHORTCUTS chips
+    void showPinMenu(intptr_t hwnd, const QPoint &globalPos);   // right-click on a pin row
+    void editCompactRule(const QString &process);   // per-app "shrink when pinned"
+    void showWindowInfo(intptr_t hwnd);             // "Window details…"
+    void startKiosk();                         // pick the app, confirm, remember it
+    void fillSnapshotMenu(QMenu *menu);        // tray "Snapshots" submenu, built on open
+    void fillBackupMenu(QMenu *menu);          // tray "Restore backup" submenu, ditto
+    void fillMacroMenu(QMenu *menu);           // tray "Macros" submenu, ditto
+    void editMacros();
+    void editSchedules();                      // tray "Scheduled actions…"
+    void editTriggers();                       // tray "Triggers…"
+    void updateTrayIcon();                     // glyph for the current taskbar theme
+    void applyKeepOnTop();                     // PinIt's own always-on-top setting
+    void applyRemoteOpacity();                 // see setRemoteSession()
+    void applyFade();                          // opacity fade setting -> PinManager
+    void applyOsd();                           // OSD position/duration settings
+    void finishOnboardingStep(onboarding::Step step, bool skipped = false);
+    void showTrayNotice();                     // "still running in the tray"
 
     PinManager      *m_manager = nullptr;
     QSystemTrayIcon *m_tray = nullptr;
@@ -55,10 +171,29 @@ private:
     QLabel          *m_emptyLabel = nullptr;
     QLabel          *m_pinnedHeader = nullptr;
     QWidget         *m_emptyCard = nullptr;
+    QWidget         *m_pendingCard = nullptr;
+    QWidget         *m_safeModeCard = nullptr;
+    QVBoxLayout     *m_pendingLayout = nullptr;
     QVBoxLayout     *m_shortcutsLayout = nullptr;
     QCheckBox       *m_soundBox = nullptr;
+    QCheckBox       *m_fadeBox = nullptr;
     QCheckBox       *m_autostartBox = nullptr;
+    QPushButton     *m_selfPin = nullptr;
     QLabel          *m_shortcutsLabel = nullptr;
+    QuickPanel      *m_quickPanel = nullptr;
+    OpacitySlider   *m_opacitySlider = nullptr;
+    PinBadges       *m_badges = nullptr;
+    EdgeDock        *m_edgeDock = nullptr;
+    Kiosk           *m_kiosk = nullptr;
+    Osd             *m_osd = nullptr;   // top-level, so owned by hand
+    QFileSystemWatcher *m_configWatcher = nullptr;
+    QTimer          *m_reloadTimer = nullptr;   // editors save in several writes
+    QHash<intptr_t, QLabel *> m_titleLabels;   // per-row title, updated in place
+    QHash<intptr_t, QLabel *> m_countdownLabels;   // temporary pins' time left, ditto
+    QSet<intptr_t>            m_reenforceNoticed;   // told the user once per window
+    bool                      m_remoteSession = false;
+    bool                      m_safeMode = false;
 
     persistence::UserSettings m_settings;
+    StartupStatus             m_startup;
 };
@@ -0,0 +1,74 @@
+#include "onboarding.h"
+
+namespace onboarding {
+
+QVector<Step> steps()
+{
+    return {Step::TrayNotice, Step::HotkeyTutorial, Step::AutostartPrompt,
+            Step::PermissionsCheck};
+}
+
+QString name(Step step)
+{
+    switch (step) {
+    case Step::TrayNotice:       return QStringLiteral("tray_notice");
+    case Step::HotkeyTutorial:   return QStringLiteral("hotkey_tutorial");
+    case Step::AutostartPrompt:  return QStringLiteral("autostart_prompt");
+    case Step::PermissionsCheck: return QStringLiteral("permissions_check");
+    }
+    return QString();
+}
+
+bool fromName(const QString &name, Step *step)
+{
+    for (Step s : steps()) {
+        if (onboarding::name(s) == name) {
+            *step = s;
+            return true;
+        }
+    }
+    return false;
+}
+
+bool isPending(const Progress &progress, Step step)
+{
+    return !progress.contains(name(step));
+}
+
+bool next(const Progress &progress, Step *step)
+{
+    for (Step s : steps()) {
+        if (isPending(progress, s)) {
+            *step = s;
+            return true;
+        }
+    }
+    return false;
+}
+
+bool finished(const Progress &progress)
+{
+    Step unused = Step::TrayNotice;
+    return !next(progress, &unused);
+}
+
+void advance(Progress &progress, Step step, bool skipped)
+{
+    progress.insert(name(step), skipped ? QStringLiteral("skipped") : QStringLiteral("done"));
+}
+
+QJsonObject toJson(const Progress &progress)
+{
+    QJsonObject list;
+    for (Step s : steps())
+        list[name(s)] = progress.value(name(s), QStringLiteral("pending"));
+    Step current = Step::TrayNotice;
+    const bool more = next(progress, &current);
+    QJsonObject o;
+    o["finished"] = !more;
+    o["current"] = more ? QJsonValue(name(current)) : QJsonValue();
+    o["steps"] = list;
+    return o;
+}
+
+} // namespace onboarding
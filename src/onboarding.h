@@ -0,0 +1,51 @@
+#pragma once
+//
+// onboarding — the first-run steps PinIt walks a new user through, in order:
+//
+//   tray_notice       closing the window leaves PinIt running in the tray
+//   hotkey_tutorial   the pin and opacity hotkeys
+//   autostart_prompt  whether to start with Windows
+//   permissions_check apps running as administrator can't be pinned from a
+//                     normal PinIt
+//
+// Progress is UserSettings::onboarding, saved as
+//   "onboarding": {"tray_notice": "done", "autostart_prompt": "skipped"}
+// A step that isn't listed is still to come. Names this version doesn't know
+// (from a newer one) are left alone, so adding a step only means adding it
+// here. MainWindow shows the steps; `PinIt.exe --onboarding-state` prints the
+// progress and `--action advance-onboarding <step>|all` moves it on, e.g. to
+// skip the whole thing on a managed PC.
+//
+#include <QJsonObject>
+#include <QMap>
+#include <QString>
+#include <QVector>
+
+namespace onboarding {
+
+enum class Step {
+    TrayNotice,
+    HotkeyTutorial,
+    AutostartPrompt,
+    PermissionsCheck,
+};
+
+using Progress = QMap<QString, QString>;   // step name -> "done" / "skipped"
+
+QVector<Step> steps();   // in the order they're offered
+QString       name(Step step);
+bool          fromName(const QString &name, Step *step);   // false if unknown
+
+bool isPending(const Progress &progress, Step step);
+// The first step still to come, if any.
+bool next(const Progress &progress, Step *step);
+bool finished(const Progress &progress);
+// Mark step done, or skipped (offered but not applicable, or turned down
+// without an answer). Done or skipped, it isn't offered again.
+void advance(Progress &progress, Step step, bool skipped = false);
+
+// {"finished": false, "current": "hotkey_tutorial",
+//  "steps": {"tray_notice": "done", "hotkey_tutorial": "pending", …}}
+QJsonObject toJson(const Progress &progress);
+
+} // namespace onboarding
@@ -0,0 +1,109 @@
+#include "opacityslider.h"
+#include "pinmanager.h"
+#include "winpin.h"
+
+#include <QGuiApplication>
+#include <QHBoxLayout>
+#include <QKeyEvent>
+#include <QLabel>
+#include <QScreen>
+#include <QSignalBlocker>
+#include <QSlider>
+
+#include <algorithm>
+
+namespace {
+
+constexpr int kGap = 8;   // between the slider and the window's edge
+
+} // namespace
+
+OpacitySlider::OpacitySlider(PinManager *manager, QWidget *parent)
+    : QFrame(parent, Qt::Popup | Qt::FramelessWindowHint)
+    , m_manager(manager)
+{
+    setObjectName(QStringLiteral("opacitySlider"));
+
+    auto *row = new QHBoxLayout(this);
+    row->setContentsMargins(12, 8, 12, 8);
+    row->setSpacing(8);
+    m_slider = new QSlider(Qt::Horizontal, this);
+    m_slider->setRange(winpin::kMinOpacity, winpin::kMaxOpacity);
+    m_slider->setSingleStep(5);   // like the opacity keys
+    m_slider->setPageStep(10);
+    m_slider->setFixedWidth(160);
+    row->addWidget(m_slider);
+    m_value = new QLabel(this);
+    m_value->setMinimumWidth(m_value->fontMetrics().horizontalAdvance(QStringLiteral("100%")));
+    row->addWidget(m_value);
+
+    connect(m_slider, &QSlider::valueChanged, this, [this](int v) {
+        m_value->setText(tr("%1%").arg(v));
+        m_manager->setOpacity(m_hwnd, v);
+    });
+    // The window went away (closed, or unpinned by a hotkey) under us.
+    connect(m_manager, &PinManager::pinsChanged, this, [this]() {
+        if (isVisible() && !m_manager->isPinned(m_hwnd))
+            hide();
+    });
+}
+
+bool OpacitySlider::popup(intptr_t hwnd, const QRect &near)
+{
+    const QVector<PinnedWindow> pins = m_manager->pinnedWindows();
+    const auto it = std::find_if(pins.begin(), pins.end(),
+                                 [hwnd](const PinnedWindow &w) { return w.hwnd == hwnd; });
+    if (it == pins.end())
+        return false;
+
+    m_hwnd = hwnd;
+    m_original = it->opacity;
+    {
+        const QSignalBlocker quiet(m_slider);   // it's already at this opacity
+        m_slider->setValue(m_original);
+    }
+    m_value->setText(tr("%1%").arg(m_original));
+
+    adjustSize();
+    place(near);
+    show();
+    activateWindow();   // for the arrow keys, and so a click elsewhere closes it
+    m_slider->setFocus();
+    return true;
+}
+
+void OpacitySlider::place(const QRect &near)
+{
+    QScreen *screen = QGuiApplication::screenAt(near.center());
+    if (!screen)
+        screen = QGuiApplication::primaryScreen();
+    const QRect avail = screen->availableGeometry();
+
+    // Right of the window's top edge; left of it if that's off screen; inside
+    // it (a maximised window) if neither fits.
+    QPoint pos(near.right() + kGap, near.top() + kGap);
+    if (pos.x() + width() > avail.right())
+        pos.setX(near.left() - width() - kGap);
+    if (pos.x() < avail.left())
+        pos.setX(near.right() - width() - kGap);
+    pos.setX(qBound(avail.left() + kGap, pos.x(), avail.right() - width() - kGap));
+    pos.setY(qBound(avail.top() + kGap, pos.y(), avail.bottom() - height() - kGap));
+    move(pos);
+}
+
+void OpacitySlider::keyPressEvent(QKeyEvent *event)
+{
+    switch (event->key()) {
+    case Qt::Key_Escape:
+        if (m_slider->value() != m_original)
+            m_manager->setOpacity(m_hwnd, m_original);
+        hide();
+        return;
+    case Qt::Key_Return:
+    case Qt::Key_Enter:
+        hide();
+        return;
+    default:
+        QFrame::keyPressEvent(event);
+    }
+}
@@ -0,0 +1,38 @@
+#pragma once
+//
+// OpacitySlider — a small slider that pops up beside a pinned window so its
+// opacity can be dragged into place, for when the opacity keys' 5% steps are
+// too slow. The arrow keys move it too; Enter (or a click elsewhere) keeps the
+// new opacity and Esc puts the old one back. Summoned by the opacity-slider
+// hotkey.
+//
+#include <QFrame>
+
+#include <cstdint>
+
+class PinManager;
+class QLabel;
+class QSlider;
+
+class OpacitySlider : public QFrame
+{
+    Q_OBJECT
+public:
+    explicit OpacitySlider(PinManager *manager, QWidget *parent = nullptr);
+
+    // Show beside near (the window's rect, in logical coordinates). False if
+    // hwnd isn't pinned.
+    bool popup(intptr_t hwnd, const QRect &near);
+
+protected:
+    void keyPressEvent(QKeyEvent *event) override;
+
+private:
+    void place(const QRect &near);
+
+    PinManager *m_manager = nullptr;
+    QSlider    *m_slider = nullptr;
+    QLabel     *m_value = nullptr;
+    intptr_t    m_hwnd = 0;
+    int         m_original = 100;   // put back by Esc
+};
@@ -0,0 +1,89 @@
+#include "osd.h"
+#include "dpi.h"
+
+#include <QFontMetrics>
+#include <QGuiApplication>
+#include <QPainter>
+#include <QScreen>
+#include <QTimer>
+
+namespace {
+
+constexpr int kPadX = 18;
+constexpr int kPadY = 10;
+constexpr int kMargin = 48;   // above the bottom edge of the window / work area
+
+} // namespace
+
+Osd::Osd()
+    : QWidget(nullptr, Qt::Tool | Qt::FramelessWindowHint | Qt::WindowStaysOnTopHint
+                           | Qt::WindowDoesNotAcceptFocus | Qt::WindowTransparentForInput)
+{
+    setAttribute(Qt::WA_TranslucentBackground);
+    setAttribute(Qt::WA_ShowWithoutActivating);
+    QFont f = font();
+    f.setPixelSize(15);
+    f.setBold(true);
+    setFont(f);
+
+    m_hideTimer = new QTimer(this);
+    m_hideTimer->setSingleShot(true);
+    m_hideTimer->setInterval(1200);
+    connect(m_hideTimer, &QTimer::timeout, this, &QWidget::hide);
+}
+
+void Osd::setDuration(int ms)
+{
+    m_hideTimer->setInterval(qBound(300, ms, 10000));
+}
+
+void Osd::showMessage(const QString &text, const QRect &near)
+{
+    m_text = text;
+    m_near = near;
+    relayout();
+    update();
+    show();
+    raise();
+    m_hideTimer->start();
+}
+
+void Osd::relayout()
+{
+    const QFontMetrics fm(font());
+    resize(fm.horizontalAdvance(m_text) + 2 * kPadX, fm.height() + 2 * kPadY);
+
+    QScreen *screen = QGuiApplication::screenAt(m_near.center());
+    if (!screen)
+        screen = QGuiApplication::primaryScreen();
+    const QRect avail = screen->availableGeometry();
+    const QRect box = (m_position == Position::Window && avail.intersects(m_near))
+                          ? m_near.intersected(avail)
+                          : avail;
+    QPoint pos(box.center().x() - width() / 2, box.bottom() - kMargin - height());
+    pos.setX(qBound(avail.left(), pos.x(), avail.right() - width()));
+    pos.setY(qBound(avail.top(), pos.y(), avail.bottom() - height()));
+    move(pos);
+}
+
+bool Osd::nativeEvent(const QByteArray &eventType, void *message, qintptr *result)
+{
+    if (dpi::isDpiChanged(eventType, message)) {
+        QTimer::singleShot(0, this, [this]() {
+            if (isVisible())
+                relayout();
+        });
+    }
+    return QWidget::nativeEvent(eventType, message, result);
+}
+
+void Osd::paintEvent(QPaintEvent *)
+{
+    QPainter p(this);
+    p.setRenderHint(QPainter::Antialiasing);
+    p.setPen(Qt::NoPen);
+    p.setBrush(QColor(0x2A, 0x26, 0x22, 220));
+    p.drawRoundedRect(rect(), height() / 2.0, height() / 2.0);
+    p.setPen(Qt::white);
+    p.drawText(rect(), Qt::AlignCenter, m_text);
+}
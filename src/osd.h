@@ -0,0 +1,39 @@
+#pragma once
+//
+// Osd — a volume-style on-screen display for hotkey actions ("Opacity 75%",
+// "Pinned: Spotify"). A borderless topmost bubble that never takes focus or
+// clicks, shown near the affected window (or at the bottom of its screen)
+// and gone again after a moment.
+//
+#include <QWidget>
+
+class QTimer;
+
+class Osd : public QWidget
+{
+    Q_OBJECT
+public:
+    enum class Position { Window, Screen };
+
+    Osd();
+
+    void setPosition(Position p) { m_position = p; }
+    void setDuration(int ms);
+
+    // near is the target window's rect in logical coordinates.
+    void showMessage(const QString &text, const QRect &near);
+
+protected:
+    void paintEvent(QPaintEvent *event) override;
+    // WM_DPICHANGED: Qt rescales the bubble, then it's laid out again so it
+    // still sits where it should on the new monitor.
+    bool nativeEvent(const QByteArray &eventType, void *message, qintptr *result) override;
+
+private:
+    void relayout();   // size for m_text, place near m_near
+
+    QString  m_text;
+    QRect    m_near;
+    Position m_position = Position::Window;
+    QTimer  *m_hideTimer = nullptr;
+};
@@ -0,0 +1,134 @@
+#include <windows.h>
+#include <psapi.h>
+#include "perf.h"
+
+#include <QDateTime>
+#include <QHash>
+#include <QTimer>
+
+#include <algorithm>
+
+namespace {
+
+constexpr int   kSampleMs = 10 * 1000;
+constexpr int   kCpuWindow = 6;            // samples averaged before warning: one minute
+constexpr qreal kCpuWarnPercent = 5.0;
+constexpr qreal kRateWarnPerSecond = 100;  // the fastest loop (edge-dock slides) runs at ~60
+
+// Keyed by the literal's address: one entry per call site, no allocation per tick.
+QHash<const char *, quint64> g_ticks;
+perf::Stats g_stats;
+PerfMonitor *g_monitor = nullptr;
+
+qint64 fileTime(const FILETIME &ft)
+{
+    return (qint64(ft.dwHighDateTime) << 32) | ft.dwLowDateTime;
+}
+
+} // namespace
+
+namespace perf {
+
+void tick(const char *loop)
+{
+    if (g_monitor)
+        g_monitor->wake();
+    ++g_ticks[loop];
+}
+
+Stats stats()
+{
+    return g_stats;
+}
+
+} // namespace perf
+
+PerfMonitor::PerfMonitor(QObject *parent)
+    : QObject(parent)
+{
+    m_timer = new QTimer(this);
+    m_timer->setInterval(kSampleMs);
+    connect(m_timer, &QTimer::timeout, this, &PerfMonitor::sample);
+    g_monitor = this;
+}
+
+PerfMonitor::~PerfMonitor()
+{
+    if (g_monitor == this)
+        g_monitor = nullptr;
+}
+
+void PerfMonitor::wake()
+{
+    if (m_timer->isActive())
+        return;
+    // The time asleep isn't part of any interval, nor of the CPU average.
+    m_lastCpu100ns = -1;
+    m_recentCpu.clear();
+    sample();   // baseline for the first interval
+    m_timer->start();
+}
+
+void PerfMonitor::sample()
+{
+    const qint64 wallMs = QDateTime::currentMSecsSinceEpoch();
+    FILETIME created, exited, kernel, user;
+    qint64 cpu100ns = -1;
+    if (GetProcessTimes(GetCurrentProcess(), &created, &exited, &kernel, &user))
+        cpu100ns = fileTime(kernel) + fileTime(user);
+
+    PROCESS_MEMORY_COUNTERS_EX mem = {};
+    mem.cb = sizeof(mem);
+    const bool haveMem = GetProcessMemoryInfo(
+        GetCurrentProcess(), reinterpret_cast<PROCESS_MEMORY_COUNTERS *>(&mem), sizeof(mem));
+
+    const qint64 elapsedMs = wallMs - m_lastWallMs;
+    if (m_lastCpu100ns >= 0 && cpu100ns >= 0 && elapsedMs > 0) {
+        SYSTEM_INFO si;
+        GetSystemInfo(&si);
+        const qreal cores = qMax<DWORD>(1, si.dwNumberOfProcessors);
+        perf::Stats s;
+        s.sampled = true;
+        // 100 ns units → ms, over the wall time all cores had.
+        s.cpuPercent = (cpu100ns - m_lastCpu100ns) / 10000.0 * 100.0 / (elapsedMs * cores);
+        if (haveMem) {
+            s.workingSetKb = qint64(mem.WorkingSetSize / 1024);
+            s.privateKb = qint64(mem.PrivateUsage / 1024);
+        }
+        for (auto it = g_ticks.cbegin(); it != g_ticks.cend(); ++it)
+            s.loopRates.push_back({QString::fromLatin1(it.key()), it.value() * 1000.0 / elapsedMs});
+        std::sort(s.loopRates.begin(), s.loopRates.end(),
+                  [](const auto &a, const auto &b) { return a.second > b.second; });
+        g_stats = s;
+
+        m_recentCpu.push_back(s.cpuPercent);
+        if (m_recentCpu.size() > kCpuWindow)
+            m_recentCpu.removeFirst();
+        qreal average = 0;
+        for (qreal c : std::as_const(m_recentCpu))
+            average += c;
+        average /= m_recentCpu.size();
+        if (!m_cpuWarned && m_recentCpu.size() == kCpuWindow && average > kCpuWarnPercent) {
+            m_cpuWarned = true;
+            qWarning("PinIt averaged %.1f%% CPU over the last minute", average);
+            emit warning(tr("PinIt has been using %1% CPU — see Diagnostics for which part.")
+                             .arg(average, 0, 'f', 1));
+        }
+        for (const auto &rate : std::as_const(s.loopRates)) {
+            if (rate.second > kRateWarnPerSecond && !m_rateWarned.contains(rate.first)) {
+                m_rateWarned.push_back(rate.first);
+                qWarning("Loop %s ran %.0f times a second", qUtf8Printable(rate.first),
+                         rate.second);
+                emit warning(tr("PinIt's %1 check is running unusually often (%2 a second).")
+                                 .arg(rate.first)
+                                 .arg(qRound(rate.second)));
+            }
+        }
+    }
+    // Nothing polled all interval: sleep until something does.
+    if (g_ticks.isEmpty())
+        m_timer->stop();
+    g_ticks.clear();
+    m_lastCpu100ns = cpu100ns;
+    m_lastWallMs = wallMs;
+}
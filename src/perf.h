@@ -0,0 +1,59 @@
+#pragma once
+//
+// perf — PinIt watching its own footprint, for "PinIt uses 5 % CPU while
+// idle" reports. Every polling loop calls tick() once per pass; PerfMonitor
+// samples the process's CPU time and memory every few seconds, turns the
+// tick counts into rates, and warns (log + signal) the first time CPU or a
+// loop's rate goes over its limit. Diagnostics shows the latest sample.
+//
+// The sampling timer only runs while some loop does: a tick starts it, and a
+// sample with no ticks since the last one stops it again, so an idle PinIt
+// isn't woken every few seconds just to measure itself being idle.
+//
+#include <QObject>
+#include <QPair>
+#include <QString>
+#include <QVector>
+
+namespace perf {
+
+// One pass of a polling loop. loop is a string literal ("reenforce",
+// "badges", …); counts are kept per call site, so keep it short and static.
+void tick(const char *loop);
+
+struct Stats {
+    bool   sampled = false;       // false until the first interval has passed
+    qreal  cpuPercent = 0;        // of all cores, over the last interval
+    qint64 workingSetKb = 0;
+    qint64 privateKb = 0;
+    QVector<QPair<QString, qreal>> loopRates;   // passes per second, busiest first
+};
+
+Stats stats();   // as of the last sample
+
+} // namespace perf
+
+class QTimer;
+
+class PerfMonitor : public QObject
+{
+    Q_OBJECT
+public:
+    explicit PerfMonitor(QObject *parent = nullptr);   // one per process
+    ~PerfMonitor() override;
+
+    void wake();   // start sampling, if it has stopped; tick() calls it
+
+signals:
+    void warning(const QString &message);   // once per cause per session
+
+private:
+    void sample();
+
+    QTimer *m_timer = nullptr;
+    qint64  m_lastCpu100ns = -1;
+    qint64  m_lastWallMs = 0;
+    QVector<qreal> m_recentCpu;   // the last minute's samples
+    bool    m_cpuWarned = false;
+    QVector<QString> m_rateWarned;
+};
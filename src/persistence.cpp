@@ -1,21 +1,47 @@
 #include "persistence.h"
+#include "audit.h"
+#include "onboarding.h"
+#include "platform.h"
+#include "policy.h"
 
+#include <QCoreApplication>
+#include <QDateTime>
 #include <QDir>
 #include <QFile>
+#include <QFileInfo>
 #include <QJsonDocument>
 #include <QJsonObject>
 #include <QJsonArray>
+#include <QRegularExpression>
 #include <QSaveFile>
+#include <QSettings>
+#include <QSqlDatabase>
+#include <QSqlQuery>
+#include <QTemporaryFile>
+
+#include <memory>
 
 namespace {
 
-QString savePath()
+QByteArray g_lastSeen;   // what load() last read or save() last wrote
+QString    g_dataDir;    // dataDir(), worked out once per setConfigDirectory()
+QString    g_unreachable;   // the chosen folder dataDir() fell back from
+
+// Where a moved data folder is recorded. It can't live in the folder itself,
+// and HKCU\Software roams with the profile, so another PC finds it too.
+const char *const kLocationKey   = "HKEY_CURRENT_USER\\Software\\PinIt";
+const char *const kLocationValue = "DataDirectory";
+const char *const kRoaming       = "%APPDATA%\\PinIt";
+
+// The damaged document load() last salvaged, so it's copied aside and reported
+// once rather than on every reload, and the report until takeRecovery().
+QByteArray               g_salvagedFrom;
+persistence::Recovery    g_recovery;
+bool                     g_recoveryPending = false;
+
+QString dataFile(const char *name)
 {
-    // dirs::data_local_dir() in the Rust app == %LOCALAPPDATA%.
-    QString base = qEnvironmentVariable("LOCALAPPDATA");
-    if (base.isEmpty())
-        base = QDir::homePath();
-    return QDir(base).filePath(QStringLiteral("PinIt/pinned.json"));
+    return QDir(persistence::dataDir()).filePath(QLatin1String(name));
 }
 
 persistence::ShortcutConfig readShortcuts(const QJsonObject &o)
@@ -25,6 +51,31 @@ persistence::ShortcutConfig readShortcuts(const QJsonObject &o)
     if (o.contains("opacity_up"))    c.opacityUp    = o.value("opacity_up").toString();
     if (o.contains("opacity_down"))  c.opacityDown  = o.value("opacity_down").toString();
     if (o.contains("toggle_window")) c.toggleWindow = o.value("toggle_window").toString();
+    if (o.contains("pin_under_cursor"))
+        c.pinUnderCursor = o.value("pin_under_cursor").toString();
+    if (o.contains("quick_panel"))   c.quickPanel   = o.value("quick_panel").toString();
+    if (o.contains("hide_all_pinned"))
+        c.hideAllPinned = o.value("hide_all_pinned").toString();
+    if (o.contains("pin_temporarily"))
+        c.pinTemporarily = o.value("pin_temporarily").toString();
+    if (o.contains("kiosk_exit"))    c.kioskExit    = o.value("kiosk_exit").toString();
+    if (o.contains("toggle_last_pinned"))
+        c.toggleLastPinned = o.value("toggle_last_pinned").toString();
+    if (o.contains("focus_last_pinned"))
+        c.focusLastPinned = o.value("focus_last_pinned").toString();
+    if (o.contains("pin_with_opacity"))
+        c.pinWithOpacity = o.value("pin_with_opacity").toString();
+    if (o.contains("opacity_slider"))
+        c.opacitySlider = o.value("opacity_slider").toString();
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        const QString n = QString::number(i + 1);
+        if (o.contains("slot_" + n))
+            c.slot[i] = o.value("slot_" + n).toString();
+        if (o.contains("assign_slot_" + n))
+            c.assignSlot[i] = o.value("assign_slot_" + n).toString();
+    }
+    c.holdToPreview = o.value("toggle_pin_hold").toBool(false);
+    c.keyboardHook  = o.value("backend").toString() == QLatin1String("keyboard_hook");
     return c;
 }
 
@@ -35,6 +86,22 @@ QJsonObject writeShortcuts(const persistence::ShortcutConfig &c)
     o["opacity_up"]    = c.opacityUp;
     o["opacity_down"]  = c.opacityDown;
     o["toggle_window"] = c.toggleWindow;
+    o["pin_under_cursor"] = c.pinUnderCursor;
+    o["quick_panel"]   = c.quickPanel;
+    o["hide_all_pinned"] = c.hideAllPinned;
+    o["pin_temporarily"] = c.pinTemporarily;
+    o["kiosk_exit"]    = c.kioskExit;
+    o["toggle_last_pinned"] = c.toggleLastPinned;
+    o["focus_last_pinned"] = c.focusLastPinned;
+    o["pin_with_opacity"] = c.pinWithOpacity;
+    o["opacity_slider"] = c.opacitySlider;
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        o["slot_" + QString::number(i + 1)] = c.slot[i];
+        o["assign_slot_" + QString::number(i + 1)] = c.assignSlot[i];
+    }
+    o["toggle_pin_hold"] = c.holdToPreview;
+    o["backend"] = c.keyboardHook ? QStringLiteral("keyboard_hook")
+                                  : QStringLiteral("register_hotkey");
     return o;
 }
 
@@ -42,9 +109,129 @@ persistence::UserSettings readSettings(const QJsonObject &o)
 {
     persistence::UserSettings s;
     s.enableSound       = o.value("enable_sound").toBool(true);
-    s.hasSeenTrayNotice = o.value("has_seen_tray_notice").toBool(false);
     s.startWithWindows  = o.value("start_with_windows").toBool(false);
+    s.autostartDelay    = o.value("autostart_delay").toInt(0);
+    s.autostartArgs     = o.value("autostart_args").toString(QStringLiteral("--minimized"));
+    s.autostartTask     = o.value("autostart_backend").toString()
+                          == QLatin1String("task_scheduler");
+    s.autostartElevated = o.value("autostart_elevated").toBool(false);
+    s.usageStats        = o.value("usage_stats").toBool(false);
+    s.auditLog          = o.value("audit_log").toBool(false);
+    s.auditLogPath      = o.value("audit_log_path").toString();
+    s.eventLog          = o.value("event_log").toBool(false);
+    s.keepOnTop         = o.value("keep_on_top").toBool(false);
+    s.animateOpacity    = o.value("animate_opacity").toBool(true);
+    s.fadeMs            = o.value("opacity_fade_ms").toInt(150);
+    s.language          = o.value("language").toString();
+    s.trayIconTheme     = o.value("tray_icon_theme").toString(QStringLiteral("auto"));
+    s.pinFeedback       = o.value("pin_feedback").toString(QStringLiteral("none"));
+    s.backdrop          = o.value("backdrop").toString(QStringLiteral("none"));
+    for (const QJsonValue &v : o.value("no_layering_apps").toArray())
+        s.noLayeringApps << v.toString();
+    s.titleMarker       = o.value("title_marker").toString();
+    s.osd               = o.value("osd").toBool(true);
+    s.osdPosition       = o.value("osd_position").toString(QStringLiteral("window"));
+    s.osdDurationMs     = o.value("osd_duration_ms").toInt(1200);
+    s.tempPinSeconds    = o.value("temporary_pin_seconds").toInt(300);
+    s.pinOpacityPercent = o.value("pin_opacity_percent").toInt(70);
+    s.idleUnpinMinutes  = o.value("idle_unpin_minutes").toInt(0);
+    s.kiosk             = o.value("kiosk").toBool(false);
+    s.kioskPath         = o.value("kiosk_path").toString();
+    s.coexistence       = o.value("coexistence").toString(QStringLiteral("defer"));
+    s.topmostLost       = o.value("topmost_lost").toString(QStringLiteral("restore"));
+    s.cornerBadge       = o.value("corner_badge").toBool(false);
+    s.badgeClickUnpin   = o.value("badge_click_unpin").toBool(false);
+    s.pinOwnedWindows   = o.value("pin_owned_windows").toBool(true);
+    s.uiaMatching       = o.value("uia_matching").toBool(false);
+    s.remoteNoOpacity   = o.value("remote_no_opacity").toBool(false);
     s.shortcuts         = readShortcuts(o.value("shortcuts").toObject());
+    const QJsonObject choices = o.value("protected_process_choices").toObject();
+    for (auto it = choices.begin(); it != choices.end(); ++it)
+        s.protectedChoices.insert(it.key(), it.value().toBool());
+    // compact_rules: { "vlc.exe": { "width": 480, "height": 270, "corner": "…" } }
+    const QJsonObject rules = o.value("compact_rules").toObject();
+    for (auto it = rules.begin(); it != rules.end(); ++it) {
+        const QJsonObject r = it.value().toObject();
+        persistence::CompactRule rule;
+        rule.size = QSize(r.value("width").toInt(), r.value("height").toInt());
+        rule.corner = r.value("corner").toString(rule.corner);
+        if (!rule.size.isEmpty())
+            s.compactRules.insert(it.key(), rule);
+    }
+    // onboarding: { "tray_notice": "done", "autostart_prompt": "skipped" }
+    const QJsonObject onboarding = o.value("onboarding").toObject();
+    for (auto it = onboarding.begin(); it != onboarding.end(); ++it) {
+        if (it.value().isString())
+            s.onboarding.insert(it.key(), it.value().toString());
+    }
+    // app_compat: { "mstsc.exe": { "no_layering": true, "reassert_after_ms": 500 } }
+    const QJsonObject compat = o.value("app_compat").toObject();
+    for (auto it = compat.begin(); it != compat.end(); ++it) {
+        const QJsonObject c = it.value().toObject();
+        persistence::AppCompat entry;
+        entry.noLayering = c.value("no_layering").toBool(false);
+        entry.enforcement = c.value("enforcement").toString();
+        entry.reassertAfterMs = c.value("reassert_after_ms").toInt(0);
+        s.appCompat.insert(it.key().toLower(), entry);
+    }
+    // macros: [ { "name": …, "shortcut": …, "steps": [ ["set-opacity", "80"], … ] } ]
+    for (const QJsonValue &v : o.value("macros").toArray()) {
+        const QJsonObject m = v.toObject();
+        persistence::Macro macro;
+        macro.name = m.value("name").toString();
+        macro.shortcut = m.value("shortcut").toString();
+        for (const QJsonValue &step : m.value("steps").toArray()) {
+            const QJsonArray words = step.toArray();
+            persistence::MacroStep ms;
+            ms.action = words.at(0).toString();
+            for (int i = 1; i < words.size(); ++i)
+                ms.args << words.at(i).toString();
+            if (!ms.action.isEmpty())
+                macro.steps.push_back(ms);
+        }
+        if (!macro.name.isEmpty())
+            s.macros.push_back(macro);
+    }
+    // schedules: [ { "name", "action": ["unpin-all"], "at": "18:00", "days": [1, …],
+    //                "every_minutes", "enabled" } ]
+    for (const QJsonValue &v : o.value("schedules").toArray()) {
+        const QJsonObject j = v.toObject();
+        persistence::Schedule sch;
+        sch.name = j.value("name").toString();
+        const QJsonArray words = j.value("action").toArray();
+        sch.action = words.at(0).toString();
+        for (int i = 1; i < words.size(); ++i)
+            sch.args << words.at(i).toString();
+        sch.at = QTime::fromString(j.value("at").toString(), QStringLiteral("HH:mm"));
+        for (const QJsonValue &d : j.value("days").toArray())
+            sch.days << d.toInt();
+        sch.everyMinutes = j.value("every_minutes").toInt(0);
+        sch.enabled = j.value("enabled").toBool(true);
+        if (!sch.action.isEmpty())
+            s.schedules.push_back(sch);
+    }
+    // triggers: [ { "name", "when": "window-opened", "process"?, "action": [...], "enabled" } ]
+    for (const QJsonValue &v : o.value("triggers").toArray()) {
+        const QJsonObject j = v.toObject();
+        persistence::Trigger t;
+        t.name = j.value("name").toString();
+        t.event = j.value("when").toString();
+        t.process = j.value("process").toString();
+        const QJsonArray words = j.value("action").toArray();
+        t.action = words.at(0).toString();
+        for (int i = 1; i < words.size(); ++i)
+            t.args << words.at(i).toString();
+        t.enabled = j.value("enabled").toBool(true);
+        if (!t.event.isEmpty() && !t.action.isEmpty())
+            s.triggers.push_back(t);
+    }
+    // pin_slots: { "1": { "process_name": "vlc.exe", "title": "VLC" }, … }
+    const QJsonObject pinSlots = o.value("pin_slots").toObject();
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        const QJsonObject j = pinSlots.value(QString::number(i + 1)).toObject();
+        s.pinSlots[i].processName = j.value("process_name").toString();
+        s.pinSlots[i].title = j.value("title").toString();
+    }
     return s;
 }
 
@@ -52,46 +239,654 @@ QJsonObject writeSettings(const persistence::UserSettings &s)
 {
     QJsonObject o;
     o["enable_sound"]         = s.enableSound;
-    o["has_seen_tray_notice"] = s.hasSeenTrayNotice;
     o["start_with_windows"]   = s.startWithWindows;
+    o["autostart_delay"]      = s.autostartDelay;
+    o["autostart_args"]       = s.autostartArgs;
+    o["autostart_backend"]    = s.autostartTask ? QStringLiteral("task_scheduler")
+                                                : QStringLiteral("run_key");
+    o["autostart_elevated"]   = s.autostartElevated;
+    o["usage_stats"]          = s.usageStats;
+    o["audit_log"]            = s.auditLog;
+    if (!s.auditLogPath.isEmpty())
+        o["audit_log_path"]   = s.auditLogPath;
+    o["event_log"]            = s.eventLog;
+    o["keep_on_top"]          = s.keepOnTop;
+    o["animate_opacity"]      = s.animateOpacity;
+    o["opacity_fade_ms"]      = s.fadeMs;
+    if (!s.language.isEmpty())
+        o["language"]         = s.language;
+    o["tray_icon_theme"]      = s.trayIconTheme;
+    o["pin_feedback"]         = s.pinFeedback;
+    o["backdrop"]             = s.backdrop;
+    if (!s.noLayeringApps.isEmpty())
+        o["no_layering_apps"] = QJsonArray::fromStringList(s.noLayeringApps);
+    o["osd"]                  = s.osd;
+    o["osd_position"]         = s.osdPosition;
+    o["osd_duration_ms"]      = s.osdDurationMs;
+    o["temporary_pin_seconds"] = s.tempPinSeconds;
+    o["pin_opacity_percent"]  = s.pinOpacityPercent;
+    o["idle_unpin_minutes"]   = s.idleUnpinMinutes;
+    o["kiosk"]                = s.kiosk;
+    if (!s.kioskPath.isEmpty())
+        o["kiosk_path"]       = s.kioskPath;
+    o["coexistence"]          = s.coexistence;
+    o["topmost_lost"]         = s.topmostLost;
+    o["corner_badge"]         = s.cornerBadge;
+    o["badge_click_unpin"]    = s.badgeClickUnpin;
+    o["pin_owned_windows"]    = s.pinOwnedWindows;
+    o["uia_matching"]         = s.uiaMatching;
+    o["remote_no_opacity"]    = s.remoteNoOpacity;
+    if (!s.titleMarker.isEmpty())
+        o["title_marker"]     = s.titleMarker;
     o["shortcuts"]            = writeShortcuts(s.shortcuts);
+    if (!s.protectedChoices.isEmpty()) {
+        QJsonObject choices;
+        for (auto it = s.protectedChoices.begin(); it != s.protectedChoices.end(); ++it)
+            choices[it.key()] = it.value();
+        o["protected_process_choices"] = choices;
+    }
+    if (!s.compactRules.isEmpty()) {
+        QJsonObject rules;
+        for (auto it = s.compactRules.begin(); it != s.compactRules.end(); ++it) {
+            QJsonObject r;
+            r["width"]  = it->size.width();
+            r["height"] = it->size.height();
+            r["corner"] = it->corner;
+            rules[it.key()] = r;
+        }
+        o["compact_rules"] = rules;
+    }
+    if (!s.onboarding.isEmpty()) {
+        QJsonObject onboarding;
+        for (auto it = s.onboarding.begin(); it != s.onboarding.end(); ++it)
+            onboarding[it.key()] = it.value();
+        o["onboarding"] = onboarding;
+    }
+    if (!s.appCompat.isEmpty()) {
+        QJsonObject compat;
+        for (auto it = s.appCompat.begin(); it != s.appCompat.end(); ++it) {
+            QJsonObject c;
+            c["no_layering"] = it->noLayering;
+            if (!it->enforcement.isEmpty())
+                c["enforcement"] = it->enforcement;
+            if (it->reassertAfterMs > 0)
+                c["reassert_after_ms"] = it->reassertAfterMs;
+            compat[it.key()] = c;
+        }
+        o["app_compat"] = compat;
+    }
+    if (!s.macros.isEmpty()) {
+        QJsonArray macros;
+        for (const persistence::Macro &macro : s.macros) {
+            QJsonArray steps;
+            for (const persistence::MacroStep &ms : macro.steps)
+                steps.append(QJsonArray::fromStringList(QStringList{ms.action} + ms.args));
+            QJsonObject m;
+            m["name"] = macro.name;
+            if (!macro.shortcut.isEmpty())
+                m["shortcut"] = macro.shortcut;
+            m["steps"] = steps;
+            macros.append(m);
+        }
+        o["macros"] = macros;
+    }
+    if (!s.schedules.isEmpty()) {
+        QJsonArray schedules;
+        for (const persistence::Schedule &sch : s.schedules) {
+            QJsonObject j;
+            j["name"] = sch.name;
+            j["action"] = QJsonArray::fromStringList(QStringList{sch.action} + sch.args);
+            if (sch.everyMinutes > 0) {
+                j["every_minutes"] = sch.everyMinutes;
+            } else {
+                j["at"] = sch.at.toString(QStringLiteral("HH:mm"));
+                if (!sch.days.isEmpty()) {
+                    QJsonArray days;
+                    for (int d : sch.days)
+                        days.append(d);
+                    j["days"] = days;
+                }
+            }
+            j["enabled"] = sch.enabled;
+            schedules.append(j);
+        }
+        o["schedules"] = schedules;
+    }
+    if (!s.triggers.isEmpty()) {
+        QJsonArray triggers;
+        for (const persistence::Trigger &t : s.triggers) {
+            QJsonObject j;
+            j["name"] = t.name;
+            j["when"] = t.event;
+            if (!t.process.isEmpty())
+                j["process"] = t.process;
+            j["action"] = QJsonArray::fromStringList(QStringList{t.action} + t.args);
+            j["enabled"] = t.enabled;
+            triggers.append(j);
+        }
+        o["triggers"] = triggers;
+    }
+    QJsonObject pinSlots;
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        if (s.pinSlots[i].processName.isEmpty())
+            continue;
+        QJsonObject j;
+        j["process_name"] = s.pinSlots[i].processName;
+        j["title"] = s.pinSlots[i].title;
+        pinSlots[QString::number(i + 1)] = j;
+    }
+    if (!pinSlots.isEmpty())
+        o["pin_slots"] = pinSlots;
     return o;
 }
 
+QJsonArray writeRect(const QRect &r)
+{
+    return QJsonArray{r.x(), r.y(), r.width(), r.height()};
+}
+
+QRect readRect(const QJsonValue &v)
+{
+    const QJsonArray a = v.toArray();
+    if (a.size() != 4)
+        return QRect();
+    return QRect(a[0].toInt(), a[1].toInt(), a[2].toInt(), a[3].toInt());
+}
+
+// snapshots: object keyed by name -> [ { process_name, path, title, rect, ... } ]
+QVector<persistence::Snapshot> readSnapshots(const QJsonObject &o)
+{
+    QVector<persistence::Snapshot> out;
+    for (auto it = o.begin(); it != o.end(); ++it) {
+        persistence::Snapshot snap;
+        snap.name = it.key();
+        for (const QJsonValue &v : it.value().toArray()) {
+            const QJsonObject e = v.toObject();
+            persistence::SnapshotEntry entry;
+            entry.processName = e.value("process_name").toString();
+            entry.path        = e.value("path").toString();
+            entry.commandLine = e.value("command_line").toString();
+            entry.title       = e.value("title").toString();
+            entry.rect        = readRect(e.value("rect"));
+            entry.opacity     = e.value("opacity").toInt(255);
+            entry.group       = e.value("group").toString();
+            if (!entry.processName.isEmpty())
+                snap.entries.push_back(entry);
+        }
+        out.push_back(snap);
+    }
+    return out;
+}
+
+QJsonObject writeSnapshots(const QVector<persistence::Snapshot> &snapshots)
+{
+    QJsonObject o;
+    for (const persistence::Snapshot &snap : snapshots) {
+        QJsonArray entries;
+        for (const persistence::SnapshotEntry &entry : snap.entries) {
+            QJsonObject e;
+            e["process_name"] = entry.processName;
+            e["path"]         = entry.path;
+            if (!entry.commandLine.isEmpty())
+                e["command_line"] = entry.commandLine;
+            e["title"]        = entry.title;
+            e["rect"]         = writeRect(entry.rect);
+            e["opacity"]      = entry.opacity;
+            if (!entry.group.isEmpty())
+                e["group"]    = entry.group;
+            entries.append(e);
+        }
+        o[snap.name] = entries;
+    }
+    return o;
+}
+
+// pinned.json is the state, always: the file users edit by hand, sync tools
+// replace and support asks for.
+QByteArray readState()
+{
+    QFile f(dataFile("pinned.json"));
+    return f.open(QIODevice::ReadOnly) ? f.readAll() : QByteArray();
+}
+
+bool writeState(const QByteArray &doc)
+{
+    QDir().mkpath(persistence::dataDir());
+    // QSaveFile writes to a temp file then atomically renames — same crash
+    // safety the Rust version got from its tmp+rename dance.
+    QSaveFile f(dataFile("pinned.json"));
+    if (!f.open(QIODevice::WriteOnly))
+        return false;
+    f.write(doc);
+    return f.commit();
+}
+
+// pinned.db — only the pin history, which would bloat pinned.json. Without
+// Qt's SQLite driver history is simply off; the state never depends on it.
+// SQLite isn't safe on a network share, so with the data folder on one the
+// history stays in the local folder.
+class HistoryDb
+{
+public:
+    ~HistoryDb()
+    {
+        // Let go of pinned.db, e.g. before the data folder moves.
+        QSqlDatabase::database(QLatin1String(kConnection), false).close();
+        QSqlDatabase::removeDatabase(QLatin1String(kConnection));
+    }
+
+    bool open()
+    {
+        if (!QSqlDatabase::isDriverAvailable(QStringLiteral("QSQLITE")))
+            return false;
+        QDir().mkpath(persistence::dataDir());
+        QSqlDatabase db = QSqlDatabase::addDatabase(QStringLiteral("QSQLITE"), kConnection);
+        db.setDatabaseName(file());
+        if (!db.open())
+            return false;
+        QSqlQuery q(db);
+        return q.exec(QStringLiteral("CREATE TABLE IF NOT EXISTS history ("
+                                     "at TEXT NOT NULL, event TEXT NOT NULL, "
+                                     "process_name TEXT, title TEXT)"));
+    }
+
+    void add(const QString &event, const QString &process, const QString &title)
+    {
+        QSqlQuery q(db());
+        q.prepare(QStringLiteral("INSERT INTO history (at, event, process_name, title) "
+                                 "VALUES (?, ?, ?, ?)"));
+        q.addBindValue(QDateTime::currentDateTime().toString(Qt::ISODate));
+        q.addBindValue(event);
+        q.addBindValue(process);
+        q.addBindValue(title);
+        q.exec();
+        // Keep the table from growing forever.
+        q.exec(QStringLiteral("DELETE FROM history WHERE rowid <= "
+                              "(SELECT MAX(rowid) FROM history) - %1").arg(kMaxHistory));
+    }
+
+    QVector<persistence::HistoryEntry> list(int limit)
+    {
+        QVector<persistence::HistoryEntry> out;
+        QSqlQuery q(db());
+        q.prepare(QStringLiteral("SELECT at, event, process_name, title FROM history "
+                                 "ORDER BY rowid DESC LIMIT ?"));
+        q.addBindValue(limit);
+        if (!q.exec())
+            return out;
+        while (q.next()) {
+            out.push_back({QDateTime::fromString(q.value(0).toString(), Qt::ISODate),
+                           q.value(1).toString(), q.value(2).toString(),
+                           q.value(3).toString()});
+        }
+        return out;
+    }
+
+    void clear()
+    {
+        QSqlQuery q(db());
+        q.exec(QStringLiteral("DELETE FROM history"));
+    }
+
+private:
+    static QString file()
+    {
+        if (!platform::isNetworkPath(persistence::dataDir()))
+            return dataFile("pinned.db");
+        QDir().mkpath(persistence::localDataDir());
+        return QDir(persistence::localDataDir()).filePath(QStringLiteral("pinned.db"));
+    }
+
+    static constexpr const char *kConnection = "pinit_history";
+    static constexpr int kMaxHistory = 5000;
+
+    static QSqlDatabase db() { return QSqlDatabase::database(QLatin1String(kConnection)); }
+};
+
+std::unique_ptr<HistoryDb> g_history;
+bool g_historyTried = false;
+
+// Opened on first use; null if SQLite isn't available.
+HistoryDb *historyDb()
+{
+    if (!g_historyTried) {
+        g_historyTried = true;
+        auto db = std::make_unique<HistoryDb>();
+        if (db->open())
+            g_history = std::move(db);
+        else
+            qWarning("SQLite unavailable; pin history is off");
+    }
+    return g_history.get();
+}
+
+void closeHistoryDb()
+{
+    g_history.reset();
+    g_historyTried = false;
+}
+
+bool sameDir(const QString &a, const QString &b)
+{
+    return QDir::cleanPath(a).compare(QDir::cleanPath(b), Qt::CaseInsensitive) == 0;
+}
+
+// What a data folder holds, relative to it: the settings, the history, usage
+// stats and backups.
+QStringList dataFiles(const QString &dir)
+{
+    QStringList files;
+    for (const char *name : {"pinned.db", "pinned.json", "usage.json"}) {
+        if (QFileInfo::exists(QDir(dir).filePath(QLatin1String(name))))
+            files << QLatin1String(name);
+    }
+    const QDir backups(QDir(dir).filePath(QStringLiteral("backups")));
+    for (const QString &f : backups.entryList({QStringLiteral("pinned-*.json")}, QDir::Files))
+        files << QStringLiteral("backups/") + f;
+    return files;
+}
+
+// A cursor over JSON text that QJsonDocument rejected, for salvage().
+struct Scanner {
+    const QByteArray &s;
+    int pos = 0;
+
+    bool atEnd() const { return pos >= s.size(); }
+    char peek() const { return s.at(pos); }
+
+    void skipSpace()
+    {
+        while (!atEnd() && (peek() == ' ' || peek() == '\n' || peek() == '\r' || peek() == '\t'))
+            ++pos;
+    }
+
+    // Index just past the string starting at `from` (on its opening quote).
+    int stringEnd(int from) const
+    {
+        int i = from + 1;
+        while (i < s.size() && s.at(i) != '"')
+            i += s.at(i) == '\\' ? 2 : 1;
+        return qMin(i + 1, int(s.size()));
+    }
+
+    // Where the value starting at pos ends: the comma or closing bracket after
+    // it at this nesting level, or the end of the text if it never closes.
+    int valueEnd() const
+    {
+        int depth = 0;
+        for (int i = pos; i < s.size(); ++i) {
+            const char c = s.at(i);
+            if (c == '"') {
+                i = stringEnd(i) - 1;
+            } else if (c == '{' || c == '[') {
+                ++depth;
+            } else if (c == '}' || c == ']') {
+                if (depth-- == 0)
+                    return i;
+            } else if (c == ',' && depth == 0) {
+                return i;
+            }
+        }
+        return s.size();
+    }
+};
+
+// One JSON value on its own, or Undefined if it doesn't parse.
+QJsonValue parseValue(const QByteArray &text)
+{
+    const QJsonDocument doc = QJsonDocument::fromJson('[' + text + ']');
+    return doc.isArray() && doc.array().size() == 1 ? doc.array().first()
+                                                    : QJsonValue(QJsonValue::Undefined);
+}
+
+QString memberPath(const QString &path, const QString &key)
+{
+    return path.isEmpty() ? key : path + QLatin1Char('.') + key;
+}
+
+// The object starting at sc.pos (on its '{'), member by member.
+QJsonObject salvageObject(Scanner &sc, const QString &path, QStringList *lost)
+{
+    QJsonObject out;
+    ++sc.pos;
+    while (true) {
+        sc.skipSpace();
+        if (sc.atEnd())
+            break;
+        if (sc.peek() == '}') {
+            ++sc.pos;
+            break;
+        }
+        if (sc.peek() == ',') {
+            ++sc.pos;
+            continue;
+        }
+        if (sc.peek() != '"') {   // not a member at all; skip past it
+            lost->push_back(memberPath(path, QStringLiteral("(unnamed)")));
+            sc.pos = qMax(sc.valueEnd(), sc.pos + 1);
+            continue;
+        }
+
+        const int keyEnd = sc.stringEnd(sc.pos);
+        const QJsonValue name = parseValue(sc.s.mid(sc.pos, keyEnd - sc.pos));
+        sc.pos = keyEnd;
+        if (!name.isString()) {   // cut off mid-name
+            lost->push_back(memberPath(path, QStringLiteral("(unnamed)")));
+            continue;
+        }
+        const QString key = name.toString();
+        sc.skipSpace();
+        if (sc.atEnd() || sc.peek() != ':') {
+            lost->push_back(memberPath(path, key));
+            continue;
+        }
+        ++sc.pos;
+        sc.skipSpace();
+
+        const int start = sc.pos;
+        const int end = sc.valueEnd();
+        const QJsonValue v = parseValue(sc.s.mid(start, end - start));
+        if (!v.isUndefined()) {
+            out.insert(key, v);
+        } else if (!sc.atEnd() && sc.peek() == '{') {
+            out.insert(key, salvageObject(sc, memberPath(path, key), lost));
+        } else {
+            lost->push_back(memberPath(path, key));
+        }
+        sc.pos = qMax(sc.pos, end);
+    }
+    return out;
+}
+
+constexpr int kMaxBackups = 10;
+const char *const kBackupStamp = "yyyyMMdd-HHmmss";
+
+QDir backupDir()
+{
+    return QDir(QDir(persistence::dataDir()).filePath(QStringLiteral("backups")));
+}
+
+// pinned-*.json in the backup folder, newest first (the stamps sort by name).
+QStringList backupFiles()
+{
+    return backupDir().entryList({QStringLiteral("pinned-*.json")}, QDir::Files,
+                                 QDir::Name | QDir::Reversed);
+}
+
 } // namespace
 
 namespace persistence {
 
+QString expandVariables(QString path)
+{
+    static const QRegularExpression var(QStringLiteral("%([^%]+)%"));
+    QRegularExpressionMatch m;
+    while ((m = var.match(path)).hasMatch())
+        path.replace(m.capturedStart(), m.capturedLength(), qEnvironmentVariable(
+                         qUtf8Printable(m.captured(1))));
+    return QDir::cleanPath(QDir::fromNativeSeparators(path));
+}
+
+bool isPortable()
+{
+    static const bool portable =
+        QCoreApplication::arguments().contains(QStringLiteral("--portable"))
+        || QFile::exists(QDir(QCoreApplication::applicationDirPath())
+                             .filePath(QStringLiteral("portable.txt")));
+    return portable;
+}
+
+QString dataDir()
+{
+    if (isPortable())
+        return QDir(QCoreApplication::applicationDirPath()).filePath(QStringLiteral("data"));
+    if (g_dataDir.isEmpty()) {
+        // PINIT_DATA_DIR wins over the chosen folder, so tests and deployment
+        // scripts can point PinIt somewhere without touching the user's choice.
+        QString chosen = qEnvironmentVariable("PINIT_DATA_DIR");
+        if (chosen.isEmpty()) {
+            chosen = QSettings(QString::fromLatin1(kLocationKey), QSettings::NativeFormat)
+                         .value(QLatin1String(kLocationValue))
+                         .toString();
+            // HKCU roams, but the folder it names may only exist on the PC
+            // it was chosen on (a second disk, a share that isn't mapped).
+            const QString dir = expandVariables(chosen);
+            if (!chosen.isEmpty() && !QDir(dir).exists() && !QDir().mkpath(dir)) {
+                qWarning("Data folder %s isn't available; using the local one",
+                         qUtf8Printable(QDir::toNativeSeparators(dir)));
+                g_unreachable = dir;
+                chosen.clear();
+            }
+        }
+        g_dataDir = chosen.isEmpty() ? localDataDir() : expandVariables(chosen);
+    }
+    return g_dataDir;
+}
+
+QString localDataDir()
+{
+    // dirs::data_local_dir() in the Rust app == %LOCALAPPDATA%.
+    QString base = qEnvironmentVariable("LOCALAPPDATA");
+    if (base.isEmpty())
+        base = QDir::homePath();
+    return QDir(base).filePath(QStringLiteral("PinIt"));
+}
+
+QString roamingDataDir()
+{
+    return expandVariables(QString::fromLatin1(kRoaming));
+}
+
+QString unreachableDataDir()
+{
+    dataDir();
+    return g_unreachable;
+}
+
+bool setConfigDirectory(const QString &path, QString *error)
+{
+    const auto tr = [](const char *text) {
+        return QCoreApplication::translate("persistence", text);
+    };
+    QStringList copied;
+    const auto fail = [&](const QString &why) {
+        for (const QString &f : copied)   // half a copy would be adopted next time
+            QFile::remove(f);
+        *error = why;
+        return false;
+    };
+    if (isPortable())
+        return fail(tr("portable mode keeps its data next to PinIt.exe"));
+    const QString to = path.isEmpty() ? localDataDir() : expandVariables(path);
+    const QString shown = QDir::toNativeSeparators(to);
+    if (!QDir::isAbsolutePath(to))
+        return fail(tr("%1 isn't a full path").arg(shown));
+    const QString from = dataDir();
+    if (sameDir(from, to))
+        return true;
+
+    if (!QDir().mkpath(to))
+        return fail(tr("couldn't create %1").arg(shown));
+    QTemporaryFile probe(QDir(to).filePath(QStringLiteral("probe-XXXXXX")));
+    if (!probe.open())
+        return fail(tr("can't write to %1").arg(shown));
+    probe.close();
+
+    closeHistoryDb();   // releases pinned.db so it can be copied
+    // A folder that already holds PinIt data (the roaming copy another PC
+    // left) is used as it is, never overwritten.
+    const bool adopt = QFileInfo::exists(QDir(to).filePath(QStringLiteral("pinned.json")));
+    if (!adopt) {
+        QDir(to).mkpath(QStringLiteral("backups"));
+        for (const QString &f : dataFiles(from)) {
+            if (f == QLatin1String("pinned.db") && platform::isNetworkPath(to))
+                continue;   // the history stays local (see HistoryDb)
+            const QString target = QDir(to).filePath(f);
+            QFile::remove(target);
+            if (!QFile::copy(QDir(from).filePath(f), target))
+                return fail(tr("couldn't copy %1 there").arg(QDir::toNativeSeparators(f)));
+            copied << target;
+        }
+    }
+
+    QSettings location(QString::fromLatin1(kLocationKey), QSettings::NativeFormat);
+    if (sameDir(to, localDataDir()))
+        location.remove(QLatin1String(kLocationValue));
+    else if (sameDir(to, roamingDataDir()))
+        location.setValue(QLatin1String(kLocationValue), QString::fromLatin1(kRoaming));
+    else
+        location.setValue(QLatin1String(kLocationValue), QDir::toNativeSeparators(to));
+    location.sync();
+    if (location.status() != QSettings::NoError)
+        return fail(tr("couldn't record the new folder in the registry"));
+
+    g_dataDir = to;
+    g_unreachable.clear();
+    qInfo("Data folder moved from %s to %s (%s)", qUtf8Printable(QDir::toNativeSeparators(from)),
+          qUtf8Printable(QDir::toNativeSeparators(to)),
+          adopt ? "using the data already there" : "copied");
+    return true;
+}
+
 SavedState load()
 {
     SavedState state;
 
-    const QString path = savePath();
-    QFile f(path);
-    if (!f.open(QIODevice::ReadOnly))
-        return state;   // missing file — defaults
-
-    const QByteArray data = f.readAll();
-    f.close();
+    const QByteArray data = readState();
+    g_lastSeen = data;
     if (data.trimmed().isEmpty())
-        return state;   // empty file — defaults
+        return state;   // nothing saved yet — defaults
 
     QJsonParseError err;
     const QJsonDocument doc = QJsonDocument::fromJson(data, &err);
+    QJsonObject root = doc.object();
     if (err.error != QJsonParseError::NoError || !doc.isObject()) {
-        // The file exists but is unreadable. Back it up before any later save
-        // overwrites it with defaults, so the user's data isn't silently lost
-        // (and is available for manual recovery).
-        qWarning("pinned.json is corrupt (%s); backing it up to pinned.json.corrupt",
-                 qUtf8Printable(err.errorString()));
-        const QString backup = path + QStringLiteral(".corrupt");
-        QFile::remove(backup);
-        QFile::copy(path, backup);
-        return state;
+        // The saved state is damaged. Keep what still reads, and copy the
+        // original aside before any later save replaces it, so nothing is
+        // silently lost (and the rest can be fixed by hand).
+        QStringList lost;
+        root = salvage(data, &lost);
+        if (data != g_salvagedFrom) {
+            g_salvagedFrom = data;
+            g_recovery = Recovery();
+            g_recovery.error = err.error != QJsonParseError::NoError
+                                   ? QStringLiteral("%1 at character %2")
+                                         .arg(err.errorString())
+                                         .arg(err.offset)
+                                   : QStringLiteral("not a JSON object");
+            g_recovery.copyPath = dataFile("pinned.json.corrupt");
+            g_recovery.lost = lost;
+            g_recoveryPending = true;
+            qWarning("Saved state is corrupt (%s); backing it up to pinned.json.corrupt",
+                     qUtf8Printable(g_recovery.error));
+            QSaveFile backup(g_recovery.copyPath);
+            if (backup.open(QIODevice::WriteOnly)) {
+                backup.write(data);
+                backup.commit();
+            }
+        }
     }
 
-    const QJsonObject root = doc.object();
-
     // pins: object keyed by "process:hwnd" -> { process_name, title, opacity }
     const QJsonObject pins = root.value("pins").toObject();
     for (auto it = pins.begin(); it != pins.end(); ++it) {
@@ -100,19 +895,67 @@ SavedState load()
         sp.processName = p.value("process_name").toString();
         sp.title       = p.value("title").toString();
         sp.opacity     = p.value("opacity").toInt(255);
+        sp.group       = p.value("group").toString();
+        sp.keepWhenIdle = p.value("keep_when_idle").toBool(false);
+        sp.followMonitor = p.value("follow_monitor").toBool(false);
+        sp.dockEdge    = p.value("dock_edge").toString();
+        sp.restoreRect = readRect(p.value("restore_rect"));
+        sp.autoRestart = p.value("auto_restart").toBool(false);
+        sp.enforcement = p.value("enforcement").toString(QStringLiteral("normal"));
+        sp.launchPath  = p.value("launch_path").toString();
+        for (const QJsonValue &a : p.value("launch_args").toArray())
+            sp.launchArgs << a.toString();
+        sp.processPath = p.value("process_path").toString();
+        sp.commandLine = p.value("command_line").toString();
+        sp.titlePattern = p.value("title_pattern").toString();
+        sp.automationId = p.value("automation_id").toString();
         if (!sp.processName.isEmpty())
             state.pins.push_back(sp);
     }
 
-    state.settings = readSettings(root.value("settings").toObject());
+    const QJsonObject settings = root.value("settings").toObject();
+    state.settings  = readSettings(policy::apply(settings));
+    state.snapshots = readSnapshots(root.value("snapshots").toObject());
+    for (const QJsonValue &v : root.value("hidden").toArray()) {
+        const QJsonObject o = v.toObject();
+        const HiddenWindow h{qint64(o.value("hwnd").toDouble()), qint64(o.value("pid").toDouble())};
+        if (h.hwnd != 0 && h.pid != 0)
+            state.hidden.push_back(h);
+    }
+    // Saved before onboarding existed (those builds always wrote
+    // has_seen_tray_notice), or with pins: not a first run, so nothing to show.
+    if (!settings.contains("onboarding")
+        && (settings.contains("has_seen_tray_notice") || !state.pins.isEmpty())) {
+        for (onboarding::Step step : onboarding::steps())
+            onboarding::advance(state.settings.onboarding, step);
+    }
+    if (data == g_salvagedFrom)
+        g_recovery.pinsKept = state.pins.size();
     return state;
 }
 
-void save(const SavedState &state)
+bool takeRecovery(Recovery *out)
 {
-    const QString path = savePath();
-    QDir().mkpath(QFileInfo(path).absolutePath());
+    if (!g_recoveryPending)
+        return false;
+    g_recoveryPending = false;
+    *out = g_recovery;
+    return true;
+}
+
+QJsonObject salvage(const QByteArray &json, QStringList *lost)
+{
+    Scanner sc{json};
+    sc.skipSpace();
+    if (sc.atEnd() || sc.peek() != '{') {
+        lost->push_back(QStringLiteral("(document)"));
+        return QJsonObject();
+    }
+    return salvageObject(sc, QString(), lost);
+}
 
+void save(const SavedState &state)
+{
     QJsonObject pins;
     for (int i = 0; i < state.pins.size(); ++i) {
         const SavedPin &sp = state.pins[i];
@@ -120,21 +963,163 @@ void save(const SavedState &state)
         p["process_name"] = sp.processName;
         p["title"]        = sp.title;
         p["opacity"]      = sp.opacity;
+        if (!sp.group.isEmpty())
+            p["group"]    = sp.group;   // optional: absent in older files
+        if (sp.keepWhenIdle)
+            p["keep_when_idle"] = true;
+        if (sp.followMonitor)
+            p["follow_monitor"] = true;
+        if (!sp.dockEdge.isEmpty())
+            p["dock_edge"] = sp.dockEdge;
+        if (!sp.restoreRect.isEmpty())
+            p["restore_rect"] = writeRect(sp.restoreRect);
+        if (sp.autoRestart)
+            p["auto_restart"] = true;
+        if (sp.enforcement != QLatin1String("normal"))
+            p["enforcement"] = sp.enforcement;
+        if (!sp.launchPath.isEmpty()) {
+            p["launch_path"] = sp.launchPath;
+            p["launch_args"] = QJsonArray::fromStringList(sp.launchArgs);
+        }
+        if (!sp.processPath.isEmpty())
+            p["process_path"] = sp.processPath;
+        if (!sp.commandLine.isEmpty())
+            p["command_line"] = sp.commandLine;
+        if (!sp.titlePattern.isEmpty())
+            p["title_pattern"] = sp.titlePattern;
+        if (!sp.automationId.isEmpty())
+            p["automation_id"] = sp.automationId;
         // Key matches the Rust format: "<process>:<index>" keeps it unique.
         pins[QStringLiteral("%1:%2").arg(sp.processName).arg(i)] = p;
     }
 
+    // Values forced by policy aren't the user's: keep what they had saved, so
+    // their own choices come back if the policy is lifted.
+    QJsonObject settings = writeSettings(state.settings);
+    const QJsonObject forced = policy::settings();
+    if (!forced.isEmpty()) {
+        const QJsonObject stored =
+            QJsonDocument::fromJson(readState()).object().value("settings").toObject();
+        const auto keep = [](QJsonObject &into, const QJsonObject &from, const QString &key) {
+            if (from.contains(key))
+                into.insert(key, from.value(key));
+            else
+                into.remove(key);
+        };
+        for (auto it = forced.begin(); it != forced.end(); ++it) {
+            if (!it.value().isObject()) {
+                keep(settings, stored, it.key());
+                continue;
+            }
+            QJsonObject inner = settings.value(it.key()).toObject();
+            const QJsonObject storedInner = stored.value(it.key()).toObject();
+            for (const QString &key : it.value().toObject().keys())
+                keep(inner, storedInner, key);
+            settings.insert(it.key(), inner);
+        }
+    }
+
     QJsonObject root;
     root["pins"]     = pins;
-    root["settings"] = writeSettings(state.settings);
+    root["settings"] = settings;
+    if (!state.snapshots.isEmpty())
+        root["snapshots"] = writeSnapshots(state.snapshots);
+    if (!state.hidden.isEmpty()) {
+        QJsonArray hidden;
+        for (const HiddenWindow &h : state.hidden)
+            hidden.append(QJsonObject{{"hwnd", double(h.hwnd)}, {"pid", double(h.pid)}});
+        root["hidden"] = hidden;
+    }
 
-    // QSaveFile writes to a temp file then atomically renames — same crash
-    // safety the Rust version got from its tmp+rename dance.
-    QSaveFile f(path);
-    if (f.open(QIODevice::WriteOnly)) {
-        f.write(QJsonDocument(root).toJson(QJsonDocument::Indented));
-        f.commit();
+    const QByteArray data = QJsonDocument(root).toJson(QJsonDocument::Indented);
+    if (writeState(data))
+        g_lastSeen = data;
+}
+
+QByteArray readRaw() { return readState(); }
+
+QJsonObject settingsToJson(const UserSettings &settings) { return writeSettings(settings); }
+
+QString filePath()   { return dataFile("pinned.json"); }
+bool    hasHistory() { return historyDb() != nullptr; }
+
+bool changedExternally()
+{
+    return readState() != g_lastSeen;
+}
+
+bool backup()
+{
+    const QByteArray data = readState();
+    if (data.trimmed().isEmpty())
+        return false;
+
+    QDir dir = backupDir();
+    dir.mkpath(QStringLiteral("."));
+    const QStringList files = backupFiles();
+    if (!files.isEmpty()) {
+        QFile newest(dir.filePath(files.first()));
+        if (newest.open(QIODevice::ReadOnly) && newest.readAll() == data)
+            return true;   // already backed up
+    }
+
+    const QString stamp = QDateTime::currentDateTime().toString(QLatin1String(kBackupStamp));
+    QSaveFile f(dir.filePath(QStringLiteral("pinned-%1.json").arg(stamp)));
+    if (!f.open(QIODevice::WriteOnly))
+        return false;
+    f.write(data);
+    if (!f.commit())
+        return false;
+
+    const QStringList all = backupFiles();
+    for (int i = kMaxBackups; i < all.size(); ++i)
+        dir.remove(all[i]);
+    return true;
+}
+
+QVector<Backup> listBackups()
+{
+    QVector<Backup> out;
+    for (const QString &file : backupFiles()) {
+        const QString id = file.mid(7, file.size() - 12);   // pinned-<id>.json
+        out.push_back({id, QDateTime::fromString(id, QLatin1String(kBackupStamp))});
     }
+    return out;
+}
+
+QByteArray readBackup(const QString &id)
+{
+    QFile f(backupDir().filePath(QStringLiteral("pinned-%1.json").arg(id)));
+    return f.open(QIODevice::ReadOnly) ? f.readAll() : QByteArray();
+}
+
+bool restoreBackup(const QString &id)
+{
+    const QByteArray data = readBackup(id);
+    if (!QJsonDocument::fromJson(data).isObject())
+        return false;
+
+    backup();   // so the restore itself can be undone
+    // g_lastSeen stays as it was, so changedExternally() reports the swap.
+    return writeState(data);
+}
+
+void recordHistory(const QString &event, const QString &processName, const QString &title)
+{
+    if (HistoryDb *db = historyDb())
+        db->add(event, processName, title);
+}
+
+QVector<HistoryEntry> history(int limit)
+{
+    HistoryDb *db = historyDb();
+    return db ? db->list(limit) : QVector<HistoryEntry>();
+}
+
+void clearHistory()
+{
+    if (HistoryDb *db = historyDb())
+        db->clear();
 }
 
 UserSettings loadSettings()
@@ -145,6 +1130,8 @@ UserSettings loadSettings()
 void saveSettings(const UserSettings &settings)
 {
     SavedState state = load();
+    if (audit::isEnabled())
+        audit::recordSettingChanges(writeSettings(state.settings), writeSettings(settings));
     state.settings = settings;
     save(state);
 }
@@ -156,4 +1143,18 @@ void savePins(const QVector<SavedPin> &pins)
     save(state);
 }
 
+void saveSnapshots(const QVector<Snapshot> &snapshots)
+{
+    SavedState state = load();
+    state.snapshots = snapshots;
+    save(state);
+}
+
+void saveHiddenWindows(const QVector<HiddenWindow> &hidden)
+{
+    SavedState state = load();
+    state.hidden = hidden;
+    save(state);
+}
+
 } // namespace persistence
@@ -1,14 +1,33 @@
 #pragma once
 //
-// persistence — load/save PinIt's state to %LOCALAPPDATA%\PinIt\pinned.json.
+// persistence — load/save PinIt's state in %LOCALAPPDATA%\PinIt.
 //
-// This is the SAME file and JSON schema the Tauri version used, so an existing
-// install's pins and settings carry straight over to this C++ build.
+// The state is pinned.json, which can be edited by hand. A short pin history
+// is kept beside it in pinned.db (SQLite); without Qt's SQLite driver there's
+// no history, and nothing else changes.
 //
+// In portable mode (a portable.txt beside PinIt.exe, or --portable) everything
+// lives in a data\ folder next to the exe instead, so PinIt can run from a USB
+// stick without leaving files on the PC. Otherwise the folder can be moved to
+// the roaming profile or anywhere else (setConfigDirectory); where it went is
+// kept in HKCU\Software\PinIt.
+//
+// Either way the state is the SAME JSON schema the Tauri version used, so an
+// existing install's pins and settings carry straight over to this C++ build.
+//
+#include <QByteArray>
+#include <QDateTime>
+#include <QJsonObject>
 #include <QString>
 #include <QHash>
+#include <QMap>
+#include <QRect>
+#include <QSize>
+#include <QStringList>
 #include <QVector>
 
+#include <array>
+
 namespace persistence {
 
 // One saved pin. opacity is stored as 8-bit alpha (0-255) to match the
@@ -17,32 +36,336 @@ struct SavedPin {
     QString processName;
     QString title;
     int     opacity = 255;   // alpha
+    QString group;           // window group name, empty if ungrouped
+    bool    keepWhenIdle = false;   // opted out of the idle auto-unpin
+    bool    followMonitor = false;  // moves to the monitor being worked on
+    QString dockEdge;               // auto-hides at this screen edge; empty if not
+    QRect   restoreRect;            // where a compact-size rule found it; empty if none
+    bool    autoRestart = false;    // relaunch the app if it exits (see PinManager)
+    QString launchPath;             // exe (and arguments) to relaunch it with
+    QStringList launchArgs;
+    QString enforcement = QStringLiteral("normal");   // see PinManager::setEnforcementPolicy
+    QString processPath;            // full exe path; restore skips other installs
+    QString commandLine;
+    QString titlePattern;           // restore needs a title containing this
+    QString automationId;           // UI Automation id, see PinManager::setUiaMatching
+};
+
+// Per-app rule: pinning a window of this app shrinks it to size (logical
+// pixels) in one corner of its monitor's work area, e.g. a mini player.
+// Unpinning puts it back where it was.
+struct CompactRule {
+    QSize   size;
+    QString corner = QStringLiteral("bottom-right");   // "top-left", "top-right", …
+};
+
+// Per-app workarounds (see compat.h), keyed like CompactRule.
+struct AppCompat {
+    bool    noLayering = false;    // never made layered: its pins stay opaque
+    QString enforcement;           // its pins' enforcement policy; empty = "normal"
+    int     reassertAfterMs = 0;   // pin it again this long after pinning; 0 = don't
+};
+
+// A macro: actions (see actions.h) run in order, e.g. pin-app obs64.exe, then
+// set-opacity 80. shortcut is an optional global hotkey in the same syntax
+// as ShortcutConfig.
+struct MacroStep {
+    QString     action;   // an action id, or "wait" (args: milliseconds)
+    QStringList args;
+};
+
+struct Macro {
+    QString            name;
+    QString            shortcut;
+    QVector<MacroStep> steps;
+};
+
+inline bool operator==(const MacroStep &a, const MacroStep &b)
+{
+    return a.action == b.action && a.args == b.args;
+}
+inline bool operator==(const Macro &a, const Macro &b)
+{
+    return a.name == b.name && a.shortcut == b.shortcut && a.steps == b.steps;
+}
+
+// A scheduled action: run action(args) daily at `at` (on `days`, 1 = Monday;
+// empty = every day), or every everyMinutes minutes when that's set.
+struct Schedule {
+    QString      name;
+    QString      action;
+    QStringList  args;
+    QTime        at = QTime(9, 0);
+    QVector<int> days;
+    int          everyMinutes = 0;
+    bool         enabled = true;
+};
+
+inline bool operator==(const Schedule &a, const Schedule &b)
+{
+    return a.name == b.name && a.action == b.action && a.args == b.args && a.at == b.at
+        && a.days == b.days && a.everyMinutes == b.everyMinutes && a.enabled == b.enabled;
+}
+
+// A trigger: when `event` happens (see triggers::events()) — for window and
+// app events, to a window of `process` if that's set — run action(args).
+struct Trigger {
+    QString     name;
+    QString     event;
+    QString     process;
+    QString     action;
+    QStringList args;
+    bool        enabled = true;
+};
+
+inline bool operator==(const Trigger &a, const Trigger &b)
+{
+    return a.name == b.name && a.event == b.event && a.process == b.process
+        && a.action == b.action && a.args == b.args && a.enabled == b.enabled;
+}
+
+// One window in a saved layout snapshot: enough to find (or relaunch) the app
+// and put its window back where it was. rect is in virtual-screen coordinates,
+// so it also records which monitor the window was on.
+struct SnapshotEntry {
+    QString processName;
+    QString path;            // exe to launch if the app isn't running
+    QString commandLine;     // as it was started, for its arguments; may be empty
+    QString title;
+    QRect   rect;
+    int     opacity = 255;   // alpha, like SavedPin
+    QString group;
+};
+
+struct Snapshot {
+    QString                name;
+    QVector<SnapshotEntry> entries;
 };
 
 // Configurable global shortcuts, stored in Tauri's string syntax
 // (e.g. "super+ctrl+KeyT") so the file stays compatible.
+// Pin slots: a window remembered under a number (by process and title, so it
+// outlives restarts) and reached straight from a hotkey.
+constexpr int kPinSlots = 3;
+
+struct PinSlot {
+    QString processName;
+    QString title;
+};
+
 struct ShortcutConfig {
     QString togglePin    = QStringLiteral("super+ctrl+KeyT");
     QString opacityUp    = QStringLiteral("super+ctrl+Equal");
     QString opacityDown  = QStringLiteral("super+ctrl+Minus");
     QString toggleWindow = QStringLiteral("super+ctrl+KeyP");
+    QString pinUnderCursor = QStringLiteral("super+ctrl+shift+KeyT");
+    QString quickPanel     = QStringLiteral("super+ctrl+shift+KeyP");
+    QString hideAllPinned  = QStringLiteral("super+ctrl+KeyH");   // the "boss key"
+    QString pinTemporarily = QStringLiteral("super+ctrl+alt+KeyT");
+    QString kioskExit      = QStringLiteral("ctrl+alt+KeyK");   // only acts in kiosk mode
+    // The most recently pinned window, wherever it is: pin / unpin it, or
+    // bring it to the front.
+    QString toggleLastPinned = QStringLiteral("super+ctrl+KeyJ,KeyT");
+    QString focusLastPinned  = QStringLiteral("super+ctrl+KeyJ,KeyL");
+    // Pin the focused window and make it see-through (pin_opacity_percent).
+    QString pinWithOpacity   = QStringLiteral("super+ctrl+KeyJ,KeyO");
+    QString opacitySlider    = QStringLiteral("super+ctrl+KeyJ,KeyS");
+    // slot[i] brings pin slot i + 1's window to the front, or toggles its pin
+    // when it's already there; assignSlot[i] puts the foreground window in it.
+    // Chords, since Win + digit belongs to the taskbar: Win+Ctrl+J, then 1.
+    std::array<QString, kPinSlots> slot = {QStringLiteral("super+ctrl+KeyJ,Digit1"),
+                                           QStringLiteral("super+ctrl+KeyJ,Digit2"),
+                                           QStringLiteral("super+ctrl+KeyJ,Digit3")};
+    std::array<QString, kPinSlots> assignSlot = {QStringLiteral("super+ctrl+shift+KeyJ,Digit1"),
+                                                 QStringLiteral("super+ctrl+shift+KeyJ,Digit2"),
+                                                 QStringLiteral("super+ctrl+shift+KeyJ,Digit3")};
+    // Pin/unpin key behaviour: it toggles (default), or also pins the
+    // foreground window only while held down, if held; a tap still toggles.
+    bool    holdToPreview = false;
+    // Match shortcuts with a low-level keyboard hook instead of RegisterHotKey
+    // (fallback for combos Windows refuses to register).
+    bool    keyboardHook = false;
 };
 
+inline bool operator==(const ShortcutConfig &a, const ShortcutConfig &b)
+{
+    return a.togglePin == b.togglePin && a.opacityUp == b.opacityUp
+        && a.opacityDown == b.opacityDown && a.toggleWindow == b.toggleWindow
+        && a.pinUnderCursor == b.pinUnderCursor && a.quickPanel == b.quickPanel
+        && a.hideAllPinned == b.hideAllPinned && a.pinTemporarily == b.pinTemporarily
+        && a.kioskExit == b.kioskExit && a.toggleLastPinned == b.toggleLastPinned
+        && a.focusLastPinned == b.focusLastPinned && a.pinWithOpacity == b.pinWithOpacity
+        && a.opacitySlider == b.opacitySlider
+        && a.slot == b.slot && a.assignSlot == b.assignSlot
+        && a.holdToPreview == b.holdToPreview && a.keyboardHook == b.keyboardHook;
+}
+inline bool operator!=(const ShortcutConfig &a, const ShortcutConfig &b) { return !(a == b); }
+
+// Every action's binding, in the order GlobalHotkeyManager registers them
+// (macro hotkeys come after these).
+inline QStringList bindings(const ShortcutConfig &c)
+{
+    QStringList all = {c.togglePin, c.opacityUp, c.opacityDown, c.toggleWindow,
+                       c.pinUnderCursor, c.quickPanel, c.hideAllPinned, c.pinTemporarily,
+                       c.kioskExit, c.toggleLastPinned, c.focusLastPinned, c.pinWithOpacity,
+                       c.opacitySlider};
+    for (int i = 0; i < kPinSlots; ++i)
+        all << c.slot[i] << c.assignSlot[i];
+    return all;
+}
+
 struct UserSettings {
     bool           enableSound      = true;
-    bool           hasSeenTrayNotice = false;
+    // First-run steps seen, by name: "done" or "skipped" (see onboarding.h).
+    // Replaces has_seen_tray_notice, which is read as tray_notice done.
+    QMap<QString, QString> onboarding;
     bool           startWithWindows = false;
+    int            autostartDelay   = 0;       // seconds after login; >0 uses Task Scheduler
+    QString        autostartArgs    = QStringLiteral("--minimized");
+    bool           autostartTask    = false;   // Task Scheduler instead of the Run key
+    bool           autostartElevated = false;  // start as administrator (implies a task)
+    bool           usageStats       = false;   // opt-in local usage stats (see usage.h)
+    bool           auditLog         = false;   // structured audit trail (see audit.h)
+    QString        auditLogPath;               // its folder; empty = dataDir()
+    bool           eventLog         = false;   // report to the Windows Event Log (eventlog.h)
+    bool           keepOnTop        = false;   // PinIt's own window stays always on top
+    bool           animateOpacity   = true;    // fade opacity changes instead of jumping
+    int            fadeMs           = 150;     // length of that fade
+    QString        language;                   // e.g. "de"; empty = follow Windows
+    // Which tray glyph to use: "auto" follows the taskbar's light/dark theme;
+    // "light"/"dark" force the one drawn for that taskbar.
+    QString        trayIconTheme = QStringLiteral("auto");
+    // What a pinned window does to confirm the pin: "none", "flash" (caption
+    // and taskbar button) or "border-pulse" (Windows 11 border colour).
+    QString        pinFeedback = QStringLiteral("none");
+    // Effect behind pinned windows: "none", "blur" or "acrylic" (Windows 11
+    // 22H2); steps down to what the PC supports.
+    QString        backdrop = QStringLiteral("none");
+    // Apps whose windows break when made layered (exe names or wildcards):
+    // their pins stay opaque. Folded into appCompat as no_layering entries.
+    QStringList    noLayeringApps;
+    bool           cornerBadge      = false;   // pin badge on each pinned window's corner
+    bool           badgeClickUnpin  = false;   // clicking that badge unpins
+    bool           pinOwnedWindows  = true;    // pinned apps' dialogs stay on top with them
+    bool           uiaMatching      = false;   // tell windows apart by UI Automation id
+    bool           remoteNoOpacity  = false;   // pins stay opaque over Remote Desktop
+    bool           osd              = true;    // on-screen display for hotkey actions
+    QString        osdPosition      = QStringLiteral("window");   // or "screen"
+    int            osdDurationMs    = 1200;
+    int            tempPinSeconds   = 300;     // how long the temporary-pin hotkey pins for
+    int            pinOpacityPercent = 70;     // what the pin-with-opacity hotkey sets
+    int            idleUnpinMinutes = 0;       // unpin pins unfocused this long; 0 = never
+    bool           kiosk            = false;   // kiosk mode (see kiosk.h), resumed at startup
+    QString        kioskPath;                  // the app it keeps on screen
+    // When PowerToys Always On Top or DeskPins already holds a window:
+    // "defer" (let it keep it on top), "override" (PinIt takes over) or "ask".
+    QString        coexistence = QStringLiteral("defer");
+    // When something else takes topmost from a pin: "restore" (put it back)
+    // or "mark" (leave it off and flag the pin as lost).
+    QString        topmostLost = QStringLiteral("restore");
+    QString        titleMarker;   // prefixed to pinned windows' titles ("📌 "); empty = off
+    // Remembered answers to "pin this protected process?", keyed by the
+    // lower-cased process name: true = always pin, false = never.
+    QHash<QString, bool> protectedChoices;
+    QHash<QString, CompactRule> compactRules;   // keyed by lower-cased process name
+    QHash<QString, AppCompat> appCompat;        // added to compat::builtIn(), likewise
+    QVector<Macro> macros;
+    QVector<Schedule> schedules;
+    QVector<Trigger> triggers;
+    std::array<PinSlot, kPinSlots> pinSlots;   // empty processName = unassigned
     ShortcutConfig shortcuts;
 };
 
+// A window the boss key hid. Kept on disk so that if PinIt ends without
+// showing it again (a crash, Task Manager), the next start does; the process
+// id guards against the handle having been reused meanwhile.
+struct HiddenWindow {
+    qint64 hwnd = 0;
+    qint64 pid = 0;
+};
+
 // Restored pin request: process + title to match against live windows.
 struct SavedState {
-    QVector<SavedPin> pins;
-    UserSettings      settings;
+    QVector<SavedPin>     pins;
+    UserSettings          settings;
+    QVector<Snapshot>     snapshots;
+    QVector<HiddenWindow> hidden;
+};
+
+// Folder for pinned.json, the log and usage stats: %LOCALAPPDATA%\PinIt unless
+// the user moved it (to the roaming profile, or a folder of their own), or
+// <exe dir>\data in portable mode. Needs the QCoreApplication to exist.
+QString dataDir();
+bool    isPortable();
+QString localDataDir();     // %LOCALAPPDATA%\PinIt, the default
+QString roamingDataDir();   // %APPDATA%\PinIt, which follows a roaming profile
+// The folder chosen for the data (on another PC, say) if it can't be used
+// here, so dataDir() fell back to localDataDir(); empty normally.
+QString unreachableDataDir();
+// Move the data folder to `path` (empty = back to %LOCALAPPDATA%\PinIt) while
+// running. The state, backups and usage stats are copied there, unless it
+// already holds PinIt data, which is then used as it is. The old folder is
+// left alone. False with a readable `error` if the folder can't be used.
+bool    setConfigDirectory(const QString &path, QString *error);
+// "%APPDATA%\PinIt" → "C:/Users/me/AppData/Roaming/PinIt".
+QString expandVariables(QString path);
+
+// Full path of pinned.json, for watching it.
+QString filePath();
+bool    hasHistory();   // false if Qt's SQLite driver is missing, for Diagnostics
+// True if pinned.json no longer holds what PinIt last read or wrote, i.e. someone
+// edited it by hand or a sync tool replaced it.
+bool    changedExternally();
+
+// One pin/unpin/close event, newest first from history().
+struct HistoryEntry {
+    QDateTime at;
+    QString   event;         // "pin", "unpin", "closed", "expired", "lost", "blocked"
+    QString   processName;
+    QString   title;
+};
+
+// Timestamped copies of the state in <data dir>\backups, so a bad change can be
+// rolled back. PinIt takes one at startup and keeps the newest few.
+struct Backup {
+    QString   id;   // "20261016-093000", also the file name's stem
+    QDateTime at;
+};
+
+bool            backup();                           // skipped if nothing changed
+QVector<Backup> listBackups();                      // newest first
+QByteArray      readBackup(const QString &id);      // its JSON; empty if unreadable
+// Replace the stored state with a backup (after backing up the current one).
+// Settings apply on the next reload; live pins aren't touched.
+bool            restoreBackup(const QString &id);
+
+// No-ops if hasHistory() is false.
+void                  recordHistory(const QString &event, const QString &processName,
+                                    const QString &title);
+QVector<HistoryEntry> history(int limit = 100);
+void                  clearHistory();
+
+// What load() made of a stored document that wasn't valid JSON. Instead of
+// starting over from defaults it keeps every pin and setting that still parses,
+// copies the original to pinned.json.corrupt, and describes the rest here.
+struct Recovery {
+    QString     error;        // the parse error and where it is
+    QString     copyPath;     // the damaged original, untouched
+    int         pinsKept = 0;
+    QStringList lost;         // what couldn't be read: "settings.shortcuts", "pins.vlc.exe:2"
 };
 
 SavedState load();
 void       save(const SavedState &state);
+// The recovery load() last had to make, reported once per damaged document;
+// false if there's nothing new to report.
+bool       takeRecovery(Recovery *out);
+// Everything salvageable from a JSON object that doesn't parse as a whole:
+// members whose values parse on their own, and objects recovered member by
+// member. The paths of what had to be dropped are appended to `lost`.
+QJsonObject salvage(const QByteArray &json, QStringList *lost);
+// The stored document exactly as load() sees it, for configschema::validate().
+QByteArray readRaw();
 
 UserSettings   loadSettings();
 void           saveSettings(const UserSettings &settings);
@@ -50,4 +373,10 @@ void           saveSettings(const UserSettings &settings);
 // Replace just the pin list, preserving settings.
 void savePins(const QVector<SavedPin> &pins);
 
+// Replace just the snapshot list, preserving pins and settings.
+void saveSnapshots(const QVector<Snapshot> &snapshots);
+
+// Replace just the boss key's hidden windows, likewise.
+void saveHiddenWindows(const QVector<HiddenWindow> &hidden);
+
 } // namespace persistence
@@ -0,0 +1,153 @@
+#include "pinbadges.h"
+#include "pinmanager.h"
+#include "winpin.h"
+#include "dpi.h"
+#include "perf.h"
+
+#include <QMouseEvent>
+#include <QPainter>
+#include <QTimer>
+#include <QWidget>
+
+#include <functional>
+
+namespace {
+
+constexpr int kSize = 22;     // logical pixels, scaled to the pinned window's DPI
+constexpr int kInset = 6;     // from the window's top-right corner, below the frame edge
+
+inline void *H(intptr_t h) { return reinterpret_cast<void *>(h); }
+
+class Badge : public QWidget
+{
+public:
+    Badge(bool clickable, std::function<void()> onClick)
+        : QWidget(nullptr, Qt::Tool | Qt::FramelessWindowHint | Qt::WindowStaysOnTopHint
+                               | Qt::WindowDoesNotAcceptFocus
+                               | (clickable ? Qt::WindowFlags() : Qt::WindowTransparentForInput))
+        , m_onClick(std::move(onClick))
+    {
+        setAttribute(Qt::WA_TranslucentBackground);
+        setAttribute(Qt::WA_ShowWithoutActivating);
+        resize(kSize, kSize);   // until follow() sizes it for its window's DPI
+        if (clickable) {
+            setCursor(Qt::PointingHandCursor);
+            setToolTip(QObject::tr("Unpin"));
+        }
+    }
+
+protected:
+    void paintEvent(QPaintEvent *) override
+    {
+        QPainter p(this);
+        p.setRenderHint(QPainter::Antialiasing);
+        p.setPen(Qt::NoPen);
+        p.setBrush(QColor(0xB6, 0x85, 0x5A, 230));   // the accent colour
+        p.drawEllipse(rect().adjusted(1, 1, -1, -1));
+        QFont f = font();
+        f.setPixelSize(qMax(1, height() / 2));
+        p.setFont(f);
+        p.setPen(Qt::white);
+        p.drawText(rect(), Qt::AlignCenter, QString::fromUtf8("\xF0\x9F\x93\x8C"));   // 📌
+    }
+
+    void mousePressEvent(QMouseEvent *e) override
+    {
+        if (e->button() == Qt::LeftButton && m_onClick)
+            m_onClick();
+    }
+
+private:
+    std::function<void()> m_onClick;
+};
+
+} // namespace
+
+PinBadges::PinBadges(PinManager *manager, QObject *parent)
+    : QObject(parent)
+    , m_manager(manager)
+{
+    // Fast enough to keep up with a window being dragged; runs only while
+    // badges are on and something is pinned.
+    m_timer = new QTimer(this);
+    m_timer->setInterval(50);
+    connect(m_timer, &QTimer::timeout, this, &PinBadges::follow);
+    connect(m_manager, &PinManager::pinsChanged, this, &PinBadges::sync);
+}
+
+PinBadges::~PinBadges()
+{
+    clear();
+}
+
+void PinBadges::setEnabled(bool enabled)
+{
+    m_enabled = enabled;
+    sync();
+}
+
+void PinBadges::setClickToUnpin(bool on)
+{
+    if (on == m_clickToUnpin)
+        return;
+    m_clickToUnpin = on;
+    clear();
+    sync();
+}
+
+void PinBadges::sync()
+{
+    if (!m_enabled) {
+        clear();
+        m_timer->stop();
+        return;
+    }
+
+    QHash<intptr_t, QWidget *> keep;
+    for (const PinnedWindow &w : m_manager->pinnedWindows()) {
+        QWidget *badge = m_badges.take(w.hwnd);
+        if (!badge) {
+            const intptr_t hwnd = w.hwnd;
+            badge = new Badge(m_clickToUnpin, [this, hwnd]() {
+                // Deferred: unpinning rebuilds the badges, this one included.
+                QTimer::singleShot(0, m_manager, [this, hwnd]() { m_manager->unpin(hwnd); });
+            });
+        }
+        keep.insert(w.hwnd, badge);
+    }
+    clear();   // whatever is left belongs to windows that are no longer pinned
+    m_badges = keep;
+
+    follow();
+    if (m_badges.isEmpty())
+        m_timer->stop();
+    else if (!m_timer->isActive())
+        m_timer->start();
+}
+
+void PinBadges::follow()
+{
+    perf::tick("badges");
+    for (auto it = m_badges.begin(); it != m_badges.end(); ++it) {
+        QWidget *badge = it.value();
+        const QRect r = winpin::windowRect(H(it.key()));
+        if (r.isEmpty() || winpin::isMinimized(H(it.key()))) {
+            badge->hide();
+            continue;
+        }
+        // In physical pixels at the window's own DPI: a badge straddling two
+        // monitors of different scale would otherwise land in the wrong spot.
+        const int windowDpi = dpi::ofWindow(H(it.key()));
+        const int size = dpi::scaled(kSize, windowDpi);
+        const int inset = dpi::scaled(kInset, windowDpi);
+        dpi::place(badge, QRect(r.right() - inset - size + 1, r.top() + inset, size, size));
+        if (!badge->isVisible())
+            badge->show();
+    }
+}
+
+void PinBadges::clear()
+{
+    qDeleteAll(m_badges);
+    m_badges.clear();
+}
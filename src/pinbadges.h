@@ -0,0 +1,37 @@
+#pragma once
+//
+// PinBadges — a tiny pin badge in the top-right corner of every pinned
+// window, as a quieter indicator than a border. Each badge is its own small
+// topmost window that follows its target (polled, like the rest of PinIt)
+// and hides while the target is minimized. Clicks pass through to the window
+// underneath unless click-to-unpin is on.
+//
+#include <QHash>
+#include <QObject>
+#include <cstdint>
+
+class PinManager;
+class QTimer;
+class QWidget;
+
+class PinBadges : public QObject
+{
+    Q_OBJECT
+public:
+    explicit PinBadges(PinManager *manager, QObject *parent = nullptr);
+    ~PinBadges() override;
+
+    void setEnabled(bool enabled);
+    void setClickToUnpin(bool on);   // recreates the badges with the new input mode
+
+private:
+    void sync();     // one badge per pin, no more
+    void follow();   // move each badge to its window's corner
+    void clear();
+
+    PinManager                *m_manager = nullptr;
+    QTimer                    *m_timer = nullptr;
+    QHash<intptr_t, QWidget *> m_badges;
+    bool                       m_enabled = false;
+    bool                       m_clickToUnpin = false;
+};
@@ -0,0 +1,47 @@
+#pragma once
+//
+// PinError — machine-readable codes for everything that can go wrong in a
+// user-facing action. The message shown to the user stays translatable; the
+// code is stable, so callers can branch on it and logs can be grepped for it.
+//
+#include <QString>
+
+enum class PinError {
+    InvalidWindow,       // the window closed, or the handle was never valid
+    ElevationRequired,   // UIPI: the target runs as administrator and PinIt doesn't
+    WindowNotResponding, // hung; touching it would freeze PinIt too
+    NoTargetWindow,      // hotkey fired with nothing focused / under the pointer
+    LaunchFailed,        // the exe couldn't be started
+    LaunchTimedOut,      // it started but never showed a window
+    SnapshotNotFound,
+    ShortcutConflict,    // reserved by Windows or held by another app
+    AutostartFailed,     // couldn't write the Run key
+    ProtectedProcess,    // the user said never to pin this security-sensitive app
+    DisabledByPolicy,    // an administrator turned the feature off (see policy.h)
+    BlockedByPolicy,     // the app is on the machine's never-pin list
+    OpacityUnsupported,  // the app misbehaves when layered (see compat.h)
+};
+// The last code; a new one goes before it or moves this along. The tests walk
+// every value up to it.
+constexpr PinError kLastPinError = PinError::OpacityUnsupported;
+
+// "ELEVATION_REQUIRED" etc. — what goes in logs and the Diagnostics report.
+inline QString errorCode(PinError e)
+{
+    switch (e) {
+    case PinError::InvalidWindow:        return QStringLiteral("INVALID_WINDOW");
+    case PinError::ElevationRequired:    return QStringLiteral("ELEVATION_REQUIRED");
+    case PinError::WindowNotResponding:  return QStringLiteral("WINDOW_NOT_RESPONDING");
+    case PinError::NoTargetWindow:       return QStringLiteral("NO_TARGET_WINDOW");
+    case PinError::LaunchFailed:         return QStringLiteral("LAUNCH_FAILED");
+    case PinError::LaunchTimedOut:       return QStringLiteral("LAUNCH_TIMED_OUT");
+    case PinError::SnapshotNotFound:     return QStringLiteral("SNAPSHOT_NOT_FOUND");
+    case PinError::ShortcutConflict:     return QStringLiteral("SHORTCUT_CONFLICT");
+    case PinError::AutostartFailed:      return QStringLiteral("AUTOSTART_FAILED");
+    case PinError::ProtectedProcess:     return QStringLiteral("PROTECTED_PROCESS");
+    case PinError::DisabledByPolicy:     return QStringLiteral("DISABLED_BY_POLICY");
+    case PinError::BlockedByPolicy:      return QStringLiteral("BLOCKED_BY_POLICY");
+    case PinError::OpacityUnsupported:   return QStringLiteral("OPACITY_UNSUPPORTED");
+    }
+    return QStringLiteral("UNKNOWN");
+}
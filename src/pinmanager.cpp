@@ -1,13 +1,49 @@
 #include "pinmanager.h"
 #include "winpin.h"
 #include "persistence.h"
+#include "platform.h"
+#include "uia.h"
+#include "dpi.h"
+#include "perf.h"
 
 #include <QTimer>
+#include <QElapsedTimer>
+#include <QFileInfo>
+#include <QProcess>
 #include <QSet>
+#include <QRect>
 #include <QtGlobal>
 
+#include <algorithm>
+
 namespace {
 inline void *H(intptr_t h) { return reinterpret_cast<void *>(h); }
+constexpr int kMaxRecent = 16;   // how many pins lastPinned() can fall back through
+constexpr int kRespondingCacheMs = 2000;   // how long setOpacity() trusts a ping
+// Pending restores are looked for this long after the list last changed;
+// after that only the Retry button (retryRestore()) looks again.
+constexpr qint64 kRetryForMs = 10 * 60 * 1000;
+
+// Exe-name patterns ("lob-*.exe"), matched whole and case-insensitively.
+QVector<QRegularExpression> wildcards(const QStringList &patterns)
+{
+    QVector<QRegularExpression> out;
+    for (const QString &p : patterns) {
+        out.push_back(QRegularExpression(
+            QRegularExpression::wildcardToRegularExpression(p.trimmed()),
+            QRegularExpression::CaseInsensitiveOption));
+    }
+    return out;
+}
+
+bool matchesAny(const QVector<QRegularExpression> &res, const QString &processName)
+{
+    for (const QRegularExpression &re : res) {
+        if (re.match(processName).hasMatch())
+            return true;
+    }
+    return false;
+}
 } // namespace
 
 PinManager::PinManager(QObject *parent)
@@ -29,6 +65,54 @@ PinManager::PinManager(QObject *parent)
     m_persistTimer->setSingleShot(true);
     m_persistTimer->setInterval(600);
     connect(m_persistTimer, &QTimer::timeout, this, [this]() { persist(); });
+
+    // Saved pins whose app wasn't open at startup: look for their window every
+    // few seconds so launching the app re-pins it without restarting PinIt.
+    // Runs only while something is pending (see updateRetryTimer), and gives
+    // up after kRetryForMs — an app that never comes back isn't polled forever.
+    m_retryTimer = new QTimer(this);
+    m_retryTimer->setInterval(3000);
+    connect(m_retryTimer, &QTimer::timeout, this, [this]() {
+        if (m_retrySince.hasExpired(kRetryForMs)) {
+            qInfo("Stopped looking for %d pending window(s)", int(m_pending.size()));
+            m_retryTimer->stop();
+            return;
+        }
+        retryRestore();
+    });
+
+    // Opacity fades step at ~60 fps, only while one is running.
+    m_fadeTimer = new QTimer(this);
+    m_fadeTimer->setTimerType(Qt::PreciseTimer);
+    m_fadeTimer->setInterval(16);
+    connect(m_fadeTimer, &QTimer::timeout, this, &PinManager::stepFades);
+
+    // Temporary pins count down once a second, only while there are any.
+    m_expiryTimer = new QTimer(this);
+    m_expiryTimer->setInterval(1000);
+    connect(m_expiryTimer, &QTimer::timeout, this, &PinManager::tickExpiries);
+}
+
+void PinManager::updateExpiryTimer()
+{
+    const bool any = std::any_of(m_pinned.cbegin(), m_pinned.cend(),
+                                 [](const PinnedWindow &w) { return w.expiresAt.isValid(); });
+    if (!any)
+        m_expiryTimer->stop();
+    else if (!m_expiryTimer->isActive())
+        m_expiryTimer->start();
+}
+
+void PinManager::updateRetryTimer()
+{
+    if (m_pending.isEmpty()) {
+        m_retryTimer->stop();
+        m_unpinnable.clear();
+        return;
+    }
+    m_retrySince.start();   // the list changed: a fresh deadline
+    if (!m_retryTimer->isActive())
+        m_retryTimer->start();
 }
 
 void PinManager::schedulePersist()
@@ -55,19 +139,82 @@ bool PinManager::pin(intptr_t hwnd, bool announce)
         return true;
 
     if (!winpin::isValidWindow(H(hwnd))) {
-        emit errorOccurred(tr("That window no longer exists."));
+        fail(PinError::InvalidWindow, tr("That window no longer exists."));
+        return false;
+    }
+    // A child window (a control, an embedded pane) can't be topmost on its
+    // own; pin the top-level window it sits in.
+    const intptr_t root = reinterpret_cast<intptr_t>(winpin::rootWindow(H(hwnd)));
+    if (root != hwnd)
+        return pin(root, announce);
+    // PinIt's own windows aren't tracked pins; the main window has its own
+    // "keep on top" toggle instead.
+    if (winpin::isOwnWindow(H(hwnd))) {
+        fail(PinError::NoTargetWindow,
+             tr("To keep PinIt itself on top, use the pin button in its window."));
         return false;
     }
 
-    const QString title = winpin::windowTitle(H(hwnd));
+    const QString title = unmarked(winpin::windowTitle(H(hwnd)));
     const QString proc  = winpin::processName(H(hwnd));
 
-    if (!winpin::applyTopmost(H(hwnd)) || !winpin::isTopmost(H(hwnd))) {
+    finishFade(hwnd);   // re-pinned mid-way through an unpin fade
+
+    if (isBlocked(proc)) {
+        qWarning("[audit] Refused to pin %s (%s): on the never-pin list", qUtf8Printable(proc),
+                 qUtf8Printable(title));
+        emit pinBlocked(hwnd, title, proc);
+        fail(PinError::BlockedByPolicy,
+             tr("%1 can't be pinned on this PC — your administrator has blocked it.").arg(proc));
+        return false;
+    }
+    if (winpin::isProtectedProcess(proc) && !m_confirmed.remove(hwnd)) {
+        const auto choice = m_protectedChoices.constFind(proc.toLower());
+        if (choice == m_protectedChoices.constEnd()) {
+            if (!m_awaitingConfirm.contains(hwnd)) {   // already asked
+                m_awaitingConfirm.insert(hwnd, announce);
+                emit pinConfirmationRequired(hwnd, title, proc);
+            }
+            return false;
+        }
+        if (!choice.value()) {
+            fail(PinError::ProtectedProcess,
+                 tr("%1 is a protected app you chose never to pin.").arg(proc));
+            return false;
+        }
+    }
+
+    // SetWindowPos messages the window's thread; on a hung app that would
+    // freeze PinIt along with it. isHung is free, so ask it before pinging.
+    if (winpin::isHung(H(hwnd)) || !winpin::isResponding(H(hwnd))) {
+        fail(PinError::WindowNotResponding,
+             tr("%1 isn't responding — try again once it recovers.").arg(proc));
+        return false;
+    }
+
+    const QString other = winpin::topmostManager(H(hwnd));
+    bool defer = false;
+    if (!other.isEmpty()) {
+        if (m_coexistChoice.contains(hwnd)) {
+            defer = !m_coexistChoice.take(hwnd);
+        } else if (m_coexistence == QLatin1String("ask")) {
+            if (!m_awaitingCoexist.contains(hwnd)) {   // already asked
+                m_awaitingCoexist.insert(hwnd, announce);
+                emit coexistenceChoiceRequired(hwnd, title, proc, other);
+            }
+            return false;
+        } else {
+            defer = m_coexistence != QLatin1String("override");
+        }
+        qInfo("%s is also held by %s; %s", qUtf8Printable(title), qUtf8Printable(other),
+              defer ? "leaving topmost to it" : "taking over");
+    }
+
+    if (!defer && (!winpin::applyTopmost(H(hwnd)) || !winpin::isTopmost(H(hwnd)))) {
         // UIPI silently blocks SetWindowPos on elevated windows; verifying the
         // style actually took is how we detect that (same as the Rust port).
-        qWarning("Pin failed for %s (likely elevated/UIPI)", qUtf8Printable(proc));
-        emit errorOccurred(tr("Can't pin %1 — it may be running as administrator.")
-                               .arg(proc));
+        fail(PinError::ElevationRequired,
+             tr("Can't pin %1 — it may be running as administrator.").arg(proc));
         return false;
     }
 
@@ -77,47 +224,156 @@ bool PinManager::pin(intptr_t hwnd, bool announce)
     w.processName = proc;
     w.opacity = 100;
     w.wasLayered = winpin::isLayered(H(hwnd));   // remember its original style
+    w.pinnedAt = QDateTime::currentDateTime();
+    w.pid = winpin::processId(H(hwnd));
+    w.processPath = winpin::processPath(H(hwnd));
+    w.commandLine = winpin::processCommandLine(H(hwnd));
+    if (m_uiaMatching)
+        w.automationId = uia::identify(H(hwnd)).automationId;
+    w.sharedWith = other;
+    w.deferred = defer;
+    w.lastFocused = w.pinnedAt;
+    const persistence::AppCompat compat = compatFor(proc);
+    if (QStringList{QStringLiteral("aggressive"), QStringLiteral("once")}.contains(
+            compat.enforcement))
+        w.enforcement = compat.enforcement;
+    const auto rule = m_compactRules.constFind(proc.toLower());
+    if (rule != m_compactRules.constEnd()) {
+        const qreal scale = dpi::scale(H(hwnd));
+        const QRect compact = winpin::cornerRect(winpin::monitorWorkArea(H(hwnd)),
+                                                 rule->size * scale, rule->corner,
+                                                 qRound(16 * scale));
+        w.restoreRect = winpin::windowRect(H(hwnd));
+        if (compact.isEmpty() || !winpin::moveWindow(H(hwnd), compact))
+            w.restoreRect = QRect();
+    }
+    if (m_backdrop != QLatin1String("none"))
+        w.backdrop = winpin::setBackdrop(H(hwnd), m_backdrop);
     m_pinned.insert(hwnd, w);
+    if (compat.reassertAfterMs > 0 && !defer)
+        QTimer::singleShot(compat.reassertAfterMs, this, [this, hwnd]() { reassert(hwnd); });
+    m_recent.removeAll(hwnd);
+    m_recent.prepend(hwnd);
+    if (m_recent.size() > kMaxRecent)
+        m_recent.removeLast();
+    if (m_pinOwned && !defer)
+        raiseOwned(m_pinned[hwnd]);
 
     persist();
     updateTimer();
     qInfo("Pinned %s (%s)", qUtf8Printable(title), qUtf8Printable(proc));
-    if (announce)
+    if (!m_titleMarker.isEmpty())
+        winpin::setWindowTitle(H(hwnd), m_titleMarker + title);
+    if (announce) {
+        confirmVisually(hwnd);
         emit pinToggled(true, title, proc);
+    }
     emit pinsChanged();
+    emitStateChanged();
     return true;
 }
 
-bool PinManager::unpin(intptr_t hwnd)
+void PinManager::confirmPin(intptr_t hwnd, bool allow)
+{
+    if (!m_awaitingConfirm.contains(hwnd))
+        return;
+    const bool announce = m_awaitingConfirm.take(hwnd);
+    if (!allow) {
+        m_awaitingLaunch.remove(hwnd);
+        if (m_awaitingRestore.remove(hwnd))
+            persist();   // turned down: don't offer the saved pin again
+        return;
+    }
+    m_confirmed.insert(hwnd);
+    resumePin(hwnd, announce);
+}
+
+void PinManager::resumePin(intptr_t hwnd, bool announce)
+{
+    if (!m_awaitingRestore.contains(hwnd)) {
+        if (pin(hwnd, announce)) {
+            if (m_awaitingLaunch.contains(hwnd))
+                finishLaunch(hwnd, m_awaitingLaunch.take(hwnd));
+        } else if (!m_awaitingConfirm.contains(hwnd) && !m_awaitingCoexist.contains(hwnd)) {
+            m_awaitingLaunch.remove(hwnd);
+        }
+        return;
+    }
+    const persistence::SavedPin saved = m_awaitingRestore.take(hwnd);
+    if (!applySaved(saved, hwnd, announce)) {
+        m_pending.push_back(saved);   // a miss after all; retried like any other
+        persist();
+        updateRetryTimer();
+        emit pendingRestoresChanged();
+    }
+}
+
+void PinManager::resolveCoexistence(intptr_t hwnd, bool takeOver)
+{
+    if (!m_awaitingCoexist.contains(hwnd))
+        return;
+    const bool announce = m_awaitingCoexist.take(hwnd);
+    m_coexistChoice.insert(hwnd, takeOver);
+    resumePin(hwnd, announce);
+}
+
+bool PinManager::unpin(intptr_t hwnd, bool announce)
 {
     auto it = m_pinned.find(hwnd);
     QString title, proc;
-    bool opacityChanged = false, wasLayered = false;
+    bool opacityChanged = false, wasLayered = false, deferred = false, backdrop = false;
     if (it != m_pinned.end()) {
         title = it->title;
         proc  = it->processName;
         opacityChanged = it->opacityChanged;
         wasLayered = it->wasLayered;
+        deferred = it->deferred;
+        backdrop = it->backdrop;
     }
 
     if (winpin::isValidWindow(H(hwnd))) {
         // Only undo opacity if we actually changed it — otherwise we'd reset an
         // app that manages its own transparency. keepLayered preserves its style.
-        if (opacityChanged)
-            winpin::restoreOpacity(H(hwnd), wasLayered);
-        winpin::removeTopmost(H(hwnd));
+        // A hung window still gets unpinned — the request is queued for when it
+        // recovers — we just mustn't wait on it.
+        const bool responding = winpin::isResponding(H(hwnd));
+        if (opacityChanged && responding) {
+            if (m_fadeMs > 0) {
+                const int from = m_fades.contains(hwnd) ? m_fades[hwnd].current : it->opacity;
+                startFade(hwnd, from, winpin::kMaxOpacity, /*restoreWhenDone=*/true, wasLayered);
+            } else {
+                winpin::restoreOpacity(H(hwnd), wasLayered);
+            }
+        }
+        if (!deferred)   // otherwise the other tool still wants it on top
+            winpin::removeTopmost(H(hwnd), /*async=*/!responding);
+        if (responding) {
+            removeMarker(hwnd);
+            if (backdrop)
+                winpin::setBackdrop(H(hwnd), QStringLiteral("none"));
+            if (it != m_pinned.end())
+                restorePlacement(*it);
+        }
     }
+    if (it != m_pinned.end())
+        releaseOwned(*it);
 
     m_pinned.remove(hwnd);
     persist();
     updateTimer();
-    emit pinToggled(false, title, proc);
+    updateExpiryTimer();
+    if (announce)
+        emit pinToggled(false, title, proc);
     emit pinsChanged();
+    emitStateChanged();
     return true;
 }
 
 bool PinManager::toggle(intptr_t hwnd)
 {
+    // Pinning a child window pins its top-level window (see pin()), so that's
+    // the one to look up, too.
+    hwnd = reinterpret_cast<intptr_t>(winpin::rootWindow(H(hwnd)));
     return isPinned(hwnd) ? unpin(hwnd) : pin(hwnd);
 }
 
@@ -125,18 +381,130 @@ void PinManager::toggleForeground()
 {
     void *fg = winpin::foregroundWindow();
     if (!fg) {
-        emit errorOccurred(tr("No window to pin — click a window first."));
+        fail(PinError::NoTargetWindow, tr("No window to pin — click a window first."));
         return;
     }
     toggle(reinterpret_cast<intptr_t>(fg));
 }
 
+bool PinManager::pinFor(intptr_t hwnd, int seconds)
+{
+    hwnd = reinterpret_cast<intptr_t>(winpin::rootWindow(H(hwnd)));   // as in toggle()
+    if (!m_pinned.contains(hwnd)) {
+        if (seconds <= 0 || !pin(hwnd))
+            return false;
+    }
+    PinnedWindow &w = m_pinned[hwnd];
+    w.expiresAt = seconds > 0 ? QDateTime::currentDateTime().addSecs(seconds) : QDateTime();
+    qInfo("%s %s", seconds > 0 ? "Unpinning after a timer:" : "Pinned for good:",
+          qUtf8Printable(w.title));
+    persist();   // a temporary pin drops out of pinned.json, a permanent one comes back
+    updateExpiryTimer();
+    if (seconds > 0)
+        emit pinCountdown(hwnd, seconds);
+    emit pinsChanged();
+    emitStateChanged();
+    return true;
+}
+
+bool PinManager::pinWithOpacity(intptr_t hwnd, int percent)
+{
+    if (!pin(hwnd))
+        return false;
+    // pin() may have pinned the top-level window hwnd sits in.
+    return setOpacity(reinterpret_cast<intptr_t>(winpin::rootWindow(H(hwnd))), percent);
+}
+
+void PinManager::pinForegroundWithOpacity()
+{
+    void *fg = winpin::foregroundWindow();
+    if (!fg) {
+        fail(PinError::NoTargetWindow, tr("No window to pin — click a window first."));
+        return;
+    }
+    pinWithOpacity(reinterpret_cast<intptr_t>(fg), m_pinOpacity);
+}
+
+intptr_t PinManager::lastPinned()
+{
+    while (!m_recent.isEmpty() && !winpin::isValidWindow(H(m_recent.first())))
+        m_recent.removeFirst();   // closed since
+    return m_recent.value(0, 0);
+}
+
+void PinManager::toggleLastPinned()
+{
+    const intptr_t hwnd = lastPinned();
+    if (!hwnd) {
+        fail(PinError::NoTargetWindow, tr("No window has been pinned yet."));
+        return;
+    }
+    toggle(hwnd);
+}
+
+bool PinManager::focusLastPinned()
+{
+    const intptr_t hwnd = lastPinned();
+    if (!hwnd) {
+        fail(PinError::NoTargetWindow, tr("No window has been pinned yet."));
+        return false;
+    }
+    return winpin::activate(H(hwnd));
+}
+
+void PinManager::toggleForegroundTemporary()
+{
+    void *fg = winpin::foregroundWindow();
+    if (!fg) {
+        fail(PinError::NoTargetWindow, tr("No window to pin — click a window first."));
+        return;
+    }
+    const intptr_t hwnd = reinterpret_cast<intptr_t>(fg);
+    if (isPinned(hwnd))
+        unpin(hwnd);
+    else
+        pinFor(hwnd, m_tempPinSeconds);
+}
+
+void PinManager::toggleUnderCursor()
+{
+    // Over the desktop or the taskbar there's nothing to pin.
+    void *target = winpin::windowUnderCursor();
+    if (!target || winpin::isOwnWindow(target) || winpin::isShellWindow(target)) {
+        fail(PinError::NoTargetWindow, tr("No window under the mouse pointer to pin."));
+        return;
+    }
+    toggle(reinterpret_cast<intptr_t>(target));
+}
+
+void PinManager::beginPreview()
+{
+    const intptr_t fg = reinterpret_cast<intptr_t>(winpin::foregroundWindow());
+    if (fg == 0 || m_pinned.contains(fg))
+        return;
+    if (pin(fg, /*announce=*/false))
+        m_previewHwnd = fg;
+}
+
+void PinManager::endPreview()
+{
+    if (m_previewHwnd == 0)
+        return;
+    if (m_pinned.contains(m_previewHwnd))
+        unpin(m_previewHwnd, /*announce=*/false);
+    m_previewHwnd = 0;
+}
+
 void PinManager::adjustForegroundOpacity(int deltaPercent)
 {
     void *fg = winpin::foregroundWindow();
     if (!fg)
         return;
-    const intptr_t hwnd = reinterpret_cast<intptr_t>(fg);
+    adjustOpacity(reinterpret_cast<intptr_t>(fg), deltaPercent);
+}
+
+void PinManager::adjustOpacity(intptr_t hwnd, int deltaPercent)
+{
     if (!m_pinned.contains(hwnd))
         return;   // only adjust opacity of pinned windows
     setOpacity(hwnd, m_pinned[hwnd].opacity + deltaPercent);
@@ -151,16 +519,487 @@ bool PinManager::setOpacity(intptr_t hwnd, int percent)
     if (percent < winpin::kMinOpacity) percent = winpin::kMinOpacity;
     if (percent > winpin::kMaxOpacity) percent = winpin::kMaxOpacity;
 
-    if (!winpin::setOpacityPercent(H(hwnd), percent))
+    if (m_opacityDisabled) {
+        fail(PinError::DisabledByPolicy, tr("Your administrator has turned off transparency."));
+        return false;
+    }
+    if (compatFor(it->processName).noLayering) {
+        fail(PinError::OpacityUnsupported,
+             tr("%1 doesn't work see-through, so its opacity stays at 100%.")
+                 .arg(it->processName));
+        return false;
+    }
+    // A slider drag calls this dozens of times a second; a ping can take
+    // 300 ms, so one answer (either way) stands for a couple of seconds.
+    const QDateTime now = QDateTime::currentDateTimeUtc();
+    if (!it->respondingCheckedAt.isValid()
+        || it->respondingCheckedAt.msecsTo(now) > kRespondingCacheMs) {
+        it->responding = winpin::isResponding(H(hwnd));
+        it->respondingCheckedAt = now;
+    }
+    if (!it->responding) {
+        fail(PinError::WindowNotResponding,
+             tr("%1 isn't responding — try again once it recovers.").arg(it->processName));
         return false;
+    }
+    if (m_opacitySuspended) {
+        // Remembered, and applied by setOpacitySuspended(false).
+    } else if (m_fadeMs > 0) {
+        // Start from wherever a running fade has got to, so slider drags glide.
+        const int from = m_fades.contains(hwnd) ? m_fades[hwnd].current : it->opacity;
+        startFade(hwnd, from, percent);
+    } else if (!winpin::setOpacityPercent(H(hwnd), percent)) {
+        return false;
+    }
 
     it->opacity = percent;
     it->opacityChanged = true;   // remember so unpin/exit undoes it
+    ++it->opacityChanges;
     schedulePersist();   // debounced — slider drags fire this dozens of times
     emit opacityChanged(hwnd, percent);
+    emitStateChanged();
     return true;
 }
 
+void PinManager::startFade(intptr_t hwnd, int from, int to, bool restoreWhenDone,
+                           bool keepLayered)
+{
+    Fade &f = m_fades[hwnd];
+    f.from = f.current = from;
+    f.to = to;
+    f.restoreWhenDone = restoreWhenDone;
+    f.keepLayered = keepLayered;
+    f.clock.start();
+    if (!m_fadeTimer->isActive())
+        m_fadeTimer->start();
+}
+
+void PinManager::stepFades()
+{
+    for (auto it = m_fades.begin(); it != m_fades.end();) {
+        if (!winpin::isValidWindow(H(it.key()))) {
+            it = m_fades.erase(it);
+            continue;
+        }
+        const double t = qMin(1.0, it->clock.elapsed() / double(qMax(1, m_fadeMs)));
+        const double eased = 1.0 - (1.0 - t) * (1.0 - t);   // ease-out
+        it->current = qRound(it->from + (it->to - it->from) * eased);
+        winpin::setOpacityPercent(H(it.key()), it->current);
+        if (t < 1.0) {
+            ++it;
+            continue;
+        }
+        if (it->restoreWhenDone)
+            winpin::restoreOpacity(H(it.key()), it->keepLayered);
+        it = m_fades.erase(it);
+    }
+    if (m_fades.isEmpty())
+        m_fadeTimer->stop();
+}
+
+void PinManager::finishFade(intptr_t hwnd)
+{
+    auto it = m_fades.find(hwnd);
+    if (it == m_fades.end())
+        return;
+    if (winpin::isValidWindow(H(hwnd))) {
+        if (it->restoreWhenDone)
+            winpin::restoreOpacity(H(hwnd), it->keepLayered);
+        else
+            winpin::setOpacityPercent(H(hwnd), it->to);
+    }
+    m_fades.erase(it);
+}
+
+void PinManager::confirmVisually(intptr_t hwnd)
+{
+    if (m_pinFeedback == QLatin1String("flash")
+        || (m_pinFeedback == QLatin1String("border-pulse")
+            && !platform::capabilities().borderColor)) {
+        winpin::flash(H(hwnd));   // Windows 10 has no border colour to pulse
+    } else if (m_pinFeedback == QLatin1String("border-pulse")) {
+        // Two pulses of PinIt's accent colour: on, off, on, off.
+        constexpr quint32 kAccent = 0xB6855A;
+        for (int step = 0; step < 4; ++step) {
+            QTimer::singleShot(step * 150, this, [hwnd, on = step % 2 == 0]() {
+                if (!winpin::isValidWindow(H(hwnd)))
+                    return;
+                if (on)
+                    winpin::setBorderColor(H(hwnd), kAccent);
+                else
+                    winpin::resetBorderColor(H(hwnd));
+            });
+        }
+    }
+}
+
+void PinManager::setBackdrop(const QString &kind)
+{
+    const QString effective = platform::backdropFor(kind, platform::capabilities());
+    if (effective == m_backdrop)
+        return;
+    if (effective != kind)
+        qInfo("Backdrop \"%s\" isn't available on this Windows; using \"%s\"",
+              qUtf8Printable(kind), qUtf8Printable(effective));
+    m_backdrop = effective;
+    for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
+        void *hwnd = H(it.key());
+        if (!winpin::isValidWindow(hwnd) || !winpin::isResponding(hwnd))
+            continue;
+        if (it->backdrop)   // switching from one effect to the other
+            winpin::setBackdrop(hwnd, QStringLiteral("none"));
+        it->backdrop = m_backdrop != QLatin1String("none") && winpin::setBackdrop(hwnd, m_backdrop);
+    }
+}
+
+void PinManager::setTitleMarker(const QString &marker)
+{
+    if (marker == m_titleMarker)
+        return;
+    for (auto it = m_pinned.cbegin(); it != m_pinned.cend(); ++it) {
+        if (winpin::isValidWindow(H(it.key())) && !it->hung)
+            removeMarker(it.key());
+    }
+    m_titleMarker = marker;
+    if (marker.isEmpty())
+        return;
+    for (auto it = m_pinned.cbegin(); it != m_pinned.cend(); ++it) {
+        if (winpin::isValidWindow(H(it.key())) && !it->hung)
+            winpin::setWindowTitle(H(it.key()), marker + it->title);
+    }
+}
+
+QString PinManager::unmarked(const QString &title) const
+{
+    if (!m_titleMarker.isEmpty() && title.startsWith(m_titleMarker))
+        return title.mid(m_titleMarker.size());
+    return title;
+}
+
+void PinManager::restorePlacement(const PinnedWindow &w)
+{
+    if (!w.restoreRect.isEmpty())
+        winpin::moveWindow(H(w.hwnd), w.restoreRect);
+}
+
+void PinManager::removeMarker(intptr_t hwnd)
+{
+    if (m_titleMarker.isEmpty())
+        return;
+    const QString title = winpin::windowTitle(H(hwnd));
+    if (title.startsWith(m_titleMarker))
+        winpin::setWindowTitle(H(hwnd), title.mid(m_titleMarker.size()));
+}
+
+bool PinManager::setGroup(intptr_t hwnd, const QString &group)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+
+    it->group = group.trimmed();
+    persist();
+    emit pinsChanged();
+    emitStateChanged();
+    return true;
+}
+
+QStringList PinManager::groups() const
+{
+    QStringList out;
+    for (const auto &w : m_pinned) {
+        if (!w.group.isEmpty() && !out.contains(w.group))
+            out << w.group;
+    }
+    out.sort(Qt::CaseInsensitive);
+    return out;
+}
+
+int PinManager::setGroupOpacity(const QString &group, int percent)
+{
+    int changed = 0;
+    for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
+        if (it->group == group && setOpacity(it.key(), percent))
+            ++changed;
+    }
+    return changed;
+}
+
+bool PinManager::launchAndPin(const QString &path, const QStringList &args, int timeoutMs,
+                              std::function<void(intptr_t)> onPinned)
+{
+    // Windows that already exist can't be the new app's window.
+    QSet<intptr_t> before;
+    for (const auto &w : winpin::enumerateWindows())
+        before.insert(w.hwnd);
+
+    qint64 pid = 0;
+    if (!QProcess::startDetached(path, args, QFileInfo(path).absolutePath(), &pid)) {
+        fail(PinError::LaunchFailed, tr("Couldn't start %1.").arg(QFileInfo(path).fileName()));
+        return false;
+    }
+    qInfo("Launched %s (pid %lld); waiting for its window", qUtf8Printable(path), pid);
+    m_launching.insert(pid);
+
+    const QString exeName = QFileInfo(path).fileName();
+    auto *poll = new QTimer(this);
+    poll->setInterval(250);
+    QElapsedTimer waited;
+    waited.start();
+    // Several launches of one exe (a snapshot with two windows of it) poll
+    // side by side: each window goes to one of them, and a window of a
+    // process another launch started goes to that one.
+    const auto done = [this, poll, pid]() {
+        poll->stop();
+        poll->deleteLater();
+        m_launching.remove(pid);
+        if (m_launching.isEmpty())
+            m_claimed.clear();
+    };
+    connect(poll, &QTimer::timeout, this,
+            [this, done, before, pid, path, args, exeName, waited, timeoutMs, onPinned]() {
+        for (const auto &w : winpin::enumerateWindows()) {
+            if (before.contains(w.hwnd) || w.title.isEmpty() || m_claimed.contains(w.hwnd))
+                continue;
+            const bool ours = w.pid == pid
+                || (w.processName.compare(exeName, Qt::CaseInsensitive) == 0
+                    && !m_launching.contains(w.pid));
+            if (ours) {
+                done();
+                if (!m_launching.isEmpty())
+                    m_claimed.insert(w.hwnd);   // so the launches still waiting skip it
+                const Launched launch{path, args, onPinned};
+                if (pin(w.hwnd)) {
+                    finishLaunch(w.hwnd, launch);
+                } else if (m_awaitingConfirm.contains(w.hwnd)
+                           || m_awaitingCoexist.contains(w.hwnd)) {
+                    // Waiting on the user's answer: the launch is finished once
+                    // they've said yes (resumePin()).
+                    m_awaitingLaunch.insert(w.hwnd, launch);
+                }
+                return;
+            }
+        }
+        if (waited.hasExpired(timeoutMs)) {
+            done();
+            fail(PinError::LaunchTimedOut,
+                 tr("%1 didn't open a window in time to pin it.").arg(exeName));
+        }
+    });
+    poll->start();
+    return true;
+}
+
+void PinManager::finishLaunch(intptr_t hwnd, const Launched &launch)
+{
+    m_pinned[hwnd].launchPath = launch.path;
+    m_pinned[hwnd].launchArgs = launch.args;
+    if (launch.onPinned)
+        launch.onPinned(hwnd);
+}
+
+bool PinManager::saveSnapshot(const QString &name)
+{
+    if (name.trimmed().isEmpty() || m_pinned.isEmpty())
+        return false;
+
+    persistence::Snapshot snap;
+    snap.name = name.trimmed();
+    for (const auto &w : m_pinned) {
+        persistence::SnapshotEntry e;
+        e.processName = w.processName;
+        e.path        = winpin::processPath(H(w.hwnd));
+        e.commandLine = w.commandLine;
+        e.title       = w.title;
+        e.rect        = winpin::windowRect(H(w.hwnd));
+        e.opacity     = winpin::percentToAlpha(w.opacity);
+        e.group       = w.group;
+        snap.entries.push_back(e);
+    }
+
+    // Saving under an existing name replaces it.
+    QVector<persistence::Snapshot> all = persistence::load().snapshots;
+    auto it = std::find_if(all.begin(), all.end(),
+                           [&](const persistence::Snapshot &s) { return s.name == snap.name; });
+    if (it != all.end())
+        *it = snap;
+    else
+        all.push_back(snap);
+    persistence::saveSnapshots(all);
+    qInfo("Saved snapshot \"%s\" (%d window(s))", qUtf8Printable(snap.name),
+          int(snap.entries.size()));
+    return true;
+}
+
+QStringList PinManager::snapshotNames() const
+{
+    QStringList names;
+    for (const persistence::Snapshot &s : persistence::load().snapshots)
+        names << s.name;
+    return names;
+}
+
+bool PinManager::deleteSnapshot(const QString &name)
+{
+    QVector<persistence::Snapshot> all = persistence::load().snapshots;
+    const auto removed = all.removeIf([&](const persistence::Snapshot &s) {
+        return s.name == name;
+    });
+    if (removed == 0)
+        return false;
+    persistence::saveSnapshots(all);
+    return true;
+}
+
+int PinManager::restoreSnapshot(const QString &name)
+{
+    const QVector<persistence::Snapshot> all = persistence::load().snapshots;
+    auto it = std::find_if(all.cbegin(), all.cend(),
+                           [&](const persistence::Snapshot &s) { return s.name == name; });
+    if (it == all.cend()) {
+        fail(PinError::SnapshotNotFound, tr("There's no snapshot called “%1”.").arg(name));
+        return 0;
+    }
+
+    const QVector<winpin::PinnableWindow> live = winpin::enumerateWindows();
+    QSet<intptr_t> used;
+    int placed = 0;
+
+    for (const persistence::SnapshotEntry &e : it->entries) {
+        // Same matching as a startup restore; a snapshot entry is a superset
+        // of a SavedPin.
+        persistence::SavedPin saved;
+        saved.processName = e.processName;
+        saved.title       = e.title;
+        saved.opacity     = e.opacity;
+        saved.group       = e.group;
+        saved.processPath = e.path;
+        saved.commandLine = e.commandLine;
+
+        const intptr_t match = findRestoreMatch(saved, live, used);
+        if (match != 0) {
+            used.insert(match);
+            winpin::moveWindow(H(match), e.rect);
+            if (isPinned(match) || applySaved(saved, match, /*announce=*/false))
+                ++placed;
+            continue;
+        }
+        if (e.path.isEmpty()) {
+            qWarning("Snapshot entry %s has no exe path; skipping", qUtf8Printable(e.processName));
+            continue;
+        }
+        // Not running: launch it and finish the job once its window shows up.
+        // Started again with its arguments (a profile, a document), not bare.
+        const QStringList args = winpin::splitCommandLine(e.commandLine).mid(1);
+        launchAndPin(e.path, args, 15000, [this, e](intptr_t hwnd) {
+            winpin::moveWindow(H(hwnd), e.rect);
+            const int percent = winpin::alphaToPercent(e.opacity);
+            if (percent < 100)
+                setOpacity(hwnd, percent);
+            if (!e.group.isEmpty())
+                setGroup(hwnd, e.group);
+        });
+    }
+    qInfo("Snapshot \"%s\": placed %d of %d window(s) immediately",
+          qUtf8Printable(name), placed, int(it->entries.size()));
+    return placed;
+}
+
+int PinManager::unpinAll()
+{
+    const QList<intptr_t> all = m_pinned.keys();
+    for (intptr_t h : all)
+        unpin(h, /*announce=*/false);
+    return all.size();
+}
+
+int PinManager::unpinGroup(const QString &group)
+{
+    // Collect first: unpin() removes from m_pinned while we'd be iterating it.
+    QVector<intptr_t> members;
+    for (const auto &w : m_pinned) {
+        if (w.group == group)
+            members.push_back(w.hwnd);
+    }
+    for (intptr_t h : members)
+        unpin(h);
+    return members.size();
+}
+
+QVector<TopmostConflict> PinManager::topmostConflicts() const
+{
+    const QVector<intptr_t> zorder = winpin::topmostWindowsInZOrder();
+    QVector<TopmostConflict> out;
+
+    for (int i = 0; i < zorder.size(); ++i) {
+        const intptr_t h = zorder[i];
+        // The shell's own topmost bars aren't something the user can fight with.
+        if (m_pinned.contains(h) || winpin::isOwnWindow(H(h)) || winpin::isShellWindow(H(h)))
+            continue;
+
+        const QRect rect = winpin::windowRect(H(h));
+        if (rect.isEmpty())
+            continue;
+
+        // Anything later in the list is below this window.
+        for (int j = i + 1; j < zorder.size(); ++j) {
+            auto pinned = m_pinned.constFind(zorder[j]);
+            if (pinned == m_pinned.constEnd())
+                continue;
+            if (!rect.intersects(winpin::windowRect(H(zorder[j]))))
+                continue;
+
+            TopmostConflict c;
+            c.hwnd = h;
+            c.title = winpin::windowTitle(H(h));
+            c.processName = winpin::processName(H(h));
+            c.coveredHwnd = pinned->hwnd;
+            c.coveredTitle = pinned->title;
+            out.push_back(c);
+            break;   // report each foreign window once
+        }
+    }
+    return out;
+}
+
+bool PinManager::demote(intptr_t hwnd)
+{
+    if (m_pinned.contains(hwnd) || !winpin::isValidWindow(H(hwnd)))
+        return false;
+
+    const bool ok = winpin::removeTopmost(H(hwnd)) && !winpin::isTopmost(H(hwnd));
+    if (ok)
+        qInfo("Demoted topmost window %s", qUtf8Printable(winpin::processName(H(hwnd))));
+    else
+        fail(PinError::ElevationRequired,
+             tr("Couldn't demote %1 — it may be running as administrator.")
+                 .arg(winpin::processName(H(hwnd))));
+    return ok;
+}
+
+QVector<winpin::PinnableWindow> PinManager::adoptableWindows() const
+{
+    QVector<winpin::PinnableWindow> out;
+    for (const winpin::PinnableWindow &w : winpin::enumerateWindows()) {
+        if (w.title.isEmpty() || m_pinned.contains(w.hwnd) || !winpin::isTopmost(H(w.hwnd))
+            || winpin::isOwnWindow(H(w.hwnd)) || winpin::isShellWindow(H(w.hwnd)))
+            continue;
+        out.push_back(w);
+    }
+    return out;
+}
+
+int PinManager::adopt(const QVector<intptr_t> &hwnds)
+{
+    int adopted = 0;
+    for (intptr_t hwnd : hwnds) {
+        if (pin(hwnd, /*announce=*/false))
+            ++adopted;
+    }
+    qInfo("Adopted %d already-topmost window(s)", adopted);
+    return adopted;
+}
+
 QVector<PinnedWindow> PinManager::pinnedWindows() const
 {
     QVector<PinnedWindow> out;
@@ -172,35 +1011,483 @@ QVector<PinnedWindow> PinManager::pinnedWindows() const
 
 void PinManager::reenforce()
 {
-    QVector<intptr_t> stale;
+    perf::tick("reenforce");
+    QVector<intptr_t> stale, retitled, reenforced, idle, lostNow;
+    bool hungChanged = false, lostChanged = false;
+    ++m_stats.passes;
+    // Focus is sampled here rather than with an EVENT_SYSTEM_FOREGROUND hook;
+    // a 2 s resolution is plenty for an idle limit measured in minutes.
+    const intptr_t fg = reinterpret_cast<intptr_t>(winpin::foregroundWindow());
+    const QDateTime now = QDateTime::currentDateTime();
+    // The monitor being worked on, for follow-mode pins. PinIt's own windows
+    // (tray menu, quick panel) don't count.
+    const QRect activeWork = fg && !winpin::isOwnWindow(H(fg)) ? winpin::monitorWorkArea(H(fg))
+                                                               : QRect();
     for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
         if (!winpin::isValidWindow(H(it.key()))) {
             stale.push_back(it.key());
             continue;
         }
-        if (!winpin::isTopmost(H(it.key())))
-            winpin::applyTopmost(H(it.key()));
+
+        if (it.key() == fg)
+            it->lastFocused = now;
+        else if (m_idleMinutes > 0 && !it->keepWhenIdle && !it->expiresAt.isValid()
+                 && it->lastFocused.secsTo(now) >= m_idleMinutes * 60)
+            idle.push_back(it.key());
+
+        const bool hung = winpin::isHung(H(it.key()));
+        if (hung != it->hung) {
+            it->hung = hung;
+            hungChanged = true;
+        }
+        if (hung) {
+            ++m_stats.skippedHung;
+            continue;   // nothing we send it will be handled until it recovers
+        }
+        if (it->followMonitor && it->dockEdge.isEmpty() && it.key() != fg
+            && !activeWork.isEmpty()
+            && !winpin::isMinimized(H(it.key()))) {
+            const QRect work = winpin::monitorWorkArea(H(it.key()));
+            if (!work.isEmpty() && work != activeWork) {
+                winpin::moveWindow(H(it.key()), winpin::placeOnMonitor(
+                                                    winpin::windowRect(H(it.key())),
+                                                    work, activeWork));
+            }
+        }
+
+        // Only touch it when it actually lost topmost (one cheap style read per
+        // pin), and post the fix asynchronously — this runs on the UI thread.
+        // "aggressive" pins are also re-raised within the topmost band, so
+        // other always-on-top windows can't settle above them.
+        // "once" pins were set at pin time and are left alone since; deferred
+        // ones belong to another tool. Under the "mark" policy a normal pin
+        // that lost topmost stays off until reassert() (or the app sets it
+        // again itself).
+        const bool lost = !winpin::isTopmost(H(it.key()));
+        const bool aggressive = it->enforcement == QLatin1String("aggressive");
+        if (it->lost != lost && (it->lost || (!it->deferred && !aggressive
+                                              && it->enforcement == QLatin1String("normal")
+                                              && m_lostPolicy == QLatin1String("mark")))) {
+            it->lost = lost;
+            lostChanged = true;
+            if (lost)
+                lostNow.push_back(it.key());
+        }
+        if (m_pinOwned && !it->deferred && !it->lost)
+            raiseOwned(*it);
+        if (!it->deferred && !it->lost && it->enforcement != QLatin1String("once")
+            && (lost || aggressive)) {
+            winpin::applyTopmost(H(it.key()), /*async=*/true);
+            if (lost) {
+                ++m_stats.reapplied;
+                ++it->reenforceCount;
+                reenforced.push_back(it.key());
+            } else {
+                ++m_stats.reraised;
+            }
+        }
+
+        // Titles go stale (browser tabs, editors) — keep ours current so the
+        // list and the saved restore-match reflect what's on screen now.
+        QString title = winpin::windowTitle(H(it.key()));
+        if (!m_titleMarker.isEmpty()) {
+            if (title.startsWith(m_titleMarker))
+                title.remove(0, m_titleMarker.size());
+            else   // the app rewrote its title; put the marker back
+                winpin::setWindowTitle(H(it.key()), m_titleMarker + title);
+        }
+        if (title != it->title) {
+            it->title = title;
+            retitled.push_back(it.key());
+        }
+    }
+
+    if ((hungChanged || lostChanged) && stale.isEmpty()) {
+        emit pinsChanged();   // the list greys out hung rows and flags lost ones
+        emitStateChanged();
+    }
+
+    if (!lostNow.isEmpty()) {
+        const QString culprit = fg && !winpin::isOwnWindow(H(fg)) ? winpin::processName(H(fg))
+                                                                  : QString();
+        for (intptr_t h : lostNow) {
+            const PinnedWindow &w = m_pinned[h];
+            qInfo("%s lost topmost (foreground: %s); leaving it off",
+                  qUtf8Printable(w.title), qUtf8Printable(culprit));
+            emit pinLost(h, w.title, w.processName, culprit);
+        }
+    }
+
+    for (intptr_t h : reenforced)
+        emit topmostReenforced(h, m_pinned.value(h).title, m_pinned.value(h).processName);
+
+    for (intptr_t h : retitled)
+        emit titleChanged(h, m_pinned.value(h).title);
+    if (!retitled.isEmpty()) {
+        schedulePersist();
+        emitStateChanged();
+    }
+
+    for (intptr_t h : idle) {
+        qInfo("Unpinning %s: not focused for %d minute(s)",
+              qUtf8Printable(m_pinned.value(h).title), m_idleMinutes);
+        expire(h);
     }
 
     if (!stale.isEmpty()) {
-        for (intptr_t h : stale)
-            m_pinned.remove(h);
+        for (intptr_t h : stale) {
+            const PinnedWindow gone = m_pinned.take(h);
+            qInfo("Pinned window closed: %s (%s)", qUtf8Printable(gone.title),
+                  qUtf8Printable(gone.processName));
+            emit pinClosed(h, gone.title, gone.processName);
+            if (gone.autoRestart && !gone.launchPath.isEmpty())
+                watchForExit(gone);
+        }
         persist();
         updateTimer();
+        updateExpiryTimer();
         emit pinsChanged();
+        emitStateChanged();
+    }
+}
+
+void PinManager::tickExpiries()
+{
+    const QDateTime now = QDateTime::currentDateTime();
+    QVector<intptr_t> expired;
+    for (auto it = m_pinned.cbegin(); it != m_pinned.cend(); ++it) {
+        if (!it->expiresAt.isValid())
+            continue;
+        const int left = int(qMax<qint64>(0, now.secsTo(it->expiresAt)));
+        emit pinCountdown(it.key(), left);
+        if (left == 0)
+            expired.push_back(it.key());
+    }
+    for (intptr_t h : expired) {
+        qInfo("Temporary pin ran out: %s", qUtf8Printable(m_pinned.value(h).title));
+        expire(h);
     }
 }
 
+void PinManager::expire(intptr_t hwnd)
+{
+    const PinnedWindow w = m_pinned.value(hwnd);
+    unpin(hwnd, /*announce=*/false);
+    emit pinExpired(hwnd, w.title, w.processName);
+}
+
+bool PinManager::setFollowMonitor(intptr_t hwnd, bool enabled)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+    it->followMonitor = enabled;
+    persist();
+    emitStateChanged();
+    return true;
+}
+
+bool PinManager::setDockEdge(intptr_t hwnd, const QString &edge)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+    it->dockEdge = edge;
+    persist();
+    emitStateChanged();
+    return true;
+}
+
+bool PinManager::setEnforcementPolicy(intptr_t hwnd, const QString &policy)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+    if (policy != QLatin1String("aggressive") && policy != QLatin1String("normal")
+        && policy != QLatin1String("once"))
+        return false;
+    it->enforcement = policy;
+    persist();
+    emitStateChanged();
+    return true;
+}
+
+void PinManager::setBlockedProcesses(const QStringList &patterns)
+{
+    m_blocked = wildcards(patterns);
+}
+
+bool PinManager::isBlocked(const QString &processName) const
+{
+    return matchesAny(m_blocked, processName);
+}
+
+void PinManager::setAppCompat(const QHash<QString, persistence::AppCompat> &table)
+{
+    m_compat.clear();
+    m_compatWildcards.clear();
+    for (auto it = table.begin(); it != table.end(); ++it) {
+        if (it.key().contains(QLatin1Char('*')) || it.key().contains(QLatin1Char('?')))
+            m_compatWildcards.push_back({wildcards({it.key()}).first(), it.value()});
+        else
+            m_compat.insert(it.key().toLower(), it.value());
+    }
+}
+
+persistence::AppCompat PinManager::compatFor(const QString &processName) const
+{
+    const auto exact = m_compat.constFind(processName.toLower());
+    if (exact != m_compat.constEnd())
+        return exact.value();
+    for (const auto &entry : m_compatWildcards) {
+        if (entry.first.match(processName).hasMatch())
+            return entry.second;
+    }
+    return {};
+}
+
+void PinManager::setPinOwnedWindows(bool enabled)
+{
+    if (m_pinOwned == enabled)
+        return;
+    m_pinOwned = enabled;
+    for (PinnedWindow &w : m_pinned) {
+        if (enabled && !w.deferred)
+            raiseOwned(w);
+        else if (!enabled)
+            releaseOwned(w);
+    }
+}
+
+void PinManager::setOpacitySuspended(bool suspended)
+{
+    if (m_opacitySuspended == suspended)
+        return;
+    m_opacitySuspended = suspended;
+    // A fade still running would carry on setting the opacity just put back.
+    if (suspended) {
+        for (const intptr_t hwnd : m_fades.keys())
+            finishFade(hwnd);
+        m_fadeTimer->stop();
+    }
+    for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
+        if (!it->opacityChanged || !winpin::isValidWindow(H(it.key())))
+            continue;
+        if (suspended)
+            winpin::restoreOpacity(H(it.key()), it->wasLayered);
+        else
+            winpin::setOpacityPercent(H(it.key()), it->opacity);
+    }
+}
+
+void PinManager::raiseOwned(PinnedWindow &w)
+{
+    // Forget dialogs that have closed — their handles get reused.
+    for (auto o = w.owned.begin(); o != w.owned.end();) {
+        if (winpin::isValidWindow(H(*o)))
+            ++o;
+        else
+            o = w.owned.erase(o);
+    }
+    for (intptr_t o : winpin::ownedWindows(H(w.hwnd))) {
+        if (w.owned.contains(o) || m_pinned.contains(o))
+            continue;   // already ours, or pinned in its own right
+        w.owned.insert(o);
+        if (!winpin::isTopmost(H(o)))
+            winpin::applyTopmost(H(o), /*async=*/true);
+    }
+}
+
+void PinManager::releaseOwned(PinnedWindow &w)
+{
+    for (intptr_t o : std::as_const(w.owned)) {
+        if (winpin::isValidWindow(H(o)) && !m_pinned.contains(o))
+            winpin::removeTopmost(H(o), /*async=*/true);
+    }
+    w.owned.clear();
+}
+
+bool PinManager::reassert(intptr_t hwnd)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end() || !winpin::isValidWindow(H(hwnd)))
+        return false;
+    winpin::applyTopmost(H(hwnd));
+    if (it->lost) {
+        it->lost = false;
+        emit pinsChanged();
+        emitStateChanged();
+    }
+    return true;
+}
+
+int PinManager::reapplyAll()
+{
+    int touched = 0;
+    bool lostChanged = false;
+    for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
+        if (it->deferred || !winpin::isValidWindow(H(it.key())) || winpin::isHung(H(it.key())))
+            continue;
+        winpin::applyTopmost(H(it.key()), /*async=*/true);
+        if (it->opacityChanged && !m_opacitySuspended)
+            winpin::setOpacityPercent(H(it.key()), it->opacity);
+        if (m_pinOwned)
+            raiseOwned(*it);
+        if (it->lost) {
+            it->lost = false;
+            lostChanged = true;
+        }
+        ++touched;
+    }
+    if (lostChanged) {
+        emit pinsChanged();
+        emitStateChanged();
+    }
+    reenforce();   // prune pins whose windows went away meanwhile
+    return touched;
+}
+
+int PinManager::handleDisplayChange()
+{
+    constexpr int kMinVisiblePx = 64;   // enough of it showing to grab and drag back
+    const QVector<QRect> monitors = winpin::monitorWorkAreas();
+    int moved = 0;
+    for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
+        if (!it->dockEdge.isEmpty() || !winpin::isValidWindow(H(it.key()))
+            || winpin::isMinimized(H(it.key())) || winpin::isHung(H(it.key())))
+            continue;
+        const QRect rect = winpin::windowRect(H(it.key()));
+        const QRect rescued = winpin::rescueRect(rect, monitors, kMinVisiblePx);
+        if (rescued != rect && winpin::moveWindow(H(it.key()), rescued)) {
+            qInfo("Moved %s back on screen after a display change",
+                  qUtf8Printable(it->processName));
+            ++moved;
+        }
+    }
+    reapplyAll();
+    emit displayTopologyChanged(monitors, moved);
+    return moved;
+}
+
+bool PinManager::setTitlePattern(intptr_t hwnd, const QString &pattern)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+    it->titlePattern = pattern.trimmed();
+    persist();
+    emitStateChanged();
+    return true;
+}
+
+bool PinManager::setAutoRestart(intptr_t hwnd, bool enabled)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+    if (enabled && it->launchPath.isEmpty()) {
+        it->launchPath = winpin::processPath(H(hwnd));
+        if (it->launchPath.isEmpty())
+            return false;
+    }
+    it->autoRestart = enabled;
+    persist();
+    emitStateChanged();
+    return true;
+}
+
+void PinManager::watchForExit(const PinnedWindow &gone)
+{
+    // The window goes before the process does, and a crashing app can linger
+    // behind Windows' error report dialog. If the process outlives the wait,
+    // the app only closed that window (to the tray, say) — leave it be.
+    auto *poll = new QTimer(this);
+    poll->setInterval(1000);
+    QElapsedTimer waited;
+    waited.start();
+    connect(poll, &QTimer::timeout, this, [this, poll, gone, waited]() {
+        if (winpin::isProcessRunning(gone.pid)) {
+            if (!waited.hasExpired(60000))
+                return;
+            qInfo("%s kept running without its window; not restarting it",
+                  qUtf8Printable(gone.processName));
+        } else {
+            relaunch(gone);
+        }
+        poll->stop();
+        poll->deleteLater();
+    });
+    poll->start();
+}
+
+void PinManager::relaunch(const PinnedWindow &gone)
+{
+    // Crash-loop guard: give up after a few restarts in quick succession.
+    const QDateTime now = QDateTime::currentDateTime();
+    PinnedWindow next = gone;
+    next.restarts = gone.lastRestart.isValid() && gone.lastRestart.secsTo(now) < 300
+                        ? gone.restarts + 1 : 1;
+    if (next.restarts > 3) {
+        fail(PinError::LaunchFailed,
+             tr("%1 keeps closing — PinIt stopped restarting it.").arg(gone.processName));
+        return;
+    }
+    next.lastRestart = now;
+    qInfo("Restarting %s (%s)", qUtf8Printable(gone.processName),
+          qUtf8Printable(gone.launchPath));
+    launchAndPin(gone.launchPath, gone.launchArgs, 30000, [this, next](intptr_t hwnd) {
+        PinnedWindow &w = m_pinned[hwnd];
+        w.autoRestart = true;
+        w.restarts = next.restarts;
+        w.lastRestart = next.lastRestart;
+        w.followMonitor = next.followMonitor;
+        w.keepWhenIdle = next.keepWhenIdle;
+        w.enforcement = next.enforcement;
+        if (next.opacity < 100)
+            setOpacity(hwnd, next.opacity);
+        if (!next.group.isEmpty())
+            setGroup(hwnd, next.group);
+        if (!next.dockEdge.isEmpty())
+            setDockEdge(hwnd, next.dockEdge);
+        persist();
+        emit appRestarted(hwnd, next.processName);
+    });
+}
+
+bool PinManager::setKeepWhenIdle(intptr_t hwnd, bool keep)
+{
+    auto it = m_pinned.find(hwnd);
+    if (it == m_pinned.end())
+        return false;
+    it->keepWhenIdle = keep;
+    persist();
+    emitStateChanged();
+    return true;
+}
+
 void PinManager::restoreAllWindows()
 {
+    // No time to animate on the way out.
+    for (const intptr_t hwnd : m_fades.keys())
+        finishFade(hwnd);
+
     int restored = 0;
     for (auto it = m_pinned.begin(); it != m_pinned.end(); ++it) {
         if (winpin::isValidWindow(H(it.key()))) {
-            if (it->opacityChanged)
+            const bool responding = winpin::isResponding(H(it.key()));
+            if (it->opacityChanged && responding)
                 winpin::restoreOpacity(H(it.key()), it->wasLayered);
-            winpin::removeTopmost(H(it.key()));
+            if (!it->deferred)
+                winpin::removeTopmost(H(it.key()), /*async=*/!responding);
+            if (responding) {
+                removeMarker(it.key());
+                if (it->backdrop)
+                    winpin::setBackdrop(H(it.key()), QStringLiteral("none"));
+                restorePlacement(*it);
+            }
             ++restored;
         }
+        releaseOwned(*it);
     }
 
     if (m_sessionEnding) {
@@ -219,58 +1506,268 @@ void PinManager::restoreAllWindows()
     // are preserved because persist() only rewrites the pin list). Closing to
     // the tray never reaches here — this runs only on a real quit (aboutToQuit).
     m_pinned.clear();
+    m_pending.clear();
+    m_awaitingRestore.clear();
     persist();
     updateTimer();
+    updateExpiryTimer();
+    updateRetryTimer();
+    emitStateChanged();
     qInfo("Restored and cleared %d pinned window(s) on manual quit", restored);
 }
 
+void PinManager::emitStateChanged()
+{
+    emit pinStateChanged(pinnedWindows());
+}
+
+void PinManager::fail(PinError code, const QString &message)
+{
+    qWarning("[%s] %s", qUtf8Printable(errorCode(code)), qUtf8Printable(message));
+    emit errorOccurred(code, message);
+}
+
 void PinManager::persist() const
 {
     // Cancel any debounced write — this immediate persist supersedes it.
     if (m_persistTimer)
         m_persistTimer->stop();
+    if (!m_saving)
+        return;
 
     QVector<persistence::SavedPin> pins;
     pins.reserve(m_pinned.size());
     for (const auto &w : m_pinned) {
+        if (w.expiresAt.isValid())
+            continue;   // a temporary pin shouldn't come back after a restart
         persistence::SavedPin sp;
         sp.processName = w.processName;
-        sp.title       = w.title;
+        // Save the live title, not the one captured at pin time — without the
+        // title marker, which won't be there for the restore to match.
+        sp.title       = winpin::isValidWindow(H(w.hwnd))
+                             ? unmarked(winpin::windowTitle(H(w.hwnd)))
+                             : w.title;
         sp.opacity     = winpin::percentToAlpha(w.opacity);
+        sp.group       = w.group;
+        sp.keepWhenIdle = w.keepWhenIdle;
+        sp.followMonitor = w.followMonitor;
+        sp.dockEdge    = w.dockEdge;
+        sp.restoreRect = w.restoreRect;
+        sp.autoRestart = w.autoRestart;
+        sp.enforcement = w.enforcement;
+        sp.launchPath  = w.launchPath;
+        sp.launchArgs  = w.launchArgs;
+        sp.processPath = w.processPath;
+        sp.commandLine = w.commandLine;
+        sp.titlePattern = w.titlePattern;
+        sp.automationId = w.automationId;
         pins.push_back(sp);
     }
+    pins += m_pending;   // keep unmatched pins for the next restore attempt
+    for (const persistence::SavedPin &sp : std::as_const(m_awaitingRestore))
+        pins.push_back(sp);
     persistence::savePins(pins);
 }
 
-void PinManager::restoreSaved()
+QString PinManager::restoreKey(const persistence::SavedPin &pin)
+{
+    return pin.processName + QLatin1Char(':') + pin.title;
+}
+
+void PinManager::dismissPendingRestore(const QString &key)
 {
+    for (int i = 0; i < m_pending.size(); ++i) {
+        if (restoreKey(m_pending[i]) == key) {
+            m_pending.remove(i);
+            persist();
+            updateRetryTimer();
+            emit pendingRestoresChanged();
+            return;
+        }
+    }
+}
+
+RestoreStats PinManager::restoreSaved()
+{
+    RestoreStats stats;
     const persistence::SavedState state = persistence::load();
+    stats.saved = state.pins.size();
     if (state.pins.isEmpty())
-        return;
+        return stats;
 
     const QVector<winpin::PinnableWindow> live = winpin::enumerateWindows();
     QSet<intptr_t> used;
 
+    bool dropped = false;
     for (const persistence::SavedPin &saved : state.pins) {
-        // Prefer an exact process+title match, else first unused window of
-        // the same process — mirrors the Rust restore() heuristic.
-        intptr_t match = 0;
-        for (const auto &w : live) {
-            if (w.processName != saved.processName || used.contains(w.hwnd))
-                continue;
-            if (!saved.title.isEmpty() && w.title == saved.title) {
-                match = w.hwnd;
-                break;
-            }
-            if (match == 0)
-                match = w.hwnd;   // fallback candidate, keep scanning for exact
+        if (isBlocked(saved.processName)) {
+            // Saved before the app was blocked; no point waiting for its window.
+            qWarning("[audit] Dropped the saved pin of %s: on the never-pin list",
+                     qUtf8Printable(saved.processName));
+            emit pinBlocked(0, saved.title, saved.processName);
+            --stats.saved;
+            dropped = true;
+            continue;
         }
-
-        if (match != 0 && pin(match, /*announce=*/false)) {
+        const intptr_t match = findRestoreMatch(saved, live, used);
+        if (match != 0 && applySaved(saved, match, /*announce=*/false)) {
             used.insert(match);
-            const int percent = winpin::alphaToPercent(saved.opacity);
-            if (percent < 100)
-                setOpacity(match, percent);
+            ++stats.restored;
+        } else {
+            m_pending.push_back(saved);
+        }
+    }
+    qInfo("Restored %d of %d saved pin(s)", stats.restored, stats.saved);
+
+    if (!m_pending.isEmpty() || dropped) {
+        persist();   // the pin() calls above wrote the file before all pending were known
+        updateRetryTimer();
+        emit pendingRestoresChanged();
+    }
+    return stats;
+}
+
+int PinManager::retryRestore()
+{
+    if (m_pending.isEmpty())
+        return 0;
+
+    const QVector<winpin::PinnableWindow> live = winpin::enumerateWindows();
+    // Skip windows that are already pinned, or that a previous retry failed to
+    // pin (elevated) — otherwise we'd report the same error every few seconds.
+    // Closed ones are forgotten; their handles can come back as new windows.
+    QSet<intptr_t> open;
+    for (const winpin::PinnableWindow &w : live)
+        open.insert(w.hwnd);
+    m_unpinnable.intersect(open);
+    QSet<intptr_t> used = m_unpinnable;
+    for (auto it = m_pinned.cbegin(); it != m_pinned.cend(); ++it)
+        used.insert(it.key());
+
+    QVector<persistence::SavedPin> still;
+    int restored = 0;
+    const QVector<persistence::SavedPin> pending = m_pending;
+    for (const persistence::SavedPin &saved : pending) {
+        const intptr_t match = findRestoreMatch(saved, live, used);
+        if (match == 0) {
+            still.push_back(saved);
+            continue;
         }
+        used.insert(match);
+        if (applySaved(saved, match, /*announce=*/true)) {
+            ++restored;
+        } else {
+            m_unpinnable.insert(match);
+            still.push_back(saved);
+        }
+    }
+    m_pending = still;
+
+    if (restored > 0) {
+        // pin() persisted mid-loop with the old pending list; write the final one.
+        qInfo("Re-pinned %d pending window(s)", restored);
+        persist();
+        updateRetryTimer();
+        emit pendingRestoresChanged();
+    }
+    return restored;
+}
+
+intptr_t PinManager::findSlotWindow(const persistence::PinSlot &slot) const
+{
+    if (slot.processName.isEmpty())
+        return 0;
+    intptr_t anyPinned = 0;
+    for (const PinnedWindow &w : m_pinned) {
+        if (w.processName.compare(slot.processName, Qt::CaseInsensitive) != 0)
+            continue;
+        if (w.title == slot.title)
+            return w.hwnd;
+        if (anyPinned == 0)
+            anyPinned = w.hwnd;
     }
+    persistence::SavedPin saved;
+    saved.processName = slot.processName;
+    saved.title = slot.title;
+    const intptr_t open = findRestoreMatch(saved, winpin::enumerateWindows(), {});
+    if (open && unmarked(winpin::windowTitle(H(open))) == slot.title)
+        return open;
+    return anyPinned ? anyPinned : open;
+}
+
+intptr_t PinManager::findRestoreMatch(const persistence::SavedPin &saved,
+                                      const QVector<winpin::PinnableWindow> &live,
+                                      const QSet<intptr_t> &used) const
+{
+    // Prefer an exact process+title match, else first unused window of
+    // the same process — mirrors the Rust restore() heuristic. A saved exe
+    // path rules out other installs of the same exe name (an unreadable path
+    // doesn't), and a matching command line breaks ties between fallbacks.
+    // A title pattern must match outright. With UIA matching on, the
+    // AutomationId outranks the command line.
+    intptr_t match = 0, byCommandLine = 0, byAutomationId = 0;
+    for (const auto &w : live) {
+        if (w.processName != saved.processName || used.contains(w.hwnd))
+            continue;
+        if (!saved.titlePattern.isEmpty()
+            && !unmarked(w.title).contains(saved.titlePattern, Qt::CaseInsensitive))
+            continue;
+        if (!saved.processPath.isEmpty()) {
+            const QString path = winpin::processPath(H(w.hwnd));
+            if (!path.isEmpty() && path.compare(saved.processPath, Qt::CaseInsensitive) != 0)
+                continue;
+        }
+        if (!saved.title.isEmpty() && w.title == saved.title)
+            return w.hwnd;
+        if (byAutomationId == 0 && m_uiaMatching && !saved.automationId.isEmpty()
+            && uia::identify(H(w.hwnd)).automationId == saved.automationId)
+            byAutomationId = w.hwnd;
+        if (byCommandLine == 0 && !saved.commandLine.isEmpty()
+            && winpin::processCommandLine(H(w.hwnd)) == saved.commandLine)
+            byCommandLine = w.hwnd;
+        if (match == 0)
+            match = w.hwnd;   // fallback candidate, keep scanning for exact
+    }
+    if (byAutomationId)
+        return byAutomationId;
+    return byCommandLine ? byCommandLine : match;
+}
+
+bool PinManager::applySaved(const persistence::SavedPin &saved, intptr_t hwnd, bool announce)
+{
+    if (!pin(hwnd, announce)) {
+        if (!m_awaitingConfirm.contains(hwnd) && !m_awaitingCoexist.contains(hwnd))
+            return false;
+        // Waiting on the user's answer, which isn't a miss: the rest of the
+        // saved pin is applied once they've said yes (resumePin()).
+        m_awaitingRestore.insert(hwnd, saved);
+        return true;
+    }
+    const int percent = winpin::alphaToPercent(saved.opacity);
+    if (percent < 100)
+        setOpacity(hwnd, percent);
+    if (!saved.group.isEmpty())
+        setGroup(hwnd, saved.group);
+    if (saved.keepWhenIdle)
+        setKeepWhenIdle(hwnd, true);
+    if (saved.followMonitor)
+        setFollowMonitor(hwnd, true);
+    if (!saved.dockEdge.isEmpty())
+        setDockEdge(hwnd, saved.dockEdge);
+    if (!saved.launchPath.isEmpty()) {
+        m_pinned[hwnd].launchPath = saved.launchPath;
+        m_pinned[hwnd].launchArgs = saved.launchArgs;
+    }
+    if (saved.autoRestart)
+        setAutoRestart(hwnd, true);
+    if (saved.enforcement != QLatin1String("normal"))
+        setEnforcementPolicy(hwnd, saved.enforcement);
+    if (!saved.titlePattern.isEmpty())
+        setTitlePattern(hwnd, saved.titlePattern);
+    // pin() just saw the window already compact; the real original is the saved one.
+    if (!saved.restoreRect.isEmpty() && !m_pinned[hwnd].restoreRect.isEmpty()) {
+        m_pinned[hwnd].restoreRect = saved.restoreRect;
+        persist();
+    }
+    return true;
 }
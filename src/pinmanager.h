@@ -6,10 +6,21 @@
 // and re-enforcement behaviour. UI and tray observe it via signals.
 //
 #include <QObject>
+#include <QDateTime>
+#include <QElapsedTimer>
 #include <QHash>
+#include <QPair>
+#include <QRegularExpression>
+#include <QSet>
 #include <QString>
+#include <QStringList>
 #include <QVector>
 #include <cstdint>
+#include <functional>
+
+#include "persistence.h"
+#include "pinerror.h"
+#include "winpin.h"
 
 class QTimer;
 
@@ -20,6 +31,76 @@ struct PinnedWindow {
     int      opacity = 100;        // percent
     bool     wasLayered = false;   // window had WS_EX_LAYERED before we pinned it
     bool     opacityChanged = false;  // we changed its opacity, so undo it on unpin
+    QString  group;                // user-defined group ("Monitoring"), empty if none
+    bool     hung = false;         // not responding as of the last re-enforce pass
+    QDateTime expiresAt;           // temporary pin: unpinned at this time; invalid if not
+
+    // Per-pin stats for Diagnostics — a high reenforceCount singles out an app
+    // that keeps fighting for topmost.
+    QDateTime pinnedAt;
+    QDateTime lastFocused;         // last time a re-enforce pass saw it in the foreground
+    bool      keepWhenIdle = false;   // exempt from the idle auto-unpin
+    bool      followMonitor = false;  // jumps to whichever monitor is in use
+    QString   dockEdge;               // "left"/"right"/"top"/"bottom" (see EdgeDock)
+    QRect     restoreRect;            // pre-compact placement, put back on unpin
+    qint64    pid = 0;
+    // Full exe path and command line at pin time, so a restore can tell two
+    // installs (or two profiles) of the same exe apart. Empty if unreadable.
+    QString   processPath;
+    QString   commandLine;
+    // Restore only matches windows whose title contains this — picks the
+    // right window of a browser, where the process name says nothing.
+    QString   titlePattern;
+    QString   automationId;        // UI Automation id, when UIA matching is on
+    // Watchdog: relaunch the app (launchPath + launchArgs) when its process
+    // exits, and pin the new window. launchPath is the command line for pins
+    // made by launchAndPin(), else the exe path.
+    bool        autoRestart = false;
+    QString     launchPath;
+    QStringList launchArgs;
+    int         restarts = 0;         // recent relaunches, for the crash-loop guard
+    QDateTime   lastRestart;
+    QString     enforcement = QStringLiteral("normal");   // "aggressive", "normal" or "once"
+    // Another always-on-top tool holding the window too (winpin::topmostManager),
+    // and whether PinIt leaves topmost to it: no re-enforcing, and unpinning
+    // doesn't clear the flag.
+    QString     sharedWith;
+    bool        deferred = false;
+    bool        lost = false;         // topmost was taken away and left off ("mark" policy)
+    bool        backdrop = false;     // we gave it a blur/acrylic backdrop; undone on unpin
+    // Owned popups (dialogs, palettes) PinIt made topmost along with it;
+    // released again on unpin.
+    QSet<intptr_t> owned;
+    int       reenforceCount = 0;
+    int       opacityChanges = 0;
+    // setOpacity()'s last isResponding() answer, and when it was asked.
+    bool      responding = true;
+    QDateTime respondingCheckedAt;
+};
+
+// A foreign always-on-top window sitting above (and overlapping) one of ours —
+// e.g. a Zoom/Teams overlay that wins the topmost fight.
+struct TopmostConflict {
+    intptr_t hwnd = 0;
+    QString  title;
+    QString  processName;
+    intptr_t coveredHwnd = 0;      // the pinned window it overlaps
+    QString  coveredTitle;
+};
+
+// Outcome of restoreSaved(): how many saved pins found a live window.
+struct RestoreStats {
+    int saved = 0;
+    int restored = 0;
+};
+
+// Counters for the periodic topmost re-enforcement, shown in Diagnostics. A
+// high reapplied count means another app keeps stealing topmost from a pin.
+struct ReenforceStats {
+    quint64 passes = 0;        // timer ticks that ran a pass
+    quint64 reapplied = 0;     // SetWindowPos calls to put a pin back on top
+    quint64 reraised = 0;      // ...and to raise an "aggressive" pin that was still on top
+    quint64 skippedHung = 0;   // windows left alone because they weren't responding
 };
 
 class PinManager : public QObject
@@ -33,21 +114,226 @@ public:
     // re-pinning a batch of saved windows at startup, which would otherwise
     // fire one sound and one notification per window).
     bool pin(intptr_t hwnd, bool announce = true);
-    bool unpin(intptr_t hwnd);
+    bool unpin(intptr_t hwnd, bool announce = true);
     bool toggle(intptr_t hwnd);
+    int  unpinAll();           // quietly; returns how many were unpinned
+
+    // Start an app and pin its first top-level window once it appears (a new
+    // window from the launched process, or from a same-named exe for launcher
+    // stubs that hand off to another process). Gives up after timeoutMs. Each
+    // window is taken by one launch only, so launching an exe twice pins two.
+    // onPinned runs once the window is pinned (used to place snapshot windows).
+    bool launchAndPin(const QString &path, const QStringList &args = {},
+                      int timeoutMs = 15000,
+                      std::function<void(intptr_t)> onPinned = nullptr);
     bool isPinned(intptr_t hwnd) const;
 
+    // Pinning a protected process (winpin::isProtectedProcess) asks first:
+    // pin() emits pinConfirmationRequired and returns false, and the answer
+    // comes back through confirmPin(). Connect it queued: the answer pins the
+    // window, which mustn't happen inside the pin() that asked. A saved pin
+    // being restored counts as restored meanwhile, and keeps its settings.
+    // choices are the remembered answers, keyed by lower-cased process name
+    // (see UserSettings::protectedChoices).
+    void setProtectedChoices(const QHash<QString, bool> &choices) { m_protectedChoices = choices; }
+    // Apps an administrator never wants pinned (policy::neverPin()): exe
+    // names, or wildcards like "lob-*.exe". pin() refuses them with
+    // PinError::BlockedByPolicy, and restoreSaved() drops their saved pins;
+    // both emit pinBlocked for the audit trail. Nothing in the UI overrides it.
+    void setBlockedProcesses(const QStringList &patterns);
+    bool isBlocked(const QString &processName) const;
+    // Per-app compact-size rules (see persistence::CompactRule), keyed by
+    // lower-cased process name. Applied when a window of that app is pinned.
+    void setCompactRules(const QHash<QString, persistence::CompactRule> &rules)
+    {
+        m_compactRules = rules;
+    }
+    void confirmPin(intptr_t hwnd, bool allow);
+
+    // A window another topmost tool (PowerToys, DeskPins) already holds:
+    // "defer" pins it without fighting over topmost, "override" pins it as
+    // usual, "ask" emits coexistenceChoiceRequired and waits for
+    // resolveCoexistence() — connect it queued, as pinConfirmationRequired.
+    void setCoexistencePolicy(const QString &policy) { m_coexistence = policy; }
+    void resolveCoexistence(intptr_t hwnd, bool takeOver);
+
+    // What the re-enforce pass does when something else clears a "normal"
+    // pin's topmost flag: "restore" puts it back (topmostReenforced),
+    // "mark" leaves it off, flags the pin as lost and emits pinLost.
+    // reassert() puts a lost pin back on top.
+    void setLostPolicy(const QString &policy) { m_lostPolicy = policy; }
+
+    // Keep a pinned window's dialogs and other owned popups on top with it,
+    // so a modal dialog doesn't open behind other apps. Checked on pin and on
+    // every re-enforce pass.
+    void setPinOwnedWindows(bool enabled);
+
+    // Record each pin's UI Automation id (uia::identify) and use it to pick
+    // between same-app windows at restore when titles don't settle it. Off by
+    // default: every lookup is a cross-process call.
+    void setUiaMatching(bool enabled) { m_uiaMatching = enabled; }
+
+    // Show every pin fully opaque without forgetting its opacity — layered
+    // windows are slow to draw over Remote Desktop. Opacity changes made
+    // meanwhile are kept and shown once this is turned off again.
+    void setOpacitySuspended(bool suspended);
+    bool opacitySuspended() const { return m_opacitySuspended; }
+    // Refuse opacity changes outright (PinError::DisabledByPolicy), for when
+    // an administrator has turned transparency off. Pair with
+    // setOpacitySuspended(true) so pins already faded show opaque.
+    void setOpacityDisabled(bool disabled) { m_opacityDisabled = disabled; }
+    // Per-app workarounds (compat::table()), keyed by lower-cased process
+    // name or a wildcard like "mstsc*.exe"; an exact name wins. pin() takes
+    // the enforcement policy and the delayed re-pin from it; for a no_layering
+    // app setOpacity() refuses with PinError::OpacityUnsupported, so its pins
+    // stay opaque.
+    void setAppCompat(const QHash<QString, persistence::AppCompat> &table);
+    persistence::AppCompat compatFor(const QString &processName) const;
+
+    bool reassert(intptr_t hwnd);
+    // After an unlock or a resume from sleep Windows can drop topmost and
+    // layered-window state without anyone asking: put every pin back on top
+    // (bar deferred ones) and re-apply the opacity PinIt set. Lost pins count
+    // as back, too. Returns how many windows were touched.
+    int reapplyAll();
+    // Monitors were plugged in, unplugged or rearranged: move pins left
+    // off-screen onto a monitor that's still there, put every pin back on top
+    // and emit displayTopologyChanged. Edge-docked pins are meant to hang
+    // off the edge and stay put. Returns how many windows were moved.
+    int handleDisplayChange();
+
+    // Temporary pins: pin hwnd (or keep it pinned) and unpin it again after
+    // seconds, with a pinCountdown every second until then. seconds <= 0 makes
+    // an existing temporary pin permanent. Temporary pins aren't saved.
+    bool pinFor(intptr_t hwnd, int seconds);
+    void setTemporaryPinDuration(int seconds) { m_tempPinSeconds = qMax(1, seconds); }
+    int  temporaryPinDuration() const { return m_tempPinSeconds; }
+
+    // Pin hwnd (or keep it pinned) and set its opacity in one step, for the
+    // "semi-transparent video in the corner" case. The hotkey version works on
+    // the foreground window at pinOpacity().
+    bool pinWithOpacity(intptr_t hwnd, int percent);
+    void pinForegroundWithOpacity();
+    void setPinOpacity(int percent)
+    {
+        m_pinOpacity = qBound(winpin::kMinOpacity, percent, winpin::kMaxOpacity);
+    }
+    int  pinOpacity() const { return m_pinOpacity; }
+
+    // Idle auto-unpin: a pin that hasn't been the foreground window for this
+    // many minutes is unpinned (pinExpired), so a forgotten topmost window
+    // doesn't linger for days. 0 turns it off. Temporary pins keep their own
+    // timer, and setKeepWhenIdle() exempts a single window.
+    void setIdleUnpinMinutes(int minutes) { m_idleMinutes = qMax(0, minutes); }
+    bool setKeepWhenIdle(intptr_t hwnd, bool keep);
+
+    // Follow mode: the window moves to whichever monitor the foreground window
+    // is on (same relative spot), so a pinned notes or timer window stays in
+    // view on multi-monitor setups. Checked on each re-enforce pass.
+    bool setFollowMonitor(intptr_t hwnd, bool enabled);
+    // Edge docking: "left", "right", "top" or "bottom" makes the window
+    // auto-hide at that screen edge (EdgeDock does the sliding); empty undocks.
+    bool setDockEdge(intptr_t hwnd, const QString &edge);
+    // Keep-alive for kiosk-style setups: if the pinned app exits (crash or
+    // close), start it again and pin its new window. False if the exe path
+    // can't be read (e.g. an elevated app).
+    bool setAutoRestart(intptr_t hwnd, bool enabled);
+    // How hard the re-enforce pass fights for this window's topmost spot:
+    //  "aggressive" — re-raise it above other topmost windows on every pass
+    //  "normal"     — re-apply topmost only once something has stripped it
+    //  "once"       — set it when pinning and never touch it again
+    // Some apps misbehave when topmost is reasserted over and over.
+    bool setEnforcementPolicy(intptr_t hwnd, const QString &policy);
+    // Restore (and the pending-restore retry) only takes a window of this app
+    // whose title contains pattern, case-insensitively — e.g. the Chrome
+    // window showing "YouTube Music". Empty goes back to the usual matching.
+    bool setTitlePattern(intptr_t hwnd, const QString &pattern);
+
+    // The open window of a pin slot: the app's window with the slot's title,
+    // pinned ones first; else one of its pins; else its best restore match.
+    // 0 if the app has no window open.
+    intptr_t findSlotWindow(const persistence::PinSlot &slot) const;
+
     // Hotkey entry points — operate on whatever window is focused.
     void toggleForeground();
+    // Pin the foreground window for temporaryPinDuration(), or unpin it if it
+    // is already pinned.
+    void toggleForegroundTemporary();
+    void toggleUnderCursor();   // for windows that refuse focus (overlays)
+    // The most recently pinned window that's still open, whether or not it's
+    // pinned now (0 if none), so one key can unpin it and pin it again.
+    intptr_t lastPinned();
+    void toggleLastPinned();
+    bool focusLastPinned();     // bring it to the front
+
+    // Hold-to-preview: pin the foreground window while the pin key is held,
+    // unpin on release. A window that was already pinned is left alone.
+    void beginPreview();
+    void endPreview();
     void adjustForegroundOpacity(int deltaPercent);
+    void adjustOpacity(intptr_t hwnd, int deltaPercent);   // pinned windows only
 
     bool setOpacity(intptr_t hwnd, int percent);
+    // Opacity changes (and the return to opaque on unpin) ease in over ms
+    // instead of jumping. 0 turns the fade off.
+    void setFadeDuration(int ms) { m_fadeMs = qMax(0, ms); }
+    // Visual confirmation on pin: "none", "flash" or "border-pulse".
+    void setPinFeedback(const QString &mode) { m_pinFeedback = mode; }
+    // Blur or acrylic behind pinned windows: "none", "blur" or "acrylic",
+    // stepped down to what this Windows supports (platform::backdropFor).
+    // Applies to the current pins as well as new ones.
+    void setBackdrop(const QString &kind);
+    QString backdrop() const { return m_backdrop; }   // after the fallback
+    // Prefix every pinned window's title with marker (e.g. "📌 ") while it's
+    // pinned; apps that rewrite their title get it back on the next re-enforce
+    // pass. Empty removes it. PinnedWindow::title never includes the marker.
+    void setTitleMarker(const QString &marker);
+
+    // Groups — user-named sets of pins acted on together. An empty name removes
+    // the window from its group. The batch calls return how many pins they hit.
+    bool        setGroup(intptr_t hwnd, const QString &group);
+    QStringList groups() const;
+    int         setGroupOpacity(const QString &group, int percent);
+    int         unpinGroup(const QString &group);
+
+    // Conflict detection: walk the topmost Z-order and report non-PinIt topmost
+    // windows that sit above a pinned window they overlap. demote() strips their
+    // topmost flag so our pin wins again.
+    QVector<TopmostConflict> topmostConflicts() const;
+    bool demote(intptr_t hwnd);
+
+    // Workspace snapshots — the whole pinned layout (process, exe, position,
+    // opacity, group) saved under a name. Restoring one re-pins windows that
+    // are open, launches apps that aren't, and moves each back into place.
+    // restoreSnapshot() returns how many windows it placed right away.
+    bool        saveSnapshot(const QString &name);
+    QStringList snapshotNames() const;
+    int         restoreSnapshot(const QString &name);
+    bool        deleteSnapshot(const QString &name);
+
+    // Adoption: windows that are already always on top without PinIt (the app
+    // set it itself, or another tool did), so they can be brought into the
+    // list and unpinned or adjusted from here. adopt() pins them quietly and
+    // returns how many it took.
+    QVector<winpin::PinnableWindow> adoptableWindows() const;
+    int adopt(const QVector<intptr_t> &hwnds);
 
     QVector<PinnedWindow> pinnedWindows() const;
     int pinnedCount() const { return m_pinned.size(); }
+    ReenforceStats reenforceStats() const { return m_stats; }
 
     // Restore pins saved from a previous session (called once at startup).
-    void restoreSaved();
+    RestoreStats restoreSaved();
+
+    // Saved pins whose window wasn't open at restore time. They stay saved
+    // (so they survive another restart) until matched or dismissed.
+    QVector<persistence::SavedPin> pendingRestores() const { return m_pending; }
+    void dismissPendingRestore(const QString &key);
+    // Re-run the restore matching for pending pins; returns how many were
+    // re-pinned. Also runs on its own every few seconds while any are pending,
+    // for ten minutes after the list last changed.
+    int  retryRestore();
+    static QString restoreKey(const persistence::SavedPin &pin);   // "process:title"
 
     // On exit: undo always-on-top + opacity on every pinned foreign window so
     // they aren't left stuck topmost/translucent. After a manual quit the pins
@@ -60,23 +346,136 @@ public:
     // in main). Makes the next restoreAllWindows() keep the saved pins so the
     // advertised "pins come back after a restart" behaviour works.
     void markSessionEnding() { m_sessionEnding = true; }
+    // Off in safe mode: the pin list on disk is left as it was, so the pins
+    // safe mode skipped restoring are still there for the next normal start.
+    void setSavingEnabled(bool enabled) { m_saving = enabled; }
 
 signals:
     void pinsChanged();
     void pinToggled(bool isPinned, const QString &title, const QString &process);
     void opacityChanged(intptr_t hwnd, int percent);
-    void errorOccurred(const QString &message);
+    void titleChanged(intptr_t hwnd, const QString &title);
+    // A pinned window closed and was dropped from the list (stale cleanup).
+    void pinClosed(intptr_t hwnd, const QString &title, const QString &process);
+    // Something took topmost away from a pin and the re-enforce pass put it back.
+    void topmostReenforced(intptr_t hwnd, const QString &title, const QString &process);
+    // Same, under the "mark" policy: the pin was left off top. foreground is
+    // the process that had focus when it was noticed — usually the culprit.
+    void pinLost(intptr_t hwnd, const QString &title, const QString &process,
+                 const QString &foreground);
+    void pendingRestoresChanged();
+    void pinConfirmationRequired(intptr_t hwnd, const QString &title, const QString &process);
+    void coexistenceChoiceRequired(intptr_t hwnd, const QString &title, const QString &process,
+                                   const QString &tool);
+    // Once a second per temporary pin; it is unpinned when this reaches 0.
+    void pinCountdown(intptr_t hwnd, int secondsLeft);
+    // A temporary pin ran out, or an idle pin was unpinned. Sent instead of
+    // pinToggled(false, …), after the window is unpinned.
+    void pinExpired(intptr_t hwnd, const QString &title, const QString &process);
+    // The watchdog relaunched an auto-restart app and pinned its new window.
+    void appRestarted(intptr_t hwnd, const QString &process);
+    // After handleDisplayChange(): every monitor's work area as it is now,
+    // and how many pins had to be brought back on screen.
+    void displayTopologyChanged(const QVector<QRect> &monitors, int moved);
+    // A pin of a never-pin app was refused. hwnd is 0 for a saved pin that
+    // restoreSaved() dropped without a window to match.
+    void pinBlocked(intptr_t hwnd, const QString &title, const QString &process);
+    // code is stable and machine-readable; message is translated, for the user.
+    void errorOccurred(PinError code, const QString &message);
+    // Consolidated change feed: fires after EVERY mutation (pin, unpin,
+    // opacity, group, stale cleanup, restore) with the full current list, so
+    // observers never need to poll pinnedWindows().
+    void pinStateChanged(const QVector<PinnedWindow> &pins);
 
 private slots:
-    void reenforce();          // periodic: re-apply topmost, drop dead windows
+    void reenforce();          // periodic: re-apply topmost, refresh titles, prune dead
+    void tickExpiries();       // count down temporary pins, unpin the ones that ran out
 
 private:
     void persist() const;
+    void fail(PinError code, const QString &message);   // log with its code, then emit
+    void emitStateChanged();   // emit pinStateChanged with the current list
     void schedulePersist();    // coalesce rapid writes (opacity slider drags)
     void updateTimer();        // run the re-enforce timer only while pins exist
+    void updateRetryTimer();   // run the pending-restore retry only while needed
+    void updateExpiryTimer();  // run the countdown only while a temporary pin exists
+    void expire(intptr_t hwnd);   // quiet unpin + pinExpired
+    intptr_t findRestoreMatch(const persistence::SavedPin &saved,
+                              const QVector<winpin::PinnableWindow> &live,
+                              const QSet<intptr_t> &used) const;
+    bool applySaved(const persistence::SavedPin &saved, intptr_t hwnd, bool announce);
+    // Pin a window the user just allowed, restoring its saved pin if it was one.
+    void resumePin(intptr_t hwnd, bool announce);
+    // What launchAndPin() does with its window once it's pinned.
+    struct Launched {
+        QString                       path;
+        QStringList                   args;
+        std::function<void(intptr_t)> onPinned;
+    };
+    void finishLaunch(intptr_t hwnd, const Launched &launch);
+
+    // An opacity fade in progress; stepped by m_fadeTimer.
+    struct Fade {
+        int           from = 100;
+        int           to = 100;
+        int           current = 100;
+        QElapsedTimer clock;
+        bool          restoreWhenDone = false;   // unpin: drop the layered style at the end
+        bool          keepLayered = false;
+    };
+    void startFade(intptr_t hwnd, int from, int to, bool restoreWhenDone = false,
+                   bool keepLayered = false);
+    void stepFades();
+    void finishFade(intptr_t hwnd);   // jump to the end state now
+    void confirmVisually(intptr_t hwnd);   // the pin feedback, if any
+    QString unmarked(const QString &title) const;   // title without m_titleMarker
+    void removeMarker(intptr_t hwnd);
+    void restorePlacement(const PinnedWindow &w);   // undo the compact-size rule
+    void raiseOwned(PinnedWindow &w);               // see setPinOwnedWindows()
+    void releaseOwned(PinnedWindow &w);
+    void watchForExit(const PinnedWindow &gone);    // auto-restart: wait, then relaunch
+    void relaunch(const PinnedWindow &gone);
 
     QHash<intptr_t, PinnedWindow> m_pinned;
     QTimer *m_timer = nullptr;
     QTimer *m_persistTimer = nullptr;  // single-shot debounce for persist()
+    QTimer *m_retryTimer = nullptr;    // polls for pending restores' windows
+    QElapsedTimer m_retrySince;        // since m_pending last changed (kRetryForMs)
     bool    m_sessionEnding = false;   // true once Windows is logging off/shutting down
+    bool    m_saving = true;           // setSavingEnabled()
+    intptr_t m_previewHwnd = 0;        // window pinned by a held pin key, if any
+    QVector<persistence::SavedPin> m_pending;   // unmatched saved pins
+    QSet<intptr_t> m_unpinnable;       // windows a retry failed to pin; not retried
+    QSet<qint64>   m_launching;        // processes launchAndPin() is waiting on
+    QSet<intptr_t> m_claimed;          // windows a launchAndPin() took, while any wait
+    QVector<intptr_t> m_recent;        // pinned windows, most recent first (lastPinned())
+    ReenforceStats m_stats;
+    QHash<QString, bool>   m_protectedChoices;
+    QVector<QRegularExpression> m_blocked;     // setBlockedProcesses(), as wildcards
+    QHash<QString, persistence::CompactRule> m_compactRules;
+    QHash<QString, persistence::AppCompat> m_compat;   // setAppCompat(), exact names
+    QVector<QPair<QRegularExpression, persistence::AppCompat>> m_compatWildcards;
+    QHash<intptr_t, bool>  m_awaitingConfirm;   // hwnd -> announce, until confirmPin()
+    QSet<intptr_t>         m_confirmed;         // allowed once; consumed by pin()
+    // Saved pins whose window is waiting on confirmPin()/resolveCoexistence().
+    QHash<intptr_t, persistence::SavedPin> m_awaitingRestore;
+    QHash<intptr_t, Launched> m_awaitingLaunch;   // launched windows waiting the same way
+    QString                m_coexistence = QStringLiteral("defer");
+    QString                m_lostPolicy = QStringLiteral("restore");
+    bool                   m_pinOwned = true;
+    bool                   m_uiaMatching = false;
+    bool                   m_opacitySuspended = false;
+    bool                   m_opacityDisabled = false;
+    QHash<intptr_t, bool>  m_awaitingCoexist;   // hwnd -> announce, until resolveCoexistence()
+    QHash<intptr_t, bool>  m_coexistChoice;     // hwnd -> take over; consumed by pin()
+    QHash<intptr_t, Fade>  m_fades;
+    QTimer                *m_fadeTimer = nullptr;
+    int                    m_fadeMs = 150;
+    QString                m_pinFeedback = QStringLiteral("none");
+    QString                m_backdrop = QStringLiteral("none");
+    QString                m_titleMarker;
+    QTimer                *m_expiryTimer = nullptr;
+    int                    m_tempPinSeconds = 300;
+    int                    m_pinOpacity = 70;
+    int                    m_idleMinutes = 0;
 };
@@ -0,0 +1,104 @@
+#include "platform.h"
+
+#include <windows.h>
+
+#include <QDir>
+#include <QGuiApplication>
+#include <QScreen>
+
+namespace {
+
+// GetVersionEx lies to unmanifested apps; RtlGetVersion doesn't.
+DWORD windowsBuild()
+{
+    using RtlGetVersionFn = LONG(WINAPI *)(OSVERSIONINFOW *);
+    HMODULE ntdll = GetModuleHandleW(L"ntdll.dll");
+    auto fn = ntdll ? reinterpret_cast<RtlGetVersionFn>(
+                          reinterpret_cast<void *>(GetProcAddress(ntdll, "RtlGetVersion")))
+                    : nullptr;
+    OSVERSIONINFOW info = {};
+    info.dwOSVersionInfoSize = sizeof(info);
+    if (!fn || fn(&info) != 0)
+        return 0;
+    return info.dwBuildNumber;
+}
+
+bool processIsElevated()
+{
+    HANDLE token = nullptr;
+    if (!OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &token))
+        return false;
+    TOKEN_ELEVATION elevation = {};
+    DWORD size = 0;
+    const bool ok = GetTokenInformation(token, TokenElevation, &elevation,
+                                        sizeof(elevation), &size);
+    CloseHandle(token);
+    return ok && elevation.TokenIsElevated;
+}
+
+platform::Capabilities detect()
+{
+    platform::Capabilities c;
+    c.build = static_cast<int>(windowsBuild());
+    // Windows 11 still reports 10.0; the build number is what tells them apart.
+    c.osName          = c.build >= 22000 ? QStringLiteral("Windows 11")
+                                         : QStringLiteral("Windows 10");
+    c.borderColor     = c.build >= 22000;
+    c.acrylic         = c.build >= 22621;
+    c.blurBehind      = GetProcAddress(GetModuleHandleW(L"user32.dll"),
+                                       "SetWindowCompositionAttribute") != nullptr;
+    c.virtualDesktops = c.build >= 10240;
+    c.elevated        = processIsElevated();
+    return c;
+}
+
+} // namespace
+
+namespace platform {
+
+const Capabilities &capabilities()
+{
+    static const Capabilities caps = detect();
+    return caps;
+}
+
+QString backdropFor(const QString &wanted, const Capabilities &caps)
+{
+    if (wanted == QLatin1String("acrylic") && caps.acrylic)
+        return wanted;
+    if ((wanted == QLatin1String("acrylic") || wanted == QLatin1String("blur"))
+        && caps.blurBehind)
+        return QStringLiteral("blur");
+    return QStringLiteral("none");
+}
+
+QPoint toLogical(const QPoint &native)
+{
+    // Qt keeps each screen's origin in native coordinates and scales from there.
+    for (QScreen *screen : QGuiApplication::screens()) {
+        const QRect geo = screen->geometry();
+        const qreal dpr = screen->devicePixelRatio();
+        const QRect nativeGeo(geo.topLeft(), geo.size() * dpr);
+        if (nativeGeo.contains(native))
+            return geo.topLeft() + (native - geo.topLeft()) / dpr;
+    }
+    return native;
+}
+
+QRect toLogical(const QRect &native)
+{
+    return QRect(toLogical(native.topLeft()), toLogical(native.bottomRight()));
+}
+
+bool isNetworkPath(const QString &path)
+{
+    const QString native = QDir::toNativeSeparators(QDir::cleanPath(path));
+    if (native.startsWith(QLatin1String("\\\\")))
+        return true;
+    if (native.size() < 2 || native.at(1) != QLatin1Char(':'))
+        return false;
+    const QString root = native.left(2) + QLatin1Char('\\');
+    return GetDriveTypeW(reinterpret_cast<const wchar_t *>(root.utf16())) == DRIVE_REMOTE;
+}
+
+} // namespace platform
@@ -0,0 +1,43 @@
+#pragma once
+//
+// platform — what this Windows install lets PinIt do, detected once at
+// startup. Features that need a newer Windows (or elevation) check here
+// instead of calling the API and failing silently, and Diagnostics lists it
+// so a bug report says which build it came from.
+//
+#include <QPoint>
+#include <QRect>
+#include <QString>
+
+namespace platform {
+
+struct Capabilities {
+    QString osName;                 // "Windows 11", "Windows 10"
+    int     build = 0;              // e.g. 22631
+    bool    opacity = true;         // layered windows (SetLayeredWindowAttributes)
+    bool    clickThrough = true;    // WS_EX_TRANSPARENT on a layered window
+    bool    borderColor = false;    // DWMWA_BORDER_COLOR — Windows 11 only
+    bool    acrylic = false;        // DWMWA_SYSTEMBACKDROP_TYPE — Windows 11 22H2 and later
+    bool    blurBehind = false;     // SetWindowCompositionAttribute — Windows 10 and later
+    bool    virtualDesktops = false;   // IVirtualDesktopManager — Windows 10 and later
+    // PinIt runs as administrator. Without it, windows of elevated apps can't
+    // be pinned (UIPI); with it, everything can.
+    bool    elevated = false;
+};
+
+const Capabilities &capabilities();
+
+// The backdrop to use for wanted ("none", "blur", "acrylic") with caps:
+// acrylic falls back to blur, blur to none, so pinned windows keep plain
+// layered opacity where the effect isn't available.
+QString backdropFor(const QString &wanted, const Capabilities &caps);
+
+// Win32 reports physical pixels (winpin::windowRect); Qt places widgets in
+// logical ones. Converts using the scale of the screen the point is on.
+QPoint toLogical(const QPoint &native);
+QRect  toLogical(const QRect &native);
+
+// A UNC path or a mapped network drive.
+bool isNetworkPath(const QString &path);
+
+} // namespace platform
@@ -0,0 +1,195 @@
+#include "policy.h"
+#include "persistence.h"
+
+#include <QCoreApplication>
+#include <QJsonArray>
+#include <QJsonDocument>
+#include <QSettings>
+#include <QStringList>
+
+namespace {
+
+const char *const kPolicyKeys[] = {
+    "HKEY_CURRENT_USER\\SOFTWARE\\Policies\\PinIt",    // read first, so that
+    "HKEY_LOCAL_MACHINE\\SOFTWARE\\Policies\\PinIt",   // the machine's wins
+};
+
+// Settings made of objects, which a registry value can't express (and which
+// are left out of the defaults while empty, so their type can't be told from
+// those). Forcing one as text would only stop readSettings() reading it.
+const char *const kStructuredKeys[] = {
+    "macros", "schedules", "triggers", "pin_slots", "compact_rules",
+    "protected_process_choices", "app_compat", "onboarding",
+};
+
+bool isStructured(const QString &name, const QJsonObject &defaults)
+{
+    if (defaults.value(name).isObject())
+        return true;
+    for (const char *key : kStructuredKeys) {
+        if (name == QLatin1String(key))
+            return true;
+    }
+    return false;
+}
+
+struct Policy {
+    QJsonObject settings;
+    bool        opacityDisabled = false;
+    QStringList neverPin;
+};
+
+// A registry value as the JSON type `like` (the default) has: DWORDs for
+// booleans and numbers, REG_SZ for text, REG_MULTI_SZ for lists.
+QJsonValue coerce(const QJsonValue &like, const QVariant &v)
+{
+    switch (like.type()) {
+    case QJsonValue::Bool:   return v.toBool();   // 1/0, or "true"/"false"
+    case QJsonValue::Double: return v.toInt();
+    case QJsonValue::Array:  return QJsonArray::fromStringList(v.toStringList());
+    default:
+        // A list that's empty by default (so absent from the defaults).
+        if (v.metaType().id() == QMetaType::QStringList)
+            return QJsonArray::fromStringList(v.toStringList());
+        return v.toString();
+    }
+}
+
+const Policy &current()
+{
+    static const Policy policy = [] {
+        Policy p;
+        const QJsonObject defaults = persistence::settingsToJson(persistence::UserSettings());
+        const QJsonObject defaultShortcuts = defaults.value("shortcuts").toObject();
+        QJsonObject shortcuts;
+        bool lockShortcuts = false;
+        for (const char *key : kPolicyKeys) {
+            QSettings reg(QString::fromLatin1(key), QSettings::NativeFormat);
+            for (const QString &name : reg.childKeys()) {
+                const QVariant v = reg.value(name);
+                if (name == QLatin1String("LockShortcuts"))
+                    lockShortcuts = v.toBool();
+                else if (name == QLatin1String("DisableOpacity"))
+                    p.opacityDisabled = v.toBool();
+                else if (name == QLatin1String("NeverPin"))
+                    p.neverPin += v.toStringList();   // both keys' lists apply
+                else if (isStructured(name, defaults))
+                    qWarning("Policy value %s ignored: it can't be set from the registry",
+                             qUtf8Printable(name));
+                else
+                    p.settings.insert(name, coerce(defaults.value(name), v));
+            }
+            reg.beginGroup(QStringLiteral("Shortcuts"));
+            for (const QString &name : reg.childKeys())
+                shortcuts.insert(name, coerce(defaultShortcuts.value(name), reg.value(name)));
+            reg.endGroup();
+        }
+        if (lockShortcuts || !shortcuts.isEmpty()) {
+            QJsonObject all = lockShortcuts ? defaultShortcuts : QJsonObject();
+            for (auto it = shortcuts.begin(); it != shortcuts.end(); ++it)
+                all.insert(it.key(), it.value());
+            p.settings.insert(QStringLiteral("shortcuts"), all);
+        }
+        p.neverPin.removeAll(QString());
+        p.neverPin.removeDuplicates();
+        if (!p.settings.isEmpty() || p.opacityDisabled)
+            qInfo("Group Policy sets: %s%s",
+                  qUtf8Printable(p.settings.keys().join(QStringLiteral(", "))),
+                  p.opacityDisabled ? " (opacity disabled)" : "");
+        if (!p.neverPin.isEmpty())
+            qInfo("Group Policy never-pin list: %s",
+                  qUtf8Printable(p.neverPin.join(QStringLiteral(", "))));
+        return p;
+    }();
+    return policy;
+}
+
+} // namespace
+
+namespace policy {
+
+QJsonObject settings()
+{
+    return current().settings;
+}
+
+QJsonObject apply(QJsonObject settings)
+{
+    const QJsonObject forced = current().settings;
+    for (auto it = forced.begin(); it != forced.end(); ++it) {
+        if (it.value().isObject()) {   // shortcuts: key by key
+            QJsonObject merged = settings.value(it.key()).toObject();
+            const QJsonObject o = it.value().toObject();
+            for (auto j = o.begin(); j != o.end(); ++j)
+                merged.insert(j.key(), j.value());
+            settings.insert(it.key(), merged);
+        } else {
+            settings.insert(it.key(), it.value());
+        }
+    }
+    return settings;
+}
+
+bool isLocked(const QString &key)
+{
+    return current().settings.contains(key);
+}
+
+bool opacityDisabled()
+{
+    return current().opacityDisabled;
+}
+
+QStringList neverPin()
+{
+    return current().neverPin;
+}
+
+QVector<Setting> effectiveSettings()
+{
+    const QJsonObject effective = persistence::settingsToJson(persistence::loadSettings());
+    const QJsonObject user =
+        QJsonDocument::fromJson(persistence::readRaw()).object().value("settings").toObject();
+    const QJsonObject forced = current().settings;
+    const auto source = [](const QJsonObject &f, const QJsonObject &u, const QString &key) {
+        return f.contains(key) ? Setting::Policy : u.contains(key) ? Setting::User
+                                                                   : Setting::Default;
+    };
+
+    QVector<Setting> out;
+    for (auto it = effective.begin(); it != effective.end(); ++it) {
+        if (it.key() != QLatin1String("shortcuts")) {
+            out.push_back({it.key(), it.value(), source(forced, user, it.key())});
+            continue;
+        }
+        const QJsonObject o = it.value().toObject();
+        const QJsonObject f = forced.value(it.key()).toObject();
+        const QJsonObject u = user.value(it.key()).toObject();
+        for (auto j = o.begin(); j != o.end(); ++j) {
+            out.push_back(
+                {it.key() + QLatin1Char('.') + j.key(), j.value(), source(f, u, j.key())});
+        }
+    }
+    if (opacityDisabled())
+        out.push_back({QStringLiteral("DisableOpacity"), true, Setting::Policy});
+    if (!neverPin().isEmpty()) {
+        out.push_back({QStringLiteral("NeverPin"), QJsonArray::fromStringList(neverPin()),
+                       Setting::Policy});
+    }
+    return out;
+}
+
+QString format(const Setting &setting)
+{
+    const auto tr = [](const char *text) {
+        return QCoreApplication::translate("policy", text);
+    };
+    QByteArray value = QJsonDocument(QJsonArray{setting.value}).toJson(QJsonDocument::Compact);
+    value = value.mid(1, value.size() - 2);   // drop the [ ]
+    const QString from = setting.source == Setting::Policy ? tr("set by policy")
+                         : setting.source == Setting::User ? tr("yours")
+                                                           : tr("default");
+    return QStringLiteral("%1 = %2 (%3)").arg(setting.key, QString::fromUtf8(value), from);
+}
+
+} // namespace policy
@@ -0,0 +1,56 @@
+#pragma once
+//
+// policy — settings an administrator enforces, read from
+// HKLM\SOFTWARE\Policies\PinIt (machine-wide) and HKCU\SOFTWARE\Policies\PinIt
+// (per user), where Group Policy puts them; the machine key wins. They're laid
+// over the user's own settings whenever the state is loaded, so the rest of
+// PinIt just sees the effective values. The user's own choices stay in
+// pinned.json and come back if the policy is lifted.
+//
+// Under either key:
+//   <settings key>        any key from pinned.json's "settings" with a plain
+//                         value: enable_sound (DWORD 0/1), opacity_fade_ms
+//                         (DWORD), tray_icon_theme (REG_SZ), …
+//   Shortcuts\<action>    that action's shortcut, e.g. toggle_pin (REG_SZ)
+//   LockShortcuts         DWORD 1: shortcuts can't be edited; any not set
+//                         under Shortcuts keep their defaults
+//   DisableOpacity        DWORD 1: pins stay fully opaque
+//   NeverPin              REG_MULTI_SZ of exe names or wildcards
+//                         ("consent.exe", "lob-*.exe") that can never be
+//                         pinned, whatever the user or their rules say
+//
+// Read once at startup; a changed policy applies the next time PinIt starts.
+//
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QString>
+#include <QStringList>
+#include <QVector>
+
+namespace policy {
+
+// The forced values, shaped like pinned.json's "settings" object.
+QJsonObject settings();
+// `settings` with the forced values laid over it.
+QJsonObject apply(QJsonObject settings);
+// True if `key` ("enable_sound", "shortcuts") is set by policy. Any forced
+// shortcut locks "shortcuts" as a whole.
+bool isLocked(const QString &key);
+bool opacityDisabled();
+// The never-pin list from both keys, for PinManager::setBlockedProcesses().
+QStringList neverPin();
+
+// One row of the effective-settings view.
+struct Setting {
+    enum Source { Default, User, Policy };
+    QString    key;     // "osd_position", "shortcuts.toggle_pin"
+    QJsonValue value;
+    Source     source;
+};
+
+// Every setting PinIt is running with and where its value came from.
+QVector<Setting> effectiveSettings();
+// "osd_position = "screen" (policy)" — for the view and `--effective-settings`.
+QString format(const Setting &setting);
+
+} // namespace policy
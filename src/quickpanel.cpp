@@ -0,0 +1,133 @@
+#include "quickpanel.h"
+#include "pinmanager.h"
+#include "winpin.h"
+
+#include <QVBoxLayout>
+#include <QHBoxLayout>
+#include <QLabel>
+#include <QPushButton>
+#include <QSlider>
+#include <QScreen>
+#include <QGuiApplication>
+#include <QCursor>
+
+namespace {
+
+constexpr int kWidth = 300;
+constexpr int kGap = 8;   // between the panel and the tray icon
+
+} // namespace
+
+QuickPanel::QuickPanel(PinManager *manager, QWidget *parent)
+    : QFrame(parent, Qt::Popup | Qt::FramelessWindowHint)
+    , m_manager(manager)
+{
+    setObjectName(QStringLiteral("quickPanel"));
+    setFixedWidth(kWidth);
+
+    auto *root = new QVBoxLayout(this);
+    root->setContentsMargins(12, 10, 12, 10);
+    root->setSpacing(6);
+    m_header = new QLabel(this);
+    m_header->setProperty("role", "section");
+    root->addWidget(m_header);
+    m_rows = new QVBoxLayout;
+    m_rows->setSpacing(4);
+    root->addLayout(m_rows);
+
+    // Keep the list live while it's open (a hotkey unpin, a window closing).
+    connect(m_manager, &PinManager::pinsChanged, this, [this]() {
+        if (isVisible())
+            rebuild();
+    });
+}
+
+void QuickPanel::popup(const QRect &anchor)
+{
+    rebuild();
+    adjustSize();
+
+    const QPoint at = anchor.isEmpty() ? QCursor::pos() : anchor.center();
+    QScreen *screen = QGuiApplication::screenAt(at);
+    const QRect avail = screen ? screen->availableGeometry() : QRect(at, size());
+
+    // Open towards the middle of the screen — above a bottom taskbar, below a
+    // top one — aligned with the icon, then clamp to the work area.
+    QPoint pos;
+    pos.setX(at.x() - width() / 2);
+    if (anchor.isEmpty() || at.y() > avail.center().y())
+        pos.setY((anchor.isEmpty() ? at.y() : anchor.top()) - height() - kGap);
+    else
+        pos.setY(anchor.bottom() + kGap);
+    pos.setX(qBound(avail.left() + kGap, pos.x(), avail.right() - width() - kGap));
+    pos.setY(qBound(avail.top() + kGap, pos.y(), avail.bottom() - height() - kGap));
+
+    move(pos);
+    show();
+    activateWindow();   // so a click elsewhere (or Esc) closes it
+}
+
+void QuickPanel::rebuild()
+{
+    // deleteLater: this runs from inside a row's own unpin click.
+    while (QLayoutItem *item = m_rows->takeAt(0)) {
+        if (QLayout *row = item->layout()) {
+            while (QLayoutItem *ri = row->takeAt(0)) {
+                if (ri->widget())
+                    ri->widget()->deleteLater();
+                delete ri;
+            }
+        }
+        if (item->widget())
+            item->widget()->deleteLater();
+        delete item;
+    }
+
+    const QVector<PinnedWindow> pins = m_manager->pinnedWindows();
+    m_header->setText(tr("PINNED (%1)").arg(pins.size()));
+    if (pins.isEmpty()) {
+        auto *none = new QLabel(tr("No windows pinned"), this);
+        none->setProperty("role", "muted");
+        m_rows->addWidget(none);
+    }
+
+    for (const PinnedWindow &w : pins) {
+        const intptr_t hwnd = w.hwnd;
+        auto *row = new QHBoxLayout;
+        row->setSpacing(6);
+
+        // Clicking the title brings the window forward.
+        auto *title = new QPushButton(this);
+        title->setObjectName(QStringLiteral("panelTitle"));
+        title->setText(title->fontMetrics().elidedText(w.title, Qt::ElideRight, 140));
+        title->setToolTip(tr("Bring %1 to the front").arg(w.processName));
+        title->setCursor(Qt::PointingHandCursor);
+        title->setEnabled(!w.hung);
+        connect(title, &QPushButton::clicked, this, [this, hwnd]() {
+            hide();
+            winpin::activate(reinterpret_cast<void *>(hwnd));
+        });
+        row->addWidget(title, 1);
+
+        auto *slider = new QSlider(Qt::Horizontal, this);
+        slider->setRange(winpin::kMinOpacity, winpin::kMaxOpacity);
+        slider->setValue(w.opacity);
+        slider->setFixedWidth(70);
+        slider->setMinimumHeight(20);
+        slider->setEnabled(!w.hung);
+        connect(slider, &QSlider::valueChanged, this,
+                [this, hwnd](int v) { m_manager->setOpacity(hwnd, v); });
+        row->addWidget(slider);
+
+        auto *unpin = new QPushButton(QString::fromUtf8("\xE2\x9C\x95"), this);   // ✕
+        unpin->setObjectName(QStringLiteral("unpin"));
+        unpin->setFixedSize(24, 24);
+        unpin->setToolTip(tr("Unpin this window"));
+        connect(unpin, &QPushButton::clicked, this,
+                [this, hwnd]() { m_manager->unpin(hwnd); });
+        row->addWidget(unpin);
+
+        m_rows->addLayout(row);
+    }
+    adjustSize();
+}
@@ -0,0 +1,29 @@
+#pragma once
+//
+// QuickPanel — a small flyout next to the tray icon listing the pinned windows
+// with opacity, bring-to-front and unpin controls. Summoned by clicking the
+// tray icon or with the quick-panel hotkey; closes as soon as it loses focus.
+//
+#include <QFrame>
+
+class PinManager;
+class QVBoxLayout;
+class QLabel;
+
+class QuickPanel : public QFrame
+{
+    Q_OBJECT
+public:
+    explicit QuickPanel(PinManager *manager, QWidget *parent = nullptr);
+
+    // Show beside anchor (the tray icon's screen rect), or at the mouse pointer
+    // when anchor is empty (no tray, or Windows didn't report its position).
+    void popup(const QRect &anchor);
+
+private:
+    void rebuild();
+
+    PinManager  *m_manager = nullptr;
+    QVBoxLayout *m_rows = nullptr;
+    QLabel      *m_header = nullptr;
+};
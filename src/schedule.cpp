@@ -0,0 +1,78 @@
+#include "schedule.h"
+#include "actions.h"
+
+#include <QTimer>
+
+#include <algorithm>
+
+namespace {
+
+constexpr int kTickMs         = 30 * 1000;
+constexpr int kCatchUpMinutes = 10;
+
+} // namespace
+
+namespace scheduling {
+
+QDateTime nextRun(const persistence::Schedule &s, const QDateTime &after)
+{
+    if (!s.enabled)
+        return QDateTime();
+    if (s.everyMinutes > 0)
+        return after.addSecs(qint64(s.everyMinutes) * 60);
+    if (!s.at.isValid())
+        return QDateTime();
+    // Today's slot if it's still ahead, else the next selected day's.
+    for (int d = 0; d <= 7; ++d) {
+        const QDate date = after.date().addDays(d);
+        const QDateTime when(date, s.at);
+        if (when > after && (s.days.isEmpty() || s.days.contains(date.dayOfWeek())))
+            return when;
+    }
+    return QDateTime();
+}
+
+} // namespace scheduling
+
+Scheduler::Scheduler(const ActionRegistry *actions, QObject *parent)
+    : QObject(parent)
+    , m_actions(actions)
+{
+    m_timer = new QTimer(this);
+    m_timer->setInterval(kTickMs);
+    connect(m_timer, &QTimer::timeout, this, &Scheduler::tick);
+}
+
+void Scheduler::setSchedules(const QVector<persistence::Schedule> &schedules)
+{
+    m_schedules = schedules;
+    m_next.clear();
+    const QDateTime now = QDateTime::currentDateTime();
+    for (const persistence::Schedule &s : m_schedules)
+        m_next.push_back(scheduling::nextRun(s, now));
+    // Idle unless something is scheduled, like PinManager's timers.
+    const bool any = std::any_of(m_next.cbegin(), m_next.cend(),
+                                 [](const QDateTime &t) { return t.isValid(); });
+    if (any && !m_timer->isActive())
+        m_timer->start();
+    else if (!any)
+        m_timer->stop();
+}
+
+void Scheduler::tick()
+{
+    const QDateTime now = QDateTime::currentDateTime();
+    for (int i = 0; i < m_schedules.size(); ++i) {
+        if (!m_next[i].isValid() || m_next[i] > now)
+            continue;
+        const persistence::Schedule &s = m_schedules[i];
+        if (m_next[i].secsTo(now) > kCatchUpMinutes * 60) {
+            qInfo("Skipping scheduled %s: missed at %s", qUtf8Printable(s.name),
+                  qUtf8Printable(m_next[i].toString(Qt::ISODate)));
+        } else {
+            qInfo("Running scheduled %s (%s)", qUtf8Printable(s.name), qUtf8Printable(s.action));
+            emit ran(s.name, m_actions->invoke(s.action, s.args));
+        }
+        m_next[i] = scheduling::nextRun(s, now);
+    }
+}
@@ -0,0 +1,47 @@
+#pragma once
+//
+// Scheduler — runs actions (see actions.h) at set times: "unpin-all at 18:00
+// on weekdays", "snapshot-restore Focus at 09:00", or every N minutes. A
+// macro is scheduled through the run-macro action.
+//
+// A 30 s timer compares the clock with each schedule's next run, so changes
+// to the system clock and sleep are picked up without re-arming anything. A
+// run missed by more than kCatchUpMinutes (the PC was asleep) is skipped.
+//
+#include <QDateTime>
+#include <QObject>
+#include <QVector>
+
+#include "persistence.h"
+
+class ActionRegistry;
+class QTimer;
+
+namespace scheduling {
+
+// When s next fires strictly after `after`; invalid if it's disabled. An
+// empty `days` means every day (the editor won't save a schedule with none
+// ticked).
+QDateTime nextRun(const persistence::Schedule &s, const QDateTime &after);
+
+} // namespace scheduling
+
+class Scheduler : public QObject
+{
+    Q_OBJECT
+public:
+    explicit Scheduler(const ActionRegistry *actions, QObject *parent = nullptr);
+
+    void setSchedules(const QVector<persistence::Schedule> &schedules);
+
+signals:
+    void ran(const QString &name, bool ok);
+
+private:
+    void tick();
+
+    const ActionRegistry           *m_actions = nullptr;
+    QTimer                         *m_timer = nullptr;
+    QVector<persistence::Schedule>  m_schedules;
+    QVector<QDateTime>              m_next;   // parallel to m_schedules
+};
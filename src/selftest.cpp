@@ -0,0 +1,252 @@
+#include "selftest.h"
+#include "sessionevents.h"
+#include "winpin.h"
+
+#include <QCoreApplication>
+#include <QDeadlineTimer>
+#include <QFileInfo>
+#include <QJsonArray>
+#include <QThread>
+#include <QWindow>
+
+#include <windows.h>
+
+#include <algorithm>
+#include <functional>
+#include <future>
+#include <thread>
+
+namespace {
+
+const wchar_t kClassName[] = L"PinItSelfTest";
+const QString kTitle = QStringLiteral("PinIt self-test");
+
+LRESULT CALLBACK dummyProc(HWND hwnd, UINT msg, WPARAM wParam, LPARAM lParam)
+{
+    if (msg == WM_DESTROY) {
+        PostQuitMessage(0);   // ends the window's thread
+        return 0;
+    }
+    return DefWindowProcW(hwnd, msg, wParam, lParam);
+}
+
+LRESULT CALLBACK passKeys(int code, WPARAM wParam, LPARAM lParam)
+{
+    return CallNextHookEx(nullptr, code, wParam, lParam);
+}
+
+// A hidden top-level window run by a thread of its own — so, like another
+// app's window, PinIt's thread doesn't pump its messages — closed again when
+// this goes out of scope.
+class DummyWindow
+{
+public:
+    DummyWindow()
+    {
+        WNDCLASSW wc = {};
+        wc.lpfnWndProc = dummyProc;
+        wc.hInstance = GetModuleHandleW(nullptr);
+        wc.lpszClassName = kClassName;
+        if (!RegisterClassW(&wc) && GetLastError() != ERROR_CLASS_ALREADY_EXISTS) {
+            m_error = GetLastError();
+            return;
+        }
+
+        std::promise<HWND> created;
+        std::future<HWND> ready = created.get_future();
+        m_thread = std::thread([this, &created]() {
+            const HWND hwnd = CreateWindowExW(0, kClassName,
+                                              reinterpret_cast<const wchar_t *>(kTitle.utf16()),
+                                              WS_OVERLAPPEDWINDOW, 100, 100, 320, 200, nullptr,
+                                              nullptr, GetModuleHandleW(nullptr), nullptr);
+            if (!hwnd)
+                m_error = GetLastError();
+            created.set_value(hwnd);
+            if (!hwnd)
+                return;
+            MSG msg;
+            while (GetMessageW(&msg, nullptr, 0, 0) > 0) {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        });
+        m_hwnd = ready.get();
+    }
+
+    ~DummyWindow()
+    {
+        if (m_hwnd)
+            PostMessageW(m_hwnd, WM_CLOSE, 0, 0);
+        if (m_thread.joinable())
+            m_thread.join();
+    }
+
+    void *handle() const { return m_hwnd; }
+    DWORD error() const { return m_error; }
+
+private:
+    HWND        m_hwnd = nullptr;
+    DWORD       m_error = 0;
+    std::thread m_thread;
+};
+
+// Keeps PinIt's own events flowing while a posted change lands.
+bool waitFor(const std::function<bool()> &done, int timeoutMs = 1000)
+{
+    const QDeadlineTimer deadline(timeoutMs);
+    while (!done()) {
+        if (deadline.hasExpired())
+            return false;
+        QCoreApplication::processEvents();
+        QThread::msleep(10);
+    }
+    return true;
+}
+
+selftest::Check checkWindow(const DummyWindow &dummy)
+{
+    selftest::Check c{QStringLiteral("window")};
+    void *h = dummy.handle();
+    if (!h) {
+        c.detail = QStringLiteral("CreateWindowEx failed (error %1)").arg(dummy.error());
+        return c;
+    }
+    const QString exe = QFileInfo(QCoreApplication::applicationFilePath()).fileName();
+    if (winpin::windowTitle(h) != kTitle)
+        c.detail = QStringLiteral("title read back as \"%1\"").arg(winpin::windowTitle(h));
+    else if (winpin::processName(h).compare(exe, Qt::CaseInsensitive) != 0)
+        c.detail = QStringLiteral("process read back as \"%1\"").arg(winpin::processName(h));
+    else
+        c.passed = true;
+    return c;
+}
+
+selftest::Check checkPin(void *h)
+{
+    selftest::Check c{QStringLiteral("pin")};
+    if (!(winpin::applyTopmost(h) && winpin::isTopmost(h))) {
+        c.detail = QStringLiteral("SetWindowPos(HWND_TOPMOST) didn't set WS_EX_TOPMOST");
+        return c;
+    }
+    // The re-enforce pass posts it instead, so a hung app can't block us.
+    winpin::removeTopmost(h);
+    if (!winpin::applyTopmost(h, true) || !waitFor([h]() { return winpin::isTopmost(h); })) {
+        c.detail = QStringLiteral("a posted SetWindowPos(HWND_TOPMOST) never landed");
+        return c;
+    }
+    c.passed = true;
+    return c;
+}
+
+selftest::Check checkUnpin(void *h)
+{
+    selftest::Check c{QStringLiteral("unpin")};
+    c.passed = winpin::removeTopmost(h) && !winpin::isTopmost(h);
+    if (!c.passed)
+        c.detail = QStringLiteral("SetWindowPos(HWND_NOTOPMOST) left WS_EX_TOPMOST set");
+    return c;
+}
+
+selftest::Check checkOpacity(void *h)
+{
+    selftest::Check c{QStringLiteral("opacity")};
+    winpin::setOpacityPercent(h, 60);
+    if (!winpin::isLayered(h) || winpin::opacityPercent(h) != 60) {
+        c.detail = QStringLiteral("asked for 60%, got %1%%2")
+                       .arg(winpin::opacityPercent(h))
+                       .arg(winpin::isLayered(h) ? QString() : QStringLiteral(", not layered"));
+        winpin::restoreOpacity(h);
+        return c;
+    }
+    winpin::restoreOpacity(h);
+    c.passed = !winpin::isLayered(h) && winpin::opacityPercent(h) == 100;
+    if (!c.passed)
+        c.detail = QStringLiteral("still layered after restoring full opacity");
+    return c;
+}
+
+// The path lock/unlock, resume and display changes take: registered on a
+// window of PinIt's own thread, seen by an application native event filter.
+selftest::Check checkSessionEvents()
+{
+    selftest::Check c{QStringLiteral("session events")};
+    QWindow probe;
+    probe.create();   // never shown
+    SessionEvents events;
+    if (!events.start(reinterpret_cast<void *>(probe.winId()))) {
+        c.detail = QStringLiteral("WTSRegisterSessionNotification failed");
+        return c;
+    }
+    bool seen = false;
+    QObject::connect(&events, &SessionEvents::displayChanged, [&seen]() { seen = true; });
+    QCoreApplication::instance()->installNativeEventFilter(&events);
+    PostMessageW(reinterpret_cast<HWND>(probe.winId()), WM_DISPLAYCHANGE, 0, 0);
+    c.passed = waitFor([&seen]() { return seen; });
+    QCoreApplication::instance()->removeNativeEventFilter(&events);
+    if (!c.passed)
+        c.detail = QStringLiteral("a posted WM_DISPLAYCHANGE never reached the event filter");
+    return c;
+}
+
+// The hotkey backend the keyboard_hook setting switches to.
+selftest::Check checkKeyboardHook()
+{
+    selftest::Check c{QStringLiteral("keyboard hook")};
+    const HHOOK hook = SetWindowsHookExW(WH_KEYBOARD_LL, passKeys, GetModuleHandleW(nullptr), 0);
+    if (!hook) {
+        c.detail = QStringLiteral("SetWindowsHookEx failed (error %1)").arg(GetLastError());
+        return c;
+    }
+    UnhookWindowsHookEx(hook);
+    c.passed = true;
+    return c;
+}
+
+} // namespace
+
+namespace selftest {
+
+QVector<Check> run()
+{
+    QVector<Check> checks;
+    {
+        const DummyWindow dummy;
+        checks << checkWindow(dummy);
+        if (void *h = dummy.handle()) {
+            checks << checkPin(h) << checkUnpin(h) << checkOpacity(h);
+        } else {
+            for (const char *name : {"pin", "unpin", "opacity"})
+                checks << Check{QString::fromLatin1(name), false,
+                                QStringLiteral("no test window")};
+        }
+    }
+    checks << checkSessionEvents() << checkKeyboardHook();
+    for (const Check &c : checks)
+        qInfo("Self-test %s: %s %s", qUtf8Printable(c.subsystem), c.passed ? "pass" : "FAIL",
+              qUtf8Printable(c.detail));
+    return checks;
+}
+
+bool allPassed(const QVector<Check> &checks)
+{
+    return std::all_of(checks.begin(), checks.end(), [](const Check &c) { return c.passed; });
+}
+
+QJsonObject toJson(const QVector<Check> &checks)
+{
+    QJsonArray list;
+    for (const Check &c : checks) {
+        QJsonObject o;
+        o["subsystem"] = c.subsystem;
+        o["passed"] = c.passed;
+        if (!c.detail.isEmpty())
+            o["detail"] = c.detail;
+        list.append(o);
+    }
+    QJsonObject report;
+    report["passed"] = allPassed(checks);
+    report["checks"] = list;
+    return report;
+}
+
+} // namespace selftest
@@ -0,0 +1,38 @@
+#pragma once
+//
+// selftest — exercises the Win32 calls PinIt is built on against a hidden
+// window of its own, for support ("does pinning work on this PC at all?")
+// and as a sanity check after an update. Each subsystem passes or fails on
+// its own:
+//
+//   window          a window on another thread is created and its title and
+//                   process can be read back
+//   pin / unpin     WS_EX_TOPMOST is set (directly and posted, as the
+//                   re-enforce pass does) and cleared again
+//   opacity         the window is made layered at 60% and opaque again
+//   session events  session notifications register, and a message reaches
+//                   PinIt's native event filter
+//   keyboard hook   a low-level keyboard hook can be installed (security
+//                   software sometimes refuses)
+//
+// `PinIt.exe --self-test` prints the report as JSON and exits non-zero if
+// anything failed; tray → Run self-test… shows it.
+//
+#include <QJsonObject>
+#include <QString>
+#include <QVector>
+
+namespace selftest {
+
+struct Check {
+    QString subsystem;
+    bool    passed = false;
+    QString detail;   // what was seen, mostly for failures
+};
+
+// Takes a second or so; needs the QGuiApplication to exist.
+QVector<Check> run();
+bool           allPassed(const QVector<Check> &checks);
+QJsonObject    toJson(const QVector<Check> &checks);
+
+} // namespace selftest
@@ -0,0 +1,63 @@
+#include <windows.h>
+#include <wtsapi32.h>
+#include "sessionevents.h"
+
+SessionEvents::SessionEvents(QObject *parent)
+    : QObject(parent)
+{
+}
+
+SessionEvents::~SessionEvents()
+{
+    if (m_hwnd)
+        WTSUnRegisterSessionNotification(static_cast<HWND>(m_hwnd));
+}
+
+bool SessionEvents::start(void *hwnd)
+{
+    if (m_hwnd)
+        return true;
+    if (!WTSRegisterSessionNotification(static_cast<HWND>(hwnd), NOTIFY_FOR_THIS_SESSION)) {
+        qWarning("WTSRegisterSessionNotification failed: %lu", GetLastError());
+        return false;
+    }
+    m_hwnd = hwnd;
+    return true;
+}
+
+bool SessionEvents::isRemoteSession()
+{
+    return GetSystemMetrics(SM_REMOTESESSION) != 0;
+}
+
+bool SessionEvents::nativeEventFilter(const QByteArray &eventType, void *message,
+                                      qintptr *result)
+{
+    Q_UNUSED(result);
+    if (eventType != "windows_generic_MSG")
+        return false;
+    const MSG *msg = static_cast<MSG *>(message);
+    if (msg->hwnd != static_cast<HWND>(m_hwnd))
+        return false;
+    if (msg->message == WM_DISPLAYCHANGE) {
+        emit displayChanged();
+        return false;
+    }
+    if (msg->message == WM_POWERBROADCAST) {
+        // Sent once per resume whether or not a user is there; the
+        // PBT_APMRESUMESUSPEND that follows a user's input would double it.
+        if (msg->wParam == PBT_APMRESUMEAUTOMATIC)
+            emit resumed();
+        return false;
+    }
+    if (msg->message != WM_WTSSESSION_CHANGE)
+        return false;
+    switch (msg->wParam) {
+    case WTS_SESSION_LOCK:   emit locked();   break;
+    case WTS_SESSION_UNLOCK: emit unlocked(); break;
+    case WTS_CONSOLE_CONNECT:
+    case WTS_REMOTE_CONNECT: emit sessionConnected(isRemoteSession()); break;
+    default: break;
+    }
+    return false;   // MainWindow may want it too
+}
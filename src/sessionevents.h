@@ -0,0 +1,42 @@
+#pragma once
+//
+// SessionEvents — Windows session notifications (WTSRegisterSessionNotification)
+// and power broadcasts turned into Qt signals: the workstation being locked
+// and unlocked, the session being connected to (at the console or over Remote
+// Desktop), the PC waking from sleep or hibernation, and the display layout
+// changing (WM_DISPLAYCHANGE: resolution, monitors plugged in or out).
+//
+// The notifications arrive as WM_WTSSESSION_CHANGE / WM_POWERBROADCAST on the
+// window passed to start(); install the object as an application native
+// event filter to see them (like GlobalHotkeyManager).
+//
+#include <QAbstractNativeEventFilter>
+#include <QObject>
+
+class SessionEvents : public QObject, public QAbstractNativeEventFilter
+{
+    Q_OBJECT
+public:
+    explicit SessionEvents(QObject *parent = nullptr);
+    ~SessionEvents() override;
+
+    bool start(void *hwnd);   // false if Windows wouldn't register it
+
+    static bool isRemoteSession();   // running over Remote Desktop (SM_REMOTESESSION)
+
+    bool nativeEventFilter(const QByteArray &eventType, void *message,
+                           qintptr *result) override;
+
+signals:
+    void locked();
+    void unlocked();
+    void resumed();   // back from sleep/hibernate (PBT_APMRESUMEAUTOMATIC)
+    // Someone connected to this session, at the console or over RDP; remote
+    // says which it is now. Switching resets window state much like unlocking.
+    void sessionConnected(bool remote);
+    // Fires several times while Windows settles on a new layout; debounce.
+    void displayChanged();
+
+private:
+    void *m_hwnd = nullptr;
+};
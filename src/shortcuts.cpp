@@ -1,9 +1,26 @@
 #include "shortcuts.h"
 
+#include <QHash>
 #include <QStringList>
 
 #include <windows.h>
 
+namespace {
+
+bool isAsciiLetter(QChar c) { return (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z'); }
+bool isAsciiDigit(QChar c)  { return c >= '0' && c <= '9'; }
+
+// A parsed step as one comparable value; 0 if it doesn't parse.
+quint64 combo(const QString &step)
+{
+    unsigned mods = 0, vk = 0;
+    if (!shortcuts::parse(step, mods, vk))
+        return 0;
+    return (quint64(mods) << 32) | vk;
+}
+
+} // namespace
+
 namespace shortcuts {
 
 bool parse(const QString &s, unsigned &mods, unsigned &vk)
@@ -26,19 +43,23 @@ bool parse(const QString &s, unsigned &mods, unsigned &vk)
         } else if (lower == "shift") {
             mods |= MOD_SHIFT;
         } else {
+            if (haveKey)
+                return false;   // "KeyA+KeyB" — RegisterHotKey takes a single key
             // The key token. Map the common Tauri key codes we use.
-            if (token.startsWith("Key") && token.size() == 4) {
+            if (token.startsWith("Key") && token.size() == 4 && isAsciiLetter(token.at(3))) {
                 vk = token.at(3).toUpper().unicode();          // KeyT -> 'T'
-            } else if (token.startsWith("Digit") && token.size() == 6) {
+            } else if (token.startsWith("Digit") && token.size() == 6
+                       && isAsciiDigit(token.at(5))) {
                 vk = token.at(5).unicode();                    // Digit5 -> '5'
             } else if (lower == "equal" || token == "=") {
                 vk = VK_OEM_PLUS;
             } else if (lower == "minus" || token == "-") {
                 vk = VK_OEM_MINUS;
-            } else if (token.size() == 1) {
+            } else if (token.size() == 1
+                       && (isAsciiLetter(token.at(0)) || isAsciiDigit(token.at(0)))) {
                 vk = token.at(0).toUpper().unicode();
             } else {
-                return false;   // unknown key token
+                return false;   // unknown key token (".", "é" would be VK_DELETE, …)
             }
             haveKey = true;
         }
@@ -74,28 +95,133 @@ QStringList displayTokens(const QString &s)
     return out;
 }
 
-QString build(bool win, bool ctrl, bool alt, bool shift, const QString &key)
+QString build(bool win, bool ctrl, bool alt, bool shift, const QString &key,
+              const QString &thenKey)
 {
+    auto keyToken = [](const QString &k) -> QString {
+        if (k.size() == 1 && k.at(0).isLetter())
+            return QStringLiteral("Key") + k.toUpper();
+        if (k.size() == 1 && k.at(0).isDigit())
+            return QStringLiteral("Digit") + k;
+        if (k == QLatin1String("="))
+            return QStringLiteral("Equal");
+        if (k == QLatin1String("-"))
+            return QStringLiteral("Minus");
+        return k;
+    };
+
     QStringList parts;
     if (win)   parts << QStringLiteral("super");
     if (ctrl)  parts << QStringLiteral("ctrl");
     if (alt)   parts << QStringLiteral("alt");
     if (shift) parts << QStringLiteral("shift");
 
-    QString tok;
-    if (key.size() == 1 && key.at(0).isLetter())
-        tok = QStringLiteral("Key") + key.toUpper();
-    else if (key.size() == 1 && key.at(0).isDigit())
-        tok = QStringLiteral("Digit") + key;
-    else if (key == QLatin1String("="))
-        tok = QStringLiteral("Equal");
-    else if (key == QLatin1String("-"))
-        tok = QStringLiteral("Minus");
-    else
-        tok = key;
-
-    parts << tok;
-    return parts.join(QLatin1Char('+'));
+    parts << keyToken(key);
+    QString out = parts.join(QLatin1Char('+'));
+    if (!thenKey.isEmpty())
+        out += QLatin1Char(',') + keyToken(thenKey);
+    return out;
+}
+
+QString reservedBy(const QString &s)
+{
+    unsigned mods = 0, vk = 0;
+    if (!parse(steps(s).value(0), mods, vk))
+        return QString();
+
+    // Combos the shell registers (or intercepts) itself, limited to the keys
+    // the shortcut editor offers. RegisterHotKey refuses most of these.
+    struct Reserved { unsigned mods; unsigned vk; const char *what; };
+    static const Reserved kReserved[] = {
+        { MOD_WIN, 'A', "Quick Settings" },
+        { MOD_WIN, 'D', "Show desktop" },
+        { MOD_WIN, 'E', "File Explorer" },
+        { MOD_WIN, 'G', "Game Bar" },
+        { MOD_WIN, 'H', "Voice typing" },
+        { MOD_WIN, 'I', "Settings" },
+        { MOD_WIN, 'K', "Cast" },
+        { MOD_WIN, 'L', "Lock the PC" },
+        { MOD_WIN, 'M', "Minimize all" },
+        { MOD_WIN, 'N', "Notification center" },
+        { MOD_WIN, 'P', "Project to a display" },
+        { MOD_WIN, 'R', "Run dialog" },
+        { MOD_WIN, 'S', "Search" },
+        { MOD_WIN, 'U', "Accessibility settings" },
+        { MOD_WIN, 'V', "Clipboard history" },
+        { MOD_WIN, 'X', "Quick Link menu" },
+        { MOD_WIN, VK_OEM_PLUS, "Magnifier zoom in" },
+        { MOD_WIN, VK_OEM_MINUS, "Magnifier zoom out" },
+        { MOD_WIN | MOD_SHIFT, 'S', "Snipping Tool" },
+        { MOD_WIN | MOD_SHIFT, 'M', "Restore minimized windows" },
+        { MOD_WIN | MOD_CONTROL, 'D', "New virtual desktop" },
+        { MOD_WIN | MOD_CONTROL, 'C', "Color filters" },
+        { MOD_WIN | MOD_CONTROL, 'O', "On-screen keyboard" },
+        { MOD_WIN | MOD_CONTROL, 'Q', "Quick Assist" },
+        { MOD_WIN | MOD_CONTROL | MOD_SHIFT, 'B', "Restart graphics driver" },
+        { MOD_WIN | MOD_ALT, 'R', "Game Bar recording" },
+        { MOD_WIN | MOD_ALT, 'G', "Game Bar record that" },
+    };
+    for (const Reserved &r : kReserved) {
+        if (r.mods == mods && r.vk == vk)
+            return QString::fromLatin1(r.what);
+    }
+
+    // Win (+ Shift/Ctrl/Alt) + digit launches or switches taskbar apps.
+    const unsigned withoutExtras = mods & ~(MOD_SHIFT | MOD_CONTROL | MOD_ALT);
+    if (withoutExtras == MOD_WIN && vk >= '0' && vk <= '9')
+        return QStringLiteral("Taskbar app %1").arg(QChar(vk));
+    return QString();
+}
+
+Problem check(const QStringList &bindings, int *index)
+{
+    auto fail = [index](Problem p, int i) {
+        if (index)
+            *index = i;
+        return p;
+    };
+
+    QVector<QVector<quint64>> parsed;
+    for (int i = 0; i < bindings.size(); ++i) {
+        const QStringList parts = steps(bindings[i]);
+        QVector<quint64> combos;
+        for (const QString &step : parts)
+            combos << combo(step);
+        if (combos.isEmpty() || combos.size() > 2 || combos.contains(0))
+            return fail(Problem::Invalid, i);
+        if ((combos[0] >> 32) == 0)
+            return fail(Problem::NoModifier, i);
+        parsed << combos;
+    }
+
+    QHash<QVector<quint64>, int> seen;
+    for (int i = 0; i < parsed.size(); ++i) {
+        if (seen.contains(parsed[i]))
+            return fail(Problem::Duplicate, i);
+        seen.insert(parsed[i], i);
+    }
+
+    for (int i = 0; i < parsed.size(); ++i) {
+        if (parsed[i].size() < 2)
+            continue;
+        for (int j = 0; j < parsed.size(); ++j) {
+            if (parsed[j].size() == 1 && parsed[j][0] == parsed[i][0])
+                return fail(Problem::ChordPrefix, qMax(i, j));
+        }
+    }
+    if (index)
+        *index = -1;
+    return Problem::None;
+}
+
+QStringList steps(const QString &s)
+{
+    QStringList out;
+    for (const QString &step : s.split(QLatin1Char(','), Qt::SkipEmptyParts)) {
+        if (!step.trimmed().isEmpty())
+            out << step.trimmed();
+    }
+    return out;
 }
 
 } // namespace shortcuts
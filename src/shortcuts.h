@@ -10,7 +10,9 @@
 namespace shortcuts {
 
 // Parse `s` into `mods` (MOD_* flags) and `vk` (virtual-key code).
-// Returns false if the string has no key or an unrecognised token.
+// Returns false if the string has no key, more than one key, or an
+// unrecognised token. Single-character keys are ASCII letters and digits
+// (plus "=" and "-"); anything else would map onto an unrelated key code.
 bool parse(const QString &s, unsigned &mods, unsigned &vk);
 
 // Turn a Tauri-style shortcut ("super+ctrl+KeyT") into display tokens for the
@@ -19,6 +21,30 @@ QStringList displayTokens(const QString &s);
 
 // Build a Tauri-style shortcut string from modifier flags + a key label
 // ("T", "5", "=", "-"). Inverse of displayTokens/parse for the editor dialog.
-QString build(bool win, bool ctrl, bool alt, bool shift, const QString &key);
+// A non-empty `thenKey` makes it a two-step chord (see steps()).
+QString build(bool win, bool ctrl, bool alt, bool shift, const QString &key,
+              const QString &thenKey = QString());
+
+// Chords: a two-step shortcut is stored as "<first>,<second>", e.g.
+// "ctrl+alt+KeyP,KeyO" = press Ctrl+Alt+P, then O. A plain shortcut is the
+// one-step case, so existing configs read unchanged. parse() and
+// displayTokens() work on a single step.
+QStringList steps(const QString &s);
+inline bool isChord(const QString &s) { return steps(s).size() > 1; }
+
+// If the shortcut (its first step) is one Windows itself owns — Win+L, Win+D,
+// Win+digit taskbar launches… — a short description of what it does there,
+// e.g. "Lock the PC". Empty if it's not a known reserved combo.
+QString reservedBy(const QString &s);
+
+// What can be wrong with a full set of bindings (one shortcut per action),
+// checked in this order: a binding that doesn't parse (or has more than two
+// steps), a first step without a modifier, two actions on the same combo,
+// and a plain shortcut that is also the first step of a chord (the chord
+// would swallow it). Combos are compared parsed, so "alt+ctrl+KeyP" and
+// "ctrl+alt+KeyP" count as the same. index, if given, gets the offending
+// binding (the later one of a pair).
+enum class Problem { None, Invalid, NoModifier, Duplicate, ChordPrefix };
+Problem check(const QStringList &bindings, int *index = nullptr);
 
 } // namespace shortcuts
@@ -1,5 +1,6 @@
 #include "shortcutsdialog.h"
 #include "shortcuts.h"
+#include "globalhotkey.h"
 
 #include <QCheckBox>
 #include <QComboBox>
@@ -9,7 +10,7 @@
 #include <QDialogButtonBox>
 #include <QMessageBox>
 #include <QStringList>
-#include <QSet>
+#include <QVector>
 
 namespace {
 
@@ -26,15 +27,23 @@ QStringList keyChoices()
 
 } // namespace
 
-ShortcutsDialog::ShortcutsDialog(const persistence::ShortcutConfig &cfg, QWidget *parent)
+ShortcutsDialog::ShortcutsDialog(const persistence::ShortcutConfig &cfg,
+                                 const QStringList &macroShortcuts, QWidget *parent)
     : QDialog(parent)
     , m_config(cfg)
 {
+    // One that's bad on its own is the Macros dialog's problem, not this one's.
+    for (const QString &s : macroShortcuts) {
+        if (shortcuts::check({s}) == shortcuts::Problem::None)
+            m_macroShortcuts << s;
+    }
+
     setWindowTitle(tr("Edit shortcuts"));
 
     auto *root = new QVBoxLayout(this);
     root->addWidget(new QLabel(tr("Pick the modifiers and key for each action.\n"
-                                  "Each shortcut needs at least one modifier."), this));
+                                  "Each shortcut needs at least one modifier.\n"
+                                  "Set \"Then\" to make it a two-step chord."), this));
 
     auto *grid = new QGridLayout;
     grid->addWidget(new QLabel(tr("Action"), this),  0, 0);
@@ -43,13 +52,37 @@ ShortcutsDialog::ShortcutsDialog(const persistence::ShortcutConfig &cfg, QWidget
     grid->addWidget(new QLabel(QStringLiteral("Alt"), this),   0, 3);
     grid->addWidget(new QLabel(QStringLiteral("Shift"), this), 0, 4);
     grid->addWidget(new QLabel(tr("Key"), this),     0, 5);
+    grid->addWidget(new QLabel(tr("Then"), this),    0, 6);
 
     m_togglePin    = addRow(grid, 1, tr("Pin / unpin"),  cfg.togglePin);
     m_opacityUp    = addRow(grid, 2, tr("Opacity +"),    cfg.opacityUp);
     m_opacityDown  = addRow(grid, 3, tr("Opacity -"),    cfg.opacityDown);
     m_toggleWindow = addRow(grid, 4, tr("Show / hide"),  cfg.toggleWindow);
+    m_pinUnderCursor = addRow(grid, 5, tr("Pin under cursor"), cfg.pinUnderCursor);
+    m_quickPanel   = addRow(grid, 6, tr("Quick panel"),  cfg.quickPanel);
+    m_hideAllPinned = addRow(grid, 7, tr("Hide all pinned"), cfg.hideAllPinned);
+    m_pinTemporarily = addRow(grid, 8, tr("Pin for a while"), cfg.pinTemporarily);
+    m_kioskExit    = addRow(grid, 9, tr("Exit kiosk mode"), cfg.kioskExit);
+    m_toggleLastPinned = addRow(grid, 10, tr("Pin / unpin last pinned"), cfg.toggleLastPinned);
+    m_focusLastPinned = addRow(grid, 11, tr("Go to last pinned"), cfg.focusLastPinned);
+    m_pinWithOpacity = addRow(grid, 12, tr("Pin see-through"), cfg.pinWithOpacity);
+    m_opacitySlider = addRow(grid, 13, tr("Opacity slider"), cfg.opacitySlider);
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        m_slot[i] = addRow(grid, 14 + 2 * i, tr("Go to slot %1").arg(i + 1), cfg.slot[i]);
+        m_assignSlot[i] = addRow(grid, 15 + 2 * i, tr("Assign to slot %1").arg(i + 1),
+                                 cfg.assignSlot[i]);
+    }
     root->addLayout(grid);
 
+    m_holdToPreview = new QCheckBox(tr("Hold Pin / unpin to preview (pins only while held)"), this);
+    m_holdToPreview->setChecked(cfg.holdToPreview);
+    root->addWidget(m_holdToPreview);
+
+    m_keyboardHook = new QCheckBox(tr("Use keyboard hook (for shortcuts Windows won't register)"),
+                                   this);
+    m_keyboardHook->setChecked(cfg.keyboardHook);
+    root->addWidget(m_keyboardHook);
+
     auto *buttons = new QDialogButtonBox(QDialogButtonBox::Ok | QDialogButtonBox::Cancel, this);
     root->addWidget(buttons);
     connect(buttons, &QDialogButtonBox::accepted, this, &ShortcutsDialog::accept);
@@ -59,7 +92,8 @@ ShortcutsDialog::ShortcutsDialog(const persistence::ShortcutConfig &cfg, QWidget
 ShortcutsDialog::Row ShortcutsDialog::addRow(QGridLayout *grid, int r,
                                              const QString &label, const QString &shortcut)
 {
-    const QStringList tokens = shortcuts::displayTokens(shortcut);
+    const QStringList parts = shortcuts::steps(shortcut);
+    const QStringList tokens = shortcuts::displayTokens(parts.value(0));
 
     Row row;
     grid->addWidget(new QLabel(label, this), r, 0);
@@ -69,6 +103,9 @@ ShortcutsDialog::Row ShortcutsDialog::addRow(QGridLayout *grid, int r,
     row.shift = new QCheckBox(this);
     row.key   = new QComboBox(this);
     row.key->addItems(keyChoices());
+    row.then  = new QComboBox(this);
+    row.then->addItem(QString::fromUtf8("\xE2\x80\x94"));   // — (no second step)
+    row.then->addItems(keyChoices());
 
     row.win->setChecked(tokens.contains(QStringLiteral("Win")));
     row.ctrl->setChecked(tokens.contains(QStringLiteral("Ctrl")));
@@ -79,28 +116,41 @@ ShortcutsDialog::Row ShortcutsDialog::addRow(QGridLayout *grid, int r,
         if (idx >= 0)
             row.key->setCurrentIndex(idx);
     }
+    if (parts.size() > 1) {
+        const QStringList then = shortcuts::displayTokens(parts[1]);
+        const int idx = then.isEmpty() ? -1 : row.then->findText(then.last());
+        if (idx > 0)
+            row.then->setCurrentIndex(idx);
+    }
 
     grid->addWidget(row.win,   r, 1, Qt::AlignCenter);
     grid->addWidget(row.ctrl,  r, 2, Qt::AlignCenter);
     grid->addWidget(row.alt,   r, 3, Qt::AlignCenter);
     grid->addWidget(row.shift, r, 4, Qt::AlignCenter);
     grid->addWidget(row.key,   r, 5);
+    grid->addWidget(row.then,  r, 6);
     return row;
 }
 
 void ShortcutsDialog::accept()
 {
     auto build = [](const Row &row) {
+        const QString then = row.then->currentIndex() > 0 ? row.then->currentText() : QString();
         return shortcuts::build(row.win->isChecked(), row.ctrl->isChecked(),
                                 row.alt->isChecked(), row.shift->isChecked(),
-                                row.key->currentText());
+                                row.key->currentText(), then);
     };
     auto hasModifier = [](const Row &row) {
         return row.win->isChecked() || row.ctrl->isChecked()
                || row.alt->isChecked() || row.shift->isChecked();
     };
 
-    const Row rows[] = {m_togglePin, m_opacityUp, m_opacityDown, m_toggleWindow};
+    QVector<Row> rows = {m_togglePin, m_opacityUp, m_opacityDown, m_toggleWindow,
+                         m_pinUnderCursor, m_quickPanel, m_hideAllPinned,
+                         m_pinTemporarily, m_kioskExit, m_toggleLastPinned,
+                         m_focusLastPinned, m_pinWithOpacity, m_opacitySlider};
+    for (int i = 0; i < persistence::kPinSlots; ++i)
+        rows << m_slot[i] << m_assignSlot[i];
     for (const Row &row : rows) {
         if (!hasModifier(row)) {
             QMessageBox::warning(this, tr("Invalid shortcut"),
@@ -114,17 +164,90 @@ void ShortcutsDialog::accept()
     cfg.opacityUp    = build(m_opacityUp);
     cfg.opacityDown  = build(m_opacityDown);
     cfg.toggleWindow = build(m_toggleWindow);
+    cfg.pinUnderCursor = build(m_pinUnderCursor);
+    cfg.quickPanel   = build(m_quickPanel);
+    cfg.hideAllPinned = build(m_hideAllPinned);
+    cfg.pinTemporarily = build(m_pinTemporarily);
+    cfg.kioskExit    = build(m_kioskExit);
+    cfg.toggleLastPinned = build(m_toggleLastPinned);
+    cfg.focusLastPinned = build(m_focusLastPinned);
+    cfg.pinWithOpacity = build(m_pinWithOpacity);
+    cfg.opacitySlider = build(m_opacitySlider);
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        cfg.slot[i] = build(m_slot[i]);
+        cfg.assignSlot[i] = build(m_assignSlot[i]);
+    }
+    cfg.holdToPreview = m_holdToPreview->isChecked();
+    cfg.keyboardHook = m_keyboardHook->isChecked();
+
+    // No two actions may share a binding, and a plain shortcut can't also be
+    // the first step of a chord — the chord would swallow it. Macro hotkeys
+    // are checked last, so a clash with one is reported at the macro.
+    const QStringList all = persistence::bindings(cfg);
+    int at = -1;
+    const shortcuts::Problem problem = shortcuts::check(all + m_macroShortcuts, &at);
+    if (problem != shortcuts::Problem::None && at >= all.size()) {
+        QMessageBox::warning(this, tr("Shortcut used by a macro"),
+            tr("A macro's hotkey already uses %1. Pick another shortcut, or change "
+               "the macro under Macros.")
+                .arg(shortcuts::displayTokens(shortcuts::steps(m_macroShortcuts[at - all.size()])
+                                                  .value(0))
+                         .join(QStringLiteral(" + "))));
+        return;
+    }
+    switch (problem) {
+    case shortcuts::Problem::None:
+        break;
+    case shortcuts::Problem::Duplicate:
+        QMessageBox::warning(this, tr("Duplicate shortcut"),
+            tr("Two actions can't use the same shortcut."));
+        return;
+    case shortcuts::Problem::ChordPrefix:
+        QMessageBox::warning(this, tr("Conflicting shortcut"),
+            tr("A shortcut can't also start another action's two-step chord."));
+        return;
+    case shortcuts::Problem::Invalid:
+        QMessageBox::warning(this, tr("Invalid shortcut"),
+            tr("A shortcut is one or two steps, like ctrl+alt+KeyM or ctrl+alt+KeyM,KeyO."));
+        return;
+    case shortcuts::Problem::NoModifier:
+        QMessageBox::warning(this, tr("Invalid shortcut"),
+            tr("Each shortcut needs at least one modifier (Win/Ctrl/Alt/Shift)."));
+        return;
+    }
 
-    // No two actions may share a binding.
-    const QStringList all = {cfg.togglePin, cfg.opacityUp, cfg.opacityDown, cfg.toggleWindow};
-    QSet<QString> seen;
+    // Warn before saving anything Windows or another app already owns — the
+    // registration would otherwise only fail after the dialog closes. Combos
+    // the current config uses are held by PinIt itself, so skip probing those.
+    QStringList held;
+    for (const QString &s : {m_config.togglePin, m_config.opacityUp, m_config.opacityDown,
+                             m_config.toggleWindow, m_config.pinUnderCursor,
+                             m_config.quickPanel, m_config.hideAllPinned,
+                             m_config.pinTemporarily, m_config.kioskExit,
+                             m_config.toggleLastPinned, m_config.focusLastPinned,
+                             m_config.pinWithOpacity, m_config.opacitySlider})
+        held << shortcuts::steps(s).value(0);
+    for (int i = 0; i < persistence::kPinSlots; ++i)
+        held << shortcuts::steps(m_config.slot[i]).value(0)
+             << shortcuts::steps(m_config.assignSlot[i]).value(0);
+
+    QStringList problems;
     for (const QString &s : all) {
-        if (seen.contains(s)) {
-            QMessageBox::warning(this, tr("Duplicate shortcut"),
-                tr("Two actions can't use the same shortcut."));
+        const QString shown = shortcuts::displayTokens(shortcuts::steps(s).value(0))
+                                  .join(QStringLiteral(" + "));
+        const QString reserved = shortcuts::reservedBy(s);
+        if (!reserved.isEmpty())
+            problems << tr("%1 is reserved by Windows (%2)").arg(shown, reserved);
+        else if (!held.contains(shortcuts::steps(s).value(0))
+                 && !GlobalHotkeyManager::isAvailable(s))
+            problems << tr("%1 is already in use by another app").arg(shown);
+    }
+    if (!problems.isEmpty()) {
+        const auto answer = QMessageBox::question(this, tr("Shortcut unavailable"),
+            tr("Some shortcuts probably won't work:\n\n%1\n\nSave anyway?")
+                .arg(problems.join(QLatin1Char('\n'))));
+        if (answer != QMessageBox::Yes)
             return;
-        }
-        seen.insert(s);
     }
 
     m_config = cfg;
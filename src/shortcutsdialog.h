@@ -1,6 +1,6 @@
 #pragma once
 //
-// ShortcutsDialog — lets the user rebind PinIt's four global shortcuts.
+// ShortcutsDialog — lets the user rebind PinIt's global shortcuts.
 //
 // Uses modifier checkboxes + a key dropdown instead of live key capture: on
 // Windows the Win/Super key is swallowed by the OS and can't be captured
@@ -8,6 +8,8 @@
 //
 #include <QDialog>
 
+#include <array>
+
 #include "persistence.h"
 
 class QCheckBox;
@@ -18,7 +20,10 @@ class ShortcutsDialog : public QDialog
 {
     Q_OBJECT
 public:
-    explicit ShortcutsDialog(const persistence::ShortcutConfig &cfg, QWidget *parent = nullptr);
+    // macroShortcuts are the macros' hotkeys, which the new bindings mustn't
+    // clash with either.
+    explicit ShortcutsDialog(const persistence::ShortcutConfig &cfg,
+                             const QStringList &macroShortcuts = {}, QWidget *parent = nullptr);
 
     // The edited config (valid only after the dialog is accepted).
     persistence::ShortcutConfig config() const { return m_config; }
@@ -30,6 +35,7 @@ private:
         QCheckBox *alt = nullptr;
         QCheckBox *shift = nullptr;
         QComboBox *key = nullptr;
+        QComboBox *then = nullptr;   // optional second chord step ("—" = none)
     };
 
     Row addRow(QGridLayout *grid, int r, const QString &label, const QString &shortcut);
@@ -39,6 +45,20 @@ private:
     Row m_opacityUp;
     Row m_opacityDown;
     Row m_toggleWindow;
+    Row m_pinUnderCursor;
+    Row m_quickPanel;
+    Row m_hideAllPinned;
+    Row m_pinTemporarily;
+    Row m_kioskExit;
+    Row m_toggleLastPinned;
+    Row m_focusLastPinned;
+    Row m_pinWithOpacity;
+    Row m_opacitySlider;
+    std::array<Row, persistence::kPinSlots> m_slot;
+    std::array<Row, persistence::kPinSlots> m_assignSlot;
+    QCheckBox *m_holdToPreview = nullptr;
+    QCheckBox *m_keyboardHook = nullptr;
 
     persistence::ShortcutConfig m_config;
+    QStringList                 m_macroShortcuts;
 };
@@ -0,0 +1,153 @@
+#include "taskbar.h"
+
+#include <windows.h>
+#include <shobjidl.h>
+#include <propsys.h>
+
+#include <QCoreApplication>
+#include <QColor>
+#include <QDir>
+#include <QFont>
+#include <QImage>
+#include <QPainter>
+#include <QPixmap>
+
+#include <cstddef>
+#include <cstring>
+
+namespace {
+
+// PKEY_Title, spelled out so we don't depend on the SDK exporting it.
+const PROPERTYKEY kTitleKey = {
+    {0xF29F85E0, 0x4FF9, 0x1068, {0xAB, 0x91, 0x08, 0x00, 0x2B, 0x27, 0xB3, 0xD9}}, 2};
+
+// Quote one argument for a Windows command line.
+QString quoteArg(const QString &arg)
+{
+    if (!arg.isEmpty() && !arg.contains(QLatin1Char(' ')) && !arg.contains(QLatin1Char('"')))
+        return arg;
+    QString q = arg;
+    q.replace(QLatin1Char('"'), QStringLiteral("\\\""));
+    return QLatin1Char('"') + q + QLatin1Char('"');
+}
+
+IShellLinkW *makeTaskLink(const taskbar::JumpTask &task)
+{
+    IShellLinkW *link = nullptr;
+    if (FAILED(CoCreateInstance(CLSID_ShellLink, nullptr, CLSCTX_INPROC_SERVER,
+                                IID_IShellLinkW, reinterpret_cast<void **>(&link))))
+        return nullptr;
+
+    const QString exe = QDir::toNativeSeparators(QCoreApplication::applicationFilePath());
+    QStringList quoted;
+    for (const QString &a : task.arguments)
+        quoted << quoteArg(a);
+    const QString args = quoted.join(QLatin1Char(' '));
+    link->SetPath(reinterpret_cast<const wchar_t *>(exe.utf16()));
+    link->SetArguments(reinterpret_cast<const wchar_t *>(args.utf16()));
+    link->SetIconLocation(reinterpret_cast<const wchar_t *>(exe.utf16()), 0);
+
+    // Tasks show the link's Title property, not its file name.
+    IPropertyStore *props = nullptr;
+    if (SUCCEEDED(link->QueryInterface(IID_IPropertyStore, reinterpret_cast<void **>(&props)))) {
+        PROPVARIANT pv;
+        PropVariantInit(&pv);
+        const std::size_t bytes = (task.title.size() + 1) * sizeof(wchar_t);
+        pv.vt = VT_LPWSTR;
+        pv.pwszVal = static_cast<wchar_t *>(CoTaskMemAlloc(bytes));
+        if (pv.pwszVal) {
+            memcpy(pv.pwszVal, task.title.utf16(), bytes);   // includes the terminator
+            props->SetValue(kTitleKey, pv);
+            props->Commit();
+        }
+        PropVariantClear(&pv);
+        props->Release();
+    }
+    return link;
+}
+
+} // namespace
+
+namespace taskbar {
+
+bool setOverlay(void *hwnd, const QIcon &icon, const QString &description)
+{
+    // COM is already initialised on the GUI thread by Qt (OleInitialize).
+    ITaskbarList3 *list = nullptr;
+    if (FAILED(CoCreateInstance(CLSID_TaskbarList, nullptr, CLSCTX_INPROC_SERVER,
+                                IID_ITaskbarList3, reinterpret_cast<void **>(&list))))
+        return false;
+
+    bool ok = false;
+    if (SUCCEEDED(list->HrInit())) {
+        HICON hicon = icon.isNull() ? nullptr : icon.pixmap(16, 16).toImage().toHICON();
+        ok = SUCCEEDED(list->SetOverlayIcon(
+            reinterpret_cast<HWND>(hwnd), hicon,
+            reinterpret_cast<const wchar_t *>(description.utf16())));
+        if (hicon)
+            DestroyIcon(hicon);   // the taskbar keeps its own copy
+    }
+    list->Release();
+    return ok;
+}
+
+bool setJumpListTasks(const QVector<JumpTask> &tasks)
+{
+    ICustomDestinationList *list = nullptr;
+    if (FAILED(CoCreateInstance(CLSID_DestinationList, nullptr, CLSCTX_INPROC_SERVER,
+                                IID_ICustomDestinationList, reinterpret_cast<void **>(&list))))
+        return false;
+
+    bool ok = false;
+    UINT maxSlots = 0;
+    IObjectArray *removed = nullptr;
+    if (SUCCEEDED(list->BeginList(&maxSlots, IID_IObjectArray,
+                                  reinterpret_cast<void **>(&removed)))) {
+        IObjectCollection *items = nullptr;
+        if (SUCCEEDED(CoCreateInstance(CLSID_EnumerableObjectCollection, nullptr,
+                                       CLSCTX_INPROC_SERVER, IID_IObjectCollection,
+                                       reinterpret_cast<void **>(&items)))) {
+            for (const JumpTask &task : tasks) {
+                if (IShellLinkW *link = makeTaskLink(task)) {
+                    items->AddObject(link);
+                    link->Release();
+                }
+            }
+            IObjectArray *array = nullptr;
+            if (SUCCEEDED(items->QueryInterface(IID_IObjectArray,
+                                                reinterpret_cast<void **>(&array)))) {
+                ok = SUCCEEDED(list->AddUserTasks(array)) && SUCCEEDED(list->CommitList());
+                array->Release();
+            }
+            items->Release();
+        }
+        removed->Release();
+    }
+    list->Release();
+    return ok;
+}
+
+QIcon countBadge(int count)
+{
+    QPixmap pm(32, 32);
+    pm.fill(Qt::transparent);
+
+    QPainter p(&pm);
+    p.setRenderHint(QPainter::Antialiasing);
+    p.setPen(Qt::NoPen);
+    p.setBrush(QColor(QStringLiteral("#c49464")));   // the app's accent colour
+    p.drawEllipse(pm.rect().adjusted(1, 1, -1, -1));
+
+    QFont f(QStringLiteral("Segoe UI"));
+    f.setBold(true);
+    f.setPixelSize(count > 9 ? 16 : 20);
+    p.setFont(f);
+    p.setPen(Qt::white);
+    p.drawText(pm.rect(), Qt::AlignCenter,
+               count > 99 ? QStringLiteral("99+") : QString::number(count));
+    p.end();
+
+    return QIcon(pm);
+}
+
+} // namespace taskbar
@@ -0,0 +1,33 @@
+#pragma once
+//
+// taskbar — PinIt's own taskbar button: the overlay badge (ITaskbarList3)
+// and the Jump List tasks (ICustomDestinationList).
+//
+// The button only exists while the main window is visible; calls made while
+// it's hidden in the tray are harmless no-ops.
+//
+#include <QIcon>
+#include <QString>
+#include <QStringList>
+#include <QVector>
+
+namespace taskbar {
+
+// Show `icon` as a small overlay on the window's taskbar button, with
+// `description` as its accessibility text. A null icon clears the overlay.
+bool setOverlay(void *hwnd, const QIcon &icon, const QString &description);
+
+// A round badge with `count` in it (e.g. the number of pinned windows).
+QIcon countBadge(int count);
+
+// One "Tasks" entry in the taskbar Jump List: relaunches PinIt with
+// `arguments`, which the single-instance channel forwards to the running copy.
+struct JumpTask {
+    QString     title;
+    QStringList arguments;
+};
+
+// Replace the Jump List's Tasks category with `tasks`.
+bool setJumpListTasks(const QVector<JumpTask> &tasks);
+
+} // namespace taskbar
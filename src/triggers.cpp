@@ -0,0 +1,147 @@
+#include "triggers.h"
+#include "actions.h"
+#include "winpin.h"
+#include "perf.h"
+
+#include <QGuiApplication>
+#include <QTimer>
+
+#include <algorithm>
+#include <functional>
+
+namespace {
+
+constexpr int kPollMs = 2000;
+
+const QLatin1String kWindowOpened("window-opened");
+const QLatin1String kWindowClosed("window-closed");
+const QLatin1String kWindowFocused("window-focused");
+const QLatin1String kAppStarted("app-started");
+const QLatin1String kMonitorAdded("monitor-connected");
+const QLatin1String kMonitorRemoved("monitor-disconnected");
+const QLatin1String kSessionLocked("session-locked");
+const QLatin1String kSessionUnlocked("session-unlocked");
+
+} // namespace
+
+namespace triggers {
+
+QVector<QPair<QString, const char *>> events()
+{
+    return {
+        { kWindowOpened,    QT_TRANSLATE_NOOP("TriggerWatcher", "A window opens") },
+        { kWindowClosed,    QT_TRANSLATE_NOOP("TriggerWatcher", "A window closes") },
+        { kWindowFocused,   QT_TRANSLATE_NOOP("TriggerWatcher", "A window comes to the front") },
+        { kAppStarted,      QT_TRANSLATE_NOOP("TriggerWatcher", "An app starts") },
+        { kMonitorAdded,    QT_TRANSLATE_NOOP("TriggerWatcher", "A monitor is connected") },
+        { kMonitorRemoved,  QT_TRANSLATE_NOOP("TriggerWatcher", "A monitor is disconnected") },
+        { kSessionLocked,   QT_TRANSLATE_NOOP("TriggerWatcher", "The PC is locked") },
+        { kSessionUnlocked, QT_TRANSLATE_NOOP("TriggerWatcher", "The PC is unlocked") },
+    };
+}
+
+bool takesProcess(const QString &event)
+{
+    return event == kWindowOpened || event == kWindowClosed || event == kWindowFocused
+        || event == kAppStarted;
+}
+
+} // namespace triggers
+
+TriggerWatcher::TriggerWatcher(const ActionRegistry *actions, QObject *parent)
+    : QObject(parent)
+    , m_actions(actions)
+{
+    m_timer = new QTimer(this);
+    m_timer->setInterval(kPollMs);
+    connect(m_timer, &QTimer::timeout, this, &TriggerWatcher::poll);
+    connect(qApp, &QGuiApplication::screenAdded, this, [this]() { fire(kMonitorAdded); });
+    connect(qApp, &QGuiApplication::screenRemoved, this, [this]() { fire(kMonitorRemoved); });
+}
+
+void TriggerWatcher::setTriggers(const QVector<persistence::Trigger> &triggers)
+{
+    m_triggers = triggers;
+    const auto any = [this](const std::function<bool(const QString &)> &wanted) {
+        return std::any_of(m_triggers.cbegin(), m_triggers.cend(),
+                           [&wanted](const persistence::Trigger &t) {
+                               return t.enabled && wanted(t.event);
+                           });
+    };
+    const bool polling = any(triggers::takesProcess);
+    const bool listWindows = any([](const QString &e) {
+        return triggers::takesProcess(e) && e != kWindowFocused;
+    });
+    if (polling && (!m_timer->isActive() || listWindows != m_listWindows)) {
+        m_seeded = false;
+        m_timer->start();
+    } else if (!polling) {
+        m_timer->stop();
+    }
+    m_listWindows = polling && listWindows;
+}
+
+void TriggerWatcher::onSessionLocked()   { fire(kSessionLocked); }
+void TriggerWatcher::onSessionUnlocked() { fire(kSessionUnlocked); }
+
+void TriggerWatcher::poll()
+{
+    perf::tick("triggers");
+    QHash<intptr_t, QString> windows;
+    QSet<QString> processes;
+    QHash<QString, intptr_t> firstWindow;   // process → a window of it, for app-started
+    if (m_listWindows) {
+        for (const winpin::PinnableWindow &w : winpin::enumerateWindows()) {
+            // PinIt's own dialogs opening could otherwise set off an action
+            // that opens another one.
+            if (winpin::isOwnWindow(reinterpret_cast<void *>(w.hwnd)))
+                continue;
+            const QString process = w.processName.toLower();
+            windows.insert(w.hwnd, process);
+            processes.insert(process);
+            if (!firstWindow.contains(process))
+                firstWindow.insert(process, w.hwnd);
+        }
+    }
+    void *fg = winpin::foregroundWindow();
+    const bool fgCounts = fg && !winpin::isOwnWindow(fg);
+    const QString fgProcess = fgCounts ? winpin::processName(fg).toLower()
+                                       : m_foregroundProcess;
+    const intptr_t fgHwnd = fgCounts ? reinterpret_cast<intptr_t>(fg) : m_foreground;
+
+    if (m_seeded) {
+        for (auto it = windows.cbegin(); it != windows.cend(); ++it) {
+            if (!m_windows.contains(it.key()))
+                fire(kWindowOpened, it.value(), it.key());
+        }
+        for (auto it = m_windows.cbegin(); it != m_windows.cend(); ++it) {
+            if (!windows.contains(it.key()))
+                fire(kWindowClosed, it.value());
+        }
+        for (const QString &process : processes) {
+            if (!m_processes.contains(process))
+                fire(kAppStarted, process, firstWindow.value(process));
+        }
+        // Switching between two windows of the same app isn't "coming to the front".
+        if (!fgProcess.isEmpty() && fgProcess != m_foregroundProcess)
+            fire(kWindowFocused, fgProcess, fgHwnd);
+    }
+    m_windows = windows;
+    m_processes = processes;
+    m_foregroundProcess = fgProcess;
+    m_foreground = fgHwnd;
+    m_seeded = true;
+}
+
+void TriggerWatcher::fire(const QString &event, const QString &process, intptr_t hwnd)
+{
+    for (const persistence::Trigger &t : std::as_const(m_triggers)) {
+        if (!t.enabled || t.event != event)
+            continue;
+        if (!t.process.isEmpty() && t.process.compare(process, Qt::CaseInsensitive) != 0)
+            continue;
+        qInfo("Trigger %s (%s%s%s) → %s", qUtf8Printable(t.name), qUtf8Printable(event),
+              process.isEmpty() ? "" : " ", qUtf8Printable(process), qUtf8Printable(t.action));
+        emit fired(t.name, m_actions->invoke(t.action, t.args, hwnd));
+    }
+}
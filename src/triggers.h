@@ -0,0 +1,74 @@
+#pragma once
+//
+// TriggerWatcher — "when X happens, do Y": runs an action (see actions.h)
+// when a window of some app opens, closes or comes to the front, when an app
+// starts, when a monitor is plugged in or removed, or when the session is
+// locked or unlocked.
+//
+// Window and app events come from polling every two seconds, as PinManager's
+// re-enforce timer does, and only while a trigger needs it: the top-level
+// windows are listed only for opened / closed / app-started triggers, a
+// "comes to the front" one just looks at the foreground window. Monitors come
+// from QGuiApplication, lock/unlock from SessionEvents. "App started" means
+// its first window appeared — a background process with no window doesn't
+// count.
+//
+// The action runs with the window that set it off as its target (see
+// ActionRegistry::invoke), so "pin the focused window" pins the window that
+// opened, came to the front or belongs to the app that started. Monitor,
+// session and window-closed events have no such window and act on the one in
+// front.
+//
+#include <QHash>
+#include <QObject>
+#include <QPair>
+#include <QSet>
+#include <QVector>
+
+#include "persistence.h"
+
+class ActionRegistry;
+class QTimer;
+
+namespace triggers {
+
+// The event ids stored in Trigger::event, with their untranslated labels
+// (translate with QCoreApplication::translate("TriggerWatcher", …) at the call
+// site), in menu order.
+QVector<QPair<QString, const char *>> events();
+// Whether the event is about a particular app, i.e. Trigger::process applies.
+bool takesProcess(const QString &event);
+
+} // namespace triggers
+
+class TriggerWatcher : public QObject
+{
+    Q_OBJECT
+public:
+    explicit TriggerWatcher(const ActionRegistry *actions, QObject *parent = nullptr);
+
+    void setTriggers(const QVector<persistence::Trigger> &triggers);
+    // One look at the windows. The timer calls it; the first call after
+    // setTriggers() only records what's there.
+    void poll();
+
+public slots:
+    void onSessionLocked();
+    void onSessionUnlocked();
+
+signals:
+    void fired(const QString &name, bool ok);
+
+private:
+    void fire(const QString &event, const QString &process = QString(), intptr_t hwnd = 0);
+
+    const ActionRegistry            *m_actions = nullptr;
+    QTimer                          *m_timer = nullptr;
+    QVector<persistence::Trigger>    m_triggers;
+    bool                             m_listWindows = false;   // not just focus triggers
+    bool                             m_seeded = false;   // first poll only records
+    QHash<intptr_t, QString>         m_windows;          // hwnd → lower-cased process
+    QSet<QString>                    m_processes;
+    intptr_t                         m_foreground = 0;
+    QString                          m_foregroundProcess;
+};
@@ -0,0 +1,60 @@
+#include "uia.h"
+
+#include <windows.h>
+#include <uiautomation.h>
+
+namespace {
+
+inline HWND H(void *hwnd) { return reinterpret_cast<HWND>(hwnd); }
+
+// One client for the process lifetime; COM is already initialised on the UI
+// thread by Qt.
+IUIAutomation *automation()
+{
+    static IUIAutomation *client = [] {
+        IUIAutomation *a = nullptr;
+        if (FAILED(CoCreateInstance(CLSID_CUIAutomation, nullptr, CLSCTX_INPROC_SERVER,
+                                    IID_PPV_ARGS(&a))))
+            return static_cast<IUIAutomation *>(nullptr);
+        return a;
+    }();
+    return client;
+}
+
+QString take(BSTR s)
+{
+    if (!s)
+        return QString();
+    const QString out = QString::fromWCharArray(s, static_cast<int>(SysStringLen(s)));
+    SysFreeString(s);
+    return out;
+}
+
+} // namespace
+
+namespace uia {
+
+Identity identify(void *hwnd)
+{
+    Identity id;
+    // A hung window would stall the UI thread for the full UIA timeout.
+    if (!IsWindow(H(hwnd)) || IsHungAppWindow(H(hwnd)))
+        return id;
+    IUIAutomation *a = automation();
+    if (!a)
+        return id;
+
+    IUIAutomationElement *el = nullptr;
+    if (FAILED(a->ElementFromHandle(H(hwnd), &el)) || !el)
+        return id;
+    BSTR s = nullptr;
+    if (SUCCEEDED(el->get_CurrentAutomationId(&s)))
+        id.automationId = take(s);
+    s = nullptr;
+    if (SUCCEEDED(el->get_CurrentName(&s)))
+        id.name = take(s);
+    el->Release();
+    return id;
+}
+
+} // namespace uia
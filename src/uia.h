@@ -0,0 +1,23 @@
+#pragma once
+//
+// uia — optional UI Automation lookups for telling windows apart when their
+// titles can't: an app's main window often carries a stable AutomationId
+// while its title changes with every document or tab.
+//
+// COM (IUIAutomation), so it stays out of the Core-only winpin. Each call is
+// a cross-process round trip; callers use it only when the user opted in.
+//
+#include <QString>
+
+namespace uia {
+
+struct Identity {
+    QString automationId;
+    QString name;
+};
+
+// Empty if UI Automation can't reach the window (hung, elevated, or the app
+// exposes nothing).
+Identity identify(void *hwnd);
+
+} // namespace uia
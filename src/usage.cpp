@@ -0,0 +1,114 @@
+#include "usage.h"
+#include "persistence.h"
+
+#include <QDate>
+#include <QDir>
+#include <QFile>
+#include <QFileInfo>
+#include <QJsonDocument>
+#include <QJsonObject>
+#include <QSaveFile>
+
+#include <algorithm>
+
+namespace {
+
+bool g_enabled = false;
+
+QString usagePath()
+{
+    return QDir(persistence::dataDir()).filePath(QStringLiteral("usage.json"));
+}
+
+QJsonObject load()
+{
+    QFile f(usagePath());
+    if (!f.open(QIODevice::ReadOnly))
+        return QJsonObject();
+    return QJsonDocument::fromJson(f.readAll()).object();
+}
+
+void save(const QJsonObject &root)
+{
+    const QString path = usagePath();
+    QDir().mkpath(QFileInfo(path).absolutePath());
+    QSaveFile f(path);
+    if (f.open(QIODevice::WriteOnly)) {
+        f.write(QJsonDocument(root).toJson(QJsonDocument::Indented));
+        f.commit();
+    }
+}
+
+// root[section][key] += 1
+void bump(const char *section, const QString &key)
+{
+    if (!g_enabled || key.isEmpty())
+        return;
+    QJsonObject root = load();
+    QJsonObject counts = root.value(QLatin1String(section)).toObject();
+    counts[key] = counts.value(key).toInt() + 1;
+    root[QLatin1String(section)] = counts;
+    save(root);
+}
+
+usage::Counts sorted(const QJsonObject &o)
+{
+    usage::Counts out;
+    for (auto it = o.begin(); it != o.end(); ++it)
+        out.push_back({it.key(), it.value().toInt()});
+    std::sort(out.begin(), out.end(),
+              [](const auto &a, const auto &b) { return a.second > b.second; });
+    return out;
+}
+
+} // namespace
+
+namespace usage {
+
+void setEnabled(bool enabled) { g_enabled = enabled; }
+bool isEnabled() { return g_enabled; }
+
+void recordPin()
+{
+    bump("pins_per_day", QDate::currentDate().toString(Qt::ISODate));
+}
+
+void recordFeature(const QString &name) { bump("features", name); }
+void recordError(const QString &code)   { bump("errors", code); }
+
+Summary summary()
+{
+    const QJsonObject root = load();
+    Summary s;
+
+    const QJsonObject days = root.value("pins_per_day").toObject();
+    for (auto it = days.begin(); it != days.end(); ++it) {
+        s.totalPins += it.value().toInt();
+        s.pinsPerDay.push_back({it.key(), it.value().toInt()});
+    }
+    // ISO dates sort chronologically as strings.
+    std::sort(s.pinsPerDay.begin(), s.pinsPerDay.end(),
+              [](const auto &a, const auto &b) { return a.first > b.first; });
+    if (s.pinsPerDay.size() > 7)
+        s.pinsPerDay.resize(7);
+
+    s.features = sorted(root.value("features").toObject());
+    s.errors   = sorted(root.value("errors").toObject());
+    return s;
+}
+
+bool exportTo(const QString &path)
+{
+    QSaveFile f(path);
+    if (!f.open(QIODevice::WriteOnly))
+        return false;
+    f.write(QJsonDocument(load()).toJson(QJsonDocument::Indented));
+    return f.commit();
+}
+
+void clear()
+{
+    QFile::remove(usagePath());
+}
+
+} // namespace usage
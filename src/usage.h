@@ -0,0 +1,36 @@
+#pragma once
+//
+// usage — opt-in, local-only usage stats: pins per day, which features get
+// used, and which errors come up. Kept in usage.json in the data folder and
+// never sent anywhere; the user can look at it (tray → Usage stats…) or export
+// it to attach to a bug report.
+//
+// Off by default. While disabled every record*() call is a no-op.
+//
+#include <QPair>
+#include <QString>
+#include <QVector>
+
+namespace usage {
+
+void setEnabled(bool enabled);   // the consent setting; call once at startup
+bool isEnabled();
+
+void recordPin();                          // one pin toward today's count
+void recordFeature(const QString &name);   // "pin_hotkey", "snapshot_restore", …
+void recordError(const QString &code);     // a PinError code
+
+using Counts = QVector<QPair<QString, int>>;   // (key, count), most used first
+
+struct Summary {
+    int    totalPins = 0;
+    Counts pinsPerDay;   // last 7 days that had any pins, newest first
+    Counts features;
+    Counts errors;
+};
+
+Summary summary();
+bool    exportTo(const QString &path);   // the raw JSON
+void    clear();
+
+} // namespace usage
@@ -0,0 +1,76 @@
+#include "windowicon.h"
+#include "winpin.h"
+
+#include <windows.h>
+#include <shellapi.h>
+
+#include <QCache>
+#include <QDir>
+#include <QImage>
+
+namespace {
+
+inline HWND H(void *hwnd) { return reinterpret_cast<HWND>(hwnd); }
+
+// Icon handles owned by the window/class — must NOT be destroyed by us.
+HICON borrowedIcon(HWND hwnd)
+{
+    // SendMessageTimeout so a hung window can't stall the UI thread.
+    for (WPARAM kind : {WPARAM(ICON_BIG), WPARAM(ICON_SMALL2), WPARAM(ICON_SMALL)}) {
+        DWORD_PTR result = 0;
+        if (SendMessageTimeoutW(hwnd, WM_GETICON, kind, 0, SMTO_ABORTIFHUNG, 100, &result)
+            && result != 0)
+            return reinterpret_cast<HICON>(result);
+    }
+    if (auto icon = reinterpret_cast<HICON>(GetClassLongPtrW(hwnd, GCLP_HICON)))
+        return icon;
+    return reinterpret_cast<HICON>(GetClassLongPtrW(hwnd, GCLP_HICONSM));
+}
+
+// Looked up once per window and size: WM_GETICON waits on the app, and the
+// exe fallback reads the file. The process id is part of the key, so a handle
+// Windows hands to a new window doesn't come back with the old one's icon.
+QCache<QString, QPixmap> g_icons(256);
+
+QPixmap load(void *hwnd, int size)
+{
+    QImage image;
+    if (HICON icon = borrowedIcon(H(hwnd))) {
+        image = QImage::fromHICON(icon);
+    } else {
+        // No window icon (common for UWP/console hosts): use the exe's icon.
+        const QString exe = QDir::toNativeSeparators(winpin::processPath(hwnd));
+        SHFILEINFOW info = {};
+        if (!exe.isEmpty()
+            && SHGetFileInfoW(reinterpret_cast<const wchar_t *>(exe.utf16()), 0, &info,
+                              sizeof(info), SHGFI_ICON | SHGFI_LARGEICON)
+            && info.hIcon) {
+            image = QImage::fromHICON(info.hIcon);
+            DestroyIcon(info.hIcon);   // SHGetFileInfo hands us ownership
+        }
+    }
+
+    if (image.isNull())
+        return QPixmap();
+    return QPixmap::fromImage(
+        image.scaled(size, size, Qt::KeepAspectRatio, Qt::SmoothTransformation));
+}
+
+} // namespace
+
+namespace windowicon {
+
+QPixmap forWindow(void *hwnd, int size)
+{
+    const QString key = QStringLiteral("%1/%2/%3")
+                            .arg(reinterpret_cast<quintptr>(hwnd))
+                            .arg(winpin::processId(hwnd))
+                            .arg(size);
+    if (const QPixmap *cached = g_icons.object(key))
+        return *cached;
+    const QPixmap icon = load(hwnd, size);
+    g_icons.insert(key, new QPixmap(icon));   // a null one too: nothing to find next time
+    return icon;
+}
+
+} // namespace windowicon
@@ -0,0 +1,17 @@
+#pragma once
+//
+// windowicon — fetch the icon a window shows in the taskbar, as a QPixmap.
+//
+// Lives apart from winpin because it needs QtGui (QImage::fromHICON); winpin
+// stays Core-only so the unit tests can link it without a GUI.
+//
+#include <QPixmap>
+
+namespace windowicon {
+
+// The window's own icon (WM_GETICON, then its class icon), falling back to the
+// icon of the process executable. Returns a null pixmap if nothing was found.
+// Cached per window, so a list rebuilt on every change asks each app once.
+QPixmap forWindow(void *hwnd, int size);
+
+} // namespace windowicon
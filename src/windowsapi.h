@@ -0,0 +1,61 @@
+#pragma once
+//
+// WindowsApi — the Win32 calls winpin's window handling is built on, behind an
+// interface so everything above it (PinManager's pin/unpin/opacity logic,
+// restore matching, the re-enforce pass) can run against an in-memory desktop
+// in unit tests; see tests/fakewindows.h. The default passes straight through
+// to user32.
+//
+// Only what pin state depends on goes through here: window existence and
+// styles, Z-order, layered alpha, titles, rects, owning process, monitors
+// and enumeration. Rarer calls (DWM, process command lines, UI Automation)
+// stay direct and simply fail on a fake handle.
+//
+#include <QRect>
+#include <QString>
+#include <QVector>
+
+namespace winpin {
+
+class WindowsApi
+{
+public:
+    virtual ~WindowsApi() = default;
+
+    enum class ZOrder { Unchanged, Topmost, NotTopmost };
+
+    virtual bool    isWindow(void *hwnd) = 0;                   // IsWindow
+    virtual bool    isVisible(void *hwnd) = 0;                  // IsWindowVisible
+    virtual bool    isIconic(void *hwnd) = 0;                   // IsIconic
+    virtual bool    isHung(void *hwnd) = 0;                     // IsHungAppWindow
+    // WM_NULL with SMTO_ABORTIFHUNG: does the window's thread answer in time?
+    virtual bool    ping(void *hwnd, int timeoutMs) = 0;
+    virtual quint32 exStyle(void *hwnd) = 0;                    // GWL_EXSTYLE
+    virtual bool    setExStyle(void *hwnd, quint32 style) = 0;
+    // SetWindowPos: change the Z-order band and/or move to rect (an empty
+    // rect keeps position and size). async posts it (SWP_ASYNCWINDOWPOS).
+    virtual bool    setWindowPos(void *hwnd, ZOrder z, const QRect &rect, bool async) = 0;
+    virtual void    frameChanged(void *hwnd) = 0;               // SWP_FRAMECHANGED
+    virtual QRect   windowRect(void *hwnd) = 0;                 // empty on failure
+    virtual bool    layeredAlpha(void *hwnd, int *alpha) = 0;   // GetLayeredWindowAttributes
+    virtual bool    setLayeredAlpha(void *hwnd, int alpha) = 0; // LWA_ALPHA
+    virtual QString windowText(void *hwnd) = 0;
+    virtual bool    setWindowText(void *hwnd, const QString &text) = 0;   // WM_SETTEXT, 500 ms
+    virtual qint64  processId(void *hwnd) = 0;                  // 0 if unknown
+    virtual QString processPath(void *hwnd) = 0;                // empty if inaccessible
+    virtual void   *foregroundWindow() = 0;
+    virtual void   *owner(void *hwnd) = 0;                      // GW_OWNER
+    virtual void   *rootWindow(void *hwnd) = 0;                 // GA_ROOT
+    virtual QVector<void *> topLevelWindows() = 0;              // EnumWindows, in Z-order
+    // Work areas (the monitor minus the taskbar): the one nearest hwnd, and
+    // every monitor's in Windows' enumeration order.
+    virtual QRect   monitorWorkArea(void *hwnd) = 0;
+    virtual QVector<QRect> monitorWorkAreas() = 0;              // EnumDisplayMonitors
+};
+
+// The implementation in use. setApi() swaps it (tests); nullptr goes back to
+// the real one. Not owned.
+WindowsApi &api();
+void setApi(WindowsApi *api);
+
+} // namespace winpin
@@ -1,7 +1,9 @@
 #include "winpin.h"
+#include "windowsapi.h"
 
 #include <windows.h>
 #include <psapi.h>
+#include <dwmapi.h>
 #include <mmsystem.h>
 
 #include <QFile>
@@ -10,10 +12,183 @@
 
 namespace {
 inline HWND H(void *hwnd) { return reinterpret_cast<HWND>(hwnd); }
+
+class User32Api : public winpin::WindowsApi
+{
+public:
+    bool isWindow(void *hwnd) override { return IsWindow(H(hwnd)) != FALSE; }
+    bool isVisible(void *hwnd) override { return IsWindowVisible(H(hwnd)) != FALSE; }
+    bool isIconic(void *hwnd) override { return IsIconic(H(hwnd)) != FALSE; }
+    bool isHung(void *hwnd) override { return IsHungAppWindow(H(hwnd)) != FALSE; }
+
+    bool ping(void *hwnd, int timeoutMs) override
+    {
+        DWORD_PTR result = 0;
+        return SendMessageTimeoutW(H(hwnd), WM_NULL, 0, 0, SMTO_ABORTIFHUNG,
+                                   static_cast<UINT>(timeoutMs), &result) != 0;
+    }
+
+    quint32 exStyle(void *hwnd) override
+    {
+        return static_cast<DWORD>(GetWindowLongW(H(hwnd), GWL_EXSTYLE));
+    }
+
+    bool setExStyle(void *hwnd, quint32 style) override
+    {
+        SetLastError(0);   // 0 is also a valid previous style
+        return SetWindowLongW(H(hwnd), GWL_EXSTYLE, static_cast<LONG>(style)) != 0
+            || GetLastError() == 0;
+    }
+
+    bool setWindowPos(void *hwnd, ZOrder z, const QRect &r, bool async) override
+    {
+        HWND after = nullptr;
+        UINT flags = async ? SWP_ASYNCWINDOWPOS : 0;
+        switch (z) {
+        case ZOrder::Topmost:    after = HWND_TOPMOST; break;
+        case ZOrder::NotTopmost: after = HWND_NOTOPMOST; break;
+        case ZOrder::Unchanged:  flags |= SWP_NOZORDER | SWP_NOACTIVATE; break;
+        }
+        if (r.isEmpty())
+            flags |= SWP_NOMOVE | SWP_NOSIZE;
+        return SetWindowPos(H(hwnd), after, r.x(), r.y(), r.width(), r.height(), flags)
+            != FALSE;
+    }
+
+    void frameChanged(void *hwnd) override
+    {
+        SetWindowPos(H(hwnd), nullptr, 0, 0, 0, 0,
+                     SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED);
+    }
+
+    QRect windowRect(void *hwnd) override
+    {
+        RECT r;
+        if (!GetWindowRect(H(hwnd), &r))
+            return QRect();
+        return QRect(QPoint(r.left, r.top), QPoint(r.right - 1, r.bottom - 1));
+    }
+
+    bool layeredAlpha(void *hwnd, int *alpha) override
+    {
+        COLORREF color = 0;
+        BYTE a = 255;
+        DWORD flags = 0;
+        if (!GetLayeredWindowAttributes(H(hwnd), &color, &a, &flags))
+            return false;
+        *alpha = a;
+        return true;
+    }
+
+    bool setLayeredAlpha(void *hwnd, int alpha) override
+    {
+        return SetLayeredWindowAttributes(H(hwnd), RGB(0, 0, 0), static_cast<BYTE>(alpha),
+                                          LWA_ALPHA) != FALSE;
+    }
+
+    QString windowText(void *hwnd) override
+    {
+        const int len = GetWindowTextLengthW(H(hwnd));
+        if (len <= 0)
+            return QString();
+        QVector<wchar_t> buf(len + 1);
+        const int copied = GetWindowTextW(H(hwnd), buf.data(), len + 1);
+        return copied > 0 ? QString::fromWCharArray(buf.data(), copied) : QString();
+    }
+
+    bool setWindowText(void *hwnd, const QString &text) override
+    {
+        DWORD_PTR result = 0;
+        return SendMessageTimeoutW(H(hwnd), WM_SETTEXT, 0,
+                                   reinterpret_cast<LPARAM>(text.utf16()), SMTO_ABORTIFHUNG,
+                                   500, &result) != 0;
+    }
+
+    qint64 processId(void *hwnd) override
+    {
+        DWORD pid = 0;
+        GetWindowThreadProcessId(H(hwnd), &pid);
+        return pid;
+    }
+
+    QString processPath(void *hwnd) override
+    {
+        const DWORD pid = static_cast<DWORD>(processId(hwnd));
+        if (pid == 0)
+            return QString();
+
+        HANDLE proc = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if (!proc)
+            return QString();
+
+        wchar_t buf[MAX_PATH] = {0};
+        DWORD size = MAX_PATH;
+        QString result;
+        if (QueryFullProcessImageNameW(proc, 0, buf, &size))
+            result = QString::fromWCharArray(buf, size);
+        CloseHandle(proc);
+        return result;
+    }
+
+    void *foregroundWindow() override { return GetForegroundWindow(); }
+    void *owner(void *hwnd) override { return GetWindow(H(hwnd), GW_OWNER); }
+    void *rootWindow(void *hwnd) override { return GetAncestor(H(hwnd), GA_ROOT); }
+
+    QVector<void *> topLevelWindows() override
+    {
+        QVector<void *> out;
+        auto cb = [](HWND hwnd, LPARAM lparam) -> BOOL {
+            reinterpret_cast<QVector<void *> *>(lparam)->push_back(hwnd);
+            return TRUE;
+        };
+        EnumWindows(cb, reinterpret_cast<LPARAM>(&out));
+        return out;
+    }
+
+    QRect monitorWorkArea(void *hwnd) override
+    {
+        MONITORINFO mi = {};
+        mi.cbSize = sizeof(mi);
+        if (!GetMonitorInfoW(MonitorFromWindow(H(hwnd), MONITOR_DEFAULTTONEAREST), &mi))
+            return QRect();
+        const RECT &r = mi.rcWork;
+        return QRect(QPoint(r.left, r.top), QPoint(r.right - 1, r.bottom - 1));
+    }
+
+    QVector<QRect> monitorWorkAreas() override
+    {
+        QVector<QRect> out;
+        auto cb = [](HMONITOR mon, HDC, LPRECT, LPARAM lparam) -> BOOL {
+            MONITORINFO mi = {};
+            mi.cbSize = sizeof(mi);
+            if (GetMonitorInfoW(mon, &mi)) {
+                const RECT &r = mi.rcWork;
+                reinterpret_cast<QVector<QRect> *>(lparam)->push_back(
+                    QRect(QPoint(r.left, r.top), QPoint(r.right - 1, r.bottom - 1)));
+            }
+            return TRUE;
+        };
+        EnumDisplayMonitors(nullptr, nullptr, cb, reinterpret_cast<LPARAM>(&out));
+        return out;
+    }
+};
+
+User32Api g_user32;
+winpin::WindowsApi *g_api = &g_user32;
 } // namespace
 
 namespace winpin {
 
+WindowsApi &api()
+{
+    return *g_api;
+}
+
+void setApi(WindowsApi *replacement)
+{
+    g_api = replacement ? replacement : &g_user32;
+}
+
 int percentToAlpha(int percent)
 {
     percent = std::clamp(percent, 0, 100);
@@ -26,148 +201,628 @@ int alphaToPercent(int alpha)
     return (alpha * 100 + 127) / 255;           // rounded
 }
 
-QString windowTitle(void *hwnd)
+QRect placeOnMonitor(const QRect &window, const QRect &from, const QRect &to)
 {
-    const int len = GetWindowTextLengthW(H(hwnd));
-    if (len <= 0)
-        return QStringLiteral("Unknown");
+    if (from.isEmpty() || to.isEmpty())
+        return window;
+    const int w = qMin(window.width(), to.width());
+    const int h = qMin(window.height(), to.height());
+    // Keep the offset as a fraction of the room the window had to move in, so
+    // a window hugging the right edge still hugs it on a narrower monitor.
+    const auto along = [](int offset, int room, int newRoom) {
+        return room > 0 ? int(qint64(offset) * newRoom / room) : 0;
+    };
+    const int x = to.left() + along(window.left() - from.left(),
+                                    from.width() - window.width(), to.width() - w);
+    const int y = to.top() + along(window.top() - from.top(),
+                                   from.height() - window.height(), to.height() - h);
+    return QRect(std::clamp(x, to.left(), to.right() - w + 1),
+                 std::clamp(y, to.top(), to.bottom() - h + 1), w, h);
+}
 
-    QVector<wchar_t> buf(len + 1);
-    const int copied = GetWindowTextW(H(hwnd), buf.data(), len + 1);
-    if (copied <= 0)
-        return QStringLiteral("Unknown");
+QRect rescueRect(const QRect &window, const QVector<QRect> &workAreas, int minVisible)
+{
+    const QRect *nearest = nullptr;
+    qint64 best = 0;
+    for (const QRect &work : workAreas) {
+        const QRect seen = window.intersected(work);
+        if (seen.width() >= minVisible && seen.height() >= minVisible)
+            return window;
+        const QPoint d = work.center() - window.center();
+        const qint64 dist = qint64(d.x()) * d.x() + qint64(d.y()) * d.y();
+        if (!nearest || dist < best) {
+            nearest = &work;
+            best = dist;
+        }
+    }
+    if (!nearest)
+        return window;
+    const int w = qMin(window.width(), nearest->width());
+    const int h = qMin(window.height(), nearest->height());
+    return QRect(std::clamp(window.left(), nearest->left(), nearest->right() - w + 1),
+                 std::clamp(window.top(), nearest->top(), nearest->bottom() - h + 1), w, h);
+}
 
-    return QString::fromWCharArray(buf.data(), copied);
+QRect cornerRect(const QRect &work, const QSize &size, const QString &corner, int margin)
+{
+    const QRect inner = work.adjusted(margin, margin, -margin, -margin);
+    if (inner.isEmpty())
+        return QRect();
+    QRect r(QPoint(0, 0), size.boundedTo(inner.size()));
+    if (corner.startsWith(QLatin1String("top")))
+        r.moveTop(inner.top());
+    else
+        r.moveBottom(inner.bottom());
+    if (corner.endsWith(QLatin1String("left")))
+        r.moveLeft(inner.left());
+    else
+        r.moveRight(inner.right());
+    return r;
 }
 
-QString processName(void *hwnd)
+QString windowTitle(void *hwnd)
 {
-    DWORD pid = 0;
-    GetWindowThreadProcessId(H(hwnd), &pid);
+    const QString title = api().windowText(hwnd);
+    return title.isEmpty() ? QStringLiteral("Unknown") : title;
+}
+
+QString processPath(void *hwnd)
+{
+    return api().processPath(hwnd);
+}
+
+QString processCommandLine(void *hwnd)
+{
+    const DWORD pid = static_cast<DWORD>(processId(hwnd));
     if (pid == 0)
-        return QStringLiteral("Unknown");
+        return QString();
+
+    // ProcessCommandLineInformation (60, Windows 8.1+) hands back a
+    // UNICODE_STRING followed by its buffer — no reading the target's PEB.
+    // Looked up at runtime so neither app nor tests need to link ntdll.
+    using NtQuery = LONG(WINAPI *)(HANDLE, int, PVOID, ULONG, PULONG);
+    static const auto query = reinterpret_cast<NtQuery>(reinterpret_cast<void *>(
+        GetProcAddress(GetModuleHandleW(L"ntdll.dll"), "NtQueryInformationProcess")));
+    if (!query)
+        return QString();
 
     HANDLE proc = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
     if (!proc)
-        return QStringLiteral("Unknown");
+        return QString();
 
-    wchar_t buf[MAX_PATH] = {0};
-    DWORD size = MAX_PATH;
-    QString result = QStringLiteral("Unknown");
-    if (QueryFullProcessImageNameW(proc, 0, buf, &size)) {
-        const QString full = QString::fromWCharArray(buf, size);
-        const int slash = full.lastIndexOf(QLatin1Char('\\'));
-        result = (slash >= 0) ? full.mid(slash + 1) : full;
+    struct UnicodeString {
+        USHORT Length;
+        USHORT MaximumLength;
+        PWSTR  Buffer;
+    };
+    QString result;
+    ULONG size = 0;
+    query(proc, 60, nullptr, 0, &size);   // fails, but reports the size needed
+    if (size > sizeof(UnicodeString)) {
+        QByteArray buf(static_cast<int>(size), Qt::Uninitialized);
+        if (query(proc, 60, buf.data(), size, &size) >= 0) {
+            const auto *s = reinterpret_cast<const UnicodeString *>(buf.constData());
+            result = QString::fromWCharArray(s->Buffer, s->Length / sizeof(wchar_t));
+        }
     }
     CloseHandle(proc);
     return result;
 }
 
+QStringList splitCommandLine(const QString &commandLine)
+{
+    QStringList args;
+    const QString &s = commandLine;
+    const int n = s.size();
+    int i = 0;
+    if (n == 0)
+        return args;
+
+    // argv[0] knows no escapes: quoted up to the next quote, else up to a space.
+    QString arg;
+    if (s.at(0) == QLatin1Char('"')) {
+        int end = s.indexOf(QLatin1Char('"'), 1);
+        if (end < 0)
+            end = n;
+        arg = s.mid(1, end - 1);
+        i = end + 1;
+    } else {
+        while (i < n && s.at(i) != QLatin1Char(' ') && s.at(i) != QLatin1Char('\t'))
+            arg += s.at(i++);
+    }
+    args << arg;
+
+    const auto isBlank = [&s](int at) {
+        return s.at(at) == QLatin1Char(' ') || s.at(at) == QLatin1Char('\t');
+    };
+    for (;;) {
+        while (i < n && isBlank(i))
+            ++i;
+        if (i >= n)
+            break;
+        arg.clear();
+        bool quoted = false;
+        while (i < n && (quoted || !isBlank(i))) {
+            const QChar c = s.at(i);
+            if (c == QLatin1Char('\\')) {
+                // 2n backslashes before a quote are n, and the quote counts;
+                // 2n+1 are n and a literal quote. Elsewhere they're literal.
+                int slashes = 0;
+                while (i < n && s.at(i) == QLatin1Char('\\')) {
+                    ++slashes;
+                    ++i;
+                }
+                if (i < n && s.at(i) == QLatin1Char('"')) {
+                    arg += QString(slashes / 2, QLatin1Char('\\'));
+                    if (slashes % 2) {
+                        arg += QLatin1Char('"');
+                        ++i;
+                    }
+                } else {
+                    arg += QString(slashes, QLatin1Char('\\'));
+                }
+            } else if (c == QLatin1Char('"')) {
+                if (quoted && i + 1 < n && s.at(i + 1) == QLatin1Char('"')) {
+                    arg += QLatin1Char('"');   // "" inside quotes
+                    i += 2;
+                } else {
+                    quoted = !quoted;
+                    ++i;
+                }
+            } else {
+                arg += c;
+                ++i;
+            }
+        }
+        args << arg;
+    }
+    return args;
+}
+
+qint64 processId(void *hwnd)
+{
+    return api().processId(hwnd);
+}
+
+bool isProcessRunning(qint64 pid)
+{
+    HANDLE proc = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, static_cast<DWORD>(pid));
+    if (!proc)
+        return false;
+    DWORD code = 0;
+    const bool running = GetExitCodeProcess(proc, &code) && code == STILL_ACTIVE;
+    CloseHandle(proc);
+    return running;
+}
+
+QString processName(void *hwnd)
+{
+    const QString full = processPath(hwnd);
+    if (full.isEmpty())
+        return QStringLiteral("Unknown");
+    const int slash = full.lastIndexOf(QLatin1Char('\\'));
+    return (slash >= 0) ? full.mid(slash + 1) : full;
+}
+
 void *foregroundWindow()
 {
-    return reinterpret_cast<void *>(GetForegroundWindow());
+    return api().foregroundWindow();
+}
+
+bool activate(void *hwnd)
+{
+    if (IsIconic(H(hwnd)))
+        ShowWindow(H(hwnd), SW_RESTORE);
+    return SetForegroundWindow(H(hwnd)) != FALSE;
+}
+
+void *windowUnderCursor()
+{
+    POINT pt;
+    if (!GetCursorPos(&pt))
+        return nullptr;
+    // WindowFromPoint returns the deepest child control; pin its top-level owner.
+    HWND child = WindowFromPoint(pt);
+    return child ? reinterpret_cast<void *>(GetAncestor(child, GA_ROOT)) : nullptr;
+}
+
+QPoint cursorPos()
+{
+    POINT pt = {};
+    GetCursorPos(&pt);
+    return QPoint(pt.x, pt.y);
 }
 
 bool isValidWindow(void *hwnd)
 {
-    return IsWindow(H(hwnd)) != FALSE;
+    return api().isWindow(hwnd);
 }
 
 bool isTopmost(void *hwnd)
 {
-    const LONG ex = GetWindowLongW(H(hwnd), GWL_EXSTYLE);
-    return (static_cast<DWORD>(ex) & WS_EX_TOPMOST) != 0;
+    return (api().exStyle(hwnd) & WS_EX_TOPMOST) != 0;
 }
 
 bool isLayered(void *hwnd)
 {
-    const LONG ex = GetWindowLongW(H(hwnd), GWL_EXSTYLE);
-    return (static_cast<DWORD>(ex) & WS_EX_LAYERED) != 0;
+    return (api().exStyle(hwnd) & WS_EX_LAYERED) != 0;
+}
+
+QString className(void *hwnd)
+{
+    wchar_t buf[256] = {0};
+    const int len = GetClassNameW(H(hwnd), buf, 256);
+    return len > 0 ? QString::fromWCharArray(buf, len) : QString();
 }
 
-bool applyTopmost(void *hwnd)
+bool isMinimized(void *hwnd)
 {
-    return SetWindowPos(H(hwnd), HWND_TOPMOST, 0, 0, 0, 0,
-                        SWP_NOMOVE | SWP_NOSIZE) != FALSE;
+    return api().isIconic(hwnd) || !api().isVisible(hwnd);
 }
 
-bool removeTopmost(void *hwnd)
+bool isMaximized(void *hwnd)
 {
-    return SetWindowPos(H(hwnd), HWND_NOTOPMOST, 0, 0, 0, 0,
-                        SWP_NOMOVE | SWP_NOSIZE) != FALSE;
+    return IsZoomed(H(hwnd)) != FALSE;
+}
+
+bool maximize(void *hwnd)
+{
+    return ShowWindowAsync(H(hwnd), SW_MAXIMIZE) != FALSE;
+}
+
+bool setVisible(void *hwnd, bool visible)
+{
+    return ShowWindowAsync(H(hwnd), visible ? SW_SHOWNA : SW_HIDE) != FALSE;
+}
+
+QRect windowRect(void *hwnd)
+{
+    return api().windowRect(hwnd);
+}
+
+bool isResponding(void *hwnd, int timeoutMs)
+{
+    return api().ping(hwnd, timeoutMs);
+}
+
+bool setWindowTitle(void *hwnd, const QString &title)
+{
+    return api().setWindowText(hwnd, title);
+}
+
+bool isHung(void *hwnd)
+{
+    return api().isHung(hwnd);
+}
+
+bool isOwnWindow(void *hwnd)
+{
+    return api().processId(hwnd) == qint64(GetCurrentProcessId());
+}
+
+bool isShellWindow(void *hwnd)
+{
+    static const QStringList kShellClasses = {
+        QStringLiteral("Shell_TrayWnd"), QStringLiteral("Shell_SecondaryTrayWnd"),
+        QStringLiteral("Progman"), QStringLiteral("WorkerW"),
+    };
+    return kShellClasses.contains(className(hwnd));
+}
+
+bool isProtectedProcess(const QString &processName)
+{
+    static const QStringList kProtected = {
+        // Windows
+        QStringLiteral("consent.exe"), QStringLiteral("logonui.exe"),
+        QStringLiteral("credentialuibroker.exe"), QStringLiteral("sechealthui.exe"),
+        QStringLiteral("securityhealthsystray.exe"), QStringLiteral("lockapp.exe"),
+        // Antivirus consoles
+        QStringLiteral("avastui.exe"), QStringLiteral("avgui.exe"),
+        QStringLiteral("avpui.exe"), QStringLiteral("bdagent.exe"),
+        QStringLiteral("egui.exe"), QStringLiteral("mbam.exe"),
+        QStringLiteral("mcuicnt.exe"), QStringLiteral("nortonui.exe"),
+    };
+    return kProtected.contains(processName, Qt::CaseInsensitive);
+}
+
+bool isBrowser(const QString &processName)
+{
+    static const QStringList kBrowsers = {
+        QStringLiteral("chrome.exe"), QStringLiteral("msedge.exe"),
+        QStringLiteral("firefox.exe"), QStringLiteral("brave.exe"),
+        QStringLiteral("opera.exe"), QStringLiteral("vivaldi.exe"),
+        QStringLiteral("arc.exe"), QStringLiteral("librewolf.exe"),
+    };
+    return kBrowsers.contains(processName, Qt::CaseInsensitive);
+}
+
+QString browserTabTitle(const QString &windowTitle)
+{
+    // Firefox separates with an em dash, the Chromium family with a hyphen.
+    const int cut = qMax(windowTitle.lastIndexOf(QStringLiteral(" - ")),
+                         windowTitle.lastIndexOf(QStringLiteral(" \u2014 ")));
+    return cut > 0 ? windowTitle.left(cut) : windowTitle;
+}
+
+QString topmostManager(void *hwnd)
+{
+    if (GetPropW(H(hwnd), L"AlwaysOnTop_Pinned"))
+        return QStringLiteral("PowerToys");
+
+    // DeskPins has no marker on the window itself; its pins are small topmost
+    // windows of its own placed over the pinned window's title bar.
+    if (!isTopmost(hwnd))
+        return QString();
+    const QRect r = windowRect(hwnd);
+    if (r.isEmpty())
+        return QString();
+    const QRect caption(r.topLeft(), QPoint(r.right(), r.top() + 40));
+    for (const intptr_t other : topmostWindowsInZOrder()) {
+        if (other == reinterpret_cast<intptr_t>(hwnd))
+            continue;
+        if (processName(reinterpret_cast<void *>(other))
+                .compare(QLatin1String("deskpins.exe"), Qt::CaseInsensitive) == 0
+            && windowRect(reinterpret_cast<void *>(other)).intersects(caption))
+            return QStringLiteral("DeskPins");
+    }
+    return QString();
+}
+
+WindowInfo windowInfo(void *hwnd)
+{
+    WindowInfo info;
+    if (!isValidWindow(hwnd))
+        return info;
+    info.hwnd        = reinterpret_cast<intptr_t>(hwnd);
+    info.title       = windowTitle(hwnd);
+    info.className   = className(hwnd);
+    info.processPath = processPath(hwnd);
+    info.processName = processName(hwnd);
+    info.pid         = processId(hwnd);
+    info.rect        = windowRect(hwnd);
+    info.opacity     = opacityPercent(hwnd);
+
+    const DWORD ex = api().exStyle(hwnd);
+    info.topmost    = (ex & WS_EX_TOPMOST) != 0;
+    info.layered    = (ex & WS_EX_LAYERED) != 0;
+    info.toolWindow = (ex & WS_EX_TOOLWINDOW) != 0;
+    if (const UINT dpi = GetDpiForWindow(H(hwnd)))
+        info.dpi = static_cast<int>(dpi);
+
+    MONITORINFOEXW mi = {};
+    mi.cbSize = sizeof(mi);
+    if (GetMonitorInfoW(MonitorFromWindow(H(hwnd), MONITOR_DEFAULTTONEAREST), &mi)) {
+        const RECT &r = mi.rcMonitor;
+        info.monitor     = QString::fromWCharArray(mi.szDevice);
+        info.monitorRect = QRect(QPoint(r.left, r.top), QPoint(r.right - 1, r.bottom - 1));
+    }
+
+    info.elevated = true;
+    if (HANDLE proc = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE,
+                                  static_cast<DWORD>(info.pid))) {
+        HANDLE token = nullptr;
+        if (OpenProcessToken(proc, TOKEN_QUERY, &token)) {
+            TOKEN_ELEVATION elevation = {};
+            DWORD size = 0;
+            if (GetTokenInformation(token, TokenElevation, &elevation, sizeof(elevation), &size))
+                info.elevated = elevation.TokenIsElevated != 0;
+            CloseHandle(token);
+        }
+        CloseHandle(proc);
+    }
+    return info;
+}
+
+bool applyTopmost(void *hwnd, bool async)
+{
+    return api().setWindowPos(hwnd, WindowsApi::ZOrder::Topmost, QRect(), async);
+}
+
+bool removeTopmost(void *hwnd, bool async)
+{
+    return api().setWindowPos(hwnd, WindowsApi::ZOrder::NotTopmost, QRect(), async);
+}
+
+bool moveWindow(void *hwnd, const QRect &r)
+{
+    if (r.isEmpty())
+        return false;
+    if (IsZoomed(H(hwnd)) || api().isIconic(hwnd))
+        ShowWindow(H(hwnd), SW_RESTORE);
+    return api().setWindowPos(hwnd, WindowsApi::ZOrder::Unchanged, r, false);
+}
+
+QRect monitorWorkArea(void *hwnd)
+{
+    return api().monitorWorkArea(hwnd);
+}
+
+QVector<QRect> monitorWorkAreas()
+{
+    return api().monitorWorkAreas();
+}
+
+bool flash(void *hwnd, int count)
+{
+    FLASHWINFO fi = {};
+    fi.cbSize = sizeof(fi);
+    fi.hwnd = H(hwnd);
+    fi.dwFlags = FLASHW_ALL;
+    fi.uCount = static_cast<UINT>(count);
+    FlashWindowEx(&fi);   // returns the previous state, not success
+    return true;
+}
+
+// DWMWA_BORDER_COLOR / DWMWA_COLOR_DEFAULT, spelled out for older SDK headers.
+constexpr DWORD kBorderColorAttr = 34;
+constexpr COLORREF kDefaultColor = 0xFFFFFFFF;
+
+bool setBorderColor(void *hwnd, quint32 rgb)
+{
+    const COLORREF c = RGB((rgb >> 16) & 0xFF, (rgb >> 8) & 0xFF, rgb & 0xFF);
+    return SUCCEEDED(DwmSetWindowAttribute(H(hwnd), kBorderColorAttr, &c, sizeof(c)));
+}
+
+bool resetBorderColor(void *hwnd)
+{
+    return SUCCEEDED(DwmSetWindowAttribute(H(hwnd), kBorderColorAttr, &kDefaultColor,
+                                           sizeof(kDefaultColor)));
+}
+
+// DWMWA_SYSTEMBACKDROP_TYPE and two of its DWMSBT_ values, likewise.
+constexpr DWORD kSystemBackdropAttr = 38;
+constexpr int   kBackdropAuto = 0;
+constexpr int   kBackdropTransient = 3;   // acrylic
+
+// SetWindowCompositionAttribute is undocumented, so it's looked up at run time
+// and its structures are declared here.
+struct AccentPolicy {
+    int   state;   // ACCENT_DISABLED = 0, ACCENT_ENABLE_BLURBEHIND = 3
+    int   flags;
+    DWORD gradientColor;
+    int   animationId;
+};
+struct CompositionAttributeData {
+    int    attribute;   // WCA_ACCENT_POLICY = 19
+    void  *data;
+    SIZE_T size;
+};
+
+bool setAccent(void *hwnd, int state)
+{
+    using SetCompositionFn = BOOL(WINAPI *)(HWND, CompositionAttributeData *);
+    static const auto fn = reinterpret_cast<SetCompositionFn>(reinterpret_cast<void *>(
+        GetProcAddress(GetModuleHandleW(L"user32.dll"), "SetWindowCompositionAttribute")));
+    if (!fn)
+        return false;
+    AccentPolicy accent = {state, 0, 0, 0};
+    CompositionAttributeData data = {19, &accent, sizeof(accent)};
+    return fn(H(hwnd), &data);
+}
+
+bool setBackdrop(void *hwnd, const QString &kind)
+{
+    if (kind == QLatin1String("acrylic")) {
+        return SUCCEEDED(DwmSetWindowAttribute(H(hwnd), kSystemBackdropAttr,
+                                               &kBackdropTransient, sizeof(int)));
+    }
+    if (kind == QLatin1String("blur"))
+        return setAccent(hwnd, 3);
+    // Take off whichever one was put on.
+    DwmSetWindowAttribute(H(hwnd), kSystemBackdropAttr, &kBackdropAuto, sizeof(int));
+    setAccent(hwnd, 0);
+    return true;
+}
+
+// DWMWA_WINDOW_CORNER_PREFERENCE and DWMWCP_DEFAULT / DWMWCP_ROUND, likewise.
+constexpr DWORD kCornerPreferenceAttr = 33;
+constexpr int   kCornerDefault = 0;
+constexpr int   kCornerRound = 2;
+
+// Windows 11 squares off a window's corners once it's WS_EX_LAYERED, and some
+// apps lose their drop shadow until the frame is recomputed. So while layered,
+// a framed window asks DWM for round corners, and gets the system's choice
+// back with the style; the frame change has DWM redraw the shadow either way.
+// The corner call fails harmlessly on Windows 10.
+void keepFrameLook(void *hwnd, bool layered)
+{
+    const LONG_PTR style = GetWindowLongPtrW(H(hwnd), GWL_STYLE);
+    if ((style & WS_CAPTION) == WS_CAPTION || (style & WS_THICKFRAME) != 0) {
+        const int corner = layered ? kCornerRound : kCornerDefault;
+        DwmSetWindowAttribute(H(hwnd), kCornerPreferenceAttr, &corner, sizeof(corner));
+    }
+    api().frameChanged(hwnd);
 }
 
 bool setOpacityPercent(void *hwnd, int percent)
 {
     percent = std::clamp(percent, kMinOpacity, kMaxOpacity);
 
-    const LONG ex = GetWindowLongW(H(hwnd), GWL_EXSTYLE);
-    if ((static_cast<DWORD>(ex) & WS_EX_LAYERED) == 0)
-        SetWindowLongW(H(hwnd), GWL_EXSTYLE, ex | WS_EX_LAYERED);
+    const quint32 ex = api().exStyle(hwnd);
+    if ((ex & WS_EX_LAYERED) == 0) {
+        api().setExStyle(hwnd, ex | WS_EX_LAYERED);
+        keepFrameLook(hwnd, true);
+    }
 
-    const BYTE alpha = static_cast<BYTE>(percentToAlpha(percent));
-    return SetLayeredWindowAttributes(H(hwnd), RGB(0, 0, 0), alpha, LWA_ALPHA) != FALSE;
+    return api().setLayeredAlpha(hwnd, percentToAlpha(percent));
 }
 
 int opacityPercent(void *hwnd)
 {
-    const LONG ex = GetWindowLongW(H(hwnd), GWL_EXSTYLE);
-    if ((static_cast<DWORD>(ex) & WS_EX_LAYERED) == 0)
+    if ((api().exStyle(hwnd) & WS_EX_LAYERED) == 0)
         return 100;
 
-    COLORREF color = 0;
-    BYTE alpha = 255;
-    DWORD flags = 0;
-    if (GetLayeredWindowAttributes(H(hwnd), &color, &alpha, &flags))
+    int alpha = 255;
+    if (api().layeredAlpha(hwnd, &alpha))
         return alphaToPercent(alpha);
     return 100;
 }
 
 bool restoreOpacity(void *hwnd, bool keepLayered)
 {
-    SetLayeredWindowAttributes(H(hwnd), RGB(0, 0, 0), 255, LWA_ALPHA);
+    api().setLayeredAlpha(hwnd, 255);
 
     // The window had WS_EX_LAYERED before we ever touched it (it manages its
     // own transparency) — leave its style alone, just reset our alpha above.
     if (keepLayered)
         return true;
 
-    const LONG ex = GetWindowLongW(H(hwnd), GWL_EXSTYLE);
-    if ((static_cast<DWORD>(ex) & WS_EX_LAYERED) != 0) {
-        SetWindowLongW(H(hwnd), GWL_EXSTYLE, ex & ~WS_EX_LAYERED);
-        SetWindowPos(H(hwnd), nullptr, 0, 0, 0, 0,
-                     SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED);
+    const quint32 ex = api().exStyle(hwnd);
+    if ((ex & WS_EX_LAYERED) != 0) {
+        api().setExStyle(hwnd, ex & ~quint32(WS_EX_LAYERED));
+        keepFrameLook(hwnd, false);
     }
     return true;
 }
 
 QVector<PinnableWindow> enumerateWindows()
 {
-    QVector<HWND> handles;
-
-    auto cb = [](HWND hwnd, LPARAM lparam) -> BOOL {
-        auto *out = reinterpret_cast<QVector<HWND> *>(lparam);
-        if (IsWindowVisible(hwnd)) {
-            const LONG ex = GetWindowLongW(hwnd, GWL_EXSTYLE);
-            if ((static_cast<DWORD>(ex) & WS_EX_TOOLWINDOW) == 0)
-                out->push_back(hwnd);
-        }
-        return TRUE;
-    };
-    EnumWindows(cb, reinterpret_cast<LPARAM>(&handles));
+    QVector<void *> handles;
+    for (void *h : api().topLevelWindows()) {
+        if (api().isVisible(h) && (api().exStyle(h) & WS_EX_TOOLWINDOW) == 0)
+            handles.push_back(h);
+    }
 
     QVector<PinnableWindow> result;
     result.reserve(handles.size());
-    for (HWND h : handles) {
+    for (void *h : handles) {
         PinnableWindow w;
         w.hwnd = reinterpret_cast<intptr_t>(h);
         w.title = windowTitle(h);
         w.processName = processName(h);
+        w.pid = processId(h);
         result.push_back(w);
     }
     return result;
 }
 
+void *rootWindow(void *hwnd)
+{
+    void *root = api().rootWindow(hwnd);
+    return root ? root : hwnd;
+}
+
+QVector<intptr_t> ownedWindows(void *owner)
+{
+    QVector<intptr_t> out;
+    for (void *h : api().topLevelWindows()) {
+        if (api().isVisible(h) && api().owner(h) == owner)
+            out.push_back(reinterpret_cast<intptr_t>(h));
+    }
+    return out;
+}
+
+QVector<intptr_t> topmostWindowsInZOrder()
+{
+    // topLevelWindows() walks top-level windows in Z-order, top to bottom.
+    QVector<intptr_t> out;
+    for (void *h : api().topLevelWindows()) {
+        if (api().isVisible(h) && (api().exStyle(h) & WS_EX_TOPMOST) != 0)
+            out.push_back(reinterpret_cast<intptr_t>(h));
+    }
+    return out;
+}
+
 void beep()
 {
     // Play a soft bundled "tick" instead of the harsh system ding. PlaySound
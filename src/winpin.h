@@ -9,7 +9,9 @@
 // HWNDs are passed around as void* so this header doesn't drag <windows.h>
 // into the rest of the app. The .cpp casts them back to HWND.
 //
+#include <QRect>
 #include <QString>
+#include <QStringList>
 #include <QVector>
 #include <cstdint>
 
@@ -25,19 +27,119 @@ struct PinnableWindow {
     intptr_t hwnd = 0;
     QString  title;
     QString  processName;
+    qint64   pid = 0;
 };
 
 // --- Window metadata ------------------------------------------------------
 QString windowTitle(void *hwnd);
 QString processName(void *hwnd);
+QString processPath(void *hwnd);     // full exe path, empty if inaccessible
+QString processCommandLine(void *hwnd);   // as the process was started; empty if inaccessible
+// A command line split the way the C runtime builds argv: argv[0] ends at
+// the first space (or closing quote), later arguments follow the backslash
+// and quote rules. For relaunching an app with what it was started with.
+QStringList splitCommandLine(const QString &commandLine);
+qint64  processId(void *hwnd);       // 0 if unknown
+bool    isProcessRunning(qint64 pid);
 void   *foregroundWindow();          // nullptr if none
+void   *windowUnderCursor();         // top-level window below the mouse, or nullptr
+QPoint  cursorPos();                 // physical screen coordinates, like windowRect()
+bool    activate(void *hwnd);         // restore if minimized, then bring to the front
 bool    isValidWindow(void *hwnd);
+bool    isMinimized(void *hwnd);      // iconic, or hidden altogether
+bool    isMaximized(void *hwnd);
+bool    maximize(void *hwnd);         // asynchronous, like setVisible()
+// Hide (SW_HIDE) or show again without activating. Asynchronous, so a hung
+// app can't block us.
+bool    setVisible(void *hwnd, bool visible);
 bool    isTopmost(void *hwnd);
 bool    isLayered(void *hwnd);        // window already has WS_EX_LAYERED
+QString className(void *hwnd);
+// WM_SETTEXT with a timeout, so a hung app can't stall us.
+bool    setWindowTitle(void *hwnd, const QString &title);
+QRect   windowRect(void *hwnd);       // screen coordinates, empty on failure
+bool    isOwnWindow(void *hwnd);      // belongs to the PinIt process itself
+// The taskbar or the desktop (Shell_TrayWnd, Progman, WorkerW…): never
+// something to pin, adopt or report.
+bool    isShellWindow(void *hwnd);
+// Security-sensitive UI — UAC, the lock screen, credential prompts, antivirus
+// consoles — where a pinned (or translucent) window could hide a real prompt.
+// Matched case-insensitively on the exe name.
+bool    isProtectedProcess(const QString &processName);
+// Chromium/Firefox-family browsers: one process name for every window, so
+// they're told apart by tab title instead. Case-insensitive on the exe name.
+bool    isBrowser(const QString &processName);
+// The active tab's name from a browser window title — "Inbox - Google Chrome"
+// gives "Inbox". Titles without a " - " / " — " suffix come back unchanged.
+QString browserTabTitle(const QString &windowTitle);
+// Another always-on-top tool already holding this window: "PowerToys" (its
+// Always On Top module tags pinned windows with an "AlwaysOnTop_Pinned"
+// property) or "DeskPins" (one of its pin windows sits on this window's
+// caption). Empty if neither.
+QString topmostManager(void *hwnd);
+// Pings the window's thread (WM_NULL) and waits at most timeoutMs. False for a
+// hung window — Win32 calls that message it would stall our UI thread.
+bool    isResponding(void *hwnd, int timeoutMs = 300);
+bool    isHung(void *hwnd);           // Windows already considers it hung (instant)
+
+// --- Inspection -----------------------------------------------------------
+// Everything about a window that's useful in a details view or for writing
+// an auto-pin / exclusion rule against it.
+struct WindowInfo {
+    intptr_t hwnd = 0;
+    QString  title;
+    QString  className;
+    QString  processName;
+    QString  processPath;
+    qint64   pid = 0;
+    QRect    rect;               // screen coordinates
+    QString  monitor;            // device name, e.g. "\\.\DISPLAY1"
+    QRect    monitorRect;
+    int      dpi = 96;
+    bool     topmost = false;
+    bool     layered = false;
+    bool     toolWindow = false;
+    // Runs as administrator. Also true when its token can't be read at all —
+    // the usual sign of an elevated process seen from a normal one.
+    bool     elevated = false;
+    int      opacity = 100;
+};
+WindowInfo windowInfo(void *hwnd);   // hwnd stays 0 if the window is gone
 
 // --- Always-on-top --------------------------------------------------------
-bool applyTopmost(void *hwnd);       // HWND_TOPMOST
-bool removeTopmost(void *hwnd);      // HWND_NOTOPMOST
+// async=true posts the change to the window's own thread (SWP_ASYNCWINDOWPOS)
+// so a hung window can't block us; the result then isn't known on return.
+bool applyTopmost(void *hwnd, bool async = false);    // HWND_TOPMOST
+bool removeTopmost(void *hwnd, bool async = false);   // HWND_NOTOPMOST
+
+// --- Placement ------------------------------------------------------------
+// Move/resize to r (screen coordinates), un-maximizing first so the size sticks.
+bool moveWindow(void *hwnd, const QRect &r);
+// Work area (minus the taskbar) of the monitor the window is mostly on.
+QRect monitorWorkArea(void *hwnd);
+QVector<QRect> monitorWorkAreas();   // every monitor's, in Windows' enumeration order
+// Where window (on the monitor whose work area is from) lands on the monitor
+// with work area to: same relative position, shrunk if needed to fit.
+QRect placeOnMonitor(const QRect &window, const QRect &from, const QRect &to);
+// size tucked into corner ("top-left", "top-right", "bottom-left",
+// "bottom-right") of work, margin pixels in from the edges; shrunk to fit.
+QRect cornerRect(const QRect &work, const QSize &size, const QString &corner, int margin);
+// Where window goes to be reachable again after a monitor went away: as is
+// if at least minVisible pixels of it each way show on some work area, else
+// moved (and shrunk if needed) into the work area nearest its centre.
+QRect rescueRect(const QRect &window, const QVector<QRect> &workAreas, int minVisible);
+
+// --- Feedback -------------------------------------------------------------
+bool flash(void *hwnd, int count = 2);   // FlashWindowEx: caption + taskbar button
+// Window border colour, rgb as 0xRRGGBB (DWMWA_BORDER_COLOR, Windows 11 only —
+// fails on Windows 10). resetBorderColor() hands it back to the system.
+bool setBorderColor(void *hwnd, quint32 rgb);
+bool resetBorderColor(void *hwnd);
+// Translucency effect behind the window: "acrylic" (DWMWA_SYSTEMBACKDROP_TYPE,
+// Windows 11 22H2 and later), "blur" (SetWindowCompositionAttribute's blur
+// behind) or "none", which takes off either. Check platform::backdropFor()
+// first — the calls fail quietly where they aren't supported.
+bool setBackdrop(void *hwnd, const QString &kind);
 
 // --- Transparency ---------------------------------------------------------
 // percent is clamped to [kMinOpacity, kMaxOpacity].
@@ -56,6 +158,16 @@ int alphaToPercent(int alpha);
 // Every visible, non-tool top-level window.
 QVector<PinnableWindow> enumerateWindows();
 
+// The top-level window a child window (a control, an embedded pane) belongs
+// to; hwnd itself if it already is one.
+void *rootWindow(void *hwnd);
+// Visible popups owned by owner — its dialogs, find boxes, tool palettes.
+QVector<intptr_t> ownedWindows(void *owner);
+
+// Every visible top-level window that has WS_EX_TOPMOST, in Z-order (topmost
+// first). Includes tool windows — overlays like Zoom's are often tool windows.
+QVector<intptr_t> topmostWindowsInZOrder();
+
 // --- Misc -----------------------------------------------------------------
 // Play the system default notification sound (used for the pin chime).
 void beep();
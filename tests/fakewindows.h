@@ -0,0 +1,181 @@
+#pragma once
+//
+// FakeWindows — an in-memory desktop behind winpin::WindowsApi, so PinManager
+// can pin, fade and restore windows in a unit test without a real one. Install
+// it with winpin::setApi(&fake) and undo with winpin::setApi(nullptr).
+//
+// Handles and process ids are made-up values no real window or process uses,
+// so the few calls that still go straight to Win32 (DWM, command lines) just
+// fail on them. The desktop starts with one 1920x1040 work area.
+//
+#include <QHash>
+#include <QRect>
+#include <QString>
+#include <QVector>
+
+#include <cstdint>
+
+#include "windowsapi.h"
+
+class FakeWindows : public winpin::WindowsApi
+{
+public:
+    static constexpr quint32 kTopmost = 0x00000008;   // WS_EX_TOPMOST
+    static constexpr quint32 kLayered = 0x00080000;   // WS_EX_LAYERED
+
+    struct Window {
+        QString title;
+        QString processPath;
+        qint64  pid = 0;
+        quint32 exStyle = 0;
+        int     alpha = 255;
+        QRect   rect = QRect(100, 100, 800, 600);
+        bool    visible = true;
+        bool    iconic = false;
+        bool    hung = false;
+        void   *owner = nullptr;
+        void   *parent = nullptr;   // set for a child window (a control, a pane)
+    };
+
+    // A new top-level window at the top of the Z-order.
+    void *add(const QString &title, const QString &processPath, quint32 exStyle = 0)
+    {
+        void *hwnd = reinterpret_cast<void *>(static_cast<uintptr_t>(0xFA000000u + ++m_next * 4));
+        Window w;
+        w.title = title;
+        w.processPath = processPath;
+        w.pid = 0x7FFF0000 + m_next;
+        w.exStyle = exStyle;
+        m_windows.insert(hwnd, w);
+        m_zorder.prepend(hwnd);
+        return hwnd;
+    }
+
+    // A child window inside `parent`, of the same process; not top-level.
+    void *addChild(void *parent, const QString &title)
+    {
+        void *hwnd = reinterpret_cast<void *>(static_cast<uintptr_t>(0xFA000000u + ++m_next * 4));
+        Window w = m_windows.value(parent);
+        w.title = title;
+        w.exStyle = 0;
+        w.owner = nullptr;
+        w.parent = parent;
+        m_windows.insert(hwnd, w);
+        return hwnd;
+    }
+
+    void close(void *hwnd)
+    {
+        m_windows.remove(hwnd);
+        m_zorder.removeAll(hwnd);
+        if (m_foreground == hwnd)
+            m_foreground = nullptr;
+    }
+
+    Window &window(void *hwnd) { return m_windows[hwnd]; }
+    bool isTopmost(void *hwnd) const { return m_windows.value(hwnd).exStyle & kTopmost; }
+    void setForeground(void *hwnd) { m_foreground = hwnd; }
+    void setMonitors(const QVector<QRect> &workAreas) { m_monitors = workAreas; }
+
+    bool isWindow(void *hwnd) override { return m_windows.contains(hwnd); }
+    bool isVisible(void *hwnd) override { return m_windows.value(hwnd).visible; }
+    bool isIconic(void *hwnd) override { return m_windows.value(hwnd).iconic; }
+    bool isHung(void *hwnd) override { return m_windows.value(hwnd).hung; }
+    bool ping(void *hwnd, int) override { return isWindow(hwnd) && !isHung(hwnd); }
+    quint32 exStyle(void *hwnd) override { return m_windows.value(hwnd).exStyle; }
+
+    bool setExStyle(void *hwnd, quint32 style) override
+    {
+        if (!isWindow(hwnd))
+            return false;
+        m_windows[hwnd].exStyle = style;
+        return true;
+    }
+
+    bool setWindowPos(void *hwnd, ZOrder z, const QRect &rect, bool) override
+    {
+        if (!isWindow(hwnd))
+            return false;
+        Window &w = m_windows[hwnd];
+        if (z == ZOrder::Topmost) {
+            w.exStyle |= kTopmost;
+            m_zorder.removeAll(hwnd);
+            m_zorder.prepend(hwnd);
+        } else if (z == ZOrder::NotTopmost) {
+            w.exStyle &= ~kTopmost;
+        }
+        if (!rect.isEmpty())
+            w.rect = rect;
+        return true;
+    }
+
+    void  frameChanged(void *) override {}
+    QRect windowRect(void *hwnd) override { return m_windows.value(hwnd).rect; }
+
+    bool layeredAlpha(void *hwnd, int *alpha) override
+    {
+        if (!(exStyle(hwnd) & kLayered))
+            return false;
+        *alpha = m_windows.value(hwnd).alpha;
+        return true;
+    }
+
+    bool setLayeredAlpha(void *hwnd, int alpha) override
+    {
+        if (!(exStyle(hwnd) & kLayered))
+            return false;
+        m_windows[hwnd].alpha = alpha;
+        return true;
+    }
+
+    QString windowText(void *hwnd) override { return m_windows.value(hwnd).title; }
+
+    bool setWindowText(void *hwnd, const QString &text) override
+    {
+        if (!isWindow(hwnd))
+            return false;
+        m_windows[hwnd].title = text;
+        return true;
+    }
+
+    qint64  processId(void *hwnd) override { return m_windows.value(hwnd).pid; }
+    QString processPath(void *hwnd) override { return m_windows.value(hwnd).processPath; }
+    void   *foregroundWindow() override { return m_foreground; }
+    void   *owner(void *hwnd) override { return m_windows.value(hwnd).owner; }
+    void *rootWindow(void *hwnd) override
+    {
+        if (!isWindow(hwnd))
+            return nullptr;
+        while (void *parent = m_windows.value(hwnd).parent)
+            hwnd = parent;
+        return hwnd;
+    }
+    QVector<void *> topLevelWindows() override { return m_zorder; }
+
+    // The monitor the window overlaps most; the first one if it's off-screen.
+    QRect monitorWorkArea(void *hwnd) override
+    {
+        if (m_monitors.isEmpty())
+            return QRect();
+        const QRect rect = windowRect(hwnd);
+        QRect best = m_monitors.first();
+        qint64 bestArea = 0;
+        for (const QRect &work : m_monitors) {
+            const QRect overlap = work & rect;
+            const qint64 area = qint64(overlap.width()) * overlap.height();
+            if (area > bestArea) {
+                best = work;
+                bestArea = area;
+            }
+        }
+        return best;
+    }
+    QVector<QRect> monitorWorkAreas() override { return m_monitors; }
+
+private:
+    QHash<void *, Window> m_windows;
+    QVector<void *>       m_zorder;   // top first
+    void                 *m_foreground = nullptr;
+    QVector<QRect>        m_monitors = {QRect(0, 0, 1920, 1040)};
+    int                   m_next = 0;
+};
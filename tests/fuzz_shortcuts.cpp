@@ -0,0 +1,41 @@
+//
+// libFuzzer target for the shortcut parser and the binding checks — the code
+// that reads hand-edited shortcut strings out of pinned.json. Build with
+// -DPINIT_FUZZ=ON (clang or clang-cl) and run:
+//
+//   build/fuzz_shortcuts -max_total_time=60
+//
+// The input is split on newlines into one binding per action, the way the
+// Shortcuts dialog hands them to shortcuts::check().
+//
+#include <QString>
+#include <QStringList>
+
+#include <cstddef>
+#include <cstdint>
+#include <cstdlib>
+
+#include "shortcuts.h"
+
+extern "C" int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size)
+{
+    const QString input = QString::fromUtf8(reinterpret_cast<const char *>(data),
+                                            static_cast<qsizetype>(size));
+    const QStringList bindings = input.split(QLatin1Char('\n'));
+
+    for (const QString &binding : bindings) {
+        unsigned mods = 0, vk = 0;
+        if (shortcuts::parse(binding, mods, vk) && vk == 0)
+            std::abort();   // parse() promises a key
+        shortcuts::displayTokens(binding);
+        shortcuts::reservedBy(binding);
+        shortcuts::steps(binding);
+    }
+
+    int index = -2;
+    const shortcuts::Problem problem = shortcuts::check(bindings, &index);
+    if (problem == shortcuts::Problem::None ? index != -1
+                                            : (index < 0 || index >= bindings.size()))
+        std::abort();
+    return 0;
+}
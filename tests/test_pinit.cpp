@@ -2,19 +2,43 @@
 // Unit tests for PinIt's pure logic (no GUI / no live windows needed):
 //  - opacity percent <-> alpha conversion is lossless (regression guard:
 //    the Rust port had a bug where opacity drifted ~1% on every restart)
-//  - the Tauri-style shortcut parser maps keys/modifiers correctly
+//  - the Tauri-style shortcut parser maps keys/modifiers correctly, and holds
+//    up against generated input (seeded, so failures reproduce)
+//  - an edited hotkey config that can't be registered rolls back
+//  - a hand-edited pinned.json gets told exactly what's wrong with it, and a
+//    damaged one keeps whatever can still be read
+//  - the audit log writes one JSON object per line and rotates
+//
+// Anything that saves goes to a temporary PINIT_DATA_DIR, never the user's own.
 //
 #include <QtTest>
 
 #include <windows.h>          // MOD_*/VK_* constants for assertions
 
+#include <QRandomGenerator>
+#include <QTemporaryDir>
+
+#include <algorithm>
+#include <iterator>
+
 #include "winpin.h"
 #include "shortcuts.h"
+#include "globalhotkey.h"
+#include "persistence.h"
+#include "audit.h"
+#include "pinerror.h"
+#include "macros.h"
+#include "schedule.h"
+#include "configschema.h"
+#include "compat.h"
+#include "onboarding.h"
+#include "platform.h"
 
 class TestPinIt : public QObject
 {
     Q_OBJECT
 private slots:
+    void initTestCase();
     void opacityRoundTripIsLossless();
     void opacityBounds();
     void shortcutParsesDefault();
@@ -22,8 +46,38 @@ private slots:
     void shortcutRejectsGarbage();
     void shortcutBuildRoundTrips();
     void shortcutBuildDisplayTokens();
+    void shortcutChordRoundTrips();
+    void shortcutReservedCombos();
+    void shortcutParseFuzz();
+    void shortcutModifierOrderIsIrrelevant();
+    void shortcutCheckFindsClashes();
+    void hotkeyUpdateRollsBack();
+    void errorCodesAreDistinct();
+    void configValidationFindsProblems();
+    void salvageKeepsReadableParts();
+    void protectedProcessesMatchCaseInsensitively();
+    void compatUserEntriesReplaceBuiltIn();
+    void onboardingGoesInOrder();
+    void browserTabTitleDropsBrowserName();
+    void commandLineSplitsLikeArgv();
+    void macroStepsParseAndRoundTrip();
+    void scheduleNextRunHonoursDays();
+    void placeOnMonitorKeepsRelativePosition();
+    void cornerRectTucksIntoCorner();
+    void rescueRectBringsBackOffscreenWindows();
+    void backdropFallsBack();
+    void auditWritesJsonLines();
+
+private:
+    QTemporaryDir m_dataDir;
 };
 
+void TestPinIt::initTestCase()
+{
+    QVERIFY(m_dataDir.isValid());
+    qputenv("PINIT_DATA_DIR", m_dataDir.path().toUtf8());
+}
+
 void TestPinIt::opacityRoundTripIsLossless()
 {
     for (int p = winpin::kMinOpacity; p <= winpin::kMaxOpacity; ++p)
@@ -99,5 +153,598 @@ void TestPinIt::shortcutBuildDisplayTokens()
     QCOMPARE(shortcuts::displayTokens(eq).last(), QStringLiteral("="));
 }
 
+// A chord is "<first>,<second>"; each step must parse on its own, and plain
+// shortcuts (every pre-chord config) must still be a single step.
+void TestPinIt::shortcutChordRoundTrips()
+{
+    const QString chord = shortcuts::build(false, true, true, false, "P", "O");
+    QCOMPARE(chord, QStringLiteral("ctrl+alt+KeyP,KeyO"));
+    QVERIFY(shortcuts::isChord(chord));
+
+    const QStringList steps = shortcuts::steps(chord);
+    QCOMPARE(steps.size(), 2);
+    unsigned mods = 0, vk = 0;
+    QVERIFY(shortcuts::parse(steps[0], mods, vk));
+    QCOMPARE(mods, unsigned(MOD_CONTROL | MOD_ALT));
+    QCOMPARE(vk, unsigned('P'));
+    QVERIFY(shortcuts::parse(steps[1], mods, vk));
+    QCOMPARE(mods, 0u);
+    QCOMPARE(vk, unsigned('O'));
+
+    // The whole chord is not a single combo.
+    QVERIFY(!shortcuts::parse(chord, mods, vk));
+
+    QVERIFY(!shortcuts::isChord(QStringLiteral("super+ctrl+KeyT")));
+    QCOMPARE(shortcuts::steps(QStringLiteral("super+ctrl+KeyT")).size(), 1);
+}
+
+// The editor warns before saving combos Windows keeps for itself.
+void TestPinIt::shortcutReservedCombos()
+{
+    QVERIFY(!shortcuts::reservedBy(QStringLiteral("super+KeyL")).isEmpty());
+    QVERIFY(!shortcuts::reservedBy(QStringLiteral("super+ctrl+KeyD")).isEmpty());
+    QVERIFY(!shortcuts::reservedBy(QStringLiteral("super+shift+Digit3")).isEmpty());
+    QVERIFY(!shortcuts::reservedBy(QStringLiteral("super+KeyL,KeyO")).isEmpty());  // chord leader
+
+    // PinIt's own defaults must not be flagged.
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+KeyT")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+Equal")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+Minus")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+KeyP")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+shift+KeyT")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+shift+KeyP")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+KeyH")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("super+ctrl+alt+KeyT")).isEmpty());
+    QVERIFY(shortcuts::reservedBy(QStringLiteral("ctrl+alt+KeyK")).isEmpty());
+}
+
+// Property checks over generated input. The generator is seeded, so a
+// failure reproduces; the failing string is in the message.
+namespace {
+
+const unsigned kAllMods = MOD_WIN | MOD_CONTROL | MOD_ALT | MOD_SHIFT;
+
+bool isOfferedKey(unsigned vk)
+{
+    return (vk >= 'A' && vk <= 'Z') || (vk >= '0' && vk <= '9') || vk == VK_OEM_PLUS
+        || vk == VK_OEM_MINUS;
+}
+
+QString keyLabel(unsigned vk)
+{
+    if (vk == VK_OEM_PLUS)
+        return QStringLiteral("=");
+    if (vk == VK_OEM_MINUS)
+        return QStringLiteral("-");
+    return QString(QChar(vk));
+}
+
+// A random binding as the editor would build it: at least one modifier.
+QString randomBinding(QRandomGenerator &rng)
+{
+    static const QString keys = QStringLiteral("ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789=-");
+    const unsigned m = 1 + rng.bounded(15u);
+    return shortcuts::build((m & 1) != 0, (m & 2) != 0, (m & 4) != 0, (m & 8) != 0,
+                            keys.at(rng.bounded(keys.size())));
+}
+
+} // namespace
+
+// Token soup: whatever parse() accepts must be a single offered key with
+// known modifiers, and must survive a trip through build().
+void TestPinIt::shortcutParseFuzz()
+{
+    static const char *const tokens[] = {
+        "ctrl", "Control", "ALT", "shift", "super", "win", "meta", "cmd", "KeyA", "keyz",
+        "Key\xc3\xa9", "Key1", "Key", "Digit5", "DigitX", "Digit", "Equal", "minus", "=", "-",
+        ".", "\xc3\xa9", " ", "", "F1", "Space", "a", "9", "+", ",", "KeyT ", "\t"
+    };
+    QRandomGenerator rng(2399);
+    for (int i = 0; i < 20000; ++i) {
+        QString s;
+        const int n = rng.bounded(1, 6);
+        for (int t = 0; t < n; ++t) {
+            if (t)
+                s += rng.bounded(8) ? QLatin1Char('+') : QLatin1Char(',');
+            s += QString::fromUtf8(tokens[rng.bounded(int(std::size(tokens)))]);
+        }
+
+        unsigned mods = 0, vk = 0;
+        const bool ok = shortcuts::parse(s, mods, vk);
+        shortcuts::displayTokens(s);
+        shortcuts::reservedBy(s);
+        if (!ok)
+            continue;
+        QVERIFY2(isOfferedKey(vk), qUtf8Printable(s));
+        QVERIFY2((mods & ~kAllMods) == 0, qUtf8Printable(s));
+        QVERIFY2(!s.contains(QLatin1Char(',')), qUtf8Printable(s));
+
+        const QString rebuilt = shortcuts::build((mods & MOD_WIN) != 0, (mods & MOD_CONTROL) != 0,
+                                                 (mods & MOD_ALT) != 0, (mods & MOD_SHIFT) != 0,
+                                                 keyLabel(vk));
+        unsigned mods2 = 0, vk2 = 0;
+        QVERIFY2(shortcuts::parse(rebuilt, mods2, vk2), qUtf8Printable(rebuilt));
+        QCOMPARE(mods2, mods);
+        QCOMPARE(vk2, vk);
+    }
+
+    // What the fuzzing turned up: these used to map onto unrelated keys.
+    unsigned mods = 0, vk = 0;
+    QVERIFY(!shortcuts::parse(QStringLiteral("ctrl+."), mods, vk));        // was VK_DELETE
+    QVERIFY(!shortcuts::parse(QStringLiteral("ctrl+Key\u00e9"), mods, vk));
+    QVERIFY(!shortcuts::parse(QStringLiteral("ctrl+Key1"), mods, vk));
+    QVERIFY(!shortcuts::parse(QStringLiteral("ctrl+KeyA+KeyB"), mods, vk));   // was KeyB
+}
+
+// Modifier order and case don't change what a step means.
+void TestPinIt::shortcutModifierOrderIsIrrelevant()
+{
+    QRandomGenerator rng(2399);
+    for (int i = 0; i < 2000; ++i) {
+        const QString s = randomBinding(rng);
+        QStringList parts = s.split(QLatin1Char('+'));
+        const QString key = parts.takeLast();
+        std::shuffle(parts.begin(), parts.end(), rng);
+        for (QString &p : parts) {
+            if (rng.bounded(2))
+                p = p.toUpper();
+        }
+        const QString shuffled = (parts << key).join(QLatin1Char('+'));
+
+        unsigned m1 = 0, v1 = 0, m2 = 0, v2 = 0;
+        QVERIFY2(shortcuts::parse(s, m1, v1), qUtf8Printable(s));
+        QVERIFY2(shortcuts::parse(shuffled, m2, v2), qUtf8Printable(shuffled));
+        QCOMPARE(m2, m1);
+        QCOMPARE(v2, v1);
+        QVERIFY(shortcuts::check({s, shuffled}) == shortcuts::Problem::Duplicate);
+    }
+}
+
+// check() reports a duplicate exactly when two bindings parse the same.
+void TestPinIt::shortcutCheckFindsClashes()
+{
+    QRandomGenerator rng(2399);
+    for (int i = 0; i < 2000; ++i) {
+        QStringList all;
+        QSet<QPair<unsigned, unsigned>> combos;
+        bool clash = false;
+        for (int a = 0; a < 9; ++a) {
+            all << randomBinding(rng);
+            unsigned mods = 0, vk = 0;
+            QVERIFY(shortcuts::parse(all.last(), mods, vk));
+            clash |= combos.contains({mods, vk});
+            combos.insert({mods, vk});
+        }
+        const shortcuts::Problem expected = clash ? shortcuts::Problem::Duplicate
+                                                  : shortcuts::Problem::None;
+        QVERIFY2(shortcuts::check(all) == expected, qUtf8Printable(all.join(" ")));
+    }
+
+    int index = -1;
+    const QString pin = QStringLiteral("super+ctrl+KeyT");
+    QVERIFY(shortcuts::check({pin, QStringLiteral("ctrl+super+KeyT")}, &index)
+            == shortcuts::Problem::Duplicate);
+    QCOMPARE(index, 1);
+    QVERIFY(shortcuts::check({pin, QStringLiteral("ctrl+alt+KeyP,KeyO"),
+                              QStringLiteral("alt+ctrl+KeyP")}, &index)
+            == shortcuts::Problem::ChordPrefix);
+    QCOMPARE(index, 2);
+    QVERIFY(shortcuts::check({pin, QStringLiteral("KeyT")}, &index)
+            == shortcuts::Problem::NoModifier);
+    QCOMPARE(index, 1);
+    QVERIFY(shortcuts::check({QStringLiteral("ctrl+KeyA,KeyB,KeyC")}, &index)
+            == shortcuts::Problem::Invalid);
+    QVERIFY(shortcuts::check({QString()}) == shortcuts::Problem::Invalid);
+    QVERIFY(shortcuts::check({pin, QStringLiteral("ctrl+alt+KeyP,KeyO")}, &index)
+            == shortcuts::Problem::None);
+    QCOMPARE(index, -1);
+
+    const QStringList all = persistence::bindings(persistence::ShortcutConfig());
+    QVERIFY(shortcuts::check(all) == shortcuts::Problem::None);
+    for (const QString &s : all)
+        QVERIFY2(shortcuts::reservedBy(s).isEmpty(), qPrintable(s));
+
+    // A macro hotkey is checked after them: one that starts a default
+    // chord is caught at the macro.
+    index = -1;
+    QVERIFY(shortcuts::check(all + QStringList{QStringLiteral("super+ctrl+KeyJ")}, &index)
+            == shortcuts::Problem::ChordPrefix);
+    QCOMPARE(index, int(all.size()));
+}
+
+// update(): a config that registers nothing rolls back to the one held
+// before; with nothing held before there's nothing to roll back to.
+void TestPinIt::hotkeyUpdateRollsBack()
+{
+    persistence::ShortcutConfig broken;
+    for (QString *s : {&broken.togglePin, &broken.opacityUp, &broken.opacityDown,
+                       &broken.toggleWindow, &broken.pinUnderCursor, &broken.quickPanel,
+                       &broken.hideAllPinned, &broken.pinTemporarily, &broken.kioskExit,
+                       &broken.toggleLastPinned, &broken.focusLastPinned,
+                       &broken.pinWithOpacity, &broken.opacitySlider})
+        *s = QStringLiteral("ctrl+Bogus");
+    for (int i = 0; i < persistence::kPinSlots; ++i)
+        broken.slot[i] = broken.assignSlot[i] = QStringLiteral("ctrl+Bogus");
+    const int actions = persistence::bindings(broken).size();
+    QVERIFY(!persistence::bindings(broken).contains(QString()));   // a new one set above
+
+    {
+        GlobalHotkeyManager fresh;
+        QVERIFY(!fresh.update(broken));
+        QVERIFY(fresh.registeredActions().isEmpty());
+        QCOMPARE(fresh.failedActions().size(), actions);
+        QVERIFY(fresh.activeConfig() == broken);
+    }
+
+    // Combos nobody is likely to hold.
+    persistence::ShortcutConfig good;
+    int digit = 1;
+    for (QString *s : {&good.togglePin, &good.opacityUp, &good.opacityDown,
+                       &good.toggleWindow, &good.pinUnderCursor, &good.quickPanel,
+                       &good.hideAllPinned, &good.pinTemporarily, &good.kioskExit})
+        *s = shortcuts::build(false, true, true, true, QString::number(digit++));
+    good.toggleLastPinned = shortcuts::build(false, true, true, true, QStringLiteral("L"));
+    good.focusLastPinned = shortcuts::build(false, true, true, true, QStringLiteral("M"));
+    good.pinWithOpacity = shortcuts::build(false, true, true, true, QStringLiteral("N"));
+    good.opacitySlider = shortcuts::build(false, true, true, true, QStringLiteral("O"));
+    for (int i = 0; i < persistence::kPinSlots; ++i) {
+        good.slot[i] = shortcuts::build(false, true, true, true, QString(QChar('A' + i)));
+        good.assignSlot[i] = shortcuts::build(false, true, true, true, QString(QChar('X' + i)));
+    }
+
+    GlobalHotkeyManager hotkeys;
+    if (!hotkeys.update(good))
+        QSKIP("RegisterHotKey is unavailable in this session");
+    QVERIFY(hotkeys.activeConfig() == good);
+    const QStringList held = hotkeys.registeredActions();
+
+    QVERIFY(!hotkeys.update(broken));
+    QCOMPARE(hotkeys.failedActions().size(), actions);   // the new config's failures
+    QVERIFY(hotkeys.activeConfig() == good);
+    QCOMPARE(hotkeys.registeredActions(), held);
+
+    // A partly working config is kept, with the rest reported.
+    persistence::ShortcutConfig partial = good;
+    partial.kioskExit = QStringLiteral("ctrl+Bogus");
+    QVERIFY(hotkeys.update(partial));
+    QVERIFY(hotkeys.activeConfig() == partial);
+    QVERIFY(hotkeys.failedActions().contains(QStringLiteral("Exit kiosk mode")));
+}
+
+// Codes are what logs and callers match on, so each must be unique.
+void TestPinIt::errorCodesAreDistinct()
+{
+    QCOMPARE(errorCode(PinError::ElevationRequired), QStringLiteral("ELEVATION_REQUIRED"));
+    QSet<QString> seen;
+    const int count = int(kLastPinError) + 1;
+    for (int i = 0; i < count; ++i) {
+        const PinError e = PinError(i);
+        QVERIFY(errorCode(e) != QStringLiteral("UNKNOWN"));
+        seen.insert(errorCode(e));
+    }
+    QCOMPARE(seen.size(), count);
+}
+
+// A user entry replaces the built-in one whole, and names are matched lower-cased.
+void TestPinIt::compatUserEntriesReplaceBuiltIn()
+{
+    QVERIFY(compat::builtIn().value(QStringLiteral("mstsc.exe")).noLayering);
+
+    persistence::AppCompat aggressive;
+    aggressive.enforcement = QStringLiteral("aggressive");
+    const auto table = compat::table({{QStringLiteral("MSTSC.exe"), aggressive},
+                                      {QStringLiteral("game.exe"), aggressive}});
+    QVERIFY(!table.value(QStringLiteral("mstsc.exe")).noLayering);
+    QCOMPARE(table.value(QStringLiteral("mstsc.exe")).enforcement, QStringLiteral("aggressive"));
+    QCOMPARE(table.value(QStringLiteral("game.exe")).enforcement, QStringLiteral("aggressive"));
+    QCOMPARE(table.value(QStringLiteral("slack.exe")).reassertAfterMs,
+             compat::builtIn().value(QStringLiteral("slack.exe")).reassertAfterMs);
+
+    // no_layering_apps still counts, on top of whatever the table says.
+    const auto folded = compat::table({{QStringLiteral("game.exe"), aggressive}},
+                                      {QStringLiteral("Game.exe"), QStringLiteral("lob-*.exe")});
+    QVERIFY(folded.value(QStringLiteral("game.exe")).noLayering);
+    QCOMPARE(folded.value(QStringLiteral("game.exe")).enforcement, QStringLiteral("aggressive"));
+    QVERIFY(folded.value(QStringLiteral("lob-*.exe")).noLayering);
+}
+
+void TestPinIt::onboardingGoesInOrder()
+{
+    // A step from a newer version is kept but doesn't count.
+    onboarding::Progress progress = {{QStringLiteral("whats_new"), QStringLiteral("done")}};
+    onboarding::Step step = onboarding::Step::PermissionsCheck;
+    QVERIFY(onboarding::next(progress, &step));
+    QCOMPARE(step, onboarding::Step::TrayNotice);
+
+    onboarding::advance(progress, onboarding::Step::TrayNotice);
+    onboarding::advance(progress, onboarding::Step::AutostartPrompt, /*skipped=*/true);
+    QVERIFY(onboarding::next(progress, &step));
+    QCOMPARE(step, onboarding::Step::HotkeyTutorial);
+
+    QJsonObject state = onboarding::toJson(progress);
+    QCOMPARE(state.value("current").toString(), QStringLiteral("hotkey_tutorial"));
+    const QJsonObject steps = state.value("steps").toObject();
+    QCOMPARE(steps.value("tray_notice").toString(), QStringLiteral("done"));
+    QCOMPARE(steps.value("autostart_prompt").toString(), QStringLiteral("skipped"));
+    QCOMPARE(steps.value("permissions_check").toString(), QStringLiteral("pending"));
+    QVERIFY(!steps.contains("whats_new"));
+
+    QVERIFY(onboarding::fromName(QStringLiteral("permissions_check"), &step));
+    QVERIFY(!onboarding::fromName(QStringLiteral("whats_new"), &step));
+    onboarding::advance(progress, onboarding::Step::HotkeyTutorial);
+    onboarding::advance(progress, onboarding::Step::PermissionsCheck);
+    QVERIFY(onboarding::finished(progress));
+    state = onboarding::toJson(progress);
+    QVERIFY(state.value("current").isNull());
+    QVERIFY(state.value("finished").toBool());
+}
+
+void TestPinIt::configValidationFindsProblems()
+{
+    QVERIFY(configschema::validate(QByteArray(R"({"pins": {}, "settings": {}})")).isEmpty());
+
+    const QVector<configschema::Issue> issues = configschema::validate(QByteArray(R"({
+        "settings": {
+            "osd_postion": "screen",
+            "osd_duration_ms": "1500",
+            "opacity_fade_ms": 90000,
+            "tray_icon_theme": "darkk",
+            "shortcuts": {"toggle_pin": "super+ctrl+KeyT", "quick_panel": "Ctrl+Alt+T+Q",
+                          "opacity_up": "super+ctrl+KeyT"},
+            "triggers": [{"when": "app-started", "action": ["pin-appp", "vlc.exe"]}]
+        }
+    })"));
+    const auto at = [&](const QString &path) {
+        for (const configschema::Issue &i : issues) {
+            if (i.path == path)
+                return i;
+        }
+        return configschema::Issue{};
+    };
+
+    QVERIFY(at(QStringLiteral("settings.osd_postion")).suggestion.contains("osd_position"));
+    QVERIFY(at(QStringLiteral("settings.osd_duration_ms")).suggestion.contains("without quotes"));
+    QVERIFY(at(QStringLiteral("settings.opacity_fade_ms")).suggestion.contains("5000"));
+    QVERIFY(at(QStringLiteral("settings.tray_icon_theme")).suggestion.contains("dark"));
+    QVERIFY(!at(QStringLiteral("settings.shortcuts.quick_panel")).expected.isEmpty());
+    QVERIFY(!at(QStringLiteral("settings.shortcuts.opacity_up")).expected.isEmpty());
+    QVERIFY(at(QStringLiteral("settings.triggers[0].action[0]")).suggestion.contains("pin-app"));
+    for (const configschema::Issue &i : issues)
+        QVERIFY(!configschema::format(i).isEmpty());
+
+    const QVector<configschema::Issue> broken = configschema::validate(QByteArray("{\"pins\": "));
+    QCOMPARE(broken.size(), 1);
+    QVERIFY(broken[0].path.isEmpty());
+}
+
+void TestPinIt::salvageKeepsReadableParts()
+{
+    // One shortcut missing its quotes, and the file cut off inside a pin.
+    const QByteArray damaged = R"({
+        "settings": {
+            "osd_duration_ms": 1500,
+            "shortcuts": {"toggle_pin": "super+ctrl+KeyT", "quick_panel": ctrl+alt+KeyP},
+            "enable_sound": false
+        },
+        "pins": {
+            "vlc.exe:0": {"process_name": "vlc.exe", "title": "VLC", "opacity": 200},
+            "code.exe:1": {"process_name": "code.exe", "title": "notes.md", "opacity": )";
+
+    QStringList lost;
+    const QJsonObject root = persistence::salvage(damaged, &lost);
+    const QJsonObject settings = root.value("settings").toObject();
+    QCOMPARE(settings.value("osd_duration_ms").toInt(), 1500);
+    QCOMPARE(settings.value("enable_sound").toBool(true), false);
+    QCOMPARE(settings.value("shortcuts").toObject().value("toggle_pin").toString(),
+             QStringLiteral("super+ctrl+KeyT"));
+    QVERIFY(lost.contains(QStringLiteral("settings.shortcuts.quick_panel")));
+
+    const QJsonObject pins = root.value("pins").toObject();
+    QCOMPARE(pins.value("vlc.exe:0").toObject().value("opacity").toInt(), 200);
+    QCOMPARE(pins.value("code.exe:1").toObject().value("title").toString(),
+             QStringLiteral("notes.md"));
+    QVERIFY(lost.contains(QStringLiteral("pins.code.exe:1.opacity")));
+
+    lost.clear();
+    QVERIFY(persistence::salvage("[1, 2", &lost).isEmpty());
+    QCOMPARE(lost, QStringList{QStringLiteral("(document)")});
+}
+
+void TestPinIt::protectedProcessesMatchCaseInsensitively()
+{
+    QVERIFY(winpin::isProtectedProcess(QStringLiteral("consent.exe")));
+    QVERIFY(winpin::isProtectedProcess(QStringLiteral("LogonUI.exe")));
+    QVERIFY(!winpin::isProtectedProcess(QStringLiteral("notepad.exe")));
+    QVERIFY(!winpin::isProtectedProcess(QString()));
+}
+
+void TestPinIt::browserTabTitleDropsBrowserName()
+{
+    QVERIFY(winpin::isBrowser(QStringLiteral("Chrome.exe")));
+    QVERIFY(!winpin::isBrowser(QStringLiteral("notepad.exe")));
+    QCOMPARE(winpin::browserTabTitle(QStringLiteral("YouTube Music - Google Chrome")),
+             QStringLiteral("YouTube Music"));
+    QCOMPARE(winpin::browserTabTitle(QStringLiteral("A - B - Microsoft\u200b Edge")),
+             QStringLiteral("A - B"));
+    QCOMPARE(winpin::browserTabTitle(QStringLiteral("Docs \u2014 Mozilla Firefox")),
+             QStringLiteral("Docs"));
+    QCOMPARE(winpin::browserTabTitle(QStringLiteral("Mozilla Firefox")),
+             QStringLiteral("Mozilla Firefox"));
+}
+
+void TestPinIt::commandLineSplitsLikeArgv()
+{
+    QCOMPARE(winpin::splitCommandLine(QStringLiteral(
+                 R"("C:\Program Files\App\app.exe" --profile "Work 2" C:\a\b)")),
+             QStringList({QStringLiteral(R"(C:\Program Files\App\app.exe)"),
+                          QStringLiteral("--profile"), QStringLiteral("Work 2"),
+                          QStringLiteral(R"(C:\a\b)")}));
+    // Backslashes only escape a quote; "" inside quotes is a quote.
+    QCOMPARE(winpin::splitCommandLine(QStringLiteral(R"(app.exe "a\" b" "x""y" c\\"d e")")),
+             QStringList({QStringLiteral("app.exe"), QStringLiteral(R"(a" b)"),
+                          QStringLiteral(R"(x"y)"), QStringLiteral(R"(c\d e)")}));
+    QVERIFY(winpin::splitCommandLine(QString()).isEmpty());
+}
+
+void TestPinIt::macroStepsParseAndRoundTrip()
+{
+    QString error;
+    const QVector<persistence::MacroStep> steps = macros::parseSteps(
+        QStringLiteral("# stream setup\npin-app obs64.exe\n\nset-opacity 80\nwait 500\n"
+                       "snapshot-restore \"Work layout\""),
+        &error);
+    QVERIFY(error.isEmpty());
+    QCOMPARE(steps.size(), 4);
+    QCOMPARE(steps[0].action, QStringLiteral("pin-app"));
+    QCOMPARE(steps[0].args, QStringList{QStringLiteral("obs64.exe")});
+    QCOMPARE(steps[2].action, QStringLiteral("wait"));
+    QCOMPARE(steps[3].args, QStringList{QStringLiteral("Work layout")});
+    QCOMPARE(macros::parseSteps(macros::formatSteps(steps), &error), steps);
+
+    QVERIFY(macros::parseSteps(QStringLiteral("set-opacity 80\nfly-away"), &error).isEmpty());
+    QVERIFY(error.contains(QStringLiteral("2")));
+    QVERIFY(macros::parseSteps(QStringLiteral("wait soon"), &error).isEmpty());
+}
+
+void TestPinIt::scheduleNextRunHonoursDays()
+{
+    persistence::Schedule s;
+    s.at = QTime(18, 0);
+    s.days = {1, 2, 3, 4, 5};   // weekdays
+    const QDateTime fridayNoon(QDate(2024, 1, 5), QTime(12, 0));   // a Friday
+
+    // Later the same day.
+    QCOMPARE(scheduling::nextRun(s, fridayNoon), QDateTime(QDate(2024, 1, 5), QTime(18, 0)));
+    // Past Friday's slot: skips the weekend.
+    QCOMPARE(scheduling::nextRun(s, fridayNoon.addSecs(8 * 3600)),
+             QDateTime(QDate(2024, 1, 8), QTime(18, 0)));
+
+    s.everyMinutes = 45;
+    QCOMPARE(scheduling::nextRun(s, fridayNoon), fridayNoon.addSecs(45 * 60));
+
+    s.enabled = false;
+    QVERIFY(!scheduling::nextRun(s, fridayNoon).isValid());
+}
+
+void TestPinIt::placeOnMonitorKeepsRelativePosition()
+{
+    const QRect left(0, 0, 1920, 1040), right(1920, 0, 1280, 984);
+
+    // Top-left corner stays top-left.
+    QCOMPARE(winpin::placeOnMonitor(QRect(0, 0, 400, 300), left, right),
+             QRect(1920, 0, 400, 300));
+    // Hugging the bottom-right edge stays hugging it on the smaller monitor.
+    QCOMPARE(winpin::placeOnMonitor(QRect(1520, 740, 400, 300), left, right),
+             QRect(2800, 684, 400, 300));
+    // Too big for the target: shrunk to fit.
+    QCOMPARE(winpin::placeOnMonitor(QRect(0, 0, 1600, 1000), left, right).size(),
+             QSize(1280, 984));
+    // Same monitor: unchanged.
+    QCOMPARE(winpin::placeOnMonitor(QRect(100, 50, 400, 300), left, left),
+             QRect(100, 50, 400, 300));
+}
+
+void TestPinIt::cornerRectTucksIntoCorner()
+{
+    const QRect work(0, 0, 1920, 1040);
+    QCOMPARE(winpin::cornerRect(work, QSize(480, 270), QStringLiteral("bottom-right"), 16),
+             QRect(1424, 754, 480, 270));
+    QCOMPARE(winpin::cornerRect(work, QSize(480, 270), QStringLiteral("top-left"), 16),
+             QRect(16, 16, 480, 270));
+    // Bigger than the work area: shrunk to fit inside the margins.
+    QCOMPARE(winpin::cornerRect(work, QSize(4000, 300), QStringLiteral("top-right"), 16),
+             QRect(16, 16, 1888, 300));
+}
+
+void TestPinIt::rescueRectBringsBackOffscreenWindows()
+{
+    const QVector<QRect> work = {QRect(0, 0, 1920, 1040)};
+
+    // Visible enough: left alone, even partly off the edge.
+    QCOMPARE(winpin::rescueRect(QRect(1800, 100, 400, 300), work, 64),
+             QRect(1800, 100, 400, 300));
+    // Left behind on a monitor to the right that was unplugged.
+    QCOMPARE(winpin::rescueRect(QRect(2200, 100, 400, 300), work, 64),
+             QRect(1520, 100, 400, 300));
+    // Only a sliver showing counts as off-screen.
+    QCOMPARE(winpin::rescueRect(QRect(-380, 500, 400, 300), work, 64),
+             QRect(0, 500, 400, 300));
+    // No monitors known: nothing to do.
+    QCOMPARE(winpin::rescueRect(QRect(5000, 0, 400, 300), {}, 64), QRect(5000, 0, 400, 300));
+}
+
+void TestPinIt::backdropFallsBack()
+{
+    platform::Capabilities win11;
+    win11.acrylic = win11.blurBehind = true;
+    platform::Capabilities win10;
+    win10.blurBehind = true;
+    const platform::Capabilities bare;
+
+    QCOMPARE(platform::backdropFor(QStringLiteral("acrylic"), win11), QStringLiteral("acrylic"));
+    QCOMPARE(platform::backdropFor(QStringLiteral("acrylic"), win10), QStringLiteral("blur"));
+    QCOMPARE(platform::backdropFor(QStringLiteral("blur"), win10), QStringLiteral("blur"));
+    QCOMPARE(platform::backdropFor(QStringLiteral("acrylic"), bare), QStringLiteral("none"));
+    QCOMPARE(platform::backdropFor(QStringLiteral("bogus"), win11), QStringLiteral("none"));
+}
+
+void TestPinIt::auditWritesJsonLines()
+{
+    QTemporaryDir dir;
+    const QString path = QDir(dir.path()).filePath(QStringLiteral("audit.jsonl"));
+    const auto lines = [](const QString &file) {
+        QFile f(file);
+        QVector<QJsonObject> out;
+        if (f.open(QIODevice::ReadOnly)) {
+            for (const QByteArray &line : f.readAll().split('\n')) {
+                if (!line.isEmpty())
+                    out.push_back(QJsonDocument::fromJson(line).object());
+            }
+        }
+        return out;
+    };
+    audit::setLocation(dir.path());
+    audit::setEnabled(true);
+
+    audit::record(QStringLiteral("pin"), QStringLiteral("vlc.exe"), QStringLiteral("ok"));
+    persistence::UserSettings settings = persistence::loadSettings();
+    settings.enableSound = !settings.enableSound;
+    persistence::saveSettings(settings);
+    audit::setRotation(1, 2);   // roll over before every write
+    audit::record(QStringLiteral("unpin"), QStringLiteral("vlc.exe"), QStringLiteral("ok"));
+    audit::setRotation(1024 * 1024, 5);
+    audit::setEnabled(false);
+    audit::record(QStringLiteral("pin"), QStringLiteral("ignored.exe"), QStringLiteral("ok"));
+    audit::flush();
+
+    const QVector<QJsonObject> old = lines(path + QStringLiteral(".1"));
+    QCOMPARE(old.size(), 2);
+    QCOMPARE(old[0].value("action").toString(), QStringLiteral("pin"));
+    QCOMPARE(old[0].value("process").toString(), QStringLiteral("vlc.exe"));
+    QCOMPARE(old[0].value("result").toString(), QStringLiteral("ok"));
+    QVERIFY(QDateTime::fromString(old[0].value("ts").toString(), Qt::ISODateWithMs).isValid());
+    QVERIFY(old[0].contains("user"));
+    const QJsonObject change = old[1].value("detail").toObject();
+    QCOMPARE(old[1].value("action").toString(), QStringLiteral("setting"));
+    QCOMPARE(change.value("key").toString(), QStringLiteral("enable_sound"));
+    QCOMPARE(change.value("to").toBool(), settings.enableSound);
+
+    const QVector<QJsonObject> current = lines(path);
+    QCOMPARE(current.size(), 1);
+    QCOMPARE(current[0].value("action").toString(), QStringLiteral("unpin"));
+
+    const audit::Status status = audit::status();
+    QVERIFY(status.writable);
+    QCOMPARE(status.rotated, 1);
+    QCOMPARE(status.written, quint64(3));
+
+    // Asking about a log that hasn't been written yet doesn't start one.
+    const QString later = QDir(dir.path()).filePath(QStringLiteral("later"));
+    audit::setLocation(later);
+    QVERIFY(audit::status().writable);
+    QVERIFY(!QFileInfo::exists(later));
+    audit::setLocation(QString());
+}
+
 QTEST_MAIN(TestPinIt)
 #include "test_pinit.moc"
@@ -0,0 +1,581 @@
+//
+// PinManager against a fake desktop (tests/fakewindows.h): pinning, opacity,
+// unpinning, temporary pins, the re-enforce pass and its policies, follow
+// mode, display changes and restore matching, checked through the styles and
+// rects PinManager leaves on the fake windows. No real window is opened, so
+// unlike pinit_window_tests this runs anywhere.
+//
+// Saved pins go to a temporary PINIT_DATA_DIR, never the user's own.
+// Triggers are checked here too, since they watch the same fake desktop.
+//
+#include <QtTest>
+#include <QTemporaryDir>
+
+#include "actions.h"
+#include "onboarding.h"
+#include "fakewindows.h"
+#include "persistence.h"
+#include "pinmanager.h"
+#include "triggers.h"
+#include "winpin.h"
+
+namespace {
+intptr_t I(void *hwnd) { return reinterpret_cast<intptr_t>(hwnd); }
+} // namespace
+
+class TestPinManager : public QObject
+{
+    Q_OBJECT
+private slots:
+    void initTestCase();
+    void init();
+    void cleanup();
+    void cleanupTestCase();
+
+    void pinMakesWindowTopmost();
+    void pinRejectsClosedWindow();
+    void opacityLayersWindow();
+    void opacityRefusedWhenDisabled();
+    void suspendStopsRunningFade();
+    void noLayeringAppStaysOpaque();
+    void unpinRestoresWindow();
+    void unpinLeavesOwnLayering();
+    void closedPinIsDropped();
+    void reenforcePutsTopmostBack();
+    void restorePrefersExactTitle();
+    void restoreWaitsForLateWindow();
+    void savingOffKeepsSavedPins();
+    void upgradeSkipsOnboarding();
+    void neverPinListBlocksPin();
+    void slotFindsItsWindow();
+    void lastPinnedTogglesAndFallsBack();
+    void pinWithOpacityPinsAndFades();
+    void childWindowPinsItsTopLevel();
+    void temporaryPinRunsOut();
+    void markPolicyLeavesLostPinOff();
+    void enforcementPolicies();
+    void followModeMovesToActiveMonitor();
+    void displayChangeRescuesOffscreenPin();
+    void hiddenWindowsAreSaved();
+    void triggerActsOnItsWindow();
+
+private:
+    QTemporaryDir m_dataDir;
+    FakeWindows  *m_fake = nullptr;
+    PinManager   *m_mgr = nullptr;
+};
+
+void TestPinManager::initTestCase()
+{
+    QVERIFY(m_dataDir.isValid());
+    qputenv("PINIT_DATA_DIR", m_dataDir.path().toUtf8());
+}
+
+void TestPinManager::init()
+{
+    persistence::savePins({});
+    m_fake = new FakeWindows;
+    winpin::setApi(m_fake);
+    m_mgr = new PinManager;
+    m_mgr->setFadeDuration(0);   // opacity changes land at once
+}
+
+void TestPinManager::cleanup()
+{
+    delete m_mgr;
+    m_mgr = nullptr;
+    winpin::setApi(nullptr);
+    delete m_fake;
+    m_fake = nullptr;
+}
+
+void TestPinManager::cleanupTestCase()
+{
+    qunsetenv("PINIT_DATA_DIR");
+}
+
+void TestPinManager::pinMakesWindowTopmost()
+{
+    void *w = m_fake->add(QStringLiteral("Notes"), QStringLiteral("C:\\Apps\\notes.exe"));
+
+    QVERIFY(m_mgr->pin(I(w), false));
+    QVERIFY(m_fake->isTopmost(w));
+    QVERIFY(m_mgr->isPinned(I(w)));
+
+    const QVector<PinnedWindow> pins = m_mgr->pinnedWindows();
+    QCOMPARE(pins.size(), 1);
+    QCOMPARE(pins[0].title, QStringLiteral("Notes"));
+    QCOMPARE(pins[0].processName, QStringLiteral("notes.exe"));
+}
+
+void TestPinManager::pinRejectsClosedWindow()
+{
+    void *w = m_fake->add(QStringLiteral("Gone"), QStringLiteral("C:\\Apps\\gone.exe"));
+    m_fake->close(w);
+
+    QSignalSpy errors(m_mgr, &PinManager::errorOccurred);
+    QVERIFY(!m_mgr->pin(I(w), false));
+    QCOMPARE(errors.size(), 1);
+    QCOMPARE(errors[0][0].value<PinError>(), PinError::InvalidWindow);
+    QCOMPARE(m_mgr->pinnedCount(), 0);
+}
+
+void TestPinManager::opacityLayersWindow()
+{
+    void *w = m_fake->add(QStringLiteral("Video"), QStringLiteral("C:\\Apps\\player.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+
+    QVERIFY(m_mgr->setOpacity(I(w), 50));
+    QVERIFY(m_fake->exStyle(w) & FakeWindows::kLayered);
+    QCOMPARE(m_fake->window(w).alpha, winpin::percentToAlpha(50));
+    QCOMPARE(m_mgr->pinnedWindows()[0].opacity, 50);
+}
+
+void TestPinManager::opacityRefusedWhenDisabled()
+{
+    void *w = m_fake->add(QStringLiteral("Video"), QStringLiteral("C:\\Apps\\player.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+    m_mgr->setOpacityDisabled(true);
+
+    QSignalSpy errors(m_mgr, &PinManager::errorOccurred);
+    QVERIFY(!m_mgr->setOpacity(I(w), 50));
+    QCOMPARE(errors.size(), 1);
+    QCOMPARE(errors[0][0].value<PinError>(), PinError::DisabledByPolicy);
+    QVERIFY(!(m_fake->exStyle(w) & FakeWindows::kLayered));
+    QCOMPARE(m_mgr->pinnedWindows()[0].opacity, 100);
+}
+
+void TestPinManager::suspendStopsRunningFade()
+{
+    void *w = m_fake->add(QStringLiteral("Video"), QStringLiteral("C:\\Apps\\player.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+    m_mgr->setFadeDuration(200);
+    QVERIFY(m_mgr->setOpacity(I(w), 40));
+
+    m_mgr->setOpacitySuspended(true);
+    QTest::qWait(300);   // past the fade's end
+    QVERIFY(!(m_fake->exStyle(w) & FakeWindows::kLayered));
+    QCOMPARE(m_fake->window(w).alpha, 255);
+
+    m_mgr->setOpacitySuspended(false);
+    QCOMPARE(m_fake->window(w).alpha, winpin::percentToAlpha(40));
+}
+
+void TestPinManager::noLayeringAppStaysOpaque()
+{
+    void *w = m_fake->add(QStringLiteral("Remote"), QStringLiteral("C:\\Apps\\MSTSC.exe"));
+    void *other = m_fake->add(QStringLiteral("Video"), QStringLiteral("C:\\Apps\\player.exe"));
+    persistence::AppCompat opaque;
+    opaque.noLayering = true;
+    m_mgr->setAppCompat({{QStringLiteral("mstsc*.exe"), opaque}});   // wildcards match too
+    QVERIFY(m_mgr->pin(I(w), false));
+    QVERIFY(m_mgr->pin(I(other), false));
+
+    QSignalSpy errors(m_mgr, &PinManager::errorOccurred);
+    QVERIFY(!m_mgr->setOpacity(I(w), 50));
+    QCOMPARE(errors.size(), 1);
+    QCOMPARE(errors[0][0].value<PinError>(), PinError::OpacityUnsupported);
+    QVERIFY(!(m_fake->exStyle(w) & FakeWindows::kLayered));
+    QVERIFY(m_mgr->setOpacity(I(other), 50));   // other apps are unaffected
+}
+
+void TestPinManager::unpinRestoresWindow()
+{
+    void *w = m_fake->add(QStringLiteral("Video"), QStringLiteral("C:\\Apps\\player.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+    QVERIFY(m_mgr->setOpacity(I(w), 40));
+
+    QVERIFY(m_mgr->unpin(I(w), false));
+    QVERIFY(!m_fake->isTopmost(w));
+    QVERIFY(!(m_fake->exStyle(w) & FakeWindows::kLayered));
+    QVERIFY(!m_mgr->isPinned(I(w)));
+}
+
+void TestPinManager::unpinLeavesOwnLayering()
+{
+    // An app that was layered before PinIt touched it keeps its style.
+    void *w = m_fake->add(QStringLiteral("Overlay"), QStringLiteral("C:\\Apps\\overlay.exe"),
+                          FakeWindows::kLayered);
+    QVERIFY(m_mgr->pin(I(w), false));
+    QVERIFY(m_mgr->setOpacity(I(w), 60));
+
+    QVERIFY(m_mgr->unpin(I(w), false));
+    QVERIFY(m_fake->exStyle(w) & FakeWindows::kLayered);
+    QCOMPARE(m_fake->window(w).alpha, 255);
+}
+
+void TestPinManager::closedPinIsDropped()
+{
+    void *w = m_fake->add(QStringLiteral("Chat"), QStringLiteral("C:\\Apps\\chat.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+
+    QSignalSpy closed(m_mgr, &PinManager::pinClosed);
+    m_fake->close(w);
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));
+
+    QCOMPARE(closed.size(), 1);
+    QCOMPARE(closed[0][0].value<intptr_t>(), I(w));
+    QCOMPARE(m_mgr->pinnedCount(), 0);
+}
+
+void TestPinManager::reenforcePutsTopmostBack()
+{
+    void *w = m_fake->add(QStringLiteral("Timer"), QStringLiteral("C:\\Apps\\timer.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+
+    QSignalSpy reenforced(m_mgr, &PinManager::topmostReenforced);
+    m_fake->window(w).exStyle &= ~FakeWindows::kTopmost;   // another app took it
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));
+
+    QVERIFY(m_fake->isTopmost(w));
+    QCOMPARE(reenforced.size(), 1);
+    QCOMPARE(m_mgr->reenforceStats().reapplied, quint64(1));
+}
+
+void TestPinManager::restorePrefersExactTitle()
+{
+    persistence::SavedPin saved;
+    saved.processName = QStringLiteral("code.exe");
+    saved.title = QStringLiteral("notes.md");
+    saved.opacity = winpin::percentToAlpha(70);
+    persistence::savePins({saved});
+
+    m_fake->add(QStringLiteral("main.cpp"), QStringLiteral("C:\\Apps\\code.exe"));
+    void *notes = m_fake->add(QStringLiteral("notes.md"), QStringLiteral("C:\\Apps\\code.exe"));
+    m_fake->add(QStringLiteral("notes.md"), QStringLiteral("C:\\Apps\\other.exe"));
+
+    const RestoreStats stats = m_mgr->restoreSaved();
+    QCOMPARE(stats.saved, 1);
+    QCOMPARE(stats.restored, 1);
+    QVERIFY(m_mgr->isPinned(I(notes)));
+    QVERIFY(m_fake->isTopmost(notes));
+    QCOMPARE(m_mgr->pinnedWindows()[0].opacity, 70);
+}
+
+void TestPinManager::restoreWaitsForLateWindow()
+{
+    persistence::SavedPin saved;
+    saved.processName = QStringLiteral("slack.exe");
+    saved.title = QStringLiteral("Slack");
+    persistence::savePins({saved});
+
+    const RestoreStats stats = m_mgr->restoreSaved();
+    QCOMPARE(stats.restored, 0);
+    QCOMPARE(m_mgr->pendingRestores().size(), 1);
+    QCOMPARE(m_mgr->retryRestore(), 0);
+
+    void *w = m_fake->add(QStringLiteral("Slack"), QStringLiteral("C:\\Apps\\slack.exe"));
+    QCOMPARE(m_mgr->retryRestore(), 1);
+    QVERIFY(m_mgr->isPinned(I(w)));
+    QVERIFY(m_mgr->pendingRestores().isEmpty());
+}
+
+void TestPinManager::savingOffKeepsSavedPins()
+{
+    persistence::SavedPin saved;
+    saved.processName = QStringLiteral("slack.exe");
+    saved.title = QStringLiteral("Slack");
+    persistence::savePins({saved});
+
+    // Safe mode: pins made this session, and the quit, leave the file alone.
+    m_mgr->setSavingEnabled(false);
+    void *w = m_fake->add(QStringLiteral("Notes"), QStringLiteral("C:\\Apps\\notes.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+    m_mgr->restoreAllWindows();
+    const QVector<persistence::SavedPin> pins = persistence::load().pins;
+    QCOMPARE(pins.size(), 1);
+    QCOMPARE(pins[0].processName, QStringLiteral("slack.exe"));
+}
+
+// Settings from a build before onboarding existed aren't a first run.
+void TestPinManager::upgradeSkipsOnboarding()
+{
+    const auto write = [](const QByteArray &doc) {
+        QFile f(persistence::filePath());
+        QVERIFY(f.open(QIODevice::WriteOnly | QIODevice::Truncate));
+        f.write(doc);
+    };
+    write(R"({"pins": {}, "settings": {"has_seen_tray_notice": false}})");
+    QVERIFY(onboarding::finished(persistence::loadSettings().onboarding));
+
+    write(R"({"pins": {"vlc.exe:0": {"process_name": "vlc.exe", "title": "VLC"}},
+              "settings": {}})");
+    QVERIFY(onboarding::finished(persistence::loadSettings().onboarding));
+
+    write(R"({"pins": {}, "settings": {"onboarding": {"tray_notice": "done"}}})");
+    QVERIFY(!onboarding::finished(persistence::loadSettings().onboarding));
+
+    write(R"({"pins": {}, "settings": {}})");   // a new install
+    QVERIFY(onboarding::isPending(persistence::loadSettings().onboarding,
+                                  onboarding::Step::TrayNotice));
+}
+
+void TestPinManager::neverPinListBlocksPin()
+{
+    m_mgr->setBlockedProcesses({QStringLiteral("notes.exe"), QStringLiteral("lob-*.exe")});
+    void *w = m_fake->add(QStringLiteral("Notes"), QStringLiteral("C:\\Apps\\Notes.exe"));
+    void *lob = m_fake->add(QStringLiteral("Ledger"), QStringLiteral("C:\\Apps\\lob-ledger.exe"));
+
+    QSignalSpy errors(m_mgr, &PinManager::errorOccurred);
+    QSignalSpy blocked(m_mgr, &PinManager::pinBlocked);
+    QVERIFY(!m_mgr->pin(I(w), false));
+    QVERIFY(!m_mgr->pin(I(lob), false));
+    QCOMPARE(errors.size(), 2);
+    QCOMPARE(errors[0][0].value<PinError>(), PinError::BlockedByPolicy);
+    QCOMPARE(blocked.size(), 2);
+    QCOMPARE(blocked[0][0].value<intptr_t>(), I(w));
+    QVERIFY(!m_fake->isTopmost(w));
+    QCOMPARE(m_mgr->pinnedCount(), 0);
+
+    // A pin saved before the app was blocked isn't restored, or kept waiting.
+    persistence::SavedPin saved;
+    saved.processName = QStringLiteral("notes.exe");
+    saved.title = QStringLiteral("Notes");
+    persistence::savePins({saved});
+    blocked.clear();
+    const RestoreStats stats = m_mgr->restoreSaved();
+    QCOMPARE(stats.saved, 0);
+    QCOMPARE(stats.restored, 0);
+    QVERIFY(m_mgr->pendingRestores().isEmpty());
+    QCOMPARE(blocked.size(), 1);
+    QCOMPARE(blocked[0][0].value<intptr_t>(), intptr_t(0));
+}
+
+void TestPinManager::slotFindsItsWindow()
+{
+    void *editor = m_fake->add(QStringLiteral("main.cpp"), QStringLiteral("C:\\Apps\\code.exe"));
+    void *notes = m_fake->add(QStringLiteral("notes.md"), QStringLiteral("C:\\Apps\\code.exe"));
+    persistence::PinSlot slot{QStringLiteral("code.exe"), QStringLiteral("notes.md")};
+
+    QCOMPARE(m_mgr->findSlotWindow(slot), I(notes));   // same title beats being pinned
+    QVERIFY(m_mgr->pin(I(editor), false));
+    QCOMPARE(m_mgr->findSlotWindow(slot), I(notes));
+
+    slot.title = QStringLiteral("old tab");   // the title moved on: its pin is the best guess
+    QCOMPARE(m_mgr->findSlotWindow(slot), I(editor));
+    QCOMPARE(m_mgr->findSlotWindow({QStringLiteral("vlc.exe"), QString()}), intptr_t(0));
+    QCOMPARE(m_mgr->findSlotWindow({}), intptr_t(0));
+}
+
+void TestPinManager::lastPinnedTogglesAndFallsBack()
+{
+    void *a = m_fake->add(QStringLiteral("Notes"), QStringLiteral("C:\\Apps\\notes.exe"));
+    void *b = m_fake->add(QStringLiteral("Player"), QStringLiteral("C:\\Apps\\vlc.exe"));
+    QCOMPARE(m_mgr->lastPinned(), intptr_t(0));
+
+    QVERIFY(m_mgr->pin(I(a), false));
+    QVERIFY(m_mgr->pin(I(b), false));
+    m_fake->setForeground(a);   // not the foreground, still the target
+    m_mgr->toggleLastPinned();
+    QVERIFY(!m_mgr->isPinned(I(b)));
+    QCOMPARE(m_mgr->lastPinned(), I(b));   // unpinning doesn't forget it
+    m_mgr->toggleLastPinned();
+    QVERIFY(m_mgr->isPinned(I(b)));
+
+    m_fake->close(b);
+    QCOMPARE(m_mgr->lastPinned(), I(a));
+}
+
+void TestPinManager::pinWithOpacityPinsAndFades()
+{
+    void *w = m_fake->add(QStringLiteral("Player"), QStringLiteral("C:\\Apps\\vlc.exe"));
+    m_fake->setForeground(w);
+
+    m_mgr->setPinOpacity(55);
+    m_mgr->pinForegroundWithOpacity();
+    QVERIFY(m_mgr->isPinned(I(w)));
+    QCOMPARE(m_fake->window(w).alpha, winpin::percentToAlpha(55));
+
+    QVERIFY(m_mgr->pinWithOpacity(I(w), 80));   // already pinned: only the opacity moves
+    QCOMPARE(m_mgr->pinnedWindows().size(), 1);
+    QCOMPARE(m_mgr->pinnedWindows()[0].opacity, 80);
+}
+
+void TestPinManager::childWindowPinsItsTopLevel()
+{
+    void *top = m_fake->add(QStringLiteral("Editor"), QStringLiteral("C:\\Apps\\code.exe"));
+    void *pane = m_fake->addChild(m_fake->addChild(top, QStringLiteral("Panel")),
+                                  QStringLiteral("Terminal"));
+
+    QVERIFY(m_mgr->toggle(I(pane)));
+    QVERIFY(m_mgr->isPinned(I(top)));
+    QVERIFY(m_fake->isTopmost(top));
+    QVERIFY(m_mgr->toggle(I(pane)));   // the same window again: unpinned
+    QVERIFY(!m_mgr->isPinned(I(top)));
+
+    QVERIFY(m_mgr->pinFor(I(pane), 60));
+    const QVector<PinnedWindow> pins = m_mgr->pinnedWindows();
+    QCOMPARE(pins.size(), 1);   // no second entry under the child's handle
+    QCOMPARE(pins[0].hwnd, I(top));
+    QVERIFY(pins[0].expiresAt.isValid());
+}
+
+void TestPinManager::temporaryPinRunsOut()
+{
+    void *w = m_fake->add(QStringLiteral("Build"), QStringLiteral("C:\\Apps\\cmd.exe"));
+    QSignalSpy countdown(m_mgr, &PinManager::pinCountdown);
+    QSignalSpy expired(m_mgr, &PinManager::pinExpired);
+
+    // seconds <= 0 on a window that isn't pinned yet has nothing to make permanent.
+    QVERIFY(!m_mgr->pinFor(I(w), 0));
+    QVERIFY(m_mgr->pinFor(I(w), 60));
+    QCOMPARE(countdown.size(), 1);
+    QCOMPARE(countdown[0][1].toInt(), 60);
+    QVERIFY(persistence::load().pins.isEmpty());   // temporary pins aren't saved
+
+    QVERIFY(m_mgr->pinFor(I(w), 0));   // made permanent: saved, no deadline
+    QVERIFY(!m_mgr->pinnedWindows()[0].expiresAt.isValid());
+    QCOMPARE(persistence::load().pins.size(), 1);
+
+    QVERIFY(m_mgr->pinFor(I(w), 1));
+    QTRY_COMPARE_WITH_TIMEOUT(expired.size(), 1, 5000);
+    QCOMPARE(expired[0][0].value<intptr_t>(), I(w));
+    QVERIFY(!m_mgr->isPinned(I(w)));
+    QVERIFY(!m_fake->isTopmost(w));
+}
+
+void TestPinManager::markPolicyLeavesLostPinOff()
+{
+    m_mgr->setLostPolicy(QStringLiteral("mark"));
+    void *w = m_fake->add(QStringLiteral("Timer"), QStringLiteral("C:\\Apps\\timer.exe"));
+    QVERIFY(m_mgr->pin(I(w), false));
+
+    QSignalSpy lost(m_mgr, &PinManager::pinLost);
+    QSignalSpy reenforced(m_mgr, &PinManager::topmostReenforced);
+    m_fake->window(w).exStyle &= ~FakeWindows::kTopmost;
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));   // reported once, not per pass
+
+    QVERIFY(!m_fake->isTopmost(w));
+    QCOMPARE(lost.size(), 1);
+    QCOMPARE(reenforced.size(), 0);
+    QVERIFY(m_mgr->pinnedWindows()[0].lost);
+    QCOMPARE(m_mgr->reenforceStats().reapplied, quint64(0));
+
+    QVERIFY(m_mgr->reassert(I(w)));
+    QVERIFY(m_fake->isTopmost(w));
+    QVERIFY(!m_mgr->pinnedWindows()[0].lost);
+}
+
+void TestPinManager::enforcementPolicies()
+{
+    void *chat = m_fake->add(QStringLiteral("Chat"), QStringLiteral("C:\\Apps\\chat.exe"));
+    void *game = m_fake->add(QStringLiteral("Overlay"), QStringLiteral("C:\\Apps\\game.exe"));
+    void *clock = m_fake->add(QStringLiteral("Clock"), QStringLiteral("C:\\Apps\\clock.exe"));
+    QVERIFY(m_mgr->pin(I(chat), false));
+    QVERIFY(m_mgr->pin(I(game), false));
+    QVERIFY(m_mgr->pin(I(clock), false));
+    QVERIFY(!m_mgr->setEnforcementPolicy(I(chat), QStringLiteral("sometimes")));
+    QVERIFY(m_mgr->setEnforcementPolicy(I(chat), QStringLiteral("aggressive")));
+    QVERIFY(m_mgr->setEnforcementPolicy(I(clock), QStringLiteral("once")));
+
+    // The last pin sits highest; an aggressive pin is raised above it anyway.
+    QCOMPARE(m_fake->topLevelWindows().first(), clock);
+    m_fake->window(clock).exStyle &= ~FakeWindows::kTopmost;
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));
+
+    QCOMPARE(m_fake->topLevelWindows().first(), chat);
+    QVERIFY(m_fake->isTopmost(game));
+    QVERIFY(!m_fake->isTopmost(clock));   // "once" pins are never put back
+    QCOMPARE(m_mgr->reenforceStats().reraised, quint64(1));
+    QCOMPARE(m_mgr->reenforceStats().reapplied, quint64(0));
+}
+
+void TestPinManager::followModeMovesToActiveMonitor()
+{
+    const QRect left(0, 0, 1920, 1040), right(1920, 0, 1920, 1040);
+    m_fake->setMonitors({left, right});
+    void *notes = m_fake->add(QStringLiteral("Notes"), QStringLiteral("C:\\Apps\\notes.exe"));
+    void *editor = m_fake->add(QStringLiteral("Editor"), QStringLiteral("C:\\Apps\\code.exe"));
+    m_fake->window(editor).rect = QRect(2200, 100, 800, 600);
+    QVERIFY(m_mgr->pin(I(notes), false));
+    QVERIFY(m_mgr->setFollowMonitor(I(notes), true));
+
+    m_fake->setForeground(editor);
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));
+    QCOMPARE(m_fake->monitorWorkArea(notes), right);
+    QCOMPARE(m_fake->window(notes).rect, QRect(2020, 100, 800, 600));   // same relative spot
+
+    QVERIFY(m_mgr->setFollowMonitor(I(notes), false));
+    m_fake->window(editor).rect = QRect(100, 100, 800, 600);
+    QVERIFY(QMetaObject::invokeMethod(m_mgr, "reenforce"));
+    QCOMPARE(m_fake->monitorWorkArea(notes), right);
+}
+
+void TestPinManager::displayChangeRescuesOffscreenPin()
+{
+    const QRect left(0, 0, 1920, 1040);
+    m_fake->setMonitors({left, QRect(1920, 0, 1920, 1040)});
+    void *video = m_fake->add(QStringLiteral("Video"), QStringLiteral("C:\\Apps\\vlc.exe"));
+    void *docked = m_fake->add(QStringLiteral("Chat"), QStringLiteral("C:\\Apps\\chat.exe"));
+    m_fake->window(video).rect = QRect(2200, 100, 800, 600);
+    m_fake->window(docked).rect = QRect(3500, 100, 400, 600);
+    QVERIFY(m_mgr->pin(I(video), false));
+    QVERIFY(m_mgr->pin(I(docked), false));
+    QVERIFY(m_mgr->setDockEdge(I(docked), QStringLiteral("right")));
+
+    QSignalSpy changed(m_mgr, &PinManager::displayTopologyChanged);
+    m_fake->setMonitors({left});   // the right-hand monitor was unplugged
+    m_fake->window(video).exStyle &= ~FakeWindows::kTopmost;
+    QCOMPARE(m_mgr->handleDisplayChange(), 1);
+
+    QCOMPARE(m_fake->window(video).rect, QRect(1120, 100, 800, 600));
+    QVERIFY(m_fake->isTopmost(video));
+    QCOMPARE(m_fake->window(docked).rect, QRect(3500, 100, 400, 600));   // EdgeDock's to place
+    QCOMPARE(changed.size(), 1);
+    QCOMPARE(changed[0][0].value<QVector<QRect>>(), QVector<QRect>{left});
+    QCOMPARE(changed[0][1].toInt(), 1);
+}
+
+void TestPinManager::hiddenWindowsAreSaved()
+{
+    persistence::saveHiddenWindows({{0x2a0b14, 4242}});
+    const QVector<persistence::HiddenWindow> hidden = persistence::load().hidden;
+    QCOMPARE(hidden.size(), 1);
+    QCOMPARE(hidden[0].hwnd, qint64(0x2a0b14));
+    QCOMPARE(hidden[0].pid, qint64(4242));
+
+    persistence::saveHiddenWindows({});
+    QVERIFY(persistence::load().hidden.isEmpty());
+    QVERIFY(!persistence::readRaw().contains("hidden"));
+}
+
+void TestPinManager::triggerActsOnItsWindow()
+{
+    ActionRegistry actions;
+    QVector<intptr_t> targets;
+    actions.bind(QStringLiteral("pin-foreground"), [&](const QStringList &) {
+        targets << actions.target();
+        return true;
+    });
+    persistence::Trigger opened;
+    opened.name = QStringLiteral("OBS opens");
+    opened.event = QStringLiteral("window-opened");
+    opened.process = QStringLiteral("obs64.exe");
+    opened.action = QStringLiteral("pin-foreground");
+    persistence::Trigger focused = opened;
+    focused.name = QStringLiteral("Editor comes to the front");
+    focused.event = QStringLiteral("window-focused");
+    focused.process = QStringLiteral("notepad.exe");
+
+    void *editor = m_fake->add(QStringLiteral("Notes"), QStringLiteral("C:\\Windows\\notepad.exe"));
+    void *browser = m_fake->add(QStringLiteral("Docs"), QStringLiteral("C:\\Apps\\firefox.exe"));
+    m_fake->setForeground(browser);
+    TriggerWatcher watcher(&actions);
+    watcher.setTriggers({opened, focused});
+    watcher.poll();   // only records what's open
+    QVERIFY(targets.isEmpty());
+
+    // The new window is pinned even though something else is in front.
+    void *obs = m_fake->add(QStringLiteral("OBS 30"), QStringLiteral("C:\\Apps\\obs64.exe"));
+    watcher.poll();
+    QCOMPARE(targets, QVector<intptr_t>{I(obs)});
+    QCOMPARE(actions.target(), intptr_t(0));
+
+    m_fake->setForeground(editor);
+    watcher.poll();
+    QCOMPARE(targets, (QVector<intptr_t>{I(obs), I(editor)}));
+}
+
+QTEST_MAIN(TestPinManager)
+#include "test_pinmanager.moc"
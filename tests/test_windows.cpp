@@ -0,0 +1,223 @@
+//
+// End-to-end tests against real Win32 windows. Each test opens a plain window
+// on a thread of its own — so, like another app's window, it has a message
+// loop PinIt doesn't run — and checks what winpin did to it through the
+// window's actual styles (WS_EX_TOPMOST, WS_EX_LAYERED, the layered alpha)
+// rather than through PinIt's own bookkeeping.
+//
+// Needs an interactive desktop, so this is a separate executable, built only
+// with -DPINIT_WINDOW_TESTS=ON (CI turns it on).
+//
+#include <QtTest>
+
+#include <windows.h>
+
+#include <future>
+#include <thread>
+
+#include "selftest.h"
+#include "winpin.h"
+
+namespace {
+
+const wchar_t kClassName[] = L"PinItTestWindow";
+
+LRESULT CALLBACK testWindowProc(HWND hwnd, UINT msg, WPARAM wParam, LPARAM lParam)
+{
+    if (msg == WM_DESTROY) {
+        PostQuitMessage(0);   // ends the window's thread
+        return 0;
+    }
+    return DefWindowProcW(hwnd, msg, wParam, lParam);
+}
+
+// A visible top-level window (an owned popup if owner is set) run by a thread
+// of its own, closed again when this goes out of scope.
+class TestWindow
+{
+public:
+    explicit TestWindow(const QString &title, DWORD exStyle = 0, HWND owner = nullptr)
+    {
+        std::promise<HWND> created;
+        std::future<HWND> ready = created.get_future();
+        m_thread = std::thread([&created, title, exStyle, owner]() {
+            const DWORD style = owner ? WS_POPUP | WS_CAPTION | WS_VISIBLE
+                                      : WS_OVERLAPPEDWINDOW | WS_VISIBLE;
+            const HWND hwnd = CreateWindowExW(exStyle, kClassName,
+                                              reinterpret_cast<const wchar_t *>(title.utf16()),
+                                              style, 100, 100, 400, 300, owner, nullptr,
+                                              GetModuleHandleW(nullptr), nullptr);
+            created.set_value(hwnd);
+            if (!hwnd)
+                return;
+            MSG msg;
+            while (GetMessageW(&msg, nullptr, 0, 0) > 0) {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        });
+        m_hwnd = ready.get();
+    }
+
+    ~TestWindow()
+    {
+        if (m_hwnd)
+            PostMessageW(m_hwnd, WM_CLOSE, 0, 0);
+        m_thread.join();
+    }
+
+    HWND hwnd() const { return m_hwnd; }
+    void *handle() const { return m_hwnd; }
+    DWORD exStyle() const { return static_cast<DWORD>(GetWindowLongW(m_hwnd, GWL_EXSTYLE)); }
+
+private:
+    HWND        m_hwnd = nullptr;
+    std::thread m_thread;
+};
+
+} // namespace
+
+class TestWindows : public QObject
+{
+    Q_OBJECT
+private slots:
+    void initTestCase();
+    void topmostRoundTrip();
+    void asyncTopmostLands();
+    void opacityRoundTrip();
+    void restoreOpacityKeepsAppsOwnLayering();
+    void metadataOfAnotherThreadsWindow();
+    void ownedPopupsAreFound();
+    void moveWindowLandsExactly();
+    void selfTestPasses();
+};
+
+void TestWindows::initTestCase()
+{
+    WNDCLASSW wc = {};
+    wc.lpfnWndProc = testWindowProc;
+    wc.hInstance = GetModuleHandleW(nullptr);
+    wc.hCursor = LoadCursorW(nullptr, IDC_ARROW);
+    wc.hbrBackground = reinterpret_cast<HBRUSH>(COLOR_WINDOW + 1);
+    wc.lpszClassName = kClassName;
+    QVERIFY(RegisterClassW(&wc) != 0);
+}
+
+void TestWindows::topmostRoundTrip()
+{
+    TestWindow w(QStringLiteral("PinIt test: topmost"));
+    QVERIFY(w.hwnd());
+    QVERIFY(!winpin::isTopmost(w.handle()));
+
+    QVERIFY(winpin::applyTopmost(w.handle()));
+    QVERIFY(w.exStyle() & WS_EX_TOPMOST);
+    QVERIFY(winpin::isTopmost(w.handle()));
+    QVERIFY(winpin::topmostWindowsInZOrder().contains(reinterpret_cast<intptr_t>(w.hwnd())));
+
+    QVERIFY(winpin::removeTopmost(w.handle()));
+    QVERIFY(!(w.exStyle() & WS_EX_TOPMOST));
+}
+
+void TestWindows::asyncTopmostLands()
+{
+    // The re-enforce pass posts SetWindowPos rather than waiting on the app.
+    TestWindow w(QStringLiteral("PinIt test: async topmost"));
+    QVERIFY(winpin::applyTopmost(w.handle(), /*async=*/true));
+    QTRY_VERIFY(winpin::isTopmost(w.handle()));
+    QVERIFY(winpin::removeTopmost(w.handle(), /*async=*/true));
+    QTRY_VERIFY(!winpin::isTopmost(w.handle()));
+}
+
+void TestWindows::opacityRoundTrip()
+{
+    TestWindow w(QStringLiteral("PinIt test: opacity"));
+    QCOMPARE(winpin::opacityPercent(w.handle()), 100);
+    QVERIFY(!winpin::isLayered(w.handle()));
+
+    QVERIFY(winpin::setOpacityPercent(w.handle(), 60));
+    QVERIFY(w.exStyle() & WS_EX_LAYERED);
+    COLORREF key = 0;
+    BYTE alpha = 0;
+    DWORD flags = 0;
+    QVERIFY(GetLayeredWindowAttributes(w.hwnd(), &key, &alpha, &flags));
+    QCOMPARE(int(alpha), winpin::percentToAlpha(60));
+    QVERIFY(flags & LWA_ALPHA);
+    QCOMPARE(winpin::opacityPercent(w.handle()), 60);
+
+    // Clamped, so a window can't be made invisible.
+    QVERIFY(winpin::setOpacityPercent(w.handle(), 1));
+    QCOMPARE(winpin::opacityPercent(w.handle()), winpin::kMinOpacity);
+
+    QVERIFY(winpin::restoreOpacity(w.handle()));
+    QVERIFY(!(w.exStyle() & WS_EX_LAYERED));
+    QCOMPARE(winpin::opacityPercent(w.handle()), 100);
+}
+
+void TestWindows::restoreOpacityKeepsAppsOwnLayering()
+{
+    TestWindow w(QStringLiteral("PinIt test: layered app"), WS_EX_LAYERED);
+    QVERIFY(SetLayeredWindowAttributes(w.hwnd(), 0, 255, LWA_ALPHA));
+
+    QVERIFY(winpin::setOpacityPercent(w.handle(), 50));
+    QVERIFY(winpin::restoreOpacity(w.handle(), /*keepLayered=*/true));
+    QVERIFY(w.exStyle() & WS_EX_LAYERED);   // the app's style is left alone
+    QCOMPARE(winpin::opacityPercent(w.handle()), 100);
+}
+
+void TestWindows::metadataOfAnotherThreadsWindow()
+{
+    HWND closed = nullptr;
+    {
+        TestWindow w(QStringLiteral("PinIt test: metadata"));
+        closed = w.hwnd();
+        QVERIFY(winpin::isValidWindow(w.handle()));
+        QCOMPARE(winpin::windowTitle(w.handle()), QStringLiteral("PinIt test: metadata"));
+        QCOMPARE(winpin::className(w.handle()), QStringLiteral("PinItTestWindow"));
+        QCOMPARE(winpin::processId(w.handle()), qint64(GetCurrentProcessId()));
+        QVERIFY(winpin::isOwnWindow(w.handle()));
+        QVERIFY(winpin::isResponding(w.handle()));
+        QVERIFY(!winpin::isHung(w.handle()));
+
+        // WM_SETTEXT goes through the window's own thread.
+        QVERIFY(winpin::setWindowTitle(w.handle(), QStringLiteral("📌 renamed")));
+        QCOMPARE(winpin::windowTitle(w.handle()), QStringLiteral("📌 renamed"));
+
+        bool listed = false;
+        for (const winpin::PinnableWindow &p : winpin::enumerateWindows())
+            listed = listed || p.hwnd == reinterpret_cast<intptr_t>(w.hwnd());
+        QVERIFY(listed);
+    }
+    QTRY_VERIFY(!winpin::isValidWindow(closed));
+}
+
+void TestWindows::ownedPopupsAreFound()
+{
+    TestWindow owner(QStringLiteral("PinIt test: owner"));
+    TestWindow popup(QStringLiteral("PinIt test: owned dialog"), 0, owner.hwnd());
+    QVERIFY(popup.hwnd());
+
+    QVERIFY(winpin::ownedWindows(owner.handle())
+                .contains(reinterpret_cast<intptr_t>(popup.hwnd())));
+    // Owned popups are top-level windows in their own right.
+    QCOMPARE(winpin::rootWindow(popup.handle()), popup.handle());
+}
+
+void TestWindows::moveWindowLandsExactly()
+{
+    TestWindow w(QStringLiteral("PinIt test: move"));
+    const QRect target(150, 120, 500, 350);
+    QVERIFY(winpin::moveWindow(w.handle(), target));
+    QCOMPARE(winpin::windowRect(w.handle()), target);
+}
+
+void TestWindows::selfTestPasses()
+{
+    const QVector<selftest::Check> checks = selftest::run();
+    QCOMPARE(checks.size(), 6);
+    for (const selftest::Check &c : checks)
+        QVERIFY2(c.passed, qPrintable(c.subsystem + QStringLiteral(": ") + c.detail));
+    QCOMPARE(selftest::toJson(checks).value("passed").toBool(), true);
+}
+
+QTEST_MAIN(TestWindows)
+#include "test_windows.moc"
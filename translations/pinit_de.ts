@@ -0,0 +1,277 @@
+<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE TS>
+<TS version="2.1" language="de_DE" sourcelanguage="en">
+<context>
+    <name>MainWindow</name>
+    <message>
+        <source>Show PinIt</source>
+        <translation>PinIt anzeigen</translation>
+    </message>
+    <message>
+        <source>Unpin all</source>
+        <translation>Alle lösen</translation>
+    </message>
+    <message>
+        <source>Adopt always-on-top windows…</source>
+        <translation>Immer-im-Vordergrund-Fenster übernehmen…</translation>
+    </message>
+    <message>
+        <source>Snapshots</source>
+        <translation>Layouts</translation>
+    </message>
+    <message>
+        <source>Save current layout…</source>
+        <translation>Aktuelles Layout speichern…</translation>
+    </message>
+    <message>
+        <source>Save snapshot</source>
+        <translation>Layout speichern</translation>
+    </message>
+    <message>
+        <source>Name for this layout (saving over an existing name replaces it):</source>
+        <translation>Name für dieses Layout (ein vorhandener Name wird ersetzt):</translation>
+    </message>
+    <message>
+        <source>Saved snapshot “%1”.</source>
+        <translation>Layout „%1“ gespeichert.</translation>
+    </message>
+    <message>
+        <source>Restore “%1”</source>
+        <translation>„%1“ wiederherstellen</translation>
+    </message>
+    <message>
+        <source>Delete</source>
+        <translation>Löschen</translation>
+    </message>
+    <message>
+        <source>Macros</source>
+        <translation>Makros</translation>
+    </message>
+    <message>
+        <source>Edit macros…</source>
+        <translation>Makros bearbeiten…</translation>
+    </message>
+    <message>
+        <source>Scheduled actions…</source>
+        <translation>Geplante Aktionen…</translation>
+    </message>
+    <message>
+        <source>Triggers…</source>
+        <translation>Auslöser…</translation>
+    </message>
+    <message>
+        <source>Restore backup</source>
+        <translation>Sicherung wiederherstellen</translation>
+    </message>
+    <message>
+        <source>No backups yet</source>
+        <translation>Noch keine Sicherungen</translation>
+    </message>
+    <message>
+        <source>Data folder…</source>
+        <translation>Datenordner…</translation>
+    </message>
+    <message>
+        <source>Diagnostics…</source>
+        <translation>Diagnose…</translation>
+    </message>
+    <message>
+        <source>Run self-test…</source>
+        <translation>Selbsttest ausführen…</translation>
+    </message>
+    <message>
+        <source>Effective settings…</source>
+        <translation>Wirksame Einstellungen…</translation>
+    </message>
+    <message>
+        <source>Check topmost conflicts…</source>
+        <translation>Vordergrund-Konflikte prüfen…</translation>
+    </message>
+    <message>
+        <source>Language</source>
+        <translation>Sprache</translation>
+    </message>
+    <message>
+        <source>Windows default</source>
+        <translation>Wie Windows</translation>
+    </message>
+    <message>
+        <source>Restart PinIt to switch the language.</source>
+        <translation>Starten Sie PinIt neu, um die Sprache zu wechseln.</translation>
+    </message>
+    <message>
+        <source>Tray icon</source>
+        <translation>Infobereichssymbol</translation>
+    </message>
+    <message>
+        <source>Match the taskbar</source>
+        <translation>Wie die Taskleiste</translation>
+    </message>
+    <message>
+        <source>Dark icon (light taskbar)</source>
+        <translation>Dunkles Symbol (helle Taskleiste)</translation>
+    </message>
+    <message>
+        <source>Light icon (dark taskbar)</source>
+        <translation>Helles Symbol (dunkle Taskleiste)</translation>
+    </message>
+    <message>
+        <source>Pin feedback</source>
+        <translation>Rückmeldung beim Anheften</translation>
+    </message>
+    <message>
+        <source>None</source>
+        <translation>Keine</translation>
+    </message>
+    <message>
+        <source>Flash the window</source>
+        <translation>Fenster aufblinken lassen</translation>
+    </message>
+    <message>
+        <source>Pulse the border (Windows 11)</source>
+        <translation>Rahmen pulsieren lassen (Windows 11)</translation>
+    </message>
+    <message>
+        <source>Add 📌 to pinned windows&apos; titles</source>
+        <translation>📌 an Titel angehefteter Fenster anhängen</translation>
+    </message>
+    <message>
+        <source>On-screen display for hotkeys</source>
+        <translation>Bildschirmanzeige für Tastenkürzel</translation>
+    </message>
+    <message>
+        <source>Show a pin badge on pinned windows</source>
+        <translation>Pin-Symbol auf angehefteten Fenstern zeigen</translation>
+    </message>
+    <message>
+        <source>Click the badge to unpin</source>
+        <translation>Klick auf das Symbol löst das Fenster</translation>
+    </message>
+    <message>
+        <source>Translucency effect</source>
+        <translation>Transparenzeffekt</translation>
+    </message>
+    <message>
+        <source>Blur behind</source>
+        <translation>Hintergrund weichzeichnen</translation>
+    </message>
+    <message>
+        <source>Acrylic (Windows 11)</source>
+        <translation>Acryl (Windows 11)</translation>
+    </message>
+    <message>
+        <source>Temporary pin length</source>
+        <translation>Dauer des vorübergehenden Anheftens</translation>
+    </message>
+    <message>
+        <source>Pin see-through at</source>
+        <translation>Angeheftete Fenster durchsichtig bei</translation>
+    </message>
+    <message>
+        <source>Unpin when unused</source>
+        <translation>Lösen, wenn ungenutzt</translation>
+    </message>
+    <message>
+        <source>Never</source>
+        <translation>Nie</translation>
+    </message>
+    <message>
+        <source>After 30 minutes</source>
+        <translation>Nach 30 Minuten</translation>
+    </message>
+    <message>
+        <source>After 1 hour</source>
+        <translation>Nach 1 Stunde</translation>
+    </message>
+    <message>
+        <source>After 4 hours</source>
+        <translation>Nach 4 Stunden</translation>
+    </message>
+    <message>
+        <source>After a day</source>
+        <translation>Nach einem Tag</translation>
+    </message>
+    <message>
+        <source>When a pin loses its top spot</source>
+        <translation>Wenn ein angeheftetes Fenster verdrängt wird</translation>
+    </message>
+    <message>
+        <source>Put it back on top</source>
+        <translation>Wieder in den Vordergrund holen</translation>
+    </message>
+    <message>
+        <source>Leave it and tell me</source>
+        <translation>So lassen und Bescheid geben</translation>
+    </message>
+    <message>
+        <source>Other always-on-top tools</source>
+        <translation>Andere Immer-im-Vordergrund-Programme</translation>
+    </message>
+    <message>
+        <source>Ask each time</source>
+        <translation>Jedes Mal fragen</translation>
+    </message>
+    <message>
+        <source>Take over windows I pin</source>
+        <translation>Von mir angeheftete Fenster übernehmen</translation>
+    </message>
+    <message>
+        <source>Let them keep their windows on top</source>
+        <translation>Ihre Fenster im Vordergrund lassen</translation>
+    </message>
+    <message>
+        <source>Keep pinned apps&apos; dialogs on top</source>
+        <translation>Dialoge angehefteter Programme im Vordergrund halten</translation>
+    </message>
+    <message>
+        <source>Match windows by UI Automation (slower)</source>
+        <translation>Fenster per UI Automation erkennen (langsamer)</translation>
+    </message>
+    <message>
+        <source>No transparency over Remote Desktop</source>
+        <translation>Keine Transparenz über Remotedesktop</translation>
+    </message>
+    <message>
+        <source>Kiosk mode…</source>
+        <translation>Kioskmodus…</translation>
+    </message>
+    <message>
+        <source>Exit kiosk mode</source>
+        <translation>Kioskmodus beenden</translation>
+    </message>
+    <message>
+        <source>Usage stats…</source>
+        <translation>Nutzungsstatistik…</translation>
+    </message>
+    <message>
+        <source>Audit log</source>
+        <translation>Überwachungsprotokoll</translation>
+    </message>
+    <message>
+        <source>Report to Windows Event Log</source>
+        <translation>An die Windows-Ereignisanzeige melden</translation>
+    </message>
+    <message>
+        <source>About PinIt</source>
+        <translation>Über PinIt</translation>
+    </message>
+    <message>
+        <source>Quit</source>
+        <translation>Beenden</translation>
+    </message>
+    <message numerus="yes">
+        <source>Unpinned %n window(s).</source>
+        <translation>
+            <numerusform>%n Fenster gelöst.</numerusform>
+            <numerusform>%n Fenster gelöst.</numerusform>
+        </translation>
+    </message>
+    <message numerus="yes">
+        <source>%n minute(s)</source>
+        <translation>
+            <numerusform>%n Minute</numerusform>
+            <numerusform>%n Minuten</numerusform>
+        </translation>
+    </message>
+</context>
+</TS>